@@ -67,6 +67,22 @@ layout(location = 17) uniform sampler2D diffuse_sampler;
 layout(location = 18) uniform sampler2D specular_sampler;
 layout(location = 19) uniform sampler2D normal_map_sampler;
 layout(location = 20) uniform bool use_normal_map;
+layout(location = 23) uniform bool use_pbr;
+// NO_PBR is defined by the engine if there are not enough texture units for the pbr samplers
+#ifndef NO_PBR
+layout(location = 24) uniform vec4 base_color;
+layout(location = 25) uniform float metallic;
+layout(location = 26) uniform float roughness;
+layout(location = 27) uniform float occlusion_strength;
+layout(location = 28) uniform vec3 emissive_color;
+layout(location = 29) uniform sampler2D base_color_sampler;
+layout(location = 30) uniform sampler2D metallic_sampler; // blue channel
+layout(location = 31) uniform sampler2D roughness_sampler; // green channel
+layout(location = 32) uniform sampler2D occlusion_sampler; // red channel
+layout(location = 33) uniform sampler2D emissive_sampler;
+#endif
+
+const float PI = 3.14159265359;
 
 vec3 sample_offset_directions[20] = vec3[]
 (
@@ -95,6 +111,17 @@ float shadow_calc_point(int i, int shadow_map_index) {
     return shadow;
 }
 
+// depth of a directional shadow map, the sampler array is only indexed with constants because llvmpipe crashes on dynamic indices here
+float dir_shadow_depth(int i, vec2 coords) {
+    switch (i) {
+        case 0: return texture(shadow_samplers[0], coords).x;
+        case 1: return texture(shadow_samplers[1], coords).x;
+        case 2: return texture(shadow_samplers[2], coords).x;
+        case 3: return texture(shadow_samplers[3], coords).x;
+        default: return texture(shadow_samplers[4], coords).x;
+    }
+}
+
 float shadow_calc_dir(int i) {
     vec3 proj_coords = frag_pos_dir_light[i].xyz / frag_pos_dir_light[i].w;
     proj_coords = proj_coords * 0.5 + 0.5;
@@ -105,7 +132,7 @@ float shadow_calc_dir(int i) {
     for (int y = -filter_size / 2; y < filter_size / 2; ++y) {
         for (int x = -filter_size / 2; x < filter_size / 2; ++x) {
             vec2 offset = vec2(x, y) / textureSize(shadow_samplers[i], 0);
-            float depth = texture(shadow_samplers[i], proj_coords.xy + offset).x;
+            float depth = dir_shadow_depth(i, proj_coords.xy + offset);
             shadow += proj_coords.z > depth + bias ? 1.0 : 0.0;
         }
     }
@@ -117,20 +144,78 @@ float shadow_calc_dir(int i) {
     return shadow;
 }
 
-void main() {
-    vec4 textured = texture(tex_sampler, v_uv).rgba * color * v_color;
-    if (textured.a < 0.001) {
-        discard;
-    }
+// normal distribution function (Trowbridge-Reitz GGX)
+float distribution_ggx(float n_dot_h, float alpha) {
+    float alpha_sq = alpha * alpha;
+    float denom = n_dot_h * n_dot_h * (alpha_sq - 1.0) + 1.0;
+    return alpha_sq / (PI * denom * denom);
+}
 
-    vec3 frag_normal;
-    if (use_normal_map) {
-        frag_normal = texture(normal_map_sampler, v_uv).rgb * 2.0 - 1.0;
-        frag_normal = normalize(TBN * frag_normal);
-    } else {
-        frag_normal = v_normal;
+// geometry function (Smith with Schlick-GGX)
+float geometry_smith(float n_dot_v, float n_dot_l, float rough) {
+    float k = (rough + 1.0) * (rough + 1.0) / 8.0;
+    float ggx_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    float ggx_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return ggx_v * ggx_l;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// reflected light of a single light source with the Cook-Torrance BRDF
+vec3 cook_torrance(vec3 normal, vec3 view_dir, vec3 light_dir, vec3 radiance, vec3 albedo, float metal, float rough) {
+    vec3 halfway_dir = normalize(view_dir + light_dir);
+    float n_dot_v = max(dot(normal, view_dir), 0.0001);
+    float n_dot_l = max(dot(normal, light_dir), 0.0);
+    vec3 f0 = mix(vec3(0.04), albedo, metal);
+    vec3 fresnel = fresnel_schlick(max(dot(halfway_dir, view_dir), 0.0), f0);
+    float ndf = distribution_ggx(max(dot(normal, halfway_dir), 0.0), rough * rough);
+    float geometry = geometry_smith(n_dot_v, n_dot_l, rough);
+    vec3 specular = ndf * geometry * fresnel / max(4.0 * n_dot_v * n_dot_l, 0.0001);
+    vec3 k_diffuse = (1.0 - fresnel) * (1.0 - metal);
+    // scaled by PI so that light intensities match the Phong shading of diffuse surfaces
+    return (k_diffuse * albedo / PI + specular) * radiance * n_dot_l * PI;
+}
+
+#ifndef NO_PBR
+// the color of the fragment with the metallic-roughness material
+vec4 pbr_color(vec4 textured, vec3 frag_normal) {
+    vec4 base = base_color * texture(base_color_sampler, v_uv) * textured;
+    vec3 albedo = base.rgb;
+    float metal = clamp(metallic * texture(metallic_sampler, v_uv).b, 0.0, 1.0);
+    float rough = clamp(roughness * texture(roughness_sampler, v_uv).g, 0.04, 1.0);
+    float occlusion = mix(1.0, texture(occlusion_sampler, v_uv).r, occlusion_strength);
+    vec3 view_dir = normalize(cam_position - frag_pos);
+
+    vec3 final_light = albedo * occlusion * ambient_light.color.rgb * ambient_light.intensity;
+    for (int i = 0; i < num_dir_lights; i++) {
+        float shadow = 1.0 - shadow_calc_dir(i) / float(num_dir_lights + num_point_lights);
+        float distance_to_light = length(frag_pos - dir_lights[i].light_pos.xyz);
+        distance_to_light = distance_to_light == 0.0 ? 0.1 : distance_to_light;
+        vec3 radiance = dir_lights[i].color.rgb * dir_lights[i].intensity * shadow / distance_to_light;
+        final_light += cook_torrance(frag_normal, view_dir, -dir_lights[i].direction, radiance, albedo, metal, rough);
     }
+    int point_light_map_index = 0;
+    for (int i = 0; i < num_point_lights; i++) {
+        vec3 light_dir = normalize(point_lights[i].light_pos.xyz - frag_pos);
+        float shadow = 1.0;
+        if (point_lights[i].has_shadows) {
+            shadow -= shadow_calc_point(i, point_light_map_index) / float(num_dir_lights + num_point_lights);
+            point_light_map_index += 1;
+        }
+        float distance_to_light = length(frag_pos - point_lights[i].light_pos.xyz);
+        distance_to_light = distance_to_light == 0.0 ? 0.1 : distance_to_light;
+        vec3 radiance = point_lights[i].color.rgb * point_lights[i].intensity * shadow / distance_to_light;
+        final_light += cook_torrance(frag_normal, view_dir, light_dir, radiance, albedo, metal, rough);
+    }
+    final_light += emissive_color * texture(emissive_sampler, v_uv).rgb;
+    return vec4(final_light, base.a);
+}
+#endif
 
+// the color of the fragment with the Blinn-Phong material
+vec4 phong_color(vec4 textured, vec3 frag_normal) {
     vec3 material_ambient_color = ambient_color * texture(ambient_sampler, v_uv).rgb;
     vec3 material_diffuse_color = diffuse_color * texture(diffuse_sampler, v_uv).rgb;
     vec3 material_specular_color = specular_color * texture(specular_sampler, v_uv).rgb;
@@ -178,7 +263,31 @@ void main() {
         final_light += spec_strenght * spec * point_lights[i].color.rgb * material_specular_color * point_lights[i].intensity;
     }
 
-    out_color = vec4(textured.rgb * final_light, textured.a);
+    return vec4(textured.rgb * final_light, textured.a);
+}
+
+#ifdef NO_PBR
+// pbr materials fall back to the Blinn-Phong material
+vec4 pbr_color(vec4 textured, vec3 frag_normal) {
+    return phong_color(textured, frag_normal);
+}
+#endif
+
+void main() {
+    vec4 textured = texture(tex_sampler, v_uv).rgba * color * v_color;
+    if (textured.a < 0.001) {
+        discard;
+    }
+
+    vec3 frag_normal;
+    if (use_normal_map) {
+        frag_normal = texture(normal_map_sampler, v_uv).rgb * 2.0 - 1.0;
+        frag_normal = normalize(TBN * frag_normal);
+    } else {
+        frag_normal = v_normal;
+    }
+
+    out_color = use_pbr ? pbr_color(textured, frag_normal) : phong_color(textured, frag_normal);
     float bloom_threshold = 1.0001 + bloom_threshold_shift;
     bright_color = dot(out_color.rgb, vec3(0.2126, 0.7152, 0.0722)) > bloom_threshold ? vec4(out_color.rgb, 1.0) : vec4(0.0, 0.0, 0.0, 1.0);
 }
@@ -10,6 +10,8 @@ layout(location = 3) in vec4 color;
 layout(location = 4) in vec3 tangent;
 layout(location = 5) in mat4 model; // takes up 4 attribute locations
 layout(location = 9) in mat3 normal_matrix; // takes up 3 attribute locations
+layout(location = 14) in uvec4 joints;
+layout(location = 15) in vec4 weights;
 
 out vec2 v_uv;
 out vec3 v_normal;
@@ -53,18 +55,24 @@ layout (std140, binding = 1, column_major) uniform matrix_block {
     vec4 cam_pos;
 };
 
+layout(location = 22) uniform bool use_skinning;
+
+#include "skinning.glsl"
+
 void main() {
-    gl_Position = projection * view * model * vec4(position, 1.0);
+    mat4 skin = skin_matrix();
+    vec4 skinned_position = skin * vec4(position, 1.0);
+    gl_Position = projection * view * model * skinned_position;
     v_uv = uv;
-    v_normal = normalize(normal_matrix * normal);
+    v_normal = normalize(normal_matrix * mat3(skin) * normal);
     v_color = color;
 
-    vec3 T = normalize(vec3(model * vec4(tangent, 0.0)));
+    vec3 T = normalize(vec3(model * skin * vec4(tangent, 0.0)));
     vec3 N = normalize(vec3(model * vec4(v_normal, 0.0)));
     vec3 B = cross(N, T);
     TBN = mat3(T, B, N);
 
-    frag_pos = vec3(model * vec4(position, 1.0));
+    frag_pos = vec3(model * skinned_position);
     frag_pos_dir_light = vec4[MAX_DIR_LIGHT_MAPS](vec4(0), vec4(0), vec4(0), vec4(0), vec4(0));
     for (int i = 0; i < num_dir_lights; i++) {
         frag_pos_dir_light[i] = dir_lights[i].light_matrix * vec4(frag_pos, 1.0);
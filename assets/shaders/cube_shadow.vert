@@ -3,10 +3,16 @@
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec2 uv;
 layout(location = 5) in mat4 model; // takes up 4 attribute locations
+layout(location = 14) in uvec4 joints;
+layout(location = 15) in vec4 weights;
 
 out vec2 v_uv;
 
+layout(location = 26) uniform bool use_skinning;
+
+#include "skinning.glsl"
+
 void main() {
-    gl_Position = model * vec4(position, 1.0);
+    gl_Position = model * skin_matrix() * vec4(position, 1.0);
     v_uv = uv;
 }
@@ -4,6 +4,8 @@ layout(location = 0) in vec3 position;
 layout(location = 5) in mat4 model; // takes up 4 attribute locations
 layout(location = 12) in vec4 outline_color;
 layout(location = 13) in float outline_thickness;
+layout(location = 14) in uvec4 joints;
+layout(location = 15) in vec4 weights;
 
 out vec4 v_outline_color;
 
@@ -13,9 +15,13 @@ layout (std140, binding = 1, column_major) uniform matrix_block {
     vec4 cam_pos;
 };
 
+layout(location = 0) uniform bool use_skinning;
+
+#include "skinning.glsl"
+
 void main() {
     mat4 scale = mat4(1.0 + outline_thickness);
     scale[3].w = 1;
-    gl_Position = projection * view * model * scale * vec4(position, 1.0);
+    gl_Position = projection * view * model * scale * skin_matrix() * vec4(position, 1.0);
     v_outline_color = outline_color;
 }
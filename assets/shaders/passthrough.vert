@@ -4,6 +4,8 @@ layout(location = 0) in vec3 position;
 layout(location = 1) in vec2 uv;
 layout(location = 3) in vec4 color;
 layout(location = 5) in mat4 model; // takes up 4 attribute locations
+layout(location = 14) in uvec4 joints;
+layout(location = 15) in vec4 weights;
 
 out vec2 v_uv;
 out vec4 v_color;
@@ -14,8 +16,12 @@ layout (std140, binding = 1, column_major) uniform matrix_block {
     vec4 cam_pos;
 };
 
+layout(location = 22) uniform bool use_skinning;
+
+#include "skinning.glsl"
+
 void main() {
-    gl_Position = projection * view * model * vec4(position, 1.0);
+    gl_Position = projection * view * model * skin_matrix() * vec4(position, 1.0);
     v_uv = uv;
     v_color = color;
 }
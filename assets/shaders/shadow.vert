@@ -3,12 +3,18 @@
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec2 uv;
 layout(location = 5) in mat4 model; // takes up 4 attribute locations
+layout(location = 14) in uvec4 joints;
+layout(location = 15) in vec4 weights;
 
 out vec2 v_uv;
 
 layout(location = 0) uniform mat4 light_matrix;
 
+layout(location = 6) uniform bool use_skinning;
+
+#include "skinning.glsl"
+
 void main() {
-    gl_Position = light_matrix * model * vec4(position, 1.0);
+    gl_Position = light_matrix * model * skin_matrix() * vec4(position, 1.0);
     v_uv = uv;
 }
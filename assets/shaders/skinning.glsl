@@ -0,0 +1,17 @@
+// shared skinning code that is included in the instance vertex shaders, expects the joints and weights attributes and the use_skinning uniform
+#define MAX_JOINTS 128
+
+layout (std140, binding = 4, column_major) uniform joint_block {
+    mat4 joint_matrices[MAX_JOINTS];
+};
+
+// blends the joint matrices of the vertex, meshes without skin are not deformed
+mat4 skin_matrix() {
+    if (!use_skinning) {
+        return mat4(1.0);
+    }
+    return weights.x * joint_matrices[joints.x]
+        + weights.y * joint_matrices[joints.y]
+        + weights.z * joint_matrices[joints.z]
+        + weights.w * joint_matrices[joints.w];
+}
@@ -1,6 +1,7 @@
 use leafy::prelude::*;
 use leafy::winit::keyboard::KeyCode;
 use std::path::Path;
+use std::rc::Rc;
 
 const GRID_SCALE: f32 = 0.3;
 
@@ -39,6 +40,12 @@ impl LeafyApp for App {
 
         let sprite_path = Path::new("examples/2D/sprite.png").into();
         assert!(entity_manager.load_sprite(&sprite_path));
+        let atlas_name: Rc<str> = "sprites".into();
+        assert!(entity_manager.load_sprite_atlas(
+            &atlas_name,
+            std::slice::from_ref(&sprite_path),
+            SpriteAtlasSettings::default()
+        ));
 
         self.sprite1 = entity_manager.create_entity(components!(Sprite {
             source: SpriteSource::Single(sprite_path.clone()),
@@ -47,7 +54,10 @@ impl LeafyApp for App {
         }));
         self.sprite2 = entity_manager.create_entity(components!(
             Sprite {
-                source: SpriteSource::Single(sprite_path),
+                source: SpriteSource::Atlas(SpriteAtlasSource {
+                    atlas: atlas_name,
+                    sprite: sprite_path,
+                }),
                 position: SpritePosition::Absolute(vec2(0.0, -GRID_SCALE)),
                 layer: SpriteLayer::Layer0,
                 projection_layer: Some(SpriteLayer::Layer1),
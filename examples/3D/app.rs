@@ -90,13 +90,17 @@ impl LeafyApp for App {
         let mut entity_manager = engine.entity_manager_mut();
 
         let hammer_mesh = entity_manager.load_asset_file("examples/3D/hammer.obj")[0];
-        let sphere_mesh = entity_manager.load_asset_file("examples/3D/sphere.obj")[0];
+        let sphere_mesh = entity_manager.load_procedural_mesh(ProceduralMesh::UVSphere {
+            segments: 64,
+            rings: 32,
+        });
 
         let wall_texture = Texture {
             path: Path::new("examples/3D/wall.png").into(),
             filtering: Filtering::Nearest,
             wrapping: Wrapping::Repeat,
             color_space: ColorSpace::RGBA8,
+            mipmaps: MipmapSettings::default().with_lod_bias(-0.5),
             is_transparent: false,
         };
         assert!(entity_manager.load_texture(&wall_texture));
@@ -45,6 +45,7 @@ impl LeafyApp for App {
 
         for row in 0..GRID_SIZE {
             for col in 0..GRID_SIZE {
+                #[allow(clippy::nonminimal_bool)]
                 let (color, flags) = if row == GRID_SIZE / 2 - 1 && col == GRID_SIZE / 2
                     || row == GRID_SIZE / 2 && col == GRID_SIZE / 2 - 1
                     || row == GRID_SIZE / 2 && col == GRID_SIZE / 2
@@ -78,8 +79,7 @@ impl LeafyApp for App {
                 let cell = self.grid_cells[row][col];
                 let was_alive = was_alive(cell, engine);
                 let neighbour_count = self.alive_neighbors(row, col, engine);
-                let should_live = was_alive && (neighbour_count == 2 || neighbour_count == 3)
-                    || !was_alive && neighbour_count == 3;
+                let should_live = neighbour_count == 3 || was_alive && neighbour_count == 2;
                 set_is_alive(cell, should_live, engine);
             }
         }
@@ -6,11 +6,15 @@ const CAM_MOUSE_SPEED: f32 = 4.0;
 
 pub struct App {
     mesh: EntityID,
+    torus_mesh: MeshHandle,
 }
 
 impl App {
     pub fn new() -> Self {
-        Self { mesh: NO_ENTITY }
+        Self {
+            mesh: NO_ENTITY,
+            torus_mesh: 0,
+        }
     }
 }
 
@@ -39,7 +43,23 @@ impl LeafyApp for App {
         let mut entity_manager = engine.entity_manager_mut();
 
         let torus_mesh = entity_manager.load_asset_file("examples/simplification/torus.obj")[0];
+        entity_manager.set_simplification_settings(
+            SimplificationSettings::default()
+                .with_levels(vec![
+                    SimplificationTarget::Ratio(0.5),
+                    SimplificationTarget::Ratio(0.25),
+                    SimplificationTarget::Ratio(0.1),
+                    SimplificationTarget::Ratio(0.05),
+                    SimplificationTarget::TriangleCount(100),
+                ])
+                .with_preserve_uv_seams(true),
+        );
         assert!(entity_manager.load_lods(torus_mesh));
+        entity_manager.set_lod_thresholds(
+            torus_mesh,
+            Some(LODThresholds::distance(vec![6.0, 9.0, 12.0, 15.0, 20.0])),
+        );
+        self.torus_mesh = torus_mesh;
 
         let _light = entity_manager.create_entity(components!(
             Position::new(1.0, 10.0, 1.0),
@@ -67,6 +87,7 @@ impl LeafyApp for App {
         ));
 
         engine.event_system_mut().add_modifier(change_mesh_lod);
+        engine.event_system_mut().add_modifier(export_mesh_lod);
         engine.event_system_mut().add_modifier(quit_app);
     }
 
@@ -74,32 +95,34 @@ impl LeafyApp for App {
 }
 
 fn change_mesh_lod(event: &KeyPress, engine: &Engine<App>) {
-    let mesh_entity = engine.app().mesh;
+    let (mesh_entity, torus_mesh) = (engine.app().mesh, engine.app().torus_mesh);
     let mut entity_manager = engine.entity_manager_mut();
+    let num_lods = entity_manager.lod_count(torus_mesh) + 1;
     let lod = entity_manager
         .get_component_mut::<LOD>(mesh_entity)
         .unwrap();
 
     if event.key == KeyCode::ArrowRight {
-        *lod = i32_to_lod((*lod as i32 + 1) % NUM_LODS).unwrap()
+        *lod = LOD::from_level((lod.level() + 1) % num_lods);
     } else if event.key == KeyCode::ArrowLeft {
-        *lod = i32_to_lod((*lod as i32 + NUM_LODS - 1) % NUM_LODS).unwrap()
+        *lod = LOD::from_level((lod.level() + num_lods - 1) % num_lods);
+    } else if event.key == KeyCode::KeyL {
+        *lod = LOD::Auto;
     }
 }
 
-fn quit_app(event: &KeyPress, engine: &Engine<App>) {
-    if event.key == KeyCode::Escape {
-        engine.quit();
+fn export_mesh_lod(event: &KeyPress, engine: &Engine<App>) {
+    if event.key == KeyCode::KeyE {
+        let (mesh_entity, torus_mesh) = (engine.app().mesh, engine.app().torus_mesh);
+        let entity_manager = engine.entity_manager();
+        let lod = *entity_manager.get_component::<LOD>(mesh_entity).unwrap();
+        let path = format!("examples/simplification/torus_lod{}.obj", lod.level());
+        entity_manager.export_mesh(torus_mesh, lod, path);
     }
 }
 
-fn i32_to_lod(value: i32) -> Result<LOD, ()> {
-    match value {
-        x if x == LOD::None as i32 => Ok(LOD::None),
-        x if x == LOD::LVL1 as i32 => Ok(LOD::LVL1),
-        x if x == LOD::LVL2 as i32 => Ok(LOD::LVL2),
-        x if x == LOD::LVL3 as i32 => Ok(LOD::LVL3),
-        x if x == LOD::LVL4 as i32 => Ok(LOD::LVL4),
-        _ => Err(()),
+fn quit_app(event: &KeyPress, engine: &Engine<App>) {
+    if event.key == KeyCode::Escape {
+        engine.quit();
     }
 }
@@ -0,0 +1,8 @@
+use leafy::env_logger::{self, Env};
+use leafy::utils::meshtool;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    env_logger::init_from_env(Env::default().filter_or("LOG_LVL", "warn"));
+    ExitCode::from(meshtool::run(std::env::args().skip(1)))
+}
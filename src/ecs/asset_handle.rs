@@ -0,0 +1,97 @@
+use crate::internal_prelude::*;
+
+/// Identifies an asset that is stored in the registers of the entity manager.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssetID {
+    /// A loaded mesh including its generated LODs.
+    Mesh(MeshHandle),
+    Hitbox(HitboxType, Option<MeshHandle>),
+    Material(String),
+    Texture(Texture),
+    Sprite(Rc<Path>),
+    SpriteSheet(Rc<Path>),
+    SpriteAtlas(Rc<str>),
+}
+
+/// the reference counting state of a single asset
+#[derive(Debug)]
+pub(crate) struct AssetEntry {
+    pub(crate) token: Rc<()>,
+    pub(crate) pinned: bool,
+}
+
+impl AssetEntry {
+    /// creates a new unpinned entry
+    pub(crate) fn new() -> Self {
+        Self {
+            token: Rc::new(()),
+            pinned: false,
+        }
+    }
+
+    /// the number of strong handles that currently exist for the asset
+    pub(crate) fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.token) - 1
+    }
+}
+
+/// A strong reference to an asset. As long as a strong handle exists, the asset is not unloaded automatically, even if no entity uses it.
+#[derive(Debug, Clone)]
+pub struct StrongAssetHandle {
+    pub(crate) id: AssetID,
+    pub(crate) token: Rc<()>,
+}
+
+impl StrongAssetHandle {
+    /// The ID of the referenced asset.
+    pub fn id(&self) -> &AssetID {
+        &self.id
+    }
+
+    /// Creates a weak handle to the same asset.
+    pub fn downgrade(&self) -> WeakAssetHandle {
+        WeakAssetHandle {
+            id: self.id.clone(),
+            token: Rc::downgrade(&self.token),
+        }
+    }
+}
+
+impl PartialEq for StrongAssetHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.token, &other.token)
+    }
+}
+
+/// A weak reference to an asset. It does not keep the asset loaded, but can be used to check if the asset is still present and to get a strong handle to it.
+#[derive(Debug, Clone)]
+pub struct WeakAssetHandle {
+    pub(crate) id: AssetID,
+    pub(crate) token: Weak<()>,
+}
+
+impl WeakAssetHandle {
+    /// The ID of the referenced asset.
+    pub fn id(&self) -> &AssetID {
+        &self.id
+    }
+
+    /// Checks wether or not the referenced asset is still loaded. A reloaded asset with the same ID is considered a different asset.
+    pub fn is_loaded(&self) -> bool {
+        self.token.strong_count() > 0
+    }
+
+    /// Creates a strong handle to the asset if it is still loaded.
+    pub fn upgrade(&self) -> Option<StrongAssetHandle> {
+        self.token.upgrade().map(|token| StrongAssetHandle {
+            id: self.id.clone(),
+            token,
+        })
+    }
+}
+
+impl PartialEq for WeakAssetHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Weak::ptr_eq(&self.token, &other.token)
+    }
+}
@@ -0,0 +1,391 @@
+use crate::internal_prelude::*;
+use crate::rendering::data::Skybox;
+use crate::rendering::gltf::{GltfData, GltfImage};
+use crate::rendering::mesh::{Hitbox, Mesh};
+use crate::rendering::mesh_cache::MeshCache;
+use crate::utils::vfs::{load_mtl, load_obj, read_file_to_string};
+use stb_image::image::Image;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use tobj::GPU_LOAD_OPTIONS;
+
+/// Identifier for an asynchronous asset loading operation.
+pub type LoadID = u64;
+
+/// All possible states of an asynchronous asset loading operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadState {
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// Handle to an asset loading operation that runs in the background. It starts in the ``Pending`` state and is updated on the main thread at the start of the frame in which the loaded data becomes available.
+#[derive(Debug, Clone)]
+pub struct PendingHandle {
+    id: LoadID,
+    shared: Rc<RefCell<(LoadState, Vec<AssetID>)>>,
+}
+
+impl PendingHandle {
+    /// creates a new handle in the pending state
+    fn new(id: LoadID) -> Self {
+        Self {
+            id,
+            shared: Rc::new(RefCell::new((LoadState::Pending, Vec::new()))),
+        }
+    }
+
+    /// The ID of the loading operation. This is also contained in the triggered events.
+    pub fn id(&self) -> LoadID {
+        self.id
+    }
+
+    /// The current state of the loading operation.
+    pub fn state(&self) -> LoadState {
+        self.shared.borrow().0
+    }
+
+    /// Checks wether or not the loading operation is still running.
+    pub fn is_pending(&self) -> bool {
+        self.state() == LoadState::Pending
+    }
+
+    /// Checks wether or not the loaded assets are ready to use.
+    pub fn is_ready(&self) -> bool {
+        self.state() == LoadState::Ready
+    }
+
+    /// The IDs of all the assets that were loaded. This is empty until the loading is finished.
+    pub fn assets(&self) -> Vec<AssetID> {
+        self.shared.borrow().1.clone()
+    }
+
+    /// The handles of all the meshes that were loaded. This is empty until the loading is finished.
+    pub fn mesh_handles(&self) -> Vec<MeshHandle> {
+        self.shared
+            .borrow()
+            .1
+            .iter()
+            .filter_map(|id| match id {
+                AssetID::Mesh(handle) => Some(*handle),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// sets the final state of the operation
+    pub(crate) fn finish(&self, state: LoadState, assets: Vec<AssetID>) {
+        *self.shared.borrow_mut() = (state, assets);
+    }
+}
+
+/// all the data of a model file that can be parsed and decoded without the gl context
+pub(crate) struct LoadedAssetFile {
+    pub(crate) meshes: Vec<Mesh>,
+    pub(crate) materials: Vec<(String, Material)>,
+    /// the decoded textures with their name and optional source file
+    pub(crate) textures: Vec<(String, Option<PathBuf>, Image<u8>)>,
+    /// the material library files referenced by an ``.obj`` file
+    pub(crate) material_files: Vec<PathBuf>,
+}
+
+impl LoadedAssetFile {
+    /// loads an ``.obj`` file with the referenced materials or a ``.gltf``/``.glb`` file, the meshes of ``.obj`` files are taken from the cache if possible
+    pub(crate) fn load(file_path: &Path, cache: Option<&MeshCache>) -> Result<Self, String> {
+        if file_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gltf") || ext.eq_ignore_ascii_case("glb"))
+        {
+            return GltfData::load(file_path).map(Self::from_gltf);
+        }
+
+        let material_files = material_libraries(file_path);
+        let (meshes, materials) = match cache.and_then(|cache| cache.meshes(file_path)) {
+            Some(meshes) => {
+                log::debug!("Loaded meshes of {file_path:?} from the cache.");
+                let mut materials = Vec::new();
+                for mtl_path in material_files.iter() {
+                    match load_mtl(mtl_path) {
+                        Ok((mtls, _)) => materials.extend(mtls),
+                        Err(msg) => return Err(format!("{msg:?}")),
+                    }
+                }
+                (meshes, materials)
+            }
+            None => {
+                let (models, materials) = match load_obj(file_path, &GPU_LOAD_OPTIONS) {
+                    Ok((models, Ok(materials))) => (models, materials),
+                    Ok((_, Err(msg))) | Err(msg) => return Err(format!("{msg:?}")),
+                };
+                let meshes = models
+                    .iter()
+                    .enumerate()
+                    .map(|(i, model)| {
+                        let mtl_name = model
+                            .mesh
+                            .material_id
+                            .map(|index| materials[index].name.clone());
+                        let mut mesh = Mesh::from_obj_data(model, Arc::from(file_path), mtl_name);
+                        mesh.source_index = i;
+                        mesh
+                    })
+                    .collect_vec();
+                if let Some(cache) = cache {
+                    cache.store_meshes(file_path, &meshes);
+                }
+                (meshes, materials)
+            }
+        };
+
+        let mut texture_names = AHashSet::new();
+        let mut textures = Vec::new();
+        for mtl in materials.iter() {
+            for texture in [
+                &mtl.ambient_texture,
+                &mtl.diffuse_texture,
+                &mtl.specular_texture,
+                &mtl.normal_texture,
+            ]
+            .into_iter()
+            .flatten()
+            .chain(
+                PBR_MTL_TEXTURE_KEYS
+                    .iter()
+                    .filter_map(|key| mtl.unknown_param.get(*key)),
+            ) {
+                let mut full_texture_path = PathBuf::from(file_path);
+                full_texture_path.set_file_name(texture);
+                let name = full_texture_path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned();
+                if !texture_names.insert(name.clone()) {
+                    continue;
+                }
+                if let Some(image) = stbi_load_u8_rgba(&full_texture_path) {
+                    textures.push((name, Some(full_texture_path), image));
+                } else {
+                    log::error!(
+                        "Error loading material texture file data from {full_texture_path:?}."
+                    );
+                }
+            }
+        }
+
+        Ok(Self {
+            meshes,
+            material_files,
+            materials: materials
+                .iter()
+                .map(|mtl| (mtl.name.clone(), Material::from_mtl(mtl)))
+                .collect(),
+            textures,
+        })
+    }
+
+    /// decodes all of the gltf images and discards the node hierarchy
+    fn from_gltf(data: GltfData) -> Self {
+        let textures = data
+            .images
+            .into_iter()
+            .filter_map(|image| {
+                let name = image.name();
+                let (decoded, source) = match image {
+                    GltfImage::External(path) => (stbi_load_u8_rgba(&path), Some(path)),
+                    GltfImage::Embedded { bytes, .. } => {
+                        (stbi_load_u8_rgba_from_memory(&bytes), None)
+                    }
+                };
+                if decoded.is_none() {
+                    log::error!("Error loading material texture data for {name:?}.");
+                }
+                decoded.map(|decoded| (name, source, decoded))
+            })
+            .collect();
+
+        Self {
+            meshes: data.meshes.into_iter().flatten().collect(),
+            materials: data.materials,
+            textures,
+            material_files: Vec::new(),
+        }
+    }
+}
+
+/// finds the paths of all the ``mtllib`` statements in an ``.obj`` file
+fn material_libraries(file_path: &Path) -> Vec<PathBuf> {
+    read_file_to_string(file_path)
+        .map(|source| {
+            source
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("mtllib "))
+                .map(|name| file_path.with_file_name(name.trim()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// a unit of work for the loading threads, the mesh jobs contain the mesh revision at the time of submission
+pub(crate) enum LoadJob {
+    AssetFile(PathBuf, Option<MeshCache>),
+    Lods(
+        MeshHandle,
+        u64,
+        Mesh,
+        SimplificationSettings,
+        Option<MeshCache>,
+    ),
+    Hitbox(HitboxType, MeshHandle, u64, Mesh, Option<MeshCache>),
+    Skybox([PathBuf; 6]),
+}
+
+impl LoadJob {
+    /// runs all of the work that does not require the gl context
+    fn run(self) -> Result<LoadOutput, String> {
+        match self {
+            Self::AssetFile(path, cache) => LoadedAssetFile::load(&path, cache.as_ref())
+                .map(|file| LoadOutput::AssetFile(path, file)),
+            Self::Lods(handle, revision, mesh, settings, cache) => Ok(LoadOutput::Lods(
+                handle,
+                revision,
+                MeshCache::lods_or_generate(cache.as_ref(), &mesh, &settings),
+            )),
+            Self::Hitbox(hitbox_type, handle, revision, mesh, cache) => Ok(LoadOutput::Hitbox(
+                hitbox_type,
+                handle,
+                revision,
+                MeshCache::hitbox_or_generate(cache.as_ref(), &mesh, hitbox_type),
+            )),
+            Self::Skybox(paths) => paths
+                .iter()
+                .map(|path| {
+                    stbi_load_u8_rgba(path)
+                        .ok_or(format!("Error loading skybox texture from {path:?}."))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(LoadOutput::Skybox),
+        }
+    }
+}
+
+/// the result of a finished loading job that still has to be uploaded and registered on the main thread
+pub(crate) enum LoadOutput {
+    AssetFile(PathBuf, LoadedAssetFile),
+    Lods(MeshHandle, u64, Vec<Mesh>),
+    Hitbox(HitboxType, MeshHandle, u64, Hitbox),
+    Skybox(Vec<Image<u8>>),
+}
+
+type JobMessage = (LoadID, LoadJob);
+type ResultMessage = (LoadID, Result<LoadOutput, String>);
+
+/// pool of worker threads that runs the loading jobs in the background
+pub(crate) struct AssetLoader {
+    job_sender: Option<Sender<JobMessage>>,
+    result_sender: Sender<ResultMessage>,
+    result_receiver: Receiver<ResultMessage>,
+    pending: AHashMap<LoadID, PendingHandle>,
+    cancelled: Vec<PendingHandle>,
+    next_load: LoadID,
+}
+
+impl AssetLoader {
+    /// creates a new loader, the worker threads are only started when the first job is submitted
+    pub(crate) fn new() -> Self {
+        let (result_sender, result_receiver) = channel();
+        Self {
+            job_sender: None,
+            result_sender,
+            result_receiver,
+            pending: AHashMap::new(),
+            cancelled: Vec::new(),
+            next_load: 1,
+        }
+    }
+
+    /// starts the worker threads that share the job queue
+    fn start_workers(&mut self) -> Sender<JobMessage> {
+        let (job_sender, job_receiver) = channel::<JobMessage>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let thread_count =
+            thread::available_parallelism().map_or(1, |n| n.get().clamp(1, MAX_LOADING_THREADS));
+
+        for i in 0..thread_count {
+            let job_receiver = job_receiver.clone();
+            let result_sender = self.result_sender.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("leafy-loader-{i}"))
+                .spawn(move || loop {
+                    let job = job_receiver.lock().unwrap().recv();
+                    match job {
+                        Ok((id, job)) => {
+                            let result = catch_unwind(AssertUnwindSafe(|| job.run()))
+                                .unwrap_or_else(|_| Err("Loading job panicked.".to_string()));
+                            if result_sender.send((id, result)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                });
+            if let Err(msg) = spawned {
+                log::error!("Error starting asset loading thread: {msg:?}.");
+            }
+        }
+        log::debug!("Started {thread_count} asset loading threads.");
+        job_sender
+    }
+
+    /// queues a job and returns the pending handle for it, fails if no loading thread is running
+    pub(crate) fn submit(&mut self, job: LoadJob) -> Result<PendingHandle, String> {
+        if self.job_sender.is_none() {
+            self.job_sender = Some(self.start_workers());
+        }
+        let id = self.next_load;
+        self.next_load += 1;
+        if self.job_sender.as_ref().unwrap().send((id, job)).is_err() {
+            // all workers are gone, they are started again with the next job
+            self.job_sender = None;
+            return Err("No asset loading thread is running.".to_string());
+        }
+        let handle = PendingHandle::new(id);
+        self.pending.insert(id, handle.clone());
+        Ok(handle)
+    }
+
+    /// collects all the finished jobs without blocking
+    pub(crate) fn poll(&mut self) -> Vec<(PendingHandle, Result<LoadOutput, String>)> {
+        let mut finished = self
+            .cancelled
+            .drain(..)
+            .map(|handle| (handle, Err("The loading was cancelled.".to_string())))
+            .collect_vec();
+        while let Ok((id, result)) = self.result_receiver.try_recv() {
+            if let Some(handle) = self.pending.remove(&id) {
+                finished.push((handle, result));
+            }
+        }
+        finished
+    }
+
+    /// the number of loading operations that are not finished yet
+    pub(crate) fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// discards the results of all running jobs, their handles fail with the next poll
+    pub(crate) fn cancel_all(&mut self) {
+        self.cancelled
+            .extend(self.pending.drain().map(|(_, handle)| handle));
+    }
+}
+
+/// a loading operation that was finalized on the main thread in the current frame
+pub(crate) struct FinishedLoad {
+    pub(crate) handle: PendingHandle,
+    pub(crate) skybox: Option<Skybox>,
+}
@@ -1,10 +1,31 @@
 use crate::internal_prelude::*;
+use crate::rendering::skinning::{ClipPlayback, MorphAnimation};
 use fyrox_sound::pool::Handle;
 use fyrox_sound::source::SoundSource;
+use std::num::NonZeroU8;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 /// The trait that all components need to implement. Must be manually implemented. ZST's are currently not supported by the engine.
-pub trait Component: Any {}
+pub trait Component: Any {
+    /// Creates a copy of the component that is used when an entity is copied into another world. Components that yield ``None`` (default) are left out of the copy.
+    fn duplicate(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// implements the component trait for a type that can be duplicated with ``Clone``
+macro_rules! impl_duplicable_component {
+    ($component:ident) => {
+        impl Component for $component {
+            fn duplicate(&self) -> Option<Self> {
+                Some(self.clone())
+            }
+        }
+    };
+}
 
 macro_rules! impl_arithmetic_basics {
     ($component:ident) => {
@@ -99,7 +120,7 @@ macro_rules! impl_basic_vec_ops {
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
 pub struct Scale(Vec3);
 
-impl Component for Scale {}
+impl_duplicable_component!(Scale);
 
 impl_basic_vec_ops!(Scale);
 
@@ -125,7 +146,7 @@ impl Default for Scale {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Orientation(pub Quat);
 
-impl Component for Orientation {}
+impl_duplicable_component!(Orientation);
 
 impl Orientation {
     /// Creates a new ``Orientation`` with angle in degrees around an axis.
@@ -149,7 +170,7 @@ impl Default for Orientation {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Position(Vec3);
 
-impl Component for Position {}
+impl_duplicable_component!(Position);
 
 impl_basic_vec_ops!(Position);
 
@@ -172,7 +193,7 @@ impl Default for Position {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Velocity(Vec3);
 
-impl Component for Velocity {}
+impl_duplicable_component!(Velocity);
 
 impl_basic_vec_ops!(Velocity);
 
@@ -203,7 +224,7 @@ impl Default for Velocity {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct Acceleration(Vec3);
 
-impl Component for Acceleration {}
+impl_duplicable_component!(Acceleration);
 
 impl_basic_vec_ops!(Acceleration);
 
@@ -234,7 +255,7 @@ impl Default for Acceleration {
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub struct AngularMomentum(Vec3);
 
-impl Component for AngularMomentum {}
+impl_duplicable_component!(AngularMomentum);
 
 impl_basic_vec_ops!(AngularMomentum);
 
@@ -269,7 +290,7 @@ pub struct Renderable {
     pub outline: OutlineData,
 }
 
-impl Component for Renderable {}
+impl_duplicable_component!(Renderable);
 
 impl Default for Renderable {
     fn default() -> Self {
@@ -295,7 +316,7 @@ pub struct RigidBody {
     pub(crate) restitution: f32,
 }
 
-impl Component for RigidBody {}
+impl_duplicable_component!(RigidBody);
 
 impl RigidBody {
     /// Changes the density of the rigid body (should be > 0).
@@ -377,7 +398,7 @@ pub struct Collider {
     pub scale: Scale,
 }
 
-impl Component for Collider {}
+impl_duplicable_component!(Collider);
 
 impl Collider {
     /// Creates a new ``Collider`` from a given hitbox type.
@@ -398,7 +419,7 @@ pub struct PointLight {
     pub has_shadows: bool,
 }
 
-impl Component for PointLight {}
+impl_duplicable_component!(PointLight);
 
 impl Default for PointLight {
     fn default() -> Self {
@@ -418,7 +439,7 @@ pub struct DirectionalLight {
     pub direction: Vec3,
 }
 
-impl Component for DirectionalLight {}
+impl_duplicable_component!(DirectionalLight);
 
 impl Default for DirectionalLight {
     fn default() -> Self {
@@ -434,10 +455,10 @@ impl Default for DirectionalLight {
 /// ### Info
 /// You can use this component independantly of the rest of the engine if you want to.
 /// The bits 8-63 do not influence engine behavior and are free to customize.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct EntityFlags(u64);
 
-impl Component for EntityFlags {}
+impl_duplicable_component!(EntityFlags);
 
 impl EntityFlags {
     /// Creates a new ``EntityFlags`` component with the given flags already set.
@@ -460,18 +481,37 @@ impl EntityFlags {
     }
 }
 
-/// Defines the level of detail for a mesh if used in combination with a ``MeshType``.
+/// Defines the level of detail for a mesh if used in combination with a ``MeshType``. The number of available levels depends on the ``SimplificationSettings`` the LODs were generated with. Levels that are not generated are not rendered.
 #[derive(Debug, Copy, Clone, PartialEq, Hash, Eq, Default, PartialOrd, Ord)]
 pub enum LOD {
+    /// The original mesh.
     #[default]
-    None = 0,
-    LVL1,
-    LVL2,
-    LVL3,
-    LVL4,
+    None,
+    /// The simplified mesh of the given level (starting at ``1``). Use ``LOD::from_level`` to create it from a level number.
+    Level(NonZeroU8),
+    /// Selects the level automatically when rendering, based on the ``LODThresholds`` of the mesh (see ``EntityManager::set_lod_thresholds``).
+    Auto,
 }
 
-impl Component for LOD {}
+impl_duplicable_component!(LOD);
+
+impl LOD {
+    /// Creates the LOD for a level number, level ``0`` is the original mesh. Levels above ``MAX_LOD_LEVEL`` are capped, as no more LOD levels are generated.
+    pub fn from_level(level: usize) -> Self {
+        match NonZeroU8::new(level.min(MAX_LOD_LEVEL) as u8) {
+            Some(level) => Self::Level(level),
+            None => Self::None,
+        }
+    }
+
+    /// The level number of the LOD, the original mesh has level ``0`` (``Auto`` also yields ``0``).
+    pub fn level(&self) -> usize {
+        match self {
+            Self::None | Self::Auto => 0,
+            Self::Level(level) => level.get() as usize,
+        }
+    }
+}
 
 /// Holds data for sprite rendering.
 #[derive(Debug, Clone, PartialEq)]
@@ -482,7 +522,7 @@ pub struct Sprite {
     pub projection_layer: Option<SpriteLayer>,
 }
 
-impl Component for Sprite {}
+impl_duplicable_component!(Sprite);
 
 impl Default for Sprite {
     fn default() -> Self {
@@ -495,9 +535,518 @@ impl Default for Sprite {
     }
 }
 
+/// Plays ``SkeletalClip``s on a ``Skeleton`` and deforms the skinned mesh of the entity's ``Renderable`` with the resulting pose. The playback time is advanced by the ``AnimationSystem``. Entities that are spawned from skinned glTF nodes get this component automatically.
+#[derive(Debug, Clone)]
+pub struct SkeletalAnimator {
+    /// Playback speed multiplier (default is ``1.0``).
+    pub speed: f32,
+    /// Wether or not clips restart once they reach their end (default is ``true``).
+    pub looping: bool,
+    pub(crate) skeleton: Rc<Skeleton>,
+    pub(crate) current: Option<ClipPlayback>,
+    pub(crate) fade_out: Option<(ClipPlayback, f32, f32)>, // previous clip, elapsed fade time, fade duration
+}
+
+impl_duplicable_component!(SkeletalAnimator);
+
+impl SkeletalAnimator {
+    /// Creates a new ``SkeletalAnimator`` that keeps the skeleton in its rest pose until a clip is played.
+    pub fn new(skeleton: Rc<Skeleton>) -> Self {
+        Self {
+            speed: 1.0,
+            looping: true,
+            skeleton,
+            current: None,
+            fade_out: None,
+        }
+    }
+
+    /// Changes the playback speed multiplier.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Enables or disables looping.
+    pub fn with_looping(mut self, flag: bool) -> Self {
+        self.looping = flag;
+        self
+    }
+
+    /// Starts playing a clip from the beginning.
+    pub fn with_clip(mut self, clip: Rc<SkeletalClip>) -> Self {
+        self.play(clip);
+        self
+    }
+
+    /// Immediately switches to a clip and plays it from the beginning.
+    pub fn play(&mut self, clip: Rc<SkeletalClip>) {
+        self.current = Some(ClipPlayback::new(clip, &self.skeleton));
+        self.fade_out = None;
+    }
+
+    /// Starts a clip from the beginning and blends it in over ``duration`` seconds, while the current clip keeps playing and is blended out.
+    pub fn cross_fade(&mut self, clip: Rc<SkeletalClip>, duration: f32) {
+        let next = ClipPlayback::new(clip, &self.skeleton);
+        match self.current.replace(next) {
+            Some(previous) if duration > 0.0 => self.fade_out = Some((previous, 0.0, duration)),
+            _ => self.fade_out = None,
+        }
+    }
+
+    /// Stops all playback and returns to the rest pose.
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.fade_out = None;
+    }
+
+    /// The skeleton that is animated.
+    pub fn skeleton(&self) -> &Rc<Skeleton> {
+        &self.skeleton
+    }
+
+    /// The name of the clip that is currently playing.
+    pub fn current_clip(&self) -> Option<&str> {
+        self.current.as_ref().map(|playback| playback.clip.name())
+    }
+
+    /// The playback time of the current clip in seconds.
+    pub fn time(&self) -> f32 {
+        self.current.as_ref().map_or(0.0, |playback| playback.time)
+    }
+
+    /// Jumps to a point in time of the current clip.
+    pub fn set_time(&mut self, time: f32) {
+        let looping = self.looping;
+        if let Some(playback) = self.current.as_mut() {
+            playback.time = 0.0;
+            playback.advance(time, looping);
+        }
+    }
+
+    /// Checks wether or not a non-looping clip has reached its end.
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && self
+                .current
+                .as_ref()
+                .is_some_and(|playback| playback.time >= playback.clip.duration())
+    }
+
+    /// moves the playback of all clips forward and ends finished cross-fades
+    pub(crate) fn advance(&mut self, delta: f32) {
+        let delta = delta * self.speed;
+        if let Some(playback) = self.current.as_mut() {
+            playback.advance(delta, self.looping);
+        }
+        if let Some((previous, elapsed, duration)) = self.fade_out.as_mut() {
+            previous.advance(delta, self.looping);
+            *elapsed += delta.abs();
+            if *elapsed >= *duration {
+                self.fade_out = None;
+            }
+        }
+    }
+
+    /// the matrices that deform the skinned mesh into the current pose
+    pub(crate) fn skinning_matrices(&self) -> Vec<Mat4> {
+        let mut pose = match self.current.as_ref() {
+            Some(playback) => playback.pose(&self.skeleton),
+            None => self.skeleton.rest_pose(),
+        };
+        if let Some((previous, elapsed, duration)) = self.fade_out.as_ref() {
+            let weight = (elapsed / duration).clamp(0.0, 1.0);
+            pose = previous
+                .pose(&self.skeleton)
+                .iter()
+                .zip(pose.iter())
+                .map(|(from, to)| from.blend(to, weight))
+                .collect();
+        }
+        self.skeleton.skinning_matrices(&pose)
+    }
+}
+
+/// Blend weights for the morph targets of the entity's ``Renderable`` mesh, target ``i`` of the mesh is added with ``weights[i]``. Missing weights count as ``0.0``. The weights can be animated with keyframes that are sampled by the ``AnimationSystem``. Entities with the same mesh and identical weights are still rendered in one instanced draw call. Entities that are spawned from glTF nodes with morph targets get this component automatically.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MorphWeights {
+    /// The current weight of every morph target.
+    pub weights: Vec<f32>,
+    /// Wether or not keyframe animations restart once they reach their end (default is ``false``).
+    pub looping: bool,
+    pub(crate) animation: Option<MorphAnimation>,
+}
+
+impl_duplicable_component!(MorphWeights);
+
+impl MorphWeights {
+    /// Creates new ``MorphWeights`` with one weight per morph target.
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self {
+            weights,
+            ..Default::default()
+        }
+    }
+
+    /// Enables or disables looping of keyframe animations.
+    pub fn with_looping(mut self, flag: bool) -> Self {
+        self.looping = flag;
+        self
+    }
+
+    /// Starts a keyframe animation, see ``MorphWeights::animate``.
+    pub fn with_keyframes(
+        mut self,
+        times: Vec<f32>,
+        weights: Vec<Vec<f32>>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.animate(times, weights, interpolation);
+        self
+    }
+
+    /// Animates the weights with one set of weights per keyframe and replaces the current animation. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three sets of weights per keyframe (in-tangent, value, out-tangent). Returns ``false`` if the keyframe data is invalid.
+    pub fn animate(
+        &mut self,
+        times: Vec<f32>,
+        weights: Vec<Vec<f32>>,
+        interpolation: Interpolation,
+    ) -> bool {
+        match MorphAnimation::new(times, weights, interpolation) {
+            Some(animation) => {
+                self.weights = animation.sample();
+                self.animation = Some(animation);
+                true
+            }
+            None => {
+                log::warn!("Invalid morph weight keyframes.");
+                false
+            }
+        }
+    }
+
+    /// Linearly blends from the current weights to the target weights over ``duration`` seconds.
+    pub fn blend_to(&mut self, weights: Vec<f32>, duration: f32) {
+        if duration <= 0.0 {
+            self.weights = weights;
+            self.animation = None;
+            return;
+        }
+        let current = self.weights.clone();
+        self.animate(
+            vec![0.0, duration],
+            vec![current, weights],
+            Interpolation::Linear,
+        );
+    }
+
+    /// Stops the keyframe animation and keeps the current weights.
+    pub fn stop(&mut self) {
+        self.animation = None;
+    }
+
+    /// Checks wether or not a keyframe animation is playing. Non-looping animations stop once they reach their end.
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+    /// samples the keyframe animation after moving it forward in time and removes finished animations
+    pub(crate) fn advance(&mut self, delta: f32) {
+        if let Some(animation) = self.animation.as_mut() {
+            animation.advance(delta, self.looping);
+            self.weights = animation.sample();
+            if !self.looping && animation.time >= animation.duration() {
+                self.animation = None;
+            }
+        }
+    }
+}
+
+/// Plays an ``AnimationClip`` that overwrites the ``Position``, ``Orientation``, ``Scale`` and light source properties of the entity that the clip has tracks for. The playback is advanced in the fixed time step of the ``AnimationSystem`` and every marker that is passed triggers an ``AnimationMarker`` event.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    /// Playback speed multiplier, negative values play the clip backwards (default is ``1.0``).
+    pub speed: f32,
+    /// Wether or not the clip restarts once it reaches its end (default is ``false``).
+    pub looping: bool,
+    pub(crate) clip: Option<Rc<AnimationClip>>,
+    pub(crate) time: f32,
+}
+
+impl_duplicable_component!(Animator);
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            looping: false,
+            clip: None,
+            time: 0.0,
+        }
+    }
+}
+
+impl Animator {
+    /// Creates a new ``Animator`` without a clip.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Changes the playback speed multiplier.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Enables or disables looping.
+    pub fn with_looping(mut self, flag: bool) -> Self {
+        self.looping = flag;
+        self
+    }
+
+    /// Starts playing a clip from the beginning.
+    pub fn with_clip(mut self, clip: Rc<AnimationClip>) -> Self {
+        self.play(clip);
+        self
+    }
+
+    /// Switches to a clip and plays it from the beginning, or from the end if the speed is negative.
+    pub fn play(&mut self, clip: Rc<AnimationClip>) {
+        self.time = if self.speed < 0.0 {
+            clip.duration()
+        } else {
+            0.0
+        };
+        self.clip = Some(clip);
+    }
+
+    /// Stops the playback, the animated components keep their current values.
+    pub fn stop(&mut self) {
+        self.clip = None;
+        self.time = 0.0;
+    }
+
+    /// The name of the clip that is currently playing.
+    pub fn current_clip(&self) -> Option<&str> {
+        self.clip.as_ref().map(|clip| clip.name())
+    }
+
+    /// The playback time of the current clip in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jumps to a point in time of the current clip without triggering the markers in between.
+    pub fn set_time(&mut self, time: f32) {
+        if let Some(clip) = self.clip.as_ref() {
+            self.time = time.clamp(0.0, clip.duration());
+        }
+    }
+
+    /// Checks wether or not a non-looping clip has reached its end (or its start if played backwards).
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && self.clip.as_ref().is_some_and(|clip| {
+                if self.speed < 0.0 {
+                    self.time <= 0.0
+                } else {
+                    self.time >= clip.duration()
+                }
+            })
+    }
+
+    /// moves the playback forward and returns the names of the markers that were passed
+    pub(crate) fn advance(&mut self, delta: f32) -> Vec<Rc<str>> {
+        if self.clip.is_none() {
+            return Vec::new();
+        }
+        let clip = self.clip.as_ref().unwrap();
+        let delta = delta * self.speed;
+        let duration = clip.duration();
+        let from = self.time;
+        let to = from + delta;
+        let loops = self.looping && duration > 0.0;
+        let wrapped = loops && !(0.0..duration).contains(&to);
+        self.time = if loops {
+            to.rem_euclid(duration)
+        } else {
+            to.clamp(0.0, duration)
+        };
+        clip.passed_markers(from, self.time, delta < 0.0, wrapped)
+    }
+}
+
+/// Animates a ``Sprite`` with a ``SpriteSource::Sheet`` by replacing the pixel region of the source with the current frame of the playing ``SpriteSequence``. The playback is advanced in the fixed time step of the ``AnimationSystem`` and triggers a ``SpriteAnimationFinished`` event once a sequence with ``SpritePlayMode::Once`` shows its last frame for its full duration.
+#[derive(Debug, Clone)]
+pub struct SpriteAnimation {
+    /// Playback speed multiplier, negative values are treated as ``0.0`` (default is ``1.0``).
+    pub speed: f32,
+    pub(crate) sequences: Vec<SpriteSequence>,
+    pub(crate) current: Option<usize>,
+    pub(crate) frame: usize,
+    pub(crate) frame_time: f32,
+    pub(crate) backwards: bool,
+    pub(crate) finished: bool,
+}
+
+impl_duplicable_component!(SpriteAnimation);
+
+impl Default for SpriteAnimation {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            sequences: Vec::new(),
+            current: None,
+            frame: 0,
+            frame_time: 0.0,
+            backwards: false,
+            finished: false,
+        }
+    }
+}
+
+impl SpriteAnimation {
+    /// Creates a new ``SpriteAnimation`` without any sequences.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Changes the playback speed multiplier.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Adds a sequence that can be played by name. A sequence with the same name is replaced.
+    pub fn with_sequence(mut self, sequence: SpriteSequence) -> Self {
+        self.add_sequence(sequence);
+        self
+    }
+
+    /// Starts playing a sequence from its first frame.
+    pub fn with_playing(mut self, name: &str) -> Self {
+        self.play(name);
+        self
+    }
+
+    /// Adds a sequence that can be played by name. A sequence with the same name is replaced and stops playing if it is the current one.
+    pub fn add_sequence(&mut self, sequence: SpriteSequence) {
+        let opt_index = self.sequence_index(&sequence.name);
+        if let Some(index) = opt_index {
+            if self.current == Some(index) {
+                self.stop();
+            }
+            self.sequences[index] = sequence;
+        } else {
+            self.sequences.push(sequence);
+        }
+    }
+
+    /// Switches to a sequence and plays it from its first frame. Returns false if there is no sequence with that name or it has no frames.
+    pub fn play(&mut self, name: &str) -> bool {
+        let opt_index = self.sequence_index(name);
+        if opt_index.is_none() {
+            log::warn!("Sprite sequence {name:?} does not exist.");
+            return false;
+        }
+        let index = opt_index.unwrap();
+        if self.sequences[index].frames.is_empty() {
+            log::warn!("Sprite sequence {name:?} has no frames.");
+            return false;
+        }
+        self.current = Some(index);
+        self.frame = 0;
+        self.frame_time = 0.0;
+        self.backwards = false;
+        self.finished = false;
+        true
+    }
+
+    /// Stops the playback, the sprite shows the pixel region of its source again.
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.frame = 0;
+        self.frame_time = 0.0;
+        self.backwards = false;
+        self.finished = false;
+    }
+
+    /// The sequence with the given name.
+    pub fn sequence(&self, name: &str) -> Option<&SpriteSequence> {
+        self.sequence_index(name)
+            .map(|index| &self.sequences[index])
+    }
+
+    /// The name of the sequence that is currently playing.
+    pub fn current_sequence(&self) -> Option<&str> {
+        self.current.map(|index| self.sequences[index].name())
+    }
+
+    /// The index of the frame that is currently shown.
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    /// The frame that is currently shown.
+    pub fn current_frame(&self) -> Option<&SpriteFrame> {
+        self.current
+            .map(|index| &self.sequences[index].frames[self.frame])
+    }
+
+    /// Checks wether or not a sequence with ``SpritePlayMode::Once`` has reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// the index of the sequence with the given name
+    fn sequence_index(&self, name: &str) -> Option<usize> {
+        self.sequences
+            .iter()
+            .position(|sequence| sequence.name.as_ref() == name)
+    }
+
+    /// moves the playback forward and returns the name of the sequence if it finished during this step
+    pub(crate) fn advance(&mut self, delta: f32) -> Option<Rc<str>> {
+        if self.current.is_none() || self.finished {
+            return None;
+        }
+        let sequence = &self.sequences[self.current.unwrap()];
+        let last = sequence.frames.len() - 1;
+        self.frame_time += delta * self.speed.max(0.0);
+        // frame durations are always positive, so this terminates
+        while self.frame_time >= sequence.frames[self.frame].duration {
+            self.frame_time -= sequence.frames[self.frame].duration;
+            if !self.backwards && self.frame < last {
+                self.frame += 1;
+            } else if self.backwards && self.frame > 0 {
+                self.frame -= 1;
+            } else {
+                match sequence.mode {
+                    SpritePlayMode::Once => {
+                        self.frame_time = 0.0;
+                        self.finished = true;
+                        return Some(sequence.name.clone());
+                    }
+                    SpritePlayMode::Loop => self.frame = 0,
+                    SpritePlayMode::PingPong => {
+                        self.backwards = !self.backwards && last > 0;
+                        self.frame = if self.backwards {
+                            last - 1
+                        } else {
+                            1.min(last)
+                        };
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
 /// Data structures that are not internally useful as a sole component but might have purpose in relation to other components. Many of them might also be usable as general-purpose types.
 pub mod utils {
     use crate::internal_prelude::*;
+    use std::hash::{Hash, Hasher};
     use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
     /// Efficient 32bit color representation.
@@ -633,6 +1182,7 @@ pub mod utils {
     }
 
     /// Represents a material that influences the rendering of the entity. You can either specify custom parameters or inherit the material data from the ``.obj`` file of the ``MeshType`` of the entity.
+    #[allow(clippy::large_enum_variant)]
     #[derive(Debug, PartialEq, Clone)]
     pub enum MaterialSource {
         Custom(Material),
@@ -646,7 +1196,7 @@ pub mod utils {
         }
     }
 
-    /// Specific material data with components either being a value or a texture to sample from.
+    /// Specific material data with components either being a value or a texture to sample from. Materials with ``pbr`` parameters are shaded with the Cook-Torrance model instead of Blinn-Phong, only the normal texture of the Phong parameters is used for them.
     #[derive(Debug, PartialEq, Clone)]
     pub struct Material {
         pub ambient: Ambient,
@@ -654,10 +1204,28 @@ pub mod utils {
         pub specular: Specular,
         pub shininess: f32,
         pub normal_texture: Option<String>,
+        pub pbr: Option<PbrMaterial>,
     }
 
     impl Material {
-        /// convert a loaded ``.mtl`` file
+        /// Creates a physically based material, the Phong parameters are derived from the base color so that exporting the material keeps its color.
+        pub fn from_pbr(pbr: PbrMaterial) -> Self {
+            let (ambient, diffuse) = match pbr.base_color_texture.clone() {
+                Some(name) => (Ambient::Texture(name.clone()), Diffuse::Texture(name)),
+                None => (
+                    Ambient::Value(pbr.base_color),
+                    Diffuse::Value(pbr.base_color),
+                ),
+            };
+            Self {
+                ambient,
+                diffuse,
+                pbr: Some(pbr),
+                ..Default::default()
+            }
+        }
+
+        /// convert a loaded ``.mtl`` file, the physically based parameters are read from the ``Pr``, ``Pm`` and ``Ke`` extension keys
         pub(crate) fn from_mtl(mtl: &tobj::Material) -> Self {
             Self {
                 ambient: mtl.ambient.map_or(
@@ -680,7 +1248,37 @@ pub mod utils {
                 ),
                 shininess: mtl.shininess.unwrap_or(32.0),
                 normal_texture: mtl.normal_texture.clone(),
+                pbr: PbrMaterial::from_mtl(mtl),
+            }
+        }
+
+        /// converts the material to the ``.mtl`` file format with the given material name
+        pub(crate) fn to_mtl(&self, name: &str) -> String {
+            let color = |color: &Color32| {
+                let color = color.to_vec4();
+                format!("{} {} {}", color.x, color.y, color.z)
+            };
+            let mut mtl = format!("newmtl {name}\n");
+            match &self.ambient {
+                Ambient::Value(value) => mtl += &format!("Ka {}\n", color(value)),
+                Ambient::Texture(file_name) => mtl += &format!("map_Ka {file_name}\n"),
+            }
+            match &self.diffuse {
+                Diffuse::Value(value) => mtl += &format!("Kd {}\n", color(value)),
+                Diffuse::Texture(file_name) => mtl += &format!("map_Kd {file_name}\n"),
+            }
+            match &self.specular {
+                Specular::Value(value) => mtl += &format!("Ks {}\n", color(value)),
+                Specular::Texture(file_name) => mtl += &format!("map_Ks {file_name}\n"),
+            }
+            mtl += &format!("Ns {}\n", self.shininess);
+            if let Some(file_name) = &self.normal_texture {
+                mtl += &format!("norm {file_name}\n");
+            }
+            if let Some(pbr) = &self.pbr {
+                mtl += &pbr.to_mtl();
             }
+            mtl
         }
 
         /// returns the ambient color as a rgb float vec if present
@@ -739,6 +1337,20 @@ pub mod utils {
                 None
             }
         }
+
+        /// returns the names of the base color, metallic, roughness, occlusion and emissive textures if present
+        pub(crate) fn pbr_textures(&self, is_base_lod: bool) -> [Option<&str>; 5] {
+            match self.pbr.as_ref().filter(|_| is_base_lod) {
+                Some(pbr) => [
+                    pbr.base_color_texture.as_deref(),
+                    pbr.metallic_texture.as_deref(),
+                    pbr.roughness_texture.as_deref(),
+                    pbr.occlusion_texture.as_deref(),
+                    pbr.emissive_texture.as_deref(),
+                ],
+                None => [None; 5],
+            }
+        }
     }
 
     impl Default for Material {
@@ -749,6 +1361,121 @@ pub mod utils {
                 specular: Specular::default(),
                 shininess: 32.0,
                 normal_texture: None,
+                pbr: None,
+            }
+        }
+    }
+
+    /// Material parameters of the metallic-roughness model. Every value is multiplied with the sample of its texture if one is present. The roughness is read from the green and the metalness from the blue channel, so the packed metallic-roughness textures of glTF as well as separate grayscale textures can be used. The occlusion is read from the red channel. GPUs with less than 20 fragment texture units render the material with Blinn-Phong shading instead.
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct PbrMaterial {
+        pub base_color: Color32,
+        pub base_color_texture: Option<String>,
+        pub metallic: f32,
+        pub metallic_texture: Option<String>,
+        pub roughness: f32,
+        pub roughness_texture: Option<String>,
+        /// How much the occlusion texture darkens the ambient light (``0.0`` disables it).
+        pub occlusion_strength: f32,
+        pub occlusion_texture: Option<String>,
+        pub emissive: Color32,
+        pub emissive_texture: Option<String>,
+        /// Multiplier of the emitted light, values above ``1.0`` make the material glow with bloom.
+        pub emissive_strength: f32,
+    }
+
+    impl PbrMaterial {
+        /// reads the physically based parameters of a loaded ``.mtl`` file if it has a roughness or metalness value or texture
+        pub(crate) fn from_mtl(mtl: &tobj::Material) -> Option<Self> {
+            let param = |key: &str| mtl.unknown_param.get(key).map(|value| value.trim());
+            let value = |key: &str| {
+                param(key).and_then(|value| {
+                    let parsed = value.parse::<f32>();
+                    if parsed.is_err() {
+                        log::warn!(
+                            "Invalid value {value:?} for {key} in material {:?}.",
+                            mtl.name
+                        );
+                    }
+                    parsed.ok()
+                })
+            };
+            let texture = |key: &str| param(key).filter(|name| !name.is_empty()).map(String::from);
+            if !["Pr", "Pm", "map_Pr", "map_Pm"]
+                .iter()
+                .any(|key| mtl.unknown_param.contains_key(*key))
+            {
+                return None;
+            }
+            let emissive = param("Ke")
+                .map(|value| {
+                    value
+                        .split_whitespace()
+                        .filter_map(|channel| channel.parse::<f32>().ok())
+                        .collect_vec()
+                })
+                .filter(|channels| channels.len() == 3);
+            // emission above 1.0 is stored as the strength since colors are clamped
+            let emissive_strength = emissive
+                .as_ref()
+                .map_or(1.0, |c| c[0].max(c[1]).max(c[2]).max(1.0));
+            let default = Self::default();
+            Some(Self {
+                base_color: mtl.diffuse.map_or(default.base_color, |color| {
+                    Color32::from_float_rgb(color[0], color[1], color[2])
+                }),
+                base_color_texture: mtl.diffuse_texture.clone(),
+                metallic: value("Pm").unwrap_or(default.metallic).clamp(0.0, 1.0),
+                metallic_texture: texture("map_Pm"),
+                roughness: value("Pr").unwrap_or(default.roughness).clamp(0.0, 1.0),
+                roughness_texture: texture("map_Pr"),
+                emissive: emissive.map_or(default.emissive, |c| {
+                    Color32::from_float_rgb(
+                        c[0] / emissive_strength,
+                        c[1] / emissive_strength,
+                        c[2] / emissive_strength,
+                    )
+                }),
+                emissive_texture: texture("map_Ke"),
+                emissive_strength,
+                ..default
+            })
+        }
+
+        /// converts the physically based parameters to the ``.mtl`` extension keys
+        pub(crate) fn to_mtl(&self) -> String {
+            let emissive = self.emissive.to_vec4() * self.emissive_strength;
+            let mut mtl = format!(
+                "Pm {}\nPr {}\nKe {} {} {}\n",
+                self.metallic, self.roughness, emissive.x, emissive.y, emissive.z
+            );
+            for (key, texture) in [
+                ("map_Pm", &self.metallic_texture),
+                ("map_Pr", &self.roughness_texture),
+                ("map_Ke", &self.emissive_texture),
+            ] {
+                if let Some(file_name) = texture {
+                    mtl += &format!("{key} {file_name}\n");
+                }
+            }
+            mtl
+        }
+    }
+
+    impl Default for PbrMaterial {
+        fn default() -> Self {
+            Self {
+                base_color: Color32::WHITE,
+                base_color_texture: None,
+                metallic: 0.0,
+                metallic_texture: None,
+                roughness: 0.5,
+                roughness_texture: None,
+                occlusion_strength: 1.0,
+                occlusion_texture: None,
+                emissive: Color32::BLACK,
+                emissive_texture: None,
+                emissive_strength: 1.0,
             }
         }
     }
@@ -834,6 +1561,7 @@ pub mod utils {
         pub filtering: Filtering,
         pub wrapping: Wrapping,
         pub color_space: ColorSpace,
+        pub mipmaps: MipmapSettings,
         pub is_transparent: bool,
     }
 
@@ -845,9 +1573,25 @@ pub mod utils {
                 filtering: Filtering::default(),
                 wrapping: Wrapping::default(),
                 color_space: ColorSpace::RGBA8,
+                mipmaps: MipmapSettings::default(),
                 is_transparent: false,
             }
         }
+
+        /// Sets the mipmap settings of the texture.
+        pub fn with_mipmaps(mut self, mipmaps: MipmapSettings) -> Self {
+            self.mipmaps = mipmaps;
+            self
+        }
+
+        /// Checks wether the texture reads from the given file (either as base image or as custom mip level).
+        pub(crate) fn uses_file(&self, path: &Path) -> bool {
+            self.path.as_ref() == path
+                || match &self.mipmaps.mode {
+                    MipmapMode::Custom(paths) => paths.iter().any(|level| level.as_ref() == path),
+                    _ => false,
+                }
+        }
     }
 
     /// Texture filtering option for rendering.
@@ -868,11 +1612,142 @@ pub mod utils {
         ClampToBorder,
     }
 
-    /// Defines the color space for a texture.
+    /// Defines the color space for a texture. The float formats keep values outside of ``[0, 1]`` (e.g. for ``.hdr`` environment maps).
+    /// Block-compressed textures use their own format and only distinguish between ``SRGBA`` and linear.
     #[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Hash, Eq)]
     pub enum ColorSpace {
         SRGBA,
         RGBA8,
+        RGBA16F,
+        RGBA32F,
+    }
+
+    /// Defines where the mip levels of a texture come from.
+    #[derive(Debug, PartialOrd, PartialEq, Clone, Hash, Eq, Default)]
+    pub enum MipmapMode {
+        /// Uses the mip chain stored in the file if there is one, otherwise generates mipmaps for uncompressed textures.
+        #[default]
+        Auto,
+        /// Always generates mipmaps on the GPU. Block-compressed textures fall back to their stored levels.
+        Generate,
+        /// Only uses the mip levels stored in the file (KTX2 or DDS).
+        Stored,
+        /// Loads the mip levels from separate files, starting at level 1. Every level must have the same format as the base image and half of the previous size.
+        Custom(Vec<Rc<Path>>),
+        /// No mipmaps are used.
+        Disabled,
+    }
+
+    /// Mipmap configuration of a texture.
+    #[derive(Debug, PartialOrd, PartialEq, Clone, Default)]
+    pub struct MipmapSettings {
+        pub mode: MipmapMode,
+        /// Maximum number of mip levels including the base level (``None`` uses all available levels).
+        pub max_levels: Option<u8>,
+        /// Bias that is added to the mip level selected during sampling, positive values result in blurrier textures.
+        pub lod_bias: f32,
+    }
+
+    impl MipmapSettings {
+        /// Sets the mipmap mode.
+        pub fn with_mode(mut self, mode: MipmapMode) -> Self {
+            self.mode = mode;
+            self
+        }
+
+        /// Sets the maximum number of mip levels.
+        pub fn with_max_levels(mut self, max_levels: u8) -> Self {
+            self.max_levels = Some(max_levels.max(1));
+            self
+        }
+
+        /// Sets the level of detail bias.
+        pub fn with_lod_bias(mut self, lod_bias: f32) -> Self {
+            self.lod_bias = lod_bias;
+            self
+        }
+    }
+
+    impl Eq for MipmapSettings {}
+
+    impl Hash for MipmapSettings {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.mode.hash(state);
+            self.max_levels.hash(state);
+            self.lod_bias.to_bits().hash(state);
+        }
+    }
+
+    /// The quantity that automatic LOD selection is based on.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub enum LODMetric {
+        /// Distance between the camera and the entity position.
+        Distance,
+        /// Projected height of the bounding sphere (computed from the scaled mesh extents) as fraction of the screen height.
+        #[default]
+        ScreenSize,
+    }
+
+    /// Thresholds for the automatic selection of LOD levels with ``LOD::Auto``. With ``LODMetric::Distance`` the ``n``'th threshold is the distance from which on level ``n + 1`` is used (ascending values), with ``LODMetric::ScreenSize`` it is the screen size below which level ``n + 1`` is used (descending values). The ``hysteresis`` is the relative margin a value has to pass a threshold by before the level changes, which prevents popping at the borders.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct LODThresholds {
+        pub metric: LODMetric,
+        pub thresholds: Vec<f32>,
+        pub hysteresis: f32,
+    }
+
+    impl LODThresholds {
+        /// Creates distance based thresholds with a hysteresis of 10%.
+        pub fn distance(thresholds: Vec<f32>) -> Self {
+            Self {
+                metric: LODMetric::Distance,
+                thresholds,
+                hysteresis: 0.1,
+            }
+        }
+
+        /// Creates screen size based thresholds with a hysteresis of 10%.
+        pub fn screen_size(thresholds: Vec<f32>) -> Self {
+            Self {
+                metric: LODMetric::ScreenSize,
+                thresholds,
+                hysteresis: 0.1,
+            }
+        }
+
+        /// Changes the relative hysteresis margin (should be >= 0).
+        pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+            self.hysteresis = hysteresis;
+            self
+        }
+
+        /// the level for a metric value starting at the previously selected level, limited to ``max_level``
+        pub(crate) fn select_level(&self, value: f32, current: usize, max_level: usize) -> usize {
+            let max_level = max_level.min(self.thresholds.len());
+            let (up, down) = (1.0 + self.hysteresis, 1.0 - self.hysteresis);
+            let coarser = |threshold: f32| match self.metric {
+                LODMetric::Distance => value > threshold * up,
+                LODMetric::ScreenSize => value < threshold * down,
+            };
+            let finer = |threshold: f32| match self.metric {
+                LODMetric::Distance => value < threshold * down,
+                LODMetric::ScreenSize => value > threshold * up,
+            };
+            let mut level = current.min(max_level);
+            while level < max_level && coarser(self.thresholds[level]) {
+                level += 1;
+            }
+            while level > 0 && finer(self.thresholds[level - 1]) {
+                level -= 1;
+            }
+            level
+        }
+    }
+
+    impl Default for LODThresholds {
+        fn default() -> Self {
+            Self::screen_size(vec![0.5, 0.25, 0.125, 0.0625])
+        }
     }
 
     /// Hitbox type specifier for an entity.
@@ -882,6 +1757,59 @@ pub mod utils {
         SimplifiedConvexHull,
         Sphere,
         Box,
+        /// Splits concave meshes into multiple convex parts that are all checked for collisions.
+        ConvexDecomposition(DecompositionSettings),
+    }
+
+    /// Settings for splitting a mesh into convex parts.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct DecompositionSettings {
+        /// Maximum number of convex parts the mesh is split into.
+        pub max_parts: usize,
+        /// Parts are no longer split once the volume that their convex hull adds is below this fraction of the mesh volume.
+        pub tolerance: f32,
+        /// Number of voxels along the longest side of the mesh used to approximate its volume (at most ``MAX_DECOMPOSITION_RESOLUTION``).
+        pub resolution: usize,
+    }
+
+    impl Default for DecompositionSettings {
+        fn default() -> Self {
+            Self {
+                max_parts: 16,
+                tolerance: 0.01,
+                resolution: 32,
+            }
+        }
+    }
+
+    impl DecompositionSettings {
+        /// Sets the maximum part count.
+        pub fn with_max_parts(mut self, max_parts: usize) -> Self {
+            self.max_parts = max_parts;
+            self
+        }
+
+        /// Sets the concavity tolerance.
+        pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+            self.tolerance = tolerance;
+            self
+        }
+
+        /// Sets the voxel resolution, which is clamped between ``1`` and ``MAX_DECOMPOSITION_RESOLUTION``.
+        pub fn with_resolution(mut self, resolution: usize) -> Self {
+            self.resolution = resolution.clamp(1, MAX_DECOMPOSITION_RESOLUTION);
+            self
+        }
+    }
+
+    impl Eq for DecompositionSettings {}
+
+    impl Hash for DecompositionSettings {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.max_parts.hash(state);
+            self.tolerance.to_bits().hash(state);
+            self.resolution.hash(state);
+        }
     }
 
     /// Defines on what depth layer the sprite will be rendered on (``Layer0`` is nearest).
@@ -912,6 +1840,7 @@ pub mod utils {
         Sheet(SpriteSheetSource),
         Colored(Color32),
         Single(Rc<Path>),
+        Atlas(SpriteAtlasSource),
     }
 
     /// Source data for a sprite from a sprite sheet.
@@ -922,6 +1851,112 @@ pub mod utils {
         pub pixel_size: (usize, usize),
     }
 
+    /// Source data for a sprite that was packed into a sprite atlas. The sprite is identified by the path it was loaded from.
+    #[derive(Debug, Clone, PartialEq, Hash, Eq)]
+    pub struct SpriteAtlasSource {
+        pub atlas: Rc<str>,
+        pub sprite: Rc<Path>,
+    }
+
+    /// Defines what happens when a ``SpriteSequence`` reaches its last frame.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+    pub enum SpritePlayMode {
+        /// Stops at the last frame and finishes the animation.
+        #[default]
+        Once,
+        /// Restarts at the first frame.
+        Loop,
+        /// Plays the frames backwards to the first one and then forwards again.
+        PingPong,
+    }
+
+    /// One frame of a ``SpriteSequence``: a pixel region of the sprite sheet and the time in seconds it is shown for.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct SpriteFrame {
+        pub pixel_index: (usize, usize),
+        pub pixel_size: (usize, usize),
+        pub duration: f32,
+    }
+
+    /// A named sequence of frames of one sprite sheet, played by a ``SpriteAnimation``.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SpriteSequence {
+        pub(crate) name: Rc<str>,
+        pub(crate) frames: Vec<SpriteFrame>,
+        pub(crate) mode: SpritePlayMode,
+    }
+
+    impl SpriteSequence {
+        /// Creates a new sequence without any frames.
+        pub fn new(name: impl AsRef<str>, mode: SpritePlayMode) -> Self {
+            Self {
+                name: Rc::from(name.as_ref()),
+                frames: Vec::new(),
+                mode,
+            }
+        }
+
+        /// Adds a frame that shows a pixel region of the sheet for ``duration`` seconds. Frames without a positive duration are skipped.
+        pub fn with_frame(
+            mut self,
+            pixel_index: (usize, usize),
+            pixel_size: (usize, usize),
+            duration: f32,
+        ) -> Self {
+            if !duration.is_finite() || duration <= 0.0 {
+                log::warn!(
+                    "Skipped frame with invalid duration {duration} in sprite sequence {:?}.",
+                    self.name
+                );
+                return self;
+            }
+            self.frames.push(SpriteFrame {
+                pixel_index,
+                pixel_size,
+                duration,
+            });
+            self
+        }
+
+        /// Adds ``count`` equally sized frames that are placed next to each other in a row of the sheet, starting at ``pixel_index``.
+        pub fn with_row(
+            mut self,
+            pixel_index: (usize, usize),
+            pixel_size: (usize, usize),
+            count: usize,
+            duration: f32,
+        ) -> Self {
+            for i in 0..count {
+                self = self.with_frame(
+                    (pixel_index.0 + i * pixel_size.0, pixel_index.1),
+                    pixel_size,
+                    duration,
+                );
+            }
+            self
+        }
+
+        /// The name of the sequence.
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// All frames of the sequence.
+        pub fn frames(&self) -> &[SpriteFrame] {
+            &self.frames
+        }
+
+        /// The play mode of the sequence.
+        pub fn mode(&self) -> SpritePlayMode {
+            self.mode
+        }
+
+        /// The time in seconds that one pass through all frames takes.
+        pub fn duration(&self) -> f32 {
+            self.frames.iter().map(|frame| frame.duration).sum()
+        }
+    }
+
     /// Sprite position on a defined grid or in absolute values.
     #[derive(Debug, Copy, Clone, PartialEq)]
     pub enum SpritePosition {
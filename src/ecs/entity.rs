@@ -6,7 +6,11 @@ use std::ptr::copy_nonoverlapping;
 /// Unique identifier for an entity. This is always attached to an entity as a component and should not be changed.
 pub type EntityID = u64;
 
-impl Component for EntityID {}
+impl Component for EntityID {
+    fn duplicate(&self) -> Option<Self> {
+        Some(*self)
+    }
+}
 
 /// component meta data, one entry in an entity type
 #[allow(unpredictable_function_pointer_comparisons)]
@@ -16,6 +20,7 @@ pub(crate) struct ComponentMetaData {
     pub(crate) size: usize,
     pub(crate) alignment: usize,
     pub(crate) drop_fn: unsafe fn(*mut u8),
+    pub(crate) clone_fn: unsafe fn(*const u8) -> Option<MetaDataComponentEntry>,
 }
 
 impl ComponentMetaData {
@@ -26,6 +31,7 @@ impl ComponentMetaData {
             size: size_of::<T>(),
             alignment: align_of::<T>(),
             drop_fn: drop_fn::<T>,
+            clone_fn: clone_fn::<T>,
         }
     }
 }
@@ -109,6 +115,13 @@ pub(crate) unsafe fn drop_fn<T: Component>(ptr: *mut u8) {
     ptr.cast::<T>().drop_in_place();
 }
 
+/// generic copy constructor that yields a new entry if the component supports duplication
+pub(crate) unsafe fn clone_fn<T: Component>(ptr: *const u8) -> Option<MetaDataComponentEntry> {
+    (*ptr.cast::<T>())
+        .duplicate()
+        .map(MetaDataComponentEntry::from_component)
+}
+
 /// manually memory managed and type erased component data storage
 pub(crate) struct ComponentStorage {
     data: Vec<u8>,
@@ -201,6 +214,19 @@ impl ComponentStorage {
         &self.data[index..index + self.meta_data.size]
     }
 
+    /// creates a duplicate entry of the n'th component if the component type supports it
+    pub(crate) fn duplicate_nth_component(&self, n: usize) -> Option<MetaDataComponentEntry> {
+        debug_assert!(
+            self.component_count() > n,
+            "Index {n} out of bounds (len is {}).",
+            self.component_count()
+        );
+        let index = n * self.stride + self.align_padding;
+
+        // this is safe because the clone function is the correct one
+        unsafe { (self.meta_data.clone_fn)(&self.data[index] as *const u8) }
+    }
+
     /// removes the n'th component and puts the last component data in its place, returns the component
     pub(crate) fn swap_remove_nth_component<T: Component>(&mut self, n: usize) -> T {
         debug_assert!(
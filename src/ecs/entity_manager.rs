@@ -1,12 +1,20 @@
+use crate::ecs::asset_handle::AssetEntry;
+use crate::ecs::asset_loader::*;
 use crate::ecs::entity::*;
 use crate::internal_prelude::*;
 use crate::rendering::data::*;
+use crate::rendering::gltf::{GltfData, GltfImage};
 use crate::rendering::mesh::{Hitbox, Mesh};
-use tobj::{load_mtl, load_obj, GPU_LOAD_OPTIONS};
+use crate::rendering::mesh_cache::MeshCache;
+use crate::utils::file_watcher::FileWatcher;
+use crate::utils::vfs::load_mtl;
 
 /// Identifier for a loaded mesh in the entity manager.
 pub type MeshHandle = u64;
 
+/// Identifier for an independent world of entities in the entity manager.
+pub type WorldID = u64;
+
 /// Creates a component list for entity creation (must use).
 #[macro_export]
 macro_rules! components {
@@ -15,15 +23,29 @@ macro_rules! components {
     };
 }
 
-/// The main manager holding both the ECS containing the enitity data and the asset data ressource registers.
+/// The main manager holding both the ECS worlds containing the enitity data and the asset data ressource registers that are shared by all worlds.
 pub struct EntityManager {
-    pub(crate) ecs: UnsafeCell<ECS>,
+    worlds: AHashMap<WorldID, UnsafeCell<ECS>>,
+    active_world: Cell<WorldID>,
+    next_world: WorldID,
+    next_entity: EntityID,
     mesh_register: AHashMap<MeshHandle, Mesh>,
-    lod_register: AHashMap<MeshHandle, [Mesh; 4]>,
+    lod_register: AHashMap<MeshHandle, Vec<Mesh>>,
+    lod_thresholds: AHashMap<MeshHandle, LODThresholds>,
     material_register: AHashMap<String, Material>,
+    skeleton_register: AHashMap<String, Rc<Skeleton>>,
+    clip_register: AHashMap<String, Rc<SkeletalClip>>,
     pub(crate) texture_map: TextureMap,
     hitbox_register: AHashMap<(HitboxType, Option<MeshHandle>), Hitbox>,
     next_mesh_handle: MeshHandle,
+    asset_entries: AHashMap<AssetID, AssetEntry>,
+    auto_unload: bool,
+    asset_loader: AssetLoader,
+    material_files: AHashSet<PathBuf>,
+    mesh_revisions: AHashMap<MeshHandle, u64>,
+    file_watcher: Option<FileWatcher>,
+    mesh_cache: Option<MeshCache>,
+    simplification_settings: SimplificationSettings,
 }
 
 impl EntityManager {
@@ -34,14 +56,31 @@ impl EntityManager {
         mesh_register.insert(2, Mesh::from_bytes(PLANE_MESH));
         mesh_register.insert(3, Mesh::from_bytes(CUBE_MESH));
 
+        let mut worlds = AHashMap::new();
+        worlds.insert(MAIN_WORLD, UnsafeCell::new(ECS::new()));
+
         Self {
-            ecs: UnsafeCell::new(ECS::new()),
+            worlds,
+            active_world: Cell::new(MAIN_WORLD),
+            next_world: MAIN_WORLD + 1,
+            next_entity: 1,
             mesh_register,
             lod_register: AHashMap::new(),
+            lod_thresholds: AHashMap::new(),
             material_register: AHashMap::new(),
+            skeleton_register: AHashMap::new(),
+            clip_register: AHashMap::new(),
             texture_map: TextureMap::new(),
             hitbox_register: AHashMap::new(),
             next_mesh_handle: 4,
+            asset_entries: AHashMap::new(),
+            auto_unload: false,
+            asset_loader: AssetLoader::new(),
+            material_files: AHashSet::new(),
+            mesh_revisions: AHashMap::new(),
+            file_watcher: None,
+            mesh_cache: None,
+            simplification_settings: SimplificationSettings::default(),
         }
     }
 
@@ -55,7 +94,9 @@ impl EntityManager {
                 .all(|(id1, id2)| id1 != id2),
             "All component types have to be different."
         );
-        let entity = self.ecs.get_mut().create_entity(components);
+        let entity = self.next_entity;
+        self.next_entity += 1;
+        self.ecs_mut().create_entity(entity, components);
         *self.get_component_mut::<EntityID>(entity).unwrap() = entity;
         self.recompute_rigid_body_data(entity);
         entity
@@ -63,22 +104,22 @@ impl EntityManager {
 
     /// Deletes an entity from the register by ``EntityID`` and returns wether or not the removal was successful.
     pub fn delete_entity(&mut self, entity: EntityID) -> bool {
-        self.ecs.get_mut().delete_entity(entity)
+        self.ecs_mut().delete_entity(entity)
     }
 
     /// Yields the component data reference of an entity if present (also returns ``None`` if the entity ID is invalid).
     pub fn get_component<T: Component>(&self, entity: EntityID) -> Option<&T> {
-        unsafe { &*self.ecs.get() }.get_component::<T>(entity)
+        self.ecs().get_component::<T>(entity)
     }
 
     /// Yields the mutable component data reference of an entity if present (also returns ``None`` if the entity ID is invalid). If data is modified that influences engine behavior and requires internal recomputations, you have to do that manually with the managers methods.
     pub fn get_component_mut<T: Component>(&mut self, entity: EntityID) -> Option<&mut T> {
-        self.ecs.get_mut().get_component_mut::<T>(entity)
+        self.ecs_mut().get_component_mut::<T>(entity)
     }
 
     /// Adds a component to an existing entity (returns ``false`` if the component is already present or the ``EntityID`` is invalid).
     pub fn add_component<T: Component>(&mut self, entity: EntityID, component: T) -> bool {
-        let success = self.ecs.get_mut().add_component::<T>(entity, component);
+        let success = self.ecs_mut().add_component::<T>(entity, component);
         if (types_eq::<T, Renderable>() || types_eq::<T, Scale>() || types_eq::<T, RigidBody>())
             && success
         {
@@ -89,12 +130,12 @@ impl EntityManager {
 
     /// Checks wether or not an entity has a component of given type associated with it (also returns ``false`` if the entity ID is invalid).
     pub fn has_component<T: Component>(&self, entity: EntityID) -> bool {
-        unsafe { &*self.ecs.get() }.has_component::<T>(entity)
+        self.ecs().has_component::<T>(entity)
     }
 
     /// Removes a component from an entity and returns the component data if present.
     pub fn remove_component<T: Component>(&mut self, entity: EntityID) -> Option<T> {
-        let removed = self.ecs.get_mut().remove_component::<T>(entity);
+        let removed = self.ecs_mut().remove_component::<T>(entity);
         if removed.is_some() && types_eq::<T, Scale>() {
             self.recompute_rigid_body_data(entity);
         }
@@ -102,8 +143,8 @@ impl EntityManager {
     }
 
     /// Iterator of all the currently stored entity IDs.
-    pub fn all_ids_iter(&self) -> impl Iterator<Item = EntityID> {
-        unsafe { &*self.ecs.get() }.entity_index.keys().copied()
+    pub fn all_ids_iter(&self) -> impl Iterator<Item = EntityID> + use<'_> {
+        self.ecs().entity_index.keys().copied()
     }
 
     /// Iterator of all the currently stored mesh handles.
@@ -116,64 +157,469 @@ impl EntityManager {
         self.material_register.keys().map(|s| s.as_str())
     }
 
-    /// Loads all the meshes in the ``.obj`` file and all the mentioned materials. Returns all the handles to the loaded meshes. If the loading fails, the returned ``Vec<MeshHandle>`` will be empty.
+    /// Creates a new empty world and returns its ID. All worlds share the loaded asset data, but the entities in each world are independent.
+    pub fn create_world(&mut self) -> WorldID {
+        let world = self.next_world;
+        self.next_world += 1;
+        self.worlds.insert(world, UnsafeCell::new(ECS::new()));
+        log::debug!("Created world {world:?}.");
+        world
+    }
+
+    /// Deletes a world and all of its entities. Returns wether or not the world existed. The ``MAIN_WORLD`` can not be deleted. If the deleted world was the active one, the ``MAIN_WORLD`` becomes active.
+    pub fn delete_world(&mut self, world: WorldID) -> bool {
+        if world == MAIN_WORLD {
+            log::warn!("The main world can not be deleted.");
+            return false;
+        }
+        if self.worlds.remove(&world).is_some() {
+            if self.active_world.get() == world {
+                self.active_world.set(MAIN_WORLD);
+            }
+            log::debug!("Deleted world {world:?}.");
+            true
+        } else {
+            log::warn!("World {world:?} not found.");
+            false
+        }
+    }
+
+    /// Sets the world that all of the entity functions and queries of the manager operate on (default is ``MAIN_WORLD``). Returns wether or not the world exists. This does not influence what world the systems process.
+    pub fn set_active_world(&mut self, world: WorldID) -> bool {
+        if self.worlds.contains_key(&world) {
+            self.active_world.set(world);
+            log::trace!("Set active world: {world:?}.");
+            true
+        } else {
+            log::warn!("World {world:?} not found.");
+            false
+        }
+    }
+
+    /// The world that all of the entity functions and queries of the manager currently operate on.
+    pub fn active_world(&self) -> WorldID {
+        self.active_world.get()
+    }
+
+    /// Iterator of all the currently existing world IDs.
+    pub fn all_worlds(&self) -> impl Iterator<Item = WorldID> + use<'_> {
+        self.worlds.keys().copied()
+    }
+
+    /// Moves an entity and all of its components from the active world to another world. The ``EntityID`` stays the same. Returns wether or not the move was successful.
+    pub fn move_entity(&mut self, entity: EntityID, target_world: WorldID) -> bool {
+        if target_world == self.active_world.get() {
+            return self.ecs().entity_index.contains_key(&entity);
+        }
+        if !self.worlds.contains_key(&target_world) {
+            log::warn!("World {target_world:?} not found.");
+            return false;
+        }
+        if let Some(components) = self.ecs_mut().take_entity(entity) {
+            self.worlds
+                .get_mut(&target_world)
+                .unwrap()
+                .get_mut()
+                .create_entity(entity, &components);
+            log::debug!("Moved entity {entity:?} to world {target_world:?}.");
+            true
+        } else {
+            log::warn!("EntityID {entity:?} not found.");
+            false
+        }
+    }
+
+    /// Copies an entity from the active world into another world (can also be the active world) and returns the ``EntityID`` of the copy. Only components that support duplication are copied (see ``Component::duplicate``).
+    pub fn copy_entity(&mut self, entity: EntityID, target_world: WorldID) -> Option<EntityID> {
+        if !self.worlds.contains_key(&target_world) {
+            log::warn!("World {target_world:?} not found.");
+            return None;
+        }
+        let components = self.ecs().duplicate_entity(entity);
+        if components.is_none() {
+            log::warn!("EntityID {entity:?} not found.");
+            return None;
+        }
+        let copy = self.next_entity;
+        self.next_entity += 1;
+
+        let target_ecs = self.worlds.get_mut(&target_world).unwrap().get_mut();
+        target_ecs.create_entity(copy, &components.unwrap());
+        *target_ecs.get_component_mut::<EntityID>(copy).unwrap() = copy;
+
+        log::debug!("Copied entity {entity:?} to world {target_world:?} as {copy:?}.");
+        Some(copy)
+    }
+
+    /// Loads all the meshes in the ``.obj`` file and all the mentioned materials. Returns all the handles to the loaded meshes. If the loading fails, the returned ``Vec<MeshHandle>`` will be empty. ``.gltf`` and ``.glb`` files are loaded like with ``load_gltf`` without spawning any entities.
     pub fn load_asset_file(&mut self, file_path: impl AsRef<Path>) -> Vec<MeshHandle> {
         let file_path = file_path.as_ref();
+        match LoadedAssetFile::load(file_path, self.mesh_cache.as_ref()) {
+            Ok(file) => self.register_asset_file(file, file_path),
+            Err(msg) => {
+                log::error!("Error loading asset file: {msg}.");
+                Vec::new()
+            }
+        }
+    }
 
-        let (models, materials) = match load_obj(file_path, &GPU_LOAD_OPTIONS) {
-            Ok((ms, mtl_load_result)) => match mtl_load_result {
-                Ok(mtls) => (ms, mtls),
-                Err(msg) => {
-                    log::error!("Error loading asset file: {msg:?}.");
-                    return Vec::new();
-                }
-            },
+    /// Starts loading an asset file like ``load_asset_file`` on a background thread. The returned handle contains the mesh handles once the loading is finished. An ``AssetReady`` or ``AssetLoadFailed`` event is triggered when the loading is finished. Returns ``None`` if the loading threads could not be started.
+    pub fn load_asset_file_async(&mut self, file_path: impl AsRef<Path>) -> Option<PendingHandle> {
+        let file_path = file_path.as_ref().to_path_buf();
+        log::debug!("Started loading asset file {file_path:?} asynchronously.");
+        self.submit_load_job(LoadJob::AssetFile(file_path, self.mesh_cache.clone()))
+    }
+
+    /// queues a job for the loading threads and logs the error if that fails
+    fn submit_load_job(&mut self, job: LoadJob) -> Option<PendingHandle> {
+        match self.asset_loader.submit(job) {
+            Ok(handle) => Some(handle),
             Err(msg) => {
-                log::error!("Error loading asset file: {msg:?}.");
-                return Vec::new();
+                log::error!("Error starting asynchronous loading: {msg}");
+                None
             }
-        };
+        }
+    }
 
-        let mut handles = Vec::with_capacity(models.len());
+    /// uploads the textures and stores the meshes and materials of a parsed asset file
+    fn register_asset_file(&mut self, file: LoadedAssetFile, file_path: &Path) -> Vec<MeshHandle> {
+        self.material_files.extend(file.material_files);
+        for (name, source, image) in file.textures {
+            self.texture_map
+                .add_material_texture_image(&name, &image, source.as_deref());
+            log::debug!("Loaded material texture: {name:?}.");
+        }
 
-        for model in models {
-            let mtl_name = model
-                .mesh
-                .material_id
-                .map(|index| materials[index].name.clone());
+        for (mtl_name, material) in file.materials {
+            if self.material_register.contains_key(&mtl_name) {
+                log::warn!("Material '{mtl_name:?}' is already loaded and is overwritten.");
+            }
+            log::debug!("Loaded material {mtl_name:?}.");
+            self.material_register.insert(mtl_name, material);
+        }
 
+        let mut handles = Vec::with_capacity(file.meshes.len());
+        for mesh in file.meshes {
             let handle = self.next_mesh_handle;
             self.next_mesh_handle += 1;
+            log::debug!("Loaded mesh {:?} from file {:?}.", mesh.name, file_path);
+            self.mesh_register.insert(handle, mesh);
+            handles.push(handle);
+        }
+        handles
+    }
 
-            self.mesh_register.insert(
-                handle,
-                Mesh::from_obj_data(&model, Rc::from(file_path), mtl_name),
-            );
-            log::debug!("Loaded mesh {:?} from file {:?}.", model.name, file_path);
+    /// Loads all the meshes, materials and textures (embedded and external) in a ``.gltf`` or ``.glb`` file. Every triangle primitive of a glTF mesh is loaded as a separate mesh and the metallic-roughness materials are loaded as ``Material``s with ``PbrMaterial`` parameters. If ``spawn_nodes`` is set, an entity with ``Position``, ``Orientation``, ``Scale`` and an inheriting ``Renderable`` is created in the active world for every primitive of every mesh node in the default scene, using the global transform of the node. Skins and animations are loaded as skeletons and skeletal clips that can be accessed by name, entities of skinned nodes also get a ``SkeletalAnimator`` and are placed by their joints instead of the node transform. Morph targets are loaded with their names from the ``targetNames`` extras and entities of meshes with morph targets get ``MorphWeights`` with the default weights of the node. Returns the handles to the loaded meshes and the IDs of the spawned entities. If the loading fails, both will be empty.
+    pub fn load_gltf(
+        &mut self,
+        file_path: impl AsRef<Path>,
+        spawn_nodes: bool,
+    ) -> (Vec<MeshHandle>, Vec<EntityID>) {
+        let file_path = file_path.as_ref();
+        let data = match GltfData::load(file_path) {
+            Ok(data) => data,
+            Err(msg) => {
+                log::error!("Error loading glTF file {file_path:?}: {msg}");
+                return (Vec::new(), Vec::new());
+            }
+        };
 
-            handles.push(handle);
+        for image in data.images.iter() {
+            match image {
+                GltfImage::External(path) => self.texture_map.add_material_texture(path),
+                GltfImage::Embedded { name, bytes } => {
+                    self.texture_map.add_embedded_material_texture(name, bytes)
+                }
+            };
         }
 
-        for mtl in materials {
-            let mtl_name = mtl.name.clone();
+        for (mtl_name, material) in data.materials {
             if self.material_register.contains_key(&mtl_name) {
                 log::warn!("Material '{mtl_name:?}' is already loaded and is overwritten.");
             }
+            log::debug!("Loaded material {mtl_name:?}.");
+            self.material_register.insert(mtl_name, material);
+        }
 
-            self.material_register
-                .insert(mtl_name, Material::from_mtl(&mtl));
+        let skeletons = data
+            .skeletons
+            .into_iter()
+            .map(|skeleton| {
+                skeleton.map(|skeleton| {
+                    let skeleton = Rc::new(skeleton);
+                    let name = skeleton.name().to_string();
+                    if self.skeleton_register.contains_key(&name) {
+                        log::warn!("Skeleton {name:?} is already loaded and is overwritten.");
+                    }
+                    log::debug!("Loaded skeleton {name:?}.");
+                    self.skeleton_register.insert(name, skeleton.clone());
+                    skeleton
+                })
+            })
+            .collect_vec();
 
-            log::debug!("Loaded material {:?}.", mtl.name);
-            self.load_material_textures_from_mtl(&mtl, file_path);
+        for clip in data.clips {
+            let name = clip.name().to_string();
+            if self.clip_register.contains_key(&name) {
+                log::warn!("Skeletal clip {name:?} is already loaded and is overwritten.");
+            }
+            log::debug!("Loaded skeletal clip {name:?}.");
+            self.clip_register.insert(name, Rc::new(clip));
         }
 
-        handles
+        let mut handles = Vec::new();
+        let mut primitive_handles = Vec::with_capacity(data.meshes.len());
+        for primitives in data.meshes {
+            let mut mesh_handles = Vec::with_capacity(primitives.len());
+            for mesh in primitives {
+                let handle = self.next_mesh_handle;
+                self.next_mesh_handle += 1;
+                log::debug!("Loaded mesh {:?} from file {:?}.", mesh.name, file_path);
+                self.mesh_register.insert(handle, mesh);
+                mesh_handles.push(handle);
+            }
+            handles.extend_from_slice(&mesh_handles);
+            primitive_handles.push(mesh_handles);
+        }
+
+        let mut entities = Vec::new();
+        if spawn_nodes {
+            for node in data.nodes {
+                let skeleton = node
+                    .skin
+                    .and_then(|skin| skeletons.get(skin).cloned().flatten());
+                // skinned meshes are placed by the joints, so the transform of their node is ignored
+                let (position, orientation, scale) = if skeleton.is_some() {
+                    Default::default()
+                } else {
+                    decompose_transform(&node.transform)
+                };
+                for handle in primitive_handles.get(node.mesh).into_iter().flatten() {
+                    let mesh = &self.mesh_register[handle];
+                    let has_material = mesh.material_name.is_some();
+                    let renderable = Renderable {
+                        mesh_type: MeshType::Custom(*handle),
+                        material_source: if has_material {
+                            MaterialSource::Inherit
+                        } else {
+                            MaterialSource::default()
+                        },
+                        ..Default::default()
+                    };
+                    let has_morph_targets = !mesh.morph_targets.is_empty();
+                    let entity = match skeleton.as_ref().filter(|_| mesh.skin.is_some()) {
+                        Some(skeleton) => self.create_entity(components!(
+                            position,
+                            orientation,
+                            scale,
+                            renderable,
+                            SkeletalAnimator::new(skeleton.clone())
+                        )),
+                        None => self.create_entity(components!(
+                            position,
+                            orientation,
+                            scale,
+                            renderable
+                        )),
+                    };
+                    if has_morph_targets {
+                        self.add_component(entity, MorphWeights::new(node.morph_weights.clone()));
+                    }
+                    entities.push(entity);
+                }
+                log::debug!("Spawned glTF node {:?}.", node.name);
+            }
+        }
+
+        (handles, entities)
+    }
+
+    /// The skeleton with the given name that was loaded from the skin of a glTF file.
+    pub fn skeleton(&self, name: impl AsRef<str>) -> Option<Rc<Skeleton>> {
+        self.skeleton_register.get(name.as_ref()).cloned()
+    }
+
+    /// The skeletal animation clip with the given name that was loaded from a glTF file.
+    pub fn skeletal_clip(&self, name: impl AsRef<str>) -> Option<Rc<SkeletalClip>> {
+        self.clip_register.get(name.as_ref()).cloned()
+    }
+
+    /// Iterator over the names of all loaded skeletal animation clips.
+    pub fn all_skeletal_clip_names(&self) -> impl Iterator<Item = &str> {
+        self.clip_register.keys().map(|name| name.as_str())
+    }
+
+    /// Generates a procedural primitive mesh with normals, tangents and texture coordinates and returns the handle to it. The mesh can be used like any loaded mesh, e.g. for LODs, hitboxes and inertia computation.
+    pub fn load_procedural_mesh(&mut self, shape: ProceduralMesh) -> MeshHandle {
+        let mesh = shape.generate();
+        let handle = self.next_mesh_handle;
+        self.next_mesh_handle += 1;
+        log::debug!("Generated procedural mesh {:?}.", mesh.name);
+        self.mesh_register.insert(handle, mesh);
+        handle
+    }
+
+    /// Registers mesh data that was built at runtime and returns the handle to it. The mesh can be used like any loaded mesh, e.g. for LODs, hitboxes and inertia computation. Returns ``None`` if the data is inconsistent (e.g. indices out of bounds or attribute counts that do not match the vertex count).
+    pub fn load_mesh_data(&mut self, data: MeshData) -> Option<MeshHandle> {
+        match data.into_mesh() {
+            Ok(mesh) => {
+                let handle = self.next_mesh_handle;
+                self.next_mesh_handle += 1;
+                log::debug!("Loaded runtime mesh {:?}.", mesh.name);
+                self.mesh_register.insert(handle, mesh);
+                Some(handle)
+            }
+            Err(msg) => {
+                log::warn!("Invalid mesh data: {msg}.");
+                None
+            }
+        }
+    }
+
+    /// Replaces the data of a loaded mesh while keeping its handle, so all entities using the mesh render the new geometry from the next frame on. The GPU buffers of the mesh are refreshed and loaded LODs and hitboxes of the mesh are regenerated (LOD generation is expensive, so meshes that change every frame should not have LODs). Rigid bodies using the mesh keep their inertia data until ``recompute_rigid_body_data`` is called. The built-in meshes can not be updated. Returns wether or not the mesh was updated.
+    pub fn update_mesh(&mut self, handle: MeshHandle, data: MeshData) -> bool {
+        if handle <= MeshType::Cube.mesh_handle() {
+            log::warn!("Built-in meshes can not be updated.");
+            return false;
+        }
+        if !self.mesh_register.contains_key(&handle) {
+            log::warn!("Required mesh data not present.");
+            return false;
+        }
+        match data.into_mesh() {
+            Ok(mesh) => {
+                self.replace_mesh(handle, mesh);
+                true
+            }
+            Err(msg) => {
+                log::warn!("Invalid mesh data, keeping the previous version: {msg}.");
+                false
+            }
+        }
+    }
+
+    /// Applies processing operations in the given order to a loaded mesh, e.g. to repair imported geometry before generating LODs and hitboxes. The mesh is replaced behind its handle like with ``update_mesh``, so GPU buffers, loaded LODs and hitboxes are refreshed. The processed mesh is no longer associated with its source file, so it is not hot reloaded and its LODs and hitboxes are not cached. The built-in meshes can not be processed. Returns wether or not the mesh was processed.
+    pub fn process_mesh(&mut self, handle: MeshHandle, operations: &[MeshOperation]) -> bool {
+        if handle <= MeshType::Cube.mesh_handle() {
+            log::warn!("Built-in meshes can not be processed.");
+            return false;
+        }
+        if let Some(mesh) = self.mesh_register.get(&handle) {
+            let mut mesh = mesh.clone();
+            for operation in operations {
+                mesh.apply_operation(*operation);
+            }
+            // the cache entries of the source file belong to the unprocessed geometry
+            mesh.source_file = Arc::from(Path::new("internal"));
+            self.replace_mesh(handle, mesh);
+            true
+        } else {
+            log::warn!("Required mesh data not present.");
+            false
+        }
+    }
+
+    /// Cuts a loaded mesh along a plane in mesh space into two new meshes and returns their handles, the front half (in direction of the plane normal) comes first. All vertex attributes are interpolated along the cut and the cross-sections are closed with cap faces that use a planar texture projection, so closed meshes stay closed. The original mesh is kept. Returns ``None`` if the mesh data is not present or the plane does not cut through the mesh.
+    pub fn slice_mesh(
+        &mut self,
+        handle: MeshHandle,
+        plane: &SlicePlane,
+    ) -> Option<(MeshHandle, MeshHandle)> {
+        let opt_mesh = self.mesh_register.get(&handle);
+        if opt_mesh.is_none() {
+            log::warn!("Required mesh data not present.");
+            return None;
+        }
+        let (front, back) = opt_mesh.unwrap().sliced(plane)?;
+        let front_handle = self.next_mesh_handle;
+        let back_handle = front_handle + 1;
+        self.next_mesh_handle += 2;
+        self.mesh_register.insert(front_handle, front);
+        self.mesh_register.insert(back_handle, back);
+        Some((front_handle, back_handle))
+    }
+
+    /// Cuts an entity with a ``Renderable`` along a plane in world space into two new entities, the front half (in direction of the plane normal) comes first. Both halves are copies of the entity that render one of the meshes created by ``slice_mesh``. If the entity has a ``Collider``, the hitbox is loaded for both new meshes and the rigid body data of both halves is recomputed, their positions are adjusted so they stay in place when the center of mass moves. The original entity is not modified, so it usually is deleted afterwards. Returns ``None`` if the slicing failed.
+    pub fn slice_entity(
+        &mut self,
+        entity: EntityID,
+        plane: &SlicePlane,
+    ) -> Option<(EntityID, EntityID)> {
+        let opt_renderable = self.ecs().get_component::<Renderable>(entity);
+        if opt_renderable.is_none() {
+            log::warn!("Entity {entity:?} has no renderable to slice.");
+            return None;
+        }
+        let handle = opt_renderable.unwrap().mesh_type.mesh_handle();
+        let position = self
+            .get_component::<Position>(entity)
+            .copied()
+            .unwrap_or_default();
+        let scale = self
+            .get_component::<Scale>(entity)
+            .copied()
+            .unwrap_or_default();
+        let orientation = self
+            .get_component::<Orientation>(entity)
+            .copied()
+            .unwrap_or_default();
+        let center_of_mass = self
+            .get_component::<RigidBody>(entity)
+            .map(|body| body.center_of_mass)
+            .unwrap_or_default();
+
+        // transform the plane into mesh space
+        let model = calc_model_matrix(&position, &scale, &orientation, &center_of_mass);
+        let inv_model = model.try_inverse()?;
+        let local_plane = SlicePlane {
+            point: mult_mat4_vec3(&inv_model, &plane.point),
+            normal: model.fixed_view::<3, 3>(0, 0).transpose() * plane.normal,
+            ..*plane
+        };
+        let halves = self.slice_mesh(handle, &local_plane);
+        if halves.is_none() {
+            log::warn!("The plane does not cut through entity {entity:?}.");
+            return None;
+        }
+        let (front, back) = halves.unwrap();
+
+        let active_world = self.active_world();
+        let mut entities = Vec::with_capacity(2);
+        for half in [front, back] {
+            let copy = self.copy_entity(entity, active_world)?;
+            self.get_component_mut::<Renderable>(copy)
+                .unwrap()
+                .mesh_type = MeshType::Custom(half);
+            if let Some(collider) = self.get_component::<Collider>(copy) {
+                self.load_hitbox(collider.hitbox_type, Some(half));
+            }
+            self.recompute_rigid_body_data(copy);
+            if let Some(body) = self.get_component::<RigidBody>(copy) {
+                // keep the rotation around the new center of mass from moving the half
+                let shift = center_of_mass - body.center_of_mass;
+                let offset = shift - orientation.rotation_matrix().fixed_view::<3, 3>(0, 0) * shift;
+                if let Some(position) = self.get_component_mut::<Position>(copy) {
+                    *position.data_mut() += offset;
+                }
+            }
+            entities.push(copy);
+        }
+        log::debug!("Sliced entity {entity:?} into {:?}.", entities);
+        Some((entities[0], entities[1]))
+    }
+
+    /// Checks a loaded mesh or one of its LODs for duplicate vertices, degenerate or flipped triangles, open borders, non-manifold edges and inconsistent winding. Returns ``None`` if the mesh data is not present.
+    pub fn validate_mesh(&self, handle: MeshHandle, lod: LOD) -> Option<MeshReport> {
+        self.mesh_from_handle(handle, lod).map(Mesh::validate)
     }
 
     /// Deletes a loaded mesh from the internal register. Returns wether or not the mesh existed. Also deletes potentially generated LODs for that mesh if present.
     pub fn delete_mesh(&mut self, handle: MeshHandle) -> bool {
         if let Some(mesh) = self.mesh_register.remove(&handle) {
             self.lod_register.remove(&handle);
+            self.lod_thresholds.remove(&handle);
+            self.mesh_revisions.remove(&handle);
+            self.asset_entries.remove(&AssetID::Mesh(handle));
             log::debug!(
                 "Deleted mesh and associated LODs from register: {:?}",
                 mesh.name
@@ -185,11 +631,95 @@ impl EntityManager {
         }
     }
 
-    /// Loads all the material data in a ``.mtl`` file and returns wether or not the file could be loaded.
+    /// Exports a loaded mesh or one of its generated LODs to an ``.obj`` file with positions, normals, texture coordinates and indices. If the mesh has a loaded native material, a ``.mtl`` file with the same name is written next to it (textures are referenced by their file names and not copied). Returns wether or not the export was successful.
+    pub fn export_mesh(&self, handle: MeshHandle, lod: LOD, file_path: impl AsRef<Path>) -> bool {
+        let file_path = file_path.as_ref();
+        if file_path.file_name().is_none() {
+            log::warn!("Export path {file_path:?} has no file name.");
+            return false;
+        }
+        let mesh = match self.mesh_from_handle(handle, lod) {
+            Some(mesh) => mesh,
+            None => {
+                log::warn!("Required mesh data not present for export.");
+                return false;
+            }
+        };
+        let material = mesh
+            .material_name
+            .as_ref()
+            .and_then(|name| self.material_register.get(name).map(|mtl| (name, mtl)));
+
+        let mtl_file = file_path.with_extension("mtl");
+        let mtl_file_name = mtl_file.file_name().unwrap().to_string_lossy();
+        let obj = mesh.to_obj(material.map(|(name, _)| (mtl_file_name.as_ref(), name.as_str())));
+        if let Err(msg) = std::fs::write(file_path, obj) {
+            log::error!(
+                "Error exporting mesh {:?} to {file_path:?}: {msg}.",
+                mesh.name
+            );
+            return false;
+        }
+        if let Some((name, mtl)) = material {
+            if let Err(msg) = std::fs::write(&mtl_file, mtl.to_mtl(name)) {
+                log::error!("Error exporting material {name:?} to {mtl_file:?}: {msg}.");
+                return false;
+            }
+        }
+        log::debug!(
+            "Exported mesh {:?} with LOD {lod:?} to {file_path:?}.",
+            mesh.name
+        );
+        true
+    }
+
+    /// Exports a loaded convex mesh hitbox to an ``.obj`` file for debugging colliders. Convex decompositions are written with one object per part. Sphere hitboxes can not be exported. Returns wether or not the export was successful.
+    pub fn export_hitbox(
+        &self,
+        hitbox_type: HitboxType,
+        opt_handle: Option<MeshHandle>,
+        file_path: impl AsRef<Path>,
+    ) -> bool {
+        let file_path = file_path.as_ref();
+        let parts = match self.hitbox_register.get(&(hitbox_type, opt_handle)) {
+            Some(Hitbox::Sphere(_)) => {
+                log::warn!("Sphere hitboxes can not be exported.");
+                return false;
+            }
+            Some(hitbox) => hitbox.convex_parts(),
+            None => {
+                log::warn!("Required hitbox data not present for export.");
+                return false;
+            }
+        };
+        // the settings of a decomposition are not part of the object name
+        let debug_name = format!("{hitbox_type:?}");
+        let name = debug_name.split('(').next().unwrap();
+        let mut obj = String::new();
+        let mut vertex_offset = 0;
+        for (i, part) in parts.iter().enumerate() {
+            let part_name = if parts.len() > 1 {
+                format!("{name}_{i}")
+            } else {
+                name.to_string()
+            };
+            obj += &part.to_obj(&part_name, vertex_offset);
+            vertex_offset += part.vertices.len();
+        }
+        if let Err(msg) = std::fs::write(file_path, obj) {
+            log::error!("Error exporting hitbox {hitbox_type:?} to {file_path:?}: {msg}.");
+            return false;
+        }
+        log::debug!("Exported hitbox {hitbox_type:?} to {file_path:?}.");
+        true
+    }
+
+    /// Loads all the material data in a ``.mtl`` file and returns wether or not the file could be loaded. Materials with a roughness or metalness (``Pr``, ``Pm``, ``map_Pr``, ``map_Pm``) get ``PbrMaterial`` parameters with the ``Kd`` base color and the ``Ke`` emission.
     pub fn load_materials(&mut self, path: impl AsRef<Path>) -> bool {
         let path = path.as_ref();
         match load_mtl(path) {
             Ok((materials, _)) => {
+                self.material_files.insert(path.to_path_buf());
                 for mtl in materials {
                     let mtl_name = mtl.name.clone();
                     if self.material_register.contains_key(&mtl_name) {
@@ -236,12 +766,22 @@ impl EntityManager {
             full_texture_path.set_file_name(normal_texture);
             self.texture_map.add_material_texture(full_texture_path);
         }
+        for pbr_texture in PBR_MTL_TEXTURE_KEYS
+            .iter()
+            .filter_map(|key| mtl.unknown_param.get(*key))
+        {
+            let mut full_texture_path = PathBuf::from(file_path);
+            full_texture_path.set_file_name(pbr_texture);
+            self.texture_map.add_material_texture(full_texture_path);
+        }
     }
 
     /// Deletes a stored material and returns wether or not the material was present.
     pub fn delete_material(&mut self, name: impl AsRef<str>) -> bool {
         let name = name.as_ref();
         if let Some(mtl) = self.material_register.remove(name) {
+            self.asset_entries
+                .remove(&AssetID::Material(name.to_string()));
             log::debug!("Deleted material {name:?}.");
             self.delete_material_textures_from_material(mtl);
             true
@@ -253,32 +793,37 @@ impl EntityManager {
 
     /// Deletes all the referenced material textures in a stored material.
     fn delete_material_textures_from_material(&mut self, mtl: Material) {
-        if let Ambient::Texture(file_name) = mtl.ambient {
-            self.texture_map.delete_material_texture(file_name);
-        }
-        if let Diffuse::Texture(file_name) = mtl.diffuse {
-            self.texture_map.delete_material_texture(file_name);
-        }
-        if let Specular::Texture(file_name) = mtl.specular {
-            self.texture_map.delete_material_texture(file_name);
-        }
-        if let Some(file_name) = mtl.normal_texture {
+        let phong_textures = [
+            mtl.ambient_texture(true),
+            mtl.diffuse_texture(true),
+            mtl.specular_texture(true),
+            mtl.normal_texture(true),
+        ];
+        // textures can be shared by several parameters, e.g. the base color of converted materials
+        for file_name in phong_textures
+            .into_iter()
+            .chain(mtl.pbr_textures(true))
+            .flatten()
+            .unique()
+        {
             self.texture_map.delete_material_texture(file_name);
         }
     }
 
-    /// Generates all LODs for a loaded mesh. Returns wether or not the given mesh was present and LODs were loaded.
+    /// Generates all LODs for a loaded mesh with the current ``SimplificationSettings``. Returns wether or not the given mesh was present and LODs were loaded.
     pub fn load_lods(&mut self, handle: MeshHandle) -> bool {
-        let opt_mesh = self.mesh_from_handle(handle, LOD::None);
-        if opt_mesh.is_some() {
-            let mesh = opt_mesh.unwrap();
+        if let Some(mesh) = self.mesh_from_handle(handle, LOD::None) {
             if self.lod_register.contains_key(&handle) {
                 log::warn!("LOD data already present for mesh {:?}.", mesh.name);
                 return false;
             }
-            let lod_array = mesh.generate_lods();
+            let lods = MeshCache::lods_or_generate(
+                self.mesh_cache.as_ref(),
+                mesh,
+                &self.simplification_settings,
+            );
             log::debug!("Loaded LODs in register for mesh: {:?}.", mesh.name);
-            self.lod_register.insert(handle, lod_array);
+            self.lod_register.insert(handle, lods);
             true
         } else {
             log::warn!("Required mesh data not present.");
@@ -286,6 +831,69 @@ impl EntityManager {
         }
     }
 
+    /// Starts generating all LODs for a loaded mesh on a background thread. Returns ``None`` if the mesh is not present, the LODs are already loaded or the loading threads could not be started. An ``AssetReady`` or ``AssetLoadFailed`` event is triggered when the generation is finished.
+    pub fn load_lods_async(&mut self, handle: MeshHandle) -> Option<PendingHandle> {
+        if self.lod_register.contains_key(&handle) {
+            log::warn!("LOD data already present for mesh {handle:?}.");
+            return None;
+        }
+        if let Some(mesh) = self.mesh_register.get(&handle) {
+            log::debug!(
+                "Started generating LODs for mesh {:?} asynchronously.",
+                mesh.name
+            );
+            let job = LoadJob::Lods(
+                handle,
+                self.mesh_revision(handle),
+                mesh.clone(),
+                self.simplification_settings.clone(),
+                self.mesh_cache.clone(),
+            );
+            self.submit_load_job(job)
+        } else {
+            log::warn!("Required mesh data not present.");
+            None
+        }
+    }
+
+    /// Sets the settings that are used for generating LODs with ``load_lods`` and ``load_lods_async``. Already loaded LODs are kept until they are deleted and loaded again.
+    pub fn set_simplification_settings(&mut self, settings: SimplificationSettings) {
+        log::debug!("Set simplification settings: {settings:?}.");
+        self.simplification_settings = settings;
+    }
+
+    /// Makes the settings that are used for generating LODs available.
+    pub fn simplification_settings(&self) -> &SimplificationSettings {
+        &self.simplification_settings
+    }
+
+    /// Sets the thresholds that are used to select the LOD level of entities with ``LOD::Auto`` that render the given mesh, ``None`` resets them to the default thresholds. Returns wether or not the mesh is present.
+    pub fn set_lod_thresholds(
+        &mut self,
+        handle: MeshHandle,
+        thresholds: Option<LODThresholds>,
+    ) -> bool {
+        if !self.mesh_register.contains_key(&handle) {
+            log::warn!("Required mesh data not present.");
+            return false;
+        }
+        match thresholds {
+            Some(thresholds) => self.lod_thresholds.insert(handle, thresholds),
+            None => self.lod_thresholds.remove(&handle),
+        };
+        true
+    }
+
+    /// the thresholds for automatic LOD selection of a mesh
+    pub(crate) fn lod_thresholds(&self, handle: MeshHandle) -> Option<&LODThresholds> {
+        self.lod_thresholds.get(&handle)
+    }
+
+    /// Yields the number of loaded LOD levels for a mesh (not including the original mesh).
+    pub fn lod_count(&self, handle: MeshHandle) -> usize {
+        self.lod_register.get(&handle).map_or(0, Vec::len)
+    }
+
     /// Deletes the stored LODs for a given mesh from the internal registers and returns wether or not that mesh was present.
     pub fn delete_lods(&mut self, handle: MeshHandle) -> bool {
         let success = self.lod_register.remove(&handle).is_some();
@@ -306,7 +914,7 @@ impl EntityManager {
         {
             let hitbox = if let Some(handle) = opt_handle {
                 if let Some(mesh) = self.mesh_register.get(&handle) {
-                    mesh.generate_hitbox(&hitbox_type)
+                    MeshCache::hitbox_or_generate(self.mesh_cache.as_ref(), mesh, hitbox_type)
                 } else {
                     log::warn!("Mesh data not present for loading the hitbox {hitbox_type:?}.");
                     return false;
@@ -330,6 +938,37 @@ impl EntityManager {
         }
     }
 
+    /// Starts generating a hitbox that depends on a loaded mesh on a background thread. Returns ``None`` if the mesh is not present, the hitbox is already loaded or the loading threads could not be started. An ``AssetReady`` or ``AssetLoadFailed`` event is triggered when the generation is finished.
+    pub fn load_hitbox_async(
+        &mut self,
+        hitbox_type: HitboxType,
+        handle: MeshHandle,
+    ) -> Option<PendingHandle> {
+        if self
+            .hitbox_register
+            .contains_key(&(hitbox_type, Some(handle)))
+        {
+            return None;
+        }
+        if let Some(mesh) = self.mesh_register.get(&handle) {
+            log::debug!(
+                "Started generating hitbox {hitbox_type:?} for mesh {:?} asynchronously.",
+                mesh.name
+            );
+            let job = LoadJob::Hitbox(
+                hitbox_type,
+                handle,
+                self.mesh_revision(handle),
+                mesh.clone(),
+                self.mesh_cache.clone(),
+            );
+            self.submit_load_job(job)
+        } else {
+            log::warn!("Mesh data not present for loading the hitbox {hitbox_type:?}.");
+            None
+        }
+    }
+
     /// Deletes a loaded hitbox and returns wether or not the hitbox was actually present.
     pub fn delete_hitbox(
         &mut self,
@@ -347,6 +986,8 @@ impl EntityManager {
             .remove(&(hitbox_type, opt_handle))
             .is_some()
         {
+            self.asset_entries
+                .remove(&AssetID::Hitbox(hitbox_type, opt_handle));
             let mesh_name = opt_handle.map(|handle| self.mesh_name_from_handle(handle).unwrap());
             log::debug!("Deleted hitbox {hitbox_type:?} from register for mesh {mesh_name:?}");
             true
@@ -362,6 +1003,8 @@ impl EntityManager {
 
     /// Deletes a stored texture and returns wether or not the texture was present.
     pub fn delete_texture(&mut self, texture: &Texture) -> bool {
+        self.asset_entries
+            .remove(&AssetID::Texture(texture.clone()));
         self.texture_map.delete_texture(texture)
     }
 
@@ -382,6 +1025,7 @@ impl EntityManager {
 
     /// Deletes a stored sprite and returns wether or not the deletion was successful.
     pub fn delete_sprite(&mut self, path: &Rc<Path>) -> bool {
+        self.asset_entries.remove(&AssetID::Sprite(path.clone()));
         self.texture_map.delete_sprite(path)
     }
 
@@ -392,15 +1036,481 @@ impl EntityManager {
 
     /// Deletes a stored sprite sheet and returns wether or not the deletion was successful.
     pub fn delete_sprite_sheet(&mut self, path: &Rc<Path>) -> bool {
+        self.asset_entries
+            .remove(&AssetID::SpriteSheet(path.clone()));
         self.texture_map.delete_sheet(path)
     }
 
+    /// Packs the sprite images from the given files into one atlas texture that is stored under the given name and returns wether or not the packing was successful. The sprites can be rendered with ``SpriteSource::Atlas`` and share one texture, so they can be drawn in fewer batches.
+    pub fn load_sprite_atlas(
+        &mut self,
+        name: &Rc<str>,
+        paths: &[Rc<Path>],
+        settings: SpriteAtlasSettings,
+    ) -> bool {
+        self.texture_map.add_atlas(name, paths, settings)
+    }
+
+    /// Deletes a stored sprite atlas and returns wether or not the deletion was successful.
+    pub fn delete_sprite_atlas(&mut self, name: &Rc<str>) -> bool {
+        self.asset_entries
+            .remove(&AssetID::SpriteAtlas(name.clone()));
+        self.texture_map.delete_atlas(name)
+    }
+
+    /// Starts loading the skybox textures from input texture paths ``[right, left, top, bottom, front, back]`` on a background thread. Once the loading is finished, the skybox is set as the current skybox of the rendering system and an ``AssetReady`` or ``AssetLoadFailed`` event is triggered. Returns ``None`` if the loading threads could not be started.
+    pub fn load_skybox_async(&mut self, paths: [impl AsRef<Path>; 6]) -> Option<PendingHandle> {
+        let paths = paths.map(|path| path.as_ref().to_path_buf());
+        self.submit_load_job(LoadJob::Skybox(paths))
+    }
+
+    /// The number of asynchronous loading operations that are not finished yet.
+    pub fn pending_load_count(&self) -> usize {
+        self.asset_loader.pending_count()
+    }
+
+    /// stores the data of all finished background loading jobs and does the necessary gl uploads
+    pub(crate) fn finish_async_loads(&mut self) -> Vec<FinishedLoad> {
+        let mut finished_loads = Vec::new();
+        for (handle, result) in self.asset_loader.poll() {
+            let mut skybox = None;
+            let assets = match result {
+                Ok(LoadOutput::AssetFile(path, file)) => Some(
+                    self.register_asset_file(file, &path)
+                        .into_iter()
+                        .map(AssetID::Mesh)
+                        .collect_vec(),
+                ),
+                Ok(LoadOutput::Lods(mesh_handle, revision, lods)) => {
+                    if self.mesh_register.contains_key(&mesh_handle)
+                        && self.mesh_revision(mesh_handle) != revision
+                    {
+                        log::warn!("Mesh {mesh_handle:?} was replaced while generating LODs.");
+                        None
+                    } else if self.mesh_register.contains_key(&mesh_handle) {
+                        if !self.lod_register.contains_key(&mesh_handle) {
+                            self.lod_register.insert(mesh_handle, lods);
+                            log::debug!(
+                                "Loaded LODs in register for mesh handle: {mesh_handle:?}."
+                            );
+                        }
+                        Some(vec![AssetID::Mesh(mesh_handle)])
+                    } else {
+                        log::warn!("Mesh {mesh_handle:?} was deleted while generating LODs.");
+                        None
+                    }
+                }
+                Ok(LoadOutput::Hitbox(hitbox_type, mesh_handle, revision, hitbox)) => {
+                    if self.mesh_register.contains_key(&mesh_handle)
+                        && self.mesh_revision(mesh_handle) != revision
+                    {
+                        log::warn!("Mesh {mesh_handle:?} was replaced while generating the hitbox {hitbox_type:?}.");
+                        None
+                    } else if self.mesh_register.contains_key(&mesh_handle) {
+                        let key = (hitbox_type, Some(mesh_handle));
+                        if !self.hitbox_register.contains_key(&key) {
+                            self.hitbox_register.insert(key, hitbox);
+                            log::debug!("Loaded hitbox {hitbox_type:?} in register for mesh handle {mesh_handle:?}.");
+                        }
+                        Some(vec![AssetID::Hitbox(hitbox_type, Some(mesh_handle))])
+                    } else {
+                        log::warn!("Mesh {mesh_handle:?} was deleted while generating the hitbox {hitbox_type:?}.");
+                        None
+                    }
+                }
+                Ok(LoadOutput::Skybox(images)) => {
+                    skybox = Some(Skybox::from_images(&images));
+                    Some(Vec::new())
+                }
+                Err(msg) => {
+                    log::error!("Error in asynchronous loading: {msg}");
+                    None
+                }
+            };
+            match assets {
+                Some(assets) => handle.finish(LoadState::Ready, assets),
+                None => handle.finish(LoadState::Failed, Vec::new()),
+            }
+            finished_loads.push(FinishedLoad { handle, skybox });
+        }
+        finished_loads
+    }
+
+    /// Checks wether or not an asset is currently loaded.
+    pub fn is_asset_loaded(&self, asset: &AssetID) -> bool {
+        match asset {
+            AssetID::Mesh(handle) => self.mesh_register.contains_key(handle),
+            AssetID::Hitbox(hitbox_type, opt_handle) => self
+                .hitbox_register
+                .contains_key(&(*hitbox_type, *opt_handle)),
+            AssetID::Material(name) => self.material_register.contains_key(name),
+            AssetID::Texture(texture) => self.texture_map.all_textures().contains(texture),
+            AssetID::Sprite(path) => self.texture_map.all_sprites().contains(path),
+            AssetID::SpriteSheet(path) => self.texture_map.all_sheets().contains(path),
+            AssetID::SpriteAtlas(name) => self.texture_map.all_atlases().contains(name),
+        }
+    }
+
+    /// Creates a strong handle to a loaded asset that keeps it from being unloaded automatically as long as the handle exists. Returns ``None`` if the asset is not loaded.
+    pub fn strong_asset_handle(&mut self, asset: AssetID) -> Option<StrongAssetHandle> {
+        if !self.is_asset_loaded(&asset) {
+            log::warn!("Asset {asset:?} is not loaded.");
+            return None;
+        }
+        let entry = self
+            .asset_entries
+            .entry(asset.clone())
+            .or_insert_with(AssetEntry::new);
+
+        Some(StrongAssetHandle {
+            id: asset,
+            token: entry.token.clone(),
+        })
+    }
+
+    /// Creates a weak handle to a loaded asset that can be used to check wether or not the asset is still loaded. Returns ``None`` if the asset is not loaded.
+    pub fn weak_asset_handle(&mut self, asset: AssetID) -> Option<WeakAssetHandle> {
+        if !self.is_asset_loaded(&asset) {
+            log::warn!("Asset {asset:?} is not loaded.");
+            return None;
+        }
+        let entry = self
+            .asset_entries
+            .entry(asset.clone())
+            .or_insert_with(AssetEntry::new);
+
+        Some(WeakAssetHandle {
+            id: asset,
+            token: Rc::downgrade(&entry.token),
+        })
+    }
+
+    /// Pins/unpins a loaded asset. Pinned assets are never unloaded automatically. Returns wether or not the asset is loaded.
+    pub fn pin_asset(&mut self, asset: AssetID, flag: bool) -> bool {
+        if !self.is_asset_loaded(&asset) {
+            log::warn!("Asset {asset:?} is not loaded.");
+            return false;
+        }
+        log::debug!("Set pinned for asset {asset:?}: {flag:?}.");
+        self.asset_entries
+            .entry(asset)
+            .or_insert_with(AssetEntry::new)
+            .pinned = flag;
+        true
+    }
+
+    /// Counts the references to an asset. This includes the usage in components of entities in all worlds as well as the existing strong handles.
+    pub fn asset_ref_count(&self, asset: &AssetID) -> usize {
+        let component_refs = self.component_asset_refs().get(asset).copied().unwrap_or(0);
+        let handle_refs = self
+            .asset_entries
+            .get(asset)
+            .map_or(0, |entry| entry.strong_count());
+        component_refs + handle_refs
+    }
+
+    /// Enables/disables the automatic unloading of unused assets at the end of every frame (default is ``false``).
+    pub fn set_auto_unload(&mut self, flag: bool) {
+        log::debug!("Set automatic asset unloading: {flag:?}.");
+        self.auto_unload = flag;
+    }
+
+    /// Unloads all assets that are neither used by any entity in any world, nor referenced by a strong handle, nor pinned. The built-in meshes are never unloaded. Returns the number of unloaded assets.
+    pub fn unload_unused_assets(&mut self) -> usize {
+        let mut asset_refs = self.component_asset_refs();
+        let is_retained = |refs: &AHashMap<AssetID, usize>, asset: &AssetID| {
+            refs.contains_key(asset)
+                || self
+                    .asset_entries
+                    .get(asset)
+                    .is_some_and(|entry| entry.pinned || entry.strong_count() > 0)
+        };
+
+        // retained meshes keep the materials they inherit alive
+        let inherited_materials = self
+            .mesh_register
+            .iter()
+            .filter(|(handle, _)| {
+                **handle <= MeshType::Cube.mesh_handle()
+                    || is_retained(&asset_refs, &AssetID::Mesh(**handle))
+            })
+            .filter_map(|(_, mesh)| mesh.material_name.clone())
+            .collect_vec();
+        for name in inherited_materials {
+            *asset_refs.entry(AssetID::Material(name)).or_default() += 1;
+        }
+        let is_unused = |asset: &AssetID| !is_retained(&asset_refs, asset);
+
+        // hitboxes go first, as they might depend on meshes
+        let unused = self
+            .hitbox_register
+            .keys()
+            .map(|(hitbox_type, opt_handle)| AssetID::Hitbox(*hitbox_type, *opt_handle))
+            .chain(
+                self.mesh_register
+                    .keys()
+                    .filter(|handle| **handle > MeshType::Cube.mesh_handle())
+                    .map(|handle| AssetID::Mesh(*handle)),
+            )
+            .chain(
+                self.material_register
+                    .keys()
+                    .map(|name| AssetID::Material(name.clone())),
+            )
+            .chain(
+                self.texture_map
+                    .all_textures()
+                    .map(|texture| AssetID::Texture(texture.clone())),
+            )
+            .chain(
+                self.texture_map
+                    .all_sprites()
+                    .map(|path| AssetID::Sprite(path.clone())),
+            )
+            .chain(
+                self.texture_map
+                    .all_sheets()
+                    .map(|path| AssetID::SpriteSheet(path.clone())),
+            )
+            .chain(
+                self.texture_map
+                    .all_atlases()
+                    .map(|name| AssetID::SpriteAtlas(name.clone())),
+            )
+            .filter(is_unused)
+            .collect_vec();
+
+        for asset in unused.iter() {
+            match asset {
+                AssetID::Mesh(handle) => self.delete_mesh(*handle),
+                AssetID::Hitbox(hitbox_type, opt_handle) => {
+                    self.delete_hitbox(*hitbox_type, *opt_handle)
+                }
+                AssetID::Material(name) => self.delete_material(name),
+                AssetID::Texture(texture) => self.delete_texture(texture),
+                AssetID::Sprite(path) => self.delete_sprite(path),
+                AssetID::SpriteSheet(path) => self.delete_sprite_sheet(path),
+                AssetID::SpriteAtlas(name) => self.delete_sprite_atlas(name),
+            };
+        }
+        if !unused.is_empty() {
+            log::debug!("Unloaded {} unused assets.", unused.len());
+        }
+        unused.len()
+    }
+
+    /// unloads unused assets if the automatic unloading is enabled (called at a safe point in every frame)
+    pub(crate) fn auto_unload_assets(&mut self) {
+        if self.auto_unload {
+            self.unload_unused_assets();
+        }
+    }
+
+    /// Sets the directory of the binary mesh cache or disables the cache with ``None`` (default is ``None``). When enabled, the processed meshes of ``.obj`` files as well as generated LODs and hitboxes are stored in the cache and loaded from there instead of processing the source file again. Cache entries are invalidated when the content of the source file changes.
+    pub fn set_mesh_cache(&mut self, cache_dir: Option<&Path>) {
+        self.mesh_cache = cache_dir.map(MeshCache::new);
+        log::debug!("Set mesh cache directory: {cache_dir:?}.");
+    }
+
+    /// Enables or disables hot reloading of asset files (default is ``false``). When enabled, the source files of all loaded meshes, materials and textures are checked for modifications periodically and reloaded behind the existing handles. If a modified file can not be loaded, the previous version is kept.
+    pub fn set_hot_reloading(&mut self, flag: bool) {
+        if flag == self.file_watcher.is_some() {
+            return;
+        }
+        self.file_watcher = flag.then(|| FileWatcher::new(HOT_RELOAD_INTERVAL));
+        log::debug!("Set asset hot reloading: {flag:?}.");
+    }
+
+    /// Checks wether or not hot reloading of asset files is enabled.
+    pub fn is_hot_reloading(&self) -> bool {
+        self.file_watcher.is_some()
+    }
+
+    /// the number of times a mesh has been replaced by hot reloading or ``update_mesh``
+    pub(crate) fn mesh_revision(&self, handle: MeshHandle) -> u64 {
+        self.mesh_revisions
+            .get(&handle)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// reloads all modified asset files if hot reloading is enabled
+    pub(crate) fn hot_reload_assets(&mut self) {
+        if self
+            .file_watcher
+            .as_ref()
+            .is_none_or(|watcher| !watcher.is_due())
+        {
+            return;
+        }
+        let mesh_files: AHashSet<PathBuf> = self
+            .mesh_register
+            .values()
+            .filter(|mesh| mesh.source_file.as_ref() != Path::new("internal"))
+            .map(|mesh| mesh.source_file.to_path_buf())
+            .collect();
+        let texture_files: AHashSet<PathBuf> =
+            self.texture_map.source_files().into_iter().collect();
+
+        let watcher = self.file_watcher.as_mut().unwrap();
+        for path in mesh_files
+            .iter()
+            .chain(&self.material_files)
+            .chain(&texture_files)
+        {
+            watcher.watch(path);
+        }
+        watcher.retain(|path| {
+            mesh_files.contains(path)
+                || self.material_files.contains(path)
+                || texture_files.contains(path)
+        });
+
+        for path in watcher.poll() {
+            if mesh_files.contains(&path) {
+                self.reload_asset_file(&path);
+            }
+            if self.material_files.contains(&path) {
+                self.reload_material_file(&path);
+            }
+            if texture_files.contains(&path) {
+                self.texture_map.reload_file(&path);
+            }
+        }
+    }
+
+    /// replaces the meshes, materials and textures of a modified asset file and regenerates the dependent LODs and hitboxes
+    fn reload_asset_file(&mut self, path: &Path) {
+        let file = match LoadedAssetFile::load(path, self.mesh_cache.as_ref()) {
+            Ok(file) => file,
+            Err(msg) => {
+                log::warn!(
+                    "Error reloading asset file {path:?}, keeping the previous version: {msg}."
+                );
+                return;
+            }
+        };
+        self.material_files.extend(file.material_files);
+        for (name, source, image) in file.textures {
+            self.texture_map
+                .add_material_texture_image(&name, &image, source.as_deref());
+        }
+        self.material_register.extend(file.materials);
+
+        let handles: Vec<MeshHandle> = self
+            .mesh_register
+            .iter()
+            .filter(|(_, mesh)| mesh.source_file.as_ref() == path)
+            .map(|(handle, _)| *handle)
+            .collect();
+        let mut new_meshes: AHashMap<String, Mesh> = file
+            .meshes
+            .into_iter()
+            .map(|mesh| (mesh.name.to_string(), mesh))
+            .collect();
+        for handle in handles {
+            let name = self.mesh_register[&handle].name.to_string();
+            if let Some(mesh) = new_meshes.remove(&name) {
+                self.replace_mesh(handle, mesh);
+            } else {
+                log::warn!("Mesh {name:?} is not present in the reloaded file {path:?}, keeping the previous version.");
+            }
+        }
+        log::debug!("Reloaded asset file {path:?}.");
+    }
+
+    /// replaces the data behind a mesh handle, regenerates the dependent LODs and hitboxes and marks the renderers of the mesh as outdated
+    fn replace_mesh(&mut self, handle: MeshHandle, mesh: Mesh) {
+        if let Some(lods) = self.lod_register.get_mut(&handle) {
+            *lods = MeshCache::lods_or_generate(
+                self.mesh_cache.as_ref(),
+                &mesh,
+                &self.simplification_settings,
+            );
+        }
+        for ((hitbox_type, opt_handle), hitbox) in self.hitbox_register.iter_mut() {
+            if *opt_handle == Some(handle) {
+                *hitbox =
+                    MeshCache::hitbox_or_generate(self.mesh_cache.as_ref(), &mesh, *hitbox_type);
+            }
+        }
+        self.mesh_register.insert(handle, mesh);
+        *self.mesh_revisions.entry(handle).or_default() += 1;
+    }
+
+    /// replaces all the materials and material textures of a modified ``.mtl`` file
+    fn reload_material_file(&mut self, path: &Path) {
+        match load_mtl(path) {
+            Ok((materials, _)) => {
+                for mtl in materials {
+                    self.material_register
+                        .insert(mtl.name.clone(), Material::from_mtl(&mtl));
+                    self.load_material_textures_from_mtl(&mtl, path);
+                }
+                log::debug!("Reloaded material file {path:?}.");
+            }
+            Err(msg) => {
+                log::warn!("Error reloading material file {path:?}, keeping the previous version: {msg:?}.");
+            }
+        }
+    }
+
+    /// counts the references to assets in the components of all entities in all worlds
+    fn component_asset_refs(&self) -> AHashMap<AssetID, usize> {
+        let mut refs = AHashMap::new();
+        let prev_world = self.active_world.get();
+
+        for world in self.worlds.keys().copied() {
+            self.focus_world(world);
+
+            for renderable in unsafe { self.query1::<&Renderable>((None, None)) } {
+                let handle = renderable.mesh_type.mesh_handle();
+                *refs.entry(AssetID::Mesh(handle)).or_default() += 1;
+
+                if let Some(texture) = renderable.mesh_attribute.texture() {
+                    *refs.entry(AssetID::Texture(texture.clone())).or_default() += 1;
+                }
+                let opt_material_name = match &renderable.material_source {
+                    MaterialSource::Named(name) => Some(name.clone()),
+                    MaterialSource::Inherit => self
+                        .mesh_register
+                        .get(&handle)
+                        .and_then(|mesh| mesh.material_name.clone()),
+                    MaterialSource::Custom(_) => None,
+                };
+                if let Some(name) = opt_material_name {
+                    *refs.entry(AssetID::Material(name)).or_default() += 1;
+                }
+            }
+            for (collider, renderable) in
+                unsafe { self.query2::<&Collider, Option<&Renderable>>((None, None)) }
+            {
+                let opt_handle = renderable.map(|r| r.mesh_type.mesh_handle());
+                *refs
+                    .entry(AssetID::Hitbox(collider.hitbox_type, opt_handle))
+                    .or_default() += 1;
+            }
+            for sprite in unsafe { self.query1::<&Sprite>((None, None)) } {
+                let asset = match &sprite.source {
+                    SpriteSource::Single(path) => AssetID::Sprite(path.clone()),
+                    SpriteSource::Sheet(source) => AssetID::SpriteSheet(source.path.clone()),
+                    SpriteSource::Atlas(source) => AssetID::SpriteAtlas(source.atlas.clone()),
+                    SpriteSource::Colored(_) => continue,
+                };
+                *refs.entry(asset).or_default() += 1;
+            }
+        }
+        self.focus_world(prev_world);
+        refs
+    }
+
     /// Computes the rigid body physics data from component data and stores it for physics sim. When you update component data that influences this, you can call this function to refresh the state. Relevant components are ``RigidBody``, ``Scale`` and ``Renderable``. When creating a new entity or adding/removing a relevant component, this will be called automatically if necessary.
     pub fn recompute_rigid_body_data(&mut self, entity: EntityID) {
-        if unsafe { &*self.ecs.get() }.has_component::<Renderable>(entity)
-            && unsafe { &*self.ecs.get() }.has_component::<RigidBody>(entity)
+        if self.ecs().has_component::<Renderable>(entity)
+            && self.ecs().has_component::<RigidBody>(entity)
         {
-            let handle = unsafe { &*self.ecs.get() }
+            let handle = self
+                .ecs()
                 .get_component::<Renderable>(entity)
                 .unwrap()
                 .mesh_type
@@ -413,11 +1523,10 @@ impl EntityManager {
             }
             let mesh = opt_mesh.unwrap();
 
-            let scale = unsafe { &*self.ecs.get() }
-                .get_component::<Scale>(entity)
-                .copied();
+            let scale = self.ecs().get_component::<Scale>(entity).copied();
 
-            let density = unsafe { &*self.ecs.get() }
+            let density = self
+                .ecs()
                 .get_component::<RigidBody>(entity)
                 .unwrap()
                 .density;
@@ -426,8 +1535,7 @@ impl EntityManager {
                 mesh.intertia_data(density, &scale.unwrap_or_default());
 
             let body = self
-                .ecs
-                .get_mut()
+                .ecs_mut()
                 .get_component_mut::<RigidBody>(entity)
                 .unwrap();
 
@@ -467,16 +1575,26 @@ impl EntityManager {
         }
     }
 
+    /// Access to the names of the morph targets of a loaded mesh with a handle, in the order of the weights in ``MorphWeights``.
+    pub fn mesh_morph_target_names_from_handle(&self, handle: MeshHandle) -> Option<Vec<&str>> {
+        if let Some(mesh) = self.mesh_register.get(&handle) {
+            Some(
+                mesh.morph_targets
+                    .iter()
+                    .map(|target| target.name.as_str())
+                    .collect(),
+            )
+        } else {
+            log::warn!("Mesh data not present.");
+            None
+        }
+    }
+
     /// Makes mesh data available for a given ``MeshHandle`` and ``LOD`` if it is stored.
     pub(crate) fn mesh_from_handle(&self, handle: MeshHandle, lod: LOD) -> Option<&Mesh> {
-        match lod {
-            LOD::None => self.mesh_register.get(&handle),
-            _ => Some(
-                self.lod_register
-                    .get(&handle)?
-                    .get(lod as usize - 1)
-                    .unwrap(),
-            ),
+        match lod.level() {
+            0 => self.mesh_register.get(&handle),
+            level => self.lod_register.get(&handle)?.get(level - 1),
         }
     }
 
@@ -491,22 +1609,54 @@ impl EntityManager {
         self.hitbox_register.get(&(hitbox, opt_handle))
     }
 
-    /// Clears all of the stored entites and their associated data and invalidates all of the IDs and Handles yielded from the system up to this point.
+    /// Temporarily switches the active world to the given world for internal processing and returns the previously active world (returns ``None`` if the world does not exist).
+    pub(crate) fn focus_world(&self, world: WorldID) -> Option<WorldID> {
+        self.worlds
+            .contains_key(&world)
+            .then(|| self.active_world.replace(world))
+    }
+
+    /// the cell holding the ecs of the active world
+    pub(crate) fn ecs_cell(&self) -> &UnsafeCell<ECS> {
+        self.worlds.get(&self.active_world.get()).unwrap()
+    }
+
+    /// the ecs of the active world
+    fn ecs(&self) -> &ECS {
+        unsafe { &*self.ecs_cell().get() }
+    }
+
+    /// the mutable ecs of the active world
+    fn ecs_mut(&mut self) -> &mut ECS {
+        self.worlds
+            .get_mut(&self.active_world.get())
+            .unwrap()
+            .get_mut()
+    }
+
+    /// Clears all of the stored entites and their associated data in all worlds and invalidates all of the IDs and Handles yielded from the system up to this point. All worlds except the ``MAIN_WORLD`` are deleted. Running asynchronous loading operations are cancelled and trigger an ``AssetLoadFailed`` event.
     pub fn clear(&mut self) {
-        self.ecs.get_mut().clear();
+        self.worlds.retain(|world, _| *world == MAIN_WORLD);
+        self.worlds.get_mut(&MAIN_WORLD).unwrap().get_mut().clear();
+        self.active_world.set(MAIN_WORLD);
         self.mesh_register.clear();
         self.lod_register.clear();
-        self.texture_map.clear();
+        self.lod_thresholds.clear();
         self.texture_map.clear();
         self.hitbox_register.clear();
+        self.asset_entries.clear();
+        self.material_files.clear();
+        self.mesh_revisions.clear();
+        self.skeleton_register.clear();
+        self.clip_register.clear();
+        self.asset_loader.cancel_all();
         log::debug!("Cleared the entity manager.");
     }
 }
 
-/// the entity component system that manages all the data associated with an entity
+/// the entity component system that manages all the data associated with an entity in one world
 #[allow(clippy::upper_case_acronyms)]
 pub(crate) struct ECS {
-    next_entity: EntityID,
     next_archetype_id: ArchetypeID,
     entity_index: AHashMap<EntityID, EntityRecord>,
     pub(crate) archetypes: AHashMap<ArchetypeID, Archetype>,
@@ -517,7 +1667,6 @@ impl ECS {
     /// creates a new ecs
     pub(crate) fn new() -> Self {
         Self {
-            next_entity: 1,
             next_archetype_id: 1,
             entity_index: AHashMap::new(),
             archetypes: AHashMap::new(),
@@ -525,11 +1674,12 @@ impl ECS {
         }
     }
 
-    /// Creates a new entity with given components, stores the given data and returns the id.
-    pub(crate) fn create_entity(&mut self, components: &[MetaDataComponentEntry]) -> EntityID {
-        let new_entity = self.next_entity;
-        self.next_entity += 1;
-
+    /// Creates a new entity with given id and components and stores the given data.
+    pub(crate) fn create_entity(
+        &mut self,
+        new_entity: EntityID,
+        components: &[MetaDataComponentEntry],
+    ) {
         let entity_type = EntityType::from(components.iter().map(|entry| entry.meta_data));
         let archetype_id = self.get_arch_id(&entity_type);
 
@@ -551,8 +1701,6 @@ impl ECS {
 
         self.entity_index
             .insert(new_entity, EntityRecord { archetype_id, row });
-
-        new_entity
     }
 
     /// Deletes a stored entity and all the associated component data. Returns wether or not the removal was successful.
@@ -572,6 +1720,48 @@ impl ECS {
         }
     }
 
+    /// Removes an entity without dropping its component data and returns the data entries (returns ``None`` if the entity ID is invalid).
+    pub(crate) fn take_entity(&mut self, entity: EntityID) -> Option<Vec<MetaDataComponentEntry>> {
+        let record = self.entity_index.remove(&entity)?;
+        let archetype = self.archetypes.get_mut(&record.archetype_id).unwrap();
+        let components = archetype
+            .components
+            .values_mut()
+            .map(|storage| {
+                let entry = MetaDataComponentEntry {
+                    bytes: SmallVec::from_slice(storage.get_nth_byte_slice(record.row)),
+                    meta_data: storage.meta_data,
+                };
+                // the ownership of the component data is moved to the entry
+                storage.swap_delete_nth_byte_slice(record.row);
+                entry
+            })
+            .collect();
+
+        if !archetype.is_empty() {
+            self.edit_record_after_delete(record.archetype_id, record.row);
+        }
+        Some(components)
+    }
+
+    /// Creates data entries of all the components of an entity that support duplication (returns ``None`` if the entity ID is invalid).
+    pub(crate) fn duplicate_entity(&self, entity: EntityID) -> Option<Vec<MetaDataComponentEntry>> {
+        let record = self.entity_index.get(&entity)?;
+        let archetype = self.archetypes.get(&record.archetype_id).unwrap();
+        let components = archetype
+            .components
+            .values()
+            .filter_map(|storage| {
+                let entry = storage.duplicate_nth_component(record.row);
+                if entry.is_none() {
+                    log::warn!("A component of entity {entity:?} does not support duplication and is not copied.");
+                }
+                entry
+            })
+            .collect();
+        Some(components)
+    }
+
     /// yields the component data reference of an entity if present (also returns ``None`` if the entity ID is invalid)
     pub(crate) fn get_component<T: Component>(&self, entity: EntityID) -> Option<&T> {
         let record = self.entity_index.get(&entity)?;
@@ -793,12 +1983,13 @@ impl ECS {
             .unwrap()
             .component_count();
 
-        let record = self
+        // there is no moved record if the removed entity was the last one in the archetype
+        if let Some(record) = self
             .entity_index
             .values_mut()
             .find(|record| record.archetype_id == archetype_id && record.row == last_index)
-            .unwrap();
-
-        record.row = changed_index;
+        {
+            record.row = changed_index;
+        }
     }
 }
@@ -0,0 +1,243 @@
+use crate::internal_prelude::*;
+use crate::rendering::skinning::{sample_keyframes, valid_keyframes};
+
+/// the animated component property of a track
+#[derive(Debug, Clone, PartialEq)]
+enum PropertyValues {
+    Position(Vec<Vec3>),
+    Orientation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+    LightIntensity(Vec<f32>),
+    LightColor(Vec<Vec4>),
+}
+
+impl PropertyValues {
+    /// the number of stored values
+    fn len(&self) -> usize {
+        match self {
+            Self::Position(values) | Self::Scale(values) => values.len(),
+            Self::Orientation(values) => values.len(),
+            Self::LightIntensity(values) => values.len(),
+            Self::LightColor(values) => values.len(),
+        }
+    }
+}
+
+/// the keyframes of one animated property
+#[derive(Debug, Clone, PartialEq)]
+struct PropertyTrack {
+    times: Vec<f32>,
+    values: PropertyValues,
+    interpolation: Interpolation,
+}
+
+/// the components of an entity that a clip can change, properties whose component is missing are not animated
+pub(crate) struct AnimatedComponents<'a> {
+    pub(crate) position: Option<&'a mut Position>,
+    pub(crate) orientation: Option<&'a mut Orientation>,
+    pub(crate) scale: Option<&'a mut Scale>,
+    pub(crate) point_light: Option<&'a mut PointLight>,
+    pub(crate) directional_light: Option<&'a mut DirectionalLight>,
+}
+
+/// A keyframe animation of the ``Position``, ``Orientation``, ``Scale`` and the light intensity and color (of a ``PointLight`` or ``DirectionalLight``) of an entity, played by an ``Animator``. Every property is animated by at most one track, adding another track for a property replaces the previous one. Markers are named points in time that trigger an ``AnimationMarker`` event when the playback passes them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    name: Rc<str>,
+    duration: f32,
+    tracks: Vec<PropertyTrack>,
+    markers: Vec<(f32, Rc<str>)>,
+}
+
+impl AnimationClip {
+    /// Creates a new clip without any tracks.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self {
+            name: Rc::from(name.as_ref()),
+            duration: 0.0,
+            tracks: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    /// Adds a track that moves the entity. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_positions(
+        self,
+        times: Vec<f32>,
+        values: Vec<Vec3>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(times, PropertyValues::Position(values), interpolation)
+    }
+
+    /// Adds a track that rotates the entity, rotations are interpolated spherically. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_orientations(
+        self,
+        times: Vec<f32>,
+        values: Vec<Quat>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(times, PropertyValues::Orientation(values), interpolation)
+    }
+
+    /// Adds a track that scales the entity. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_scales(
+        self,
+        times: Vec<f32>,
+        values: Vec<Vec3>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(times, PropertyValues::Scale(values), interpolation)
+    }
+
+    /// Adds a track for the intensity of the light source of the entity. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_light_intensities(
+        self,
+        times: Vec<f32>,
+        values: Vec<f32>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(times, PropertyValues::LightIntensity(values), interpolation)
+    }
+
+    /// Adds a track for the color of the light source of the entity, the colors are interpolated per RGBA channel. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe (the tangents are converted from colors as well).
+    pub fn with_light_colors(
+        self,
+        times: Vec<f32>,
+        values: Vec<Color32>,
+        interpolation: Interpolation,
+    ) -> Self {
+        let values = values.iter().map(|color| color.to_vec4()).collect();
+        self.with_track(times, PropertyValues::LightColor(values), interpolation)
+    }
+
+    /// Adds a named marker at a point in time in seconds.
+    pub fn with_marker(mut self, time: f32, name: impl AsRef<str>) -> Self {
+        if !time.is_finite() || time < 0.0 {
+            log::warn!(
+                "Skipped marker {:?} with invalid time {time} in clip {:?}.",
+                name.as_ref(),
+                self.name
+            );
+            return self;
+        }
+        self.duration = self.duration.max(time);
+        self.markers.push((time, Rc::from(name.as_ref())));
+        self.markers.sort_by(|(t1, _), (t2, _)| t1.total_cmp(t2));
+        self
+    }
+
+    /// adds a track if the keyframe data is consistent and replaces the previous track of the property
+    fn with_track(
+        mut self,
+        times: Vec<f32>,
+        values: PropertyValues,
+        interpolation: Interpolation,
+    ) -> Self {
+        if !valid_keyframes(&times, values.len(), interpolation) {
+            log::warn!("Skipped invalid track in clip {:?}.", self.name);
+            return self;
+        }
+        self.tracks.retain(|track| {
+            std::mem::discriminant(&track.values) != std::mem::discriminant(&values)
+        });
+        self.duration = self.duration.max(*times.last().unwrap());
+        self.tracks.push(PropertyTrack {
+            times,
+            values,
+            interpolation,
+        });
+        self
+    }
+
+    /// The name of the clip.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The time of the last keyframe or marker in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// the shared name of the clip for events
+    pub(crate) fn shared_name(&self) -> &Rc<str> {
+        &self.name
+    }
+
+    /// collects the markers that the playback passes when moving from one point in time to another (backwards if ``reverse`` is set), looping playback wraps around at the end
+    pub(crate) fn passed_markers(
+        &self,
+        from: f32,
+        to: f32,
+        reverse: bool,
+        wrapped: bool,
+    ) -> Vec<Rc<str>> {
+        let in_range = |time: f32| match (reverse, wrapped) {
+            (false, false) => {
+                // the end of the clip counts as passed once it is reached
+                (from <= time && time < to) || (time == to && to == self.duration && from < to)
+            }
+            (false, true) => time >= from || time < to,
+            (true, false) => to < time && time <= from,
+            (true, true) => time <= from || time > to,
+        };
+        self.markers
+            .iter()
+            .filter(|(time, _)| in_range(*time))
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+
+    /// overwrites the animated properties of the components with the values at a point in time
+    pub(crate) fn apply(&self, time: f32, components: AnimatedComponents) {
+        let AnimatedComponents {
+            mut position,
+            mut orientation,
+            mut scale,
+            mut point_light,
+            mut directional_light,
+        } = components;
+        for track in self.tracks.iter() {
+            let (times, interpolation) = (&track.times, track.interpolation);
+            match &track.values {
+                PropertyValues::Position(values) => {
+                    if let Some(position) = position.as_deref_mut() {
+                        *position =
+                            Position::from(sample_keyframes(times, values, interpolation, time));
+                    }
+                }
+                PropertyValues::Orientation(values) => {
+                    if let Some(orientation) = orientation.as_deref_mut() {
+                        orientation.0 = sample_keyframes(times, values, interpolation, time);
+                    }
+                }
+                PropertyValues::Scale(values) => {
+                    if let Some(scale) = scale.as_deref_mut() {
+                        *scale = Scale::from(sample_keyframes(times, values, interpolation, time));
+                    }
+                }
+                PropertyValues::LightIntensity(values) => {
+                    let intensity = sample_keyframes(times, values, interpolation, time).max(0.0);
+                    if let Some(light) = point_light.as_deref_mut() {
+                        light.intensity = intensity;
+                    }
+                    if let Some(light) = directional_light.as_deref_mut() {
+                        light.intensity = intensity;
+                    }
+                }
+                PropertyValues::LightColor(values) => {
+                    let color = sample_keyframes(times, values, interpolation, time)
+                        .map(|channel| channel.clamp(0.0, 1.0));
+                    let color = Color32::from_float_rgba(color.x, color.y, color.z, color.w);
+                    if let Some(light) = point_light.as_deref_mut() {
+                        light.color = color;
+                    }
+                    if let Some(light) = directional_light.as_deref_mut() {
+                        light.color = color;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,8 @@
+pub mod asset_handle;
+pub mod asset_loader;
 pub mod component;
 pub mod entity;
 pub mod entity_manager;
+pub mod keyframe_animation;
 pub mod query;
+pub mod tween;
@@ -207,7 +207,7 @@ macro_rules! impl_query {
                 &'a self,
                 filter: (Option<IncludeFilter>, Option<ExcludeFilter>)
             ) -> $sname<'a, $($ret), +> {
-                (&mut *self.ecs.get()).$fname::<$($ret), +>(filter)
+                (&mut *self.ecs_cell().get()).$fname::<$($ret), +>(filter)
             }
         }
     };
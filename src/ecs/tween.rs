@@ -0,0 +1,459 @@
+use crate::ecs::entity_manager::EntityManager;
+use crate::internal_prelude::*;
+use crate::rendering::skinning::slerp;
+use std::f32::consts::PI;
+
+/// Identifier for a tween that runs in the ``AnimationSystem``.
+pub type TweenID = u64;
+
+/// Easing curves that map the linear progress of a tween in ``[0, 1]`` to the interpolation factor. ``In`` curves start slowly, ``Out`` curves end slowly and ``InOut`` curves do both. The ``Back`` and ``Elastic`` curves overshoot the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+/// the part of the tween that an easing curve is applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EasingDirection {
+    In,
+    Out,
+    InOut,
+}
+
+impl Easing {
+    /// Evaluates the curve at a progress in ``[0, 1]``, the result is ``0`` at the start and ``1`` at the end.
+    pub fn apply(&self, t: f32) -> f32 {
+        use EasingDirection::*;
+        let t = t.clamp(0.0, 1.0);
+        let (curve, direction): (fn(f32) -> f32, EasingDirection) = match self {
+            Self::Linear => return t,
+            Self::QuadIn => (|t| t * t, In),
+            Self::QuadOut => (|t| t * t, Out),
+            Self::QuadInOut => (|t| t * t, InOut),
+            Self::CubicIn => (|t| t * t * t, In),
+            Self::CubicOut => (|t| t * t * t, Out),
+            Self::CubicInOut => (|t| t * t * t, InOut),
+            Self::QuartIn => (|t| t.powi(4), In),
+            Self::QuartOut => (|t| t.powi(4), Out),
+            Self::QuartInOut => (|t| t.powi(4), InOut),
+            Self::SineIn => (sine_in, In),
+            Self::SineOut => (sine_in, Out),
+            Self::SineInOut => (sine_in, InOut),
+            Self::ExpoIn => (expo_in, In),
+            Self::ExpoOut => (expo_in, Out),
+            Self::ExpoInOut => (expo_in, InOut),
+            Self::CircIn => (circ_in, In),
+            Self::CircOut => (circ_in, Out),
+            Self::CircInOut => (circ_in, InOut),
+            Self::BackIn => (back_in, In),
+            Self::BackOut => (back_in, Out),
+            Self::BackInOut => (back_in, InOut),
+            Self::ElasticIn => (elastic_in, In),
+            Self::ElasticOut => (elastic_in, Out),
+            Self::ElasticInOut => (elastic_in, InOut),
+            Self::BounceIn => (bounce_in, In),
+            Self::BounceOut => (bounce_in, Out),
+            Self::BounceInOut => (bounce_in, InOut),
+        };
+        match direction {
+            In => curve(t),
+            Out => 1.0 - curve(1.0 - t),
+            InOut if t < 0.5 => curve(2.0 * t) / 2.0,
+            InOut => 1.0 - curve(2.0 - 2.0 * t) / 2.0,
+        }
+    }
+}
+
+fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t == 0.0 {
+        0.0
+    } else {
+        2.0_f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn circ_in(t: f32) -> f32 {
+    1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+fn back_in(t: f32) -> f32 {
+    let overshoot = 1.70158;
+    (overshoot + 1.0) * t * t * t - overshoot * t * t
+}
+
+fn elastic_in(t: f32) -> f32 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+    -(2.0_f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+}
+
+fn bounce_in(t: f32) -> f32 {
+    // the bounce curve is defined for the end of the motion
+    let t = 1.0 - t;
+    let (n, d) = (7.5625, 2.75);
+    let out = if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - 1.5 / d;
+        n * t * t + 0.75
+    } else if t < 2.5 / d {
+        let t = t - 2.25 / d;
+        n * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d;
+        n * t * t + 0.984375
+    };
+    1.0 - out
+}
+
+/// Values that can be animated by a ``Tween``.
+pub trait Tweenable: Copy + 'static {
+    /// Interpolates to another value, ``t`` may be outside of ``[0, 1]`` for overshooting easing curves.
+    fn tween(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Tweenable for Vec4 {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Tweenable for Quat {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        slerp(self, other, t)
+    }
+}
+
+impl Tweenable for Color32 {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        let color = self
+            .to_vec4()
+            .lerp(&other.to_vec4(), t)
+            .map(|channel| channel.clamp(0.0, 1.0));
+        Color32::from_float_rgba(color.x, color.y, color.z, color.w)
+    }
+}
+
+impl Tweenable for Position {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        Position::from(self.data().tween(other.data(), t))
+    }
+}
+
+impl Tweenable for Scale {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        Scale::from(self.data().tween(other.data(), t))
+    }
+}
+
+impl Tweenable for Orientation {
+    fn tween(&self, other: &Self, t: f32) -> Self {
+        Orientation(self.0.tween(&other.0, t))
+    }
+}
+
+/// How often a ``Tween`` is played after the first time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TweenRepeat {
+    #[default]
+    Never,
+    Times(u32),
+    Forever,
+}
+
+/// Animation of a single value to a target value over time. The tween starts at the value that the animated field has when the tween begins (after the delay), unless a start value is given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween<V: Tweenable> {
+    target: V,
+    start: Option<V>,
+    duration: f32,
+    delay: f32,
+    easing: Easing,
+    repeat: TweenRepeat,
+    yoyo: bool,
+}
+
+impl<V: Tweenable> Tween<V> {
+    /// Creates a linear tween to a target value that takes ``duration`` seconds.
+    pub fn new(target: V, duration: f32) -> Self {
+        Self {
+            target,
+            start: None,
+            duration: duration.max(0.0),
+            delay: 0.0,
+            easing: Easing::Linear,
+            repeat: TweenRepeat::Never,
+            yoyo: false,
+        }
+    }
+
+    /// Sets a fixed start value.
+    pub fn with_start(mut self, start: V) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Changes the easing curve.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Waits ``delay`` seconds before the tween begins.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay.max(0.0);
+        self
+    }
+
+    /// Plays the tween again after it ends.
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every second repetition backwards from the target to the start value, so ``TweenRepeat::Times(1)`` goes there and back.
+    pub fn with_yoyo(mut self, flag: bool) -> Self {
+        self.yoyo = flag;
+        self
+    }
+
+    /// the interpolation factor and wether or not the tween is finished after ``elapsed`` seconds since the end of the delay
+    fn progress(&self, elapsed: f32) -> (f32, bool) {
+        let cycles = match self.repeat {
+            TweenRepeat::Never => 1.0,
+            TweenRepeat::Times(count) => count as f32 + 1.0,
+            TweenRepeat::Forever => f32::INFINITY,
+        };
+        if self.duration <= 0.0 {
+            let backwards = self.yoyo && cycles.is_finite() && cycles % 2.0 == 0.0;
+            return (if backwards { 0.0 } else { 1.0 }, true);
+        }
+        let total = self.duration * cycles;
+        let (cycle, progress) = if elapsed >= total {
+            (cycles - 1.0, 1.0)
+        } else {
+            let cycle = (elapsed / self.duration).floor();
+            (cycle, elapsed / self.duration - cycle)
+        };
+        let t = if self.yoyo && cycle % 2.0 == 1.0 {
+            1.0 - progress
+        } else {
+            progress
+        };
+        (self.easing.apply(t), elapsed >= total)
+    }
+}
+
+/// the state of a tween after a time step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TweenState {
+    Running,
+    Finished,
+    /// the entity or the animated field does not exist anymore
+    Invalid,
+}
+
+/// a tween that is bound to a field of a component type
+trait FieldTween {
+    /// moves the tween forward and writes the new value to the field of the entity
+    fn advance(
+        &mut self,
+        entity_manager: &mut EntityManager,
+        entity: EntityID,
+        delta: f32,
+    ) -> TweenState;
+}
+
+/// the playback state of a tween on a component field
+struct ComponentTween<C: Component, V: Tweenable> {
+    field: fn(&mut C) -> Option<&mut V>,
+    tween: Tween<V>,
+    elapsed: f32,
+}
+
+impl<C: Component, V: Tweenable> FieldTween for ComponentTween<C, V> {
+    fn advance(
+        &mut self,
+        entity_manager: &mut EntityManager,
+        entity: EntityID,
+        delta: f32,
+    ) -> TweenState {
+        self.elapsed += delta;
+        let elapsed = self.elapsed - self.tween.delay;
+        if elapsed < 0.0 {
+            return TweenState::Running;
+        }
+        let opt_value = entity_manager
+            .get_component_mut::<C>(entity)
+            .and_then(self.field);
+        if opt_value.is_none() {
+            return TweenState::Invalid;
+        }
+        let value = opt_value.unwrap();
+        let start = *self.tween.start.get_or_insert(*value);
+        let (t, finished) = self.tween.progress(elapsed);
+        *value = start.tween(&self.tween.target, t);
+        if finished {
+            TweenState::Finished
+        } else {
+            TweenState::Running
+        }
+    }
+}
+
+/// a tween of an entity that might wait for another tween to finish
+struct ActiveTween {
+    id: TweenID,
+    entity: EntityID,
+    after: Option<TweenID>,
+    field_tween: Box<dyn FieldTween>,
+}
+
+/// all of the running and waiting tweens
+#[derive(Default)]
+pub(crate) struct TweenRegister {
+    tweens: Vec<ActiveTween>,
+    next_id: TweenID,
+}
+
+impl TweenRegister {
+    /// adds a tween that starts once the tween ``after`` is finished or right away
+    pub(crate) fn add<C: Component, V: Tweenable>(
+        &mut self,
+        entity: EntityID,
+        field: fn(&mut C) -> Option<&mut V>,
+        tween: Tween<V>,
+        after: Option<TweenID>,
+    ) -> TweenID {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tweens.push(ActiveTween {
+            id,
+            entity,
+            after: after.filter(|after| self.contains(*after)),
+            field_tween: Box::new(ComponentTween {
+                field,
+                tween,
+                elapsed: 0.0,
+            }),
+        });
+        id
+    }
+
+    /// wether or not a tween is running or waiting
+    pub(crate) fn contains(&self, id: TweenID) -> bool {
+        self.tweens.iter().any(|tween| tween.id == id)
+    }
+
+    /// removes the tweens that match the predicate together with all of the tweens that are chained after them, returns the number of removed tweens
+    pub(crate) fn remove(&mut self, predicate: impl Fn(TweenID, EntityID) -> bool) -> usize {
+        let mut removed = self
+            .tweens
+            .iter()
+            .filter(|tween| predicate(tween.id, tween.entity))
+            .map(|tween| tween.id)
+            .collect::<AHashSet<_>>();
+        loop {
+            let chained = self
+                .tweens
+                .iter()
+                .filter(|tween| !removed.contains(&tween.id))
+                .filter(|tween| tween.after.is_some_and(|after| removed.contains(&after)))
+                .map(|tween| tween.id)
+                .collect_vec();
+            if chained.is_empty() {
+                break;
+            }
+            removed.extend(chained);
+        }
+        self.tweens.retain(|tween| !removed.contains(&tween.id));
+        removed.len()
+    }
+
+    /// moves all started tweens forward, returns the finished tweens with their entities
+    pub(crate) fn update(
+        &mut self,
+        entity_manager: &mut EntityManager,
+        delta: f32,
+    ) -> Vec<(TweenID, EntityID)> {
+        let mut finished = Vec::new();
+        let mut invalid = AHashSet::new();
+        for tween in self.tweens.iter_mut().filter(|tween| tween.after.is_none()) {
+            match tween
+                .field_tween
+                .advance(entity_manager, tween.entity, delta)
+            {
+                TweenState::Running => {}
+                TweenState::Finished => finished.push((tween.id, tween.entity)),
+                TweenState::Invalid => {
+                    log::debug!("Tween {} of entity {:?} was removed because its target value does not exist.", tween.id, tween.entity);
+                    invalid.insert(tween.id);
+                }
+            }
+        }
+        self.remove(|id, _| invalid.contains(&id));
+        // tweens that were waiting for a finished tween start in the next time step
+        for tween in self.tweens.iter_mut() {
+            if tween
+                .after
+                .is_some_and(|after| finished.iter().any(|(id, _)| *id == after))
+            {
+                tween.after = None;
+            }
+        }
+        self.tweens
+            .retain(|tween| !finished.iter().any(|(id, _)| *id == tween.id));
+        finished
+    }
+}
@@ -29,10 +29,14 @@ pub struct Engine<A: LeafyApp> {
 }
 
 impl<A: LeafyApp> Engine<A> {
-    /// Engine setup on startup.
-    pub(crate) fn new(config: EngineAttributes) -> Self {
+    /// Engine setup on startup, a headless engine does not use an audio output device.
+    pub(crate) fn new(config: EngineAttributes, headless: bool) -> Self {
         let video_system = VideoSystem::new(config);
-        let audio_system = AudioSystem::new();
+        let audio_system = if headless {
+            AudioSystem::without_device()
+        } else {
+            AudioSystem::new()
+        };
         let animation_system = AnimationSystem::new();
         let entity_manager = EntityManager::new();
         let mut event_system = EventSystem::new();
@@ -70,30 +74,93 @@ impl<A: LeafyApp> Engine<A> {
         self.exit_state.take().unwrap()
     }
 
+    /// creates the offscreen context and rendering system and initializes the app
+    pub(crate) fn init_headless(&mut self, app: A) -> Result<(), Box<dyn Error>> {
+        self.video_system_mut().create_headless_context()?;
+
+        let res = self.video_system().window_resolution();
+        let mut rendering_system = RenderingSystem::new(res.width, res.height);
+        rendering_system.use_offscreen_target(res.width, res.height)?;
+        rendering_system.update_viewport_ratio(self.video_system().current_viewport_ratio());
+        self.rendering_system = Some(RefCell::new(rendering_system));
+
+        self.app = Some(RefCell::new(app));
+        self.app_mut().init(self);
+        Ok(())
+    }
+
+    /// Runs a single frame of the headless engine with a fixed time delta instead of the real elapsed time, which makes the results reproducible. Returns ``false`` as soon as ``quit`` was called. Does nothing if the engine was not built with ``EngineAttributes::build_headless_engine``.
+    pub fn step_frame(&mut self, dt: TimeDuration) -> bool {
+        if !self.video_system().is_headless() {
+            log::warn!("Frames can only be stepped manually in headless mode.");
+            return false;
+        }
+        self.video_system_mut().update_draw_timer();
+        self.on_frame_redraw(dt);
+        !self.should_quit.get()
+    }
+
     /// Gets called every frame and contains the main engine logic.
-    fn on_frame_redraw(&mut self) {
+    fn on_frame_redraw(&mut self, dt: TimeDuration) {
+        self.finish_async_loads();
+        self.entity_manager_mut().hot_reload_assets();
+
         self.app_mut().on_frame_update(self);
 
-        self.time_step_sim();
+        self.time_step_sim(dt);
 
         self.audio_system_mut()
             .update(self.entity_manager_mut().deref_mut());
 
         self.rendering_system_mut()
             .render(self.entity_manager().deref());
+
+        self.entity_manager_mut().auto_unload_assets();
+    }
+
+    /// Finalizes all finished background loading operations and triggers the respective events.
+    fn finish_async_loads(&mut self) {
+        let finished_loads = self.entity_manager_mut().finish_async_loads();
+        for finished in finished_loads {
+            if let Some(skybox) = finished.skybox {
+                self.rendering_system_mut().skybox = Some(skybox);
+            }
+            let load = finished.handle.id();
+            if finished.handle.is_ready() {
+                self.trigger_event(AssetReady {
+                    load,
+                    assets: finished.handle.assets(),
+                });
+            } else {
+                self.trigger_event(AssetLoadFailed { load });
+            }
+        }
     }
 
-    /// All of the time-sensitive simulations for a single time step.
-    fn time_step_sim(&mut self) {
+    /// All of the time-sensitive simulations for a single time step of ``dt``.
+    fn time_step_sim(&mut self, dt: TimeDuration) {
         self.animation_system_mut().last_collisions.clear();
-        let dt = self.time_of_last_sim.delta_time();
-        self.time_of_last_sim.reset();
         let transformed_dt = dt * self.animation_system().animation_speed;
         self.time_accumulated += transformed_dt;
 
         while self.time_accumulated >= TIME_STEP {
             if self.mode() == EngineMode::Running {
                 self.animation_system_mut().update(self);
+                // the events are triggered after the update so that listeners can access the animation system
+                let markers = std::mem::take(&mut self.animation_system_mut().passed_markers);
+                for marker in markers {
+                    self.trigger_event(marker);
+                }
+                let finished_tweens =
+                    std::mem::take(&mut self.animation_system_mut().finished_tweens);
+                for finished in finished_tweens {
+                    self.trigger_event(finished);
+                }
+                let finished_sprite_animations =
+                    std::mem::take(&mut self.animation_system_mut().finished_sprite_animations);
+                for finished in finished_sprite_animations {
+                    self.trigger_event(finished);
+                }
             }
             self.time_accumulated -= TIME_STEP;
         }
@@ -213,7 +280,9 @@ impl<A: LeafyApp> ApplicationHandler for Engine<A> {
             WindowEvent::RedrawRequested => {
                 if self.video_system().should_redraw() {
                     self.video_system_mut().update_draw_timer();
-                    self.on_frame_redraw();
+                    let dt = self.time_of_last_sim.delta_time();
+                    self.time_of_last_sim.reset();
+                    self.on_frame_redraw(dt);
                     self.video_system().swap_window();
                 }
                 if self.should_quit.get() {
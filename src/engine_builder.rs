@@ -12,7 +12,7 @@ pub struct EngineAttributes {
     title: &'static str,
     pub(crate) fps_cap: Option<f64>,
     pub(crate) bg_fps_cap: Option<f64>,
-    size: (u32, u32),
+    pub(crate) size: (u32, u32),
     min_size: Option<(u32, u32)>,
     pub(crate) enforced_ratio: Option<f32>,
     transparent: bool,
@@ -64,7 +64,7 @@ impl EngineAttributes {
         self
     }
 
-    /// Sets the width and height for the window (default is 800 x 450) in physical size.
+    /// Sets the width and height for the window (default is 800 x 450) in physical size. In headless mode, this is the resolution of the offscreen frame.
     pub fn with_size(mut self, width: u32, height: u32) -> Self {
         self.size = (width, height);
         self
@@ -146,7 +146,17 @@ impl EngineAttributes {
                 ));
             }
         }
-        let engine = Engine::new(self);
+        let engine = Engine::new(self, false);
+
+        Ok(engine)
+    }
+
+    /// Builds an engine that renders offscreen without a window (e.g. on CI machines or servers) and initializes the given app right away. This creates an EGL context that either runs surfaceless or on a pbuffer and also works with software rasterizers like Mesa's llvmpipe. The frame resolution is the size set with ``with_size``, all window-specific attributes are ignored. There is no event loop, so every frame has to be produced manually via ``Engine::step_frame``. The rendered image can be read back with ``RenderingSystem::read_frame_pixels``. Sounds are processed without an audio output device.
+    pub fn build_headless_engine<A: LeafyApp>(self, app: A) -> Result<Engine<A>, String> {
+        let mut engine = Engine::new(self, true);
+        engine
+            .init_headless(app)
+            .map_err(|err| format!("Headless engine creation failed: {err}"))?;
 
         Ok(engine)
     }
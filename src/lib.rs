@@ -15,10 +15,14 @@ pub use winit;
 /// All features that are very common to use.
 pub mod prelude {
     pub use crate::components;
+    pub use crate::ecs::asset_handle::{AssetID, StrongAssetHandle, WeakAssetHandle};
+    pub use crate::ecs::asset_loader::{LoadID, LoadState, PendingHandle};
     pub use crate::ecs::component::utils::*;
     pub use crate::ecs::component::*;
     pub use crate::ecs::entity::EntityID;
-    pub use crate::ecs::entity_manager::MeshHandle;
+    pub use crate::ecs::entity_manager::{MeshHandle, WorldID};
+    pub use crate::ecs::keyframe_animation::AnimationClip;
+    pub use crate::ecs::tween::{Easing, Tween, TweenID, TweenRepeat, Tweenable};
     pub use crate::engine::{Engine, EngineMode, LeafyApp};
     pub use crate::engine_builder::EngineAttributes;
     pub use crate::exclude_filter;
@@ -35,6 +39,14 @@ pub mod prelude {
     pub use crate::include_filter;
     pub use crate::itertools::Itertools;
     pub use crate::log;
+    pub use crate::rendering::mesh::{MeshData, SimplificationSettings, SimplificationTarget};
+    pub use crate::rendering::mesh_processing::{MeshOperation, MeshReport};
+    pub use crate::rendering::mesh_slicing::SlicePlane;
+    pub use crate::rendering::procedural::ProceduralMesh;
+    pub use crate::rendering::skinning::{
+        Interpolation, Joint, JointTransform, SkeletalClip, Skeleton,
+    };
+    pub use crate::rendering::sprite_atlas::SpriteAtlasSettings;
     pub use crate::systems::event_system::events::user_space::*;
     pub use crate::systems::event_system::events::*;
     pub use crate::systems::rendering_system::ShadowResolution;
@@ -62,6 +74,7 @@ pub(crate) mod internal_prelude {
     pub(crate) use std::ops::{Deref, DerefMut};
     pub(crate) use std::path::{Path, PathBuf};
     pub(crate) use std::rc::{Rc, Weak};
+    pub(crate) use std::sync::Arc;
     pub(crate) use std::time::{Duration, Instant};
 }
 
@@ -69,6 +82,8 @@ pub(crate) mod internal_prelude {
 mod tests {
     use crate::ecs::entity_manager::EntityManager;
     use crate::prelude::*;
+    use crate::rendering::mesh::Hitbox;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn entity_test() {
@@ -114,4 +129,1264 @@ mod tests {
         );
         assert_eq!(unsafe { ecs.query1::<&Position>((None, None)) }.count(), 3);
     }
+
+    #[test]
+    fn world_test() {
+        let mut ecs = EntityManager::new();
+        let preview = ecs.create_world();
+        let a = ecs.create_entity(components!(Position::origin(), Scale::default()));
+        let b = ecs.create_entity(components!(Position::origin()));
+        assert!(ecs.move_entity(a, preview));
+        assert!(!ecs.has_component::<Position>(a));
+        let c = ecs.copy_entity(b, preview).unwrap();
+        assert_ne!(b, c);
+        assert_eq!(unsafe { ecs.query1::<&Position>((None, None)) }.count(), 1);
+
+        assert!(ecs.set_active_world(preview));
+        assert!(ecs.has_component::<Scale>(a));
+        assert_eq!(*ecs.get_component::<EntityID>(c).unwrap(), c);
+        assert_eq!(unsafe { ecs.query1::<&Position>((None, None)) }.count(), 2);
+
+        assert!(ecs.delete_world(preview));
+        assert!(!ecs.delete_world(MAIN_WORLD));
+        assert_eq!(ecs.active_world(), MAIN_WORLD);
+    }
+
+    #[test]
+    fn asset_handle_test() {
+        let mut ecs = EntityManager::new();
+        let sphere = AssetID::Hitbox(HitboxType::Sphere, None);
+        let cube = AssetID::Hitbox(HitboxType::Box, None);
+        assert!(ecs.load_hitbox(HitboxType::Sphere, None));
+        assert!(ecs.load_hitbox(HitboxType::Box, None));
+        let entity = ecs.create_entity(components!(
+            Position::origin(),
+            Collider::from_type(HitboxType::Box)
+        ));
+        let handle = ecs.strong_asset_handle(cube.clone()).unwrap();
+        let weak = handle.downgrade();
+        assert_eq!(ecs.asset_ref_count(&cube), 2);
+
+        assert!(ecs.pin_asset(sphere.clone(), true));
+        assert_eq!(ecs.unload_unused_assets(), 0);
+        drop(handle);
+        assert!(weak.is_loaded());
+        assert!(ecs.delete_entity(entity));
+        assert_eq!(ecs.unload_unused_assets(), 1);
+        assert!(!weak.is_loaded());
+        assert!(ecs.is_asset_loaded(&sphere));
+
+        let path = std::env::temp_dir().join("leafy_asset_handle_test.mtl");
+        std::fs::write(&path, "newmtl red\nKd 1 0 0\n").unwrap();
+        assert!(ecs.load_materials(&path));
+        std::fs::remove_file(&path).unwrap();
+        let mesh = ecs
+            .load_mesh_data(
+                MeshData::new(
+                    "triangle",
+                    vec![Vec3::zeros(), X_AXIS, Z_AXIS],
+                    vec![0, 1, 2],
+                )
+                .with_material_name("red"),
+            )
+            .unwrap();
+        let material = AssetID::Material("red".to_string());
+        let handle = ecs.strong_asset_handle(AssetID::Mesh(mesh)).unwrap();
+        assert_eq!(ecs.unload_unused_assets(), 0);
+        assert!(ecs.is_asset_loaded(&material));
+        drop(handle);
+        assert_eq!(ecs.unload_unused_assets(), 2);
+        assert!(!ecs.is_asset_loaded(&material));
+    }
+
+    #[test]
+    fn async_loading_test() {
+        let mut ecs = EntityManager::new();
+        let pending = ecs.load_hitbox_async(HitboxType::ConvexHull, 3).unwrap();
+        assert!(pending.is_pending());
+        assert!(ecs.load_lods_async(42).is_none());
+
+        let start = Instant::now();
+        while ecs.pending_load_count() > 0 && start.elapsed() < Duration::from_secs(10) {
+            ecs.finish_async_loads();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(pending.is_ready());
+        assert_eq!(
+            pending.assets(),
+            vec![AssetID::Hitbox(HitboxType::ConvexHull, Some(3))]
+        );
+        assert!(ecs.is_asset_loaded(&AssetID::Hitbox(HitboxType::ConvexHull, Some(3))));
+
+        let triangle = || {
+            MeshData::new(
+                "triangle",
+                vec![Vec3::zeros(), X_AXIS, Z_AXIS],
+                vec![0, 1, 2],
+            )
+        };
+        let mesh = ecs.load_mesh_data(triangle()).unwrap();
+        let stale = ecs.load_hitbox_async(HitboxType::Box, mesh).unwrap();
+        assert!(ecs.update_mesh(mesh, triangle()));
+        let start = Instant::now();
+        while ecs.pending_load_count() > 0 && start.elapsed() < Duration::from_secs(10) {
+            ecs.finish_async_loads();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(stale.state(), LoadState::Failed);
+        assert!(!ecs.is_asset_loaded(&AssetID::Hitbox(HitboxType::Box, Some(mesh))));
+
+        let cancelled = ecs.load_hitbox_async(HitboxType::Box, 3).unwrap();
+        ecs.clear();
+        assert_eq!(ecs.pending_load_count(), 0);
+        assert_eq!(ecs.finish_async_loads().len(), 1);
+        assert_eq!(cancelled.state(), LoadState::Failed);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(ecs.finish_async_loads().is_empty());
+        assert!(!ecs.is_asset_loaded(&AssetID::Hitbox(HitboxType::Box, Some(3))));
+    }
+
+    #[test]
+    fn file_watcher_test() {
+        use crate::utils::file_watcher::FileWatcher;
+
+        let path = std::env::temp_dir().join("leafy_file_watcher_test.mtl");
+        std::fs::write(&path, "newmtl a").unwrap();
+        let mut watcher = FileWatcher::new(Duration::ZERO);
+        watcher.watch(&path);
+        watcher.watch("missing.mtl");
+        assert!(watcher.is_due());
+        assert!(watcher.poll().is_empty());
+
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(watcher.poll(), vec![path.clone()]);
+        assert!(watcher.poll().is_empty());
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.poll().is_empty());
+    }
+
+    #[test]
+    fn archive_test() {
+        use crate::utils::vfs::*;
+
+        let text = "mtllib test.mtl\n".repeat(20);
+        let path = std::env::temp_dir().join("leafy_archive_test.pak");
+        ArchiveBuilder::new()
+            .with_bytes("models/test.obj", text.clone().into_bytes())
+            .with_bytes("./models/raw.bin", vec![1, 2, 3])
+            .write(&path)
+            .unwrap();
+        let mut corrupt = std::fs::read(&path).unwrap();
+        let stored_size = 8 + 4 + 4 + 2 + "models/test.obj".len() + 8;
+        corrupt[stored_size..stored_size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        let corrupt_path = std::env::temp_dir().join("leafy_archive_test_corrupt.pak");
+        std::fs::write(&corrupt_path, corrupt).unwrap();
+        assert!(!mount_archive(&corrupt_path));
+        std::fs::remove_file(&corrupt_path).unwrap();
+        assert!(mount_archive(&path));
+        assert!(file_exists("models/raw.bin"));
+        assert!(!file_exists("models/missing.obj"));
+        assert_eq!(read_file_to_string("./models/test.obj").unwrap(), text);
+        assert_eq!(read_file("models/raw.bin").unwrap(), vec![1, 2, 3]);
+        unmount_all();
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_file("models/raw.bin").is_err());
+    }
+
+    #[test]
+    fn mesh_cache_test() {
+        let dir = std::env::temp_dir().join("leafy_mesh_cache_test");
+        let obj = dir.join("tri.obj");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &obj,
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n",
+        )
+        .unwrap();
+
+        let mut ecs = EntityManager::new();
+        ecs.set_mesh_cache(Some(&dir.join("cache")));
+        let first = ecs.load_asset_file(&obj)[0];
+        assert!(ecs.load_hitbox(HitboxType::ConvexHull, Some(first)));
+        let cached = ecs.load_asset_file(&obj)[0];
+        assert!(ecs.load_hitbox(HitboxType::ConvexHull, Some(cached)));
+        assert_eq!(
+            ecs.mesh_from_handle(first, LOD::None).unwrap().positions,
+            ecs.mesh_from_handle(cached, LOD::None).unwrap().positions
+        );
+
+        std::fs::write(
+            &obj,
+            "v 0 0 0\nv 2 0 0\nv 0 2 0\nv 0 0 2\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n",
+        )
+        .unwrap();
+        let changed = ecs.load_asset_file(&obj)[0];
+        let mesh = ecs.mesh_from_handle(changed, LOD::None).unwrap();
+        assert_eq!(mesh.max_reach, vec3(2.0, 2.0, 2.0));
+
+        std::fs::write(
+            &obj,
+            "o part\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\no part\nv 0 0 0\nv 4 0 0\nv 0 4 0\nf 4 5 6\n",
+        )
+        .unwrap();
+        let radii = |ecs: &mut EntityManager| {
+            ecs.load_asset_file(&obj)
+                .into_iter()
+                .map(|handle| {
+                    assert!(ecs.load_hitbox(HitboxType::Sphere, Some(handle)));
+                    match ecs.hitbox_from_data(HitboxType::Sphere, Some(handle)) {
+                        Some(Hitbox::Sphere(radius)) => *radius,
+                        _ => panic!("missing sphere hitbox"),
+                    }
+                })
+                .collect_vec()
+        };
+        let generated = radii(&mut ecs);
+        assert!(generated[0] < generated[1]);
+        assert_eq!(radii(&mut ecs), generated);
+
+        std::fs::write(
+            &obj,
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nv 5 0 0\nv 10 0 0\nf 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\nf 1 5 6\n",
+        )
+        .unwrap();
+        let source = ecs.load_asset_file(&obj)[0];
+        let processed = ecs.load_asset_file(&obj)[0];
+        for handle in [source, processed] {
+            if handle == processed {
+                assert!(ecs.process_mesh(handle, &[MeshOperation::RemoveDegenerateFaces]));
+            }
+            assert!(ecs.load_lods(handle));
+            assert!(ecs.load_hitbox(HitboxType::Sphere, Some(handle)));
+        }
+        let lod_reach = |handle| {
+            ecs.mesh_from_handle(handle, LOD::from_level(1))
+                .unwrap()
+                .max_reach
+        };
+        assert_ne!(lod_reach(source), lod_reach(processed));
+        let radius = |handle| match ecs.hitbox_from_data(HitboxType::Sphere, Some(handle)) {
+            Some(Hitbox::Sphere(radius)) => *radius,
+            _ => panic!("missing sphere hitbox"),
+        };
+        assert!(radius(processed) < radius(source));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_test() {
+        let mut ecs = EntityManager::new();
+        let path = std::env::temp_dir().join("leafy_export_test.obj");
+        assert!(ecs.export_mesh(3, LOD::None, &path));
+        let exported = ecs.load_asset_file(&path)[0];
+        let (cube, copy) = (
+            ecs.mesh_from_handle(3, LOD::None).unwrap(),
+            ecs.mesh_from_handle(exported, LOD::None).unwrap(),
+        );
+        assert_eq!(cube.indices.len(), copy.indices.len());
+        assert_eq!(cube.max_reach, copy.max_reach);
+
+        assert!(ecs.load_hitbox(HitboxType::Box, None));
+        assert!(ecs.export_hitbox(HitboxType::Box, None, &path));
+        assert!(!ecs.export_mesh(3, LOD::from_level(1), &path));
+        assert!(!ecs.export_mesh(3, LOD::None, ""));
+        assert!(!ecs.export_mesh(3, LOD::None, std::env::temp_dir().join("..")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn procedural_mesh_test() {
+        let mut ecs = EntityManager::new();
+        let convex_shapes = [
+            ProceduralMesh::UVSphere {
+                segments: 16,
+                rings: 8,
+            },
+            ProceduralMesh::IcoSphere { subdivisions: 2 },
+            ProceduralMesh::Cylinder { segments: 12 },
+            ProceduralMesh::Capsule {
+                segments: 12,
+                rings: 4,
+                length: 2.0,
+            },
+            ProceduralMesh::Cone { segments: 12 },
+        ];
+        for shape in convex_shapes {
+            let handle = ecs.load_procedural_mesh(shape);
+            let mesh = ecs.mesh_from_handle(handle, LOD::None).unwrap();
+            assert_eq!(mesh.indices.len() % 3, 0);
+            assert_eq!(mesh.tangents.len(), mesh.positions.len());
+            for (a, b, c) in mesh
+                .indices
+                .iter()
+                .map(|i| mesh.positions[*i as usize])
+                .tuples()
+            {
+                assert!((b - a).cross(&(c - a)).dot(&(a + b + c)) > 0.0, "{shape:?}");
+            }
+            assert!(ecs.load_hitbox(HitboxType::ConvexHull, Some(handle)));
+        }
+        let torus = ecs.load_procedural_mesh(ProceduralMesh::Torus {
+            major_segments: 24,
+            minor_segments: 12,
+            minor_radius: 0.25,
+        });
+        assert_eq!(
+            ecs.mesh_from_handle(torus, LOD::None).unwrap().max_reach,
+            vec3(1.25, 0.25, 1.25)
+        );
+        assert!(ecs.load_lods(torus));
+        let plane = ecs.load_procedural_mesh(ProceduralMesh::Plane { subdivisions: 4 });
+        assert_eq!(
+            ecs.mesh_from_handle(plane, LOD::None)
+                .unwrap()
+                .indices
+                .len(),
+            96
+        );
+        let cylinder = ecs.load_procedural_mesh(ProceduralMesh::Cylinder { segments: u32::MAX });
+        assert_eq!(
+            ecs.mesh_from_handle(cylinder, LOD::None)
+                .unwrap()
+                .positions
+                .len(),
+            2 * 1025 + 2 * 1026
+        );
+    }
+
+    #[test]
+    fn runtime_mesh_test() {
+        let mut ecs = EntityManager::new();
+        let tetrahedron = |height: f32| {
+            MeshData::new(
+                "tetrahedron",
+                vec![
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(1.0, 0.0, 0.0),
+                    vec3(0.0, 0.0, 1.0),
+                    vec3(0.0, height, 0.0),
+                ],
+                vec![0, 1, 2, 0, 3, 1, 0, 2, 3, 1, 3, 2],
+            )
+        };
+        assert!(ecs
+            .load_mesh_data(tetrahedron(1.0).with_normals(vec![Y_AXIS; 3]))
+            .is_none());
+        assert!(ecs
+            .load_mesh_data(MeshData::new(
+                "broken",
+                vec![Vec3::zeros(); 3],
+                vec![0, 1, 3]
+            ))
+            .is_none());
+
+        let handle = ecs.load_mesh_data(tetrahedron(1.0)).unwrap();
+        assert!(ecs.load_hitbox(HitboxType::ConvexHull, Some(handle)));
+        assert!(ecs.update_mesh(handle, tetrahedron(3.0)));
+        assert!(!ecs.update_mesh(MeshType::Cube.mesh_handle(), tetrahedron(3.0)));
+        assert_eq!(ecs.mesh_revision(handle), 1);
+
+        let mesh = ecs.mesh_from_handle(handle, LOD::None).unwrap();
+        assert_eq!(mesh.max_reach, vec3(1.0, 3.0, 1.0));
+        assert_eq!(mesh.tangents.len(), 4);
+        match ecs.hitbox_from_data(HitboxType::ConvexHull, Some(handle)) {
+            Some(Hitbox::ConvexMesh(hitbox)) => {
+                assert!(hitbox.vertices.iter().any(|v| v.y == 3.0))
+            }
+            _ => panic!("hitbox was not regenerated"),
+        }
+    }
+
+    #[test]
+    fn simplification_test() {
+        let mut ecs = EntityManager::new();
+        let plane = ecs.load_procedural_mesh(ProceduralMesh::Plane { subdivisions: 8 });
+        ecs.set_simplification_settings(
+            SimplificationSettings::default()
+                .with_levels(vec![
+                    SimplificationTarget::Ratio(0.5),
+                    SimplificationTarget::TriangleCount(20),
+                ])
+                .with_preserve_boundaries(true),
+        );
+        assert!(ecs.load_lods(plane));
+        assert_eq!(ecs.lod_count(plane), 2);
+        assert!(ecs.mesh_from_handle(plane, LOD::from_level(3)).is_none());
+
+        let is_boundary = |v: &Vec3| v.x.abs() == 1.0 || v.z.abs() == 1.0;
+        let boundary_count = |lod: LOD| {
+            let mesh = ecs.mesh_from_handle(plane, lod).unwrap();
+            mesh.positions
+                .iter()
+                .filter(|v| is_boundary(v))
+                .map(|v| (v.x.to_bits(), v.z.to_bits()))
+                .unique()
+                .count()
+        };
+        let triangle_count = |lod: LOD| ecs.mesh_from_handle(plane, lod).unwrap().num_indices() / 3;
+        assert_eq!(triangle_count(LOD::None), 128);
+        assert!(triangle_count(LOD::from_level(1)) <= 64);
+        assert!(triangle_count(LOD::from_level(2)) < triangle_count(LOD::from_level(1)));
+        assert_eq!(boundary_count(LOD::None), 32);
+        assert_eq!(boundary_count(LOD::from_level(1)), 32);
+        assert_eq!(boundary_count(LOD::from_level(2)), 32);
+
+        assert!(ecs.delete_lods(plane));
+        ecs.set_simplification_settings(
+            SimplificationSettings::default().with_max_error(Some(-1.0)),
+        );
+        assert!(ecs.load_lods(plane));
+        assert_eq!(ecs.lod_count(plane), 4);
+        assert_eq!(
+            ecs.mesh_from_handle(plane, LOD::from_level(4))
+                .unwrap()
+                .num_indices(),
+            384
+        );
+        assert_eq!(
+            LOD::from_level(2),
+            LOD::Level(std::num::NonZeroU8::new(2).unwrap())
+        );
+        assert_eq!(LOD::from_level(1000).level(), MAX_LOD_LEVEL);
+        assert_eq!(LOD::from_level(0), LOD::None);
+        assert_eq!(LOD::from_level(0).level(), 0);
+    }
+
+    #[test]
+    fn auto_lod_test() {
+        let distance = LODThresholds::distance(vec![10.0, 20.0, 40.0]);
+        assert_eq!(distance.select_level(5.0, 0, 4), 0);
+        assert_eq!(distance.select_level(25.0, 0, 4), 2);
+        assert_eq!(distance.select_level(25.0, 0, 1), 1);
+        assert_eq!(distance.select_level(100.0, 0, 4), 3);
+        // within the hysteresis margin the previous level is kept
+        assert_eq!(distance.select_level(10.5, 0, 4), 0);
+        assert_eq!(distance.select_level(10.5, 1, 4), 1);
+        assert_eq!(distance.select_level(9.5, 1, 4), 1);
+        assert_eq!(distance.select_level(8.5, 1, 4), 0);
+
+        let screen_size = LODThresholds::screen_size(vec![0.5, 0.1]).with_hysteresis(0.0);
+        assert_eq!(screen_size.select_level(0.8, 2, 2), 0);
+        assert_eq!(screen_size.select_level(0.3, 0, 2), 1);
+        assert_eq!(screen_size.select_level(0.05, 0, 2), 2);
+        assert_eq!(LOD::Auto.level(), 0);
+    }
+
+    #[test]
+    fn mesh_processing_test() {
+        let mut ecs = EntityManager::new();
+        assert!(ecs.validate_mesh(3, LOD::None).unwrap().is_valid());
+
+        // a quad made of two triangles with duplicated corners, a degenerate triangle and flipped normals
+        let quad = MeshData::new(
+            "quad",
+            vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 1.0),
+            ],
+            vec![0, 1, 2, 3, 4, 5, 0, 2, 3],
+        )
+        .with_normals(vec![-Y_AXIS; 6]);
+        let handle = ecs.load_mesh_data(quad).unwrap();
+        let report = ecs.validate_mesh(handle, LOD::None).unwrap();
+        assert_eq!(report.duplicate_vertices, 2);
+        assert_eq!(report.degenerate_faces, 1);
+        assert_eq!(report.flipped_faces, 2);
+        assert_eq!(report.boundary_edges, 4);
+        assert!(report.is_manifold() && !report.is_closed() && report.has_consistent_winding());
+
+        assert!(ecs.process_mesh(
+            handle,
+            &[
+                MeshOperation::WeldVertices {
+                    tolerance: 0.001,
+                    positions_only: false,
+                },
+                MeshOperation::RemoveDegenerateFaces,
+                MeshOperation::RecomputeNormals {
+                    smoothing_angle: 0.0
+                },
+                MeshOperation::RecomputeTangents,
+            ]
+        ));
+        let report = ecs.validate_mesh(handle, LOD::None).unwrap();
+        assert_eq!((report.vertex_count, report.triangle_count), (4, 2));
+        assert_eq!(
+            report.duplicate_vertices + report.degenerate_faces + report.flipped_faces,
+            0
+        );
+        let mesh = ecs.mesh_from_handle(handle, LOD::None).unwrap();
+        assert!(mesh.normals.iter().all(|n| *n == Y_AXIS));
+        assert!(mesh
+            .tangents
+            .iter()
+            .all(|t| t.dot(&Y_AXIS).abs() < 1e-6 && (t.norm() - 1.0).abs() < 1e-6));
+
+        let tetrahedron = || {
+            MeshData::new(
+                "tetrahedron",
+                vec![
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(1.0, 0.0, 0.0),
+                    vec3(0.0, 0.0, 1.0),
+                    vec3(0.0, 1.0, 0.0),
+                ],
+                vec![0, 1, 2, 0, 3, 1, 0, 2, 3, 1, 3, 2],
+            )
+        };
+        let handle = ecs.load_mesh_data(tetrahedron()).unwrap();
+        assert!(ecs.validate_mesh(handle, LOD::None).unwrap().is_closed());
+        for (smoothing_angle, vertex_count) in [(0.0, 12), (180.0, 4)] {
+            assert!(ecs.update_mesh(handle, tetrahedron()));
+            assert!(ecs.process_mesh(
+                handle,
+                &[MeshOperation::RecomputeNormals { smoothing_angle }]
+            ));
+            let report = ecs.validate_mesh(handle, LOD::None).unwrap();
+            assert_eq!(report.vertex_count, vertex_count);
+            assert!(report.is_valid());
+        }
+    }
+
+    #[test]
+    fn gltf_test() {
+        use crate::rendering::gltf::GltfData;
+        use base64::Engine;
+
+        let mut buffer = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2] {
+            buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        let data = base64::engine::general_purpose::STANDARD.encode(&buffer);
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [
+                    {{ "translation": [0, 2, 0], "children": [1] }},
+                    {{ "name": "triangle", "mesh": 0, "scale": [2, 2, 2] }}
+                ],
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }}] }}],
+                "materials": [{{ "name": "red", "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1] }} }}],
+                "buffers": [{{ "byteLength": 42, "uri": "data:application/octet-stream;base64,{data}" }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ]
+            }}"#
+        );
+        let path = std::env::temp_dir().join("leafy_gltf_test.gltf");
+        std::fs::write(&path, &json).unwrap();
+        let gltf = GltfData::load(&path).unwrap();
+        let huge_count = json.replace(
+            r#""count": 3, "type": "SCALAR""#,
+            r#""count": 4611686018427387904, "type": "SCALAR""#,
+        );
+        std::fs::write(&path, huge_count).unwrap();
+        assert!(GltfData::load(&path).is_err());
+        let parent_image = json.replace(
+            r#""materials": ["#,
+            r#""images": [{ "uri": ".." }], "materials": ["#,
+        );
+        std::fs::write(&path, parent_image).unwrap();
+        assert!(GltfData::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        let nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(crate::utils::json::JsonValue::parse(&nested).is_none());
+        let shallow = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(crate::utils::json::JsonValue::parse(&shallow).is_some());
+
+        let mesh = &gltf.meshes[0][0];
+        assert_eq!(mesh.positions[1], vec3(1.0, 0.0, 0.0));
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(mesh.material_name.as_deref(), Some("red"));
+        assert_eq!(mesh.normals[0], vec3(0.0, 0.0, 1.0));
+        assert_eq!(gltf.materials[0].1.diffuse, Diffuse::Value(Color32::RED));
+        let pbr = gltf.materials[0].1.pbr.as_ref().unwrap();
+        assert_eq!((pbr.base_color, pbr.metallic), (Color32::RED, 1.0));
+
+        let (position, _, scale) = decompose_transform(&gltf.nodes[0].transform);
+        assert_eq!(position, Position::new(0.0, 2.0, 0.0));
+        assert_eq!(scale, Scale::from_factor(2.0));
+    }
+
+    #[test]
+    fn texture_format_test() {
+        use crate::rendering::texture_formats::{decode_texture, PixelFormat};
+
+        // 8x8 DXT1 texture with three of the four mip levels
+        let mut dds = vec![0; 128];
+        dds[..4].copy_from_slice(b"DDS ");
+        let mut header = |offset: usize, value: u32| {
+            dds[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        header(4, 124);
+        header(8, 0x20000);
+        header(12, 8);
+        header(16, 8);
+        header(28, 3);
+        header(80, 0x4);
+        header(84, u32::from_le_bytes(*b"DXT1"));
+        dds.resize(128 + 32 + 8 + 8, 7);
+        let data = decode_texture(&dds).unwrap();
+        assert_eq!(data.format, PixelFormat::BC1Alpha);
+        assert_eq!(data.levels.iter().map(Vec::len).collect_vec(), [32, 8, 8]);
+        assert_eq!(data.level_dimensions(2), (2, 2));
+        assert_eq!(data.mip_chain(&MipmapSettings::default()), (3, false));
+        let limited = MipmapSettings::default().with_max_levels(2);
+        assert_eq!(data.mip_chain(&limited), (2, false));
+        assert!(decode_texture(&dds[..100]).is_err());
+
+        // 2x1 sRGB RGBA8 KTX2 texture without stored mip levels
+        let mut ktx2 = vec![
+            0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+        ];
+        for value in [43, 1, 2, 1, 0, 0, 1, 1, 0] {
+            ktx2.extend(u32::to_le_bytes(value));
+        }
+        ktx2.resize(80, 0);
+        for value in [104, 8, 8] {
+            ktx2.extend(u64::to_le_bytes(value));
+        }
+        ktx2.extend([255, 0, 0, 255, 0, 255, 0, 255]);
+        let data = decode_texture(&ktx2).unwrap();
+        assert_eq!((data.format, data.srgb), (PixelFormat::RGBA8, true));
+        assert_eq!(data.levels, [vec![255, 0, 0, 255, 0, 255, 0, 255]]);
+        assert_eq!(data.mip_chain(&MipmapSettings::default()), (1, true));
+        let disabled = MipmapSettings::default().with_mode(MipmapMode::Disabled);
+        assert_eq!(data.mip_chain(&disabled), (1, false));
+        let (internal_format, _, _) = data.format.gl_formats(ColorSpace::RGBA8, data.srgb);
+        assert_eq!(internal_format, gl::SRGB_ALPHA);
+
+        ktx2[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(decode_texture(&ktx2).is_err());
+        assert_eq!(PixelFormat::RGBA32F.level_size(usize::MAX / 8, 4), None);
+    }
+
+    #[test]
+    fn sprite_atlas_test() {
+        use crate::rendering::sprite_atlas::build_atlas;
+        use stb_image::image::Image;
+        use std::path::Path;
+        use std::rc::Rc;
+
+        let images = [(3, 5, 1), (8, 2, 2), (4, 4, 3), (1, 1, 4)]
+            .into_iter()
+            .map(|(width, height, value)| {
+                let path: Rc<Path> = Path::new(&format!("sprite{value}.png")).into();
+                let image = Image::new(width, height, 4, vec![value; width * height * 4]);
+                (path, image)
+            })
+            .collect_vec();
+        let settings = SpriteAtlasSettings::default();
+        let atlas = build_atlas(&images, &settings).unwrap();
+        assert!(atlas.image.width.is_power_of_two() && atlas.image.height.is_power_of_two());
+
+        let pixel = |x: usize, y: usize| atlas.image.data[(y * atlas.image.width + x) * 4];
+        let cells = images
+            .iter()
+            .map(|(path, image)| {
+                let region = atlas.regions[path];
+                assert_eq!(region.pixel_size, (image.width, image.height));
+                let (x, y) = region.pixel_index;
+                // the sprite itself and the extruded border both hold the sprite pixels
+                assert_eq!(pixel(x, y), image.data[0]);
+                assert_eq!(pixel(x - 1, y - 1), image.data[0]);
+                assert_eq!(pixel(x + image.width, y + image.height), image.data[0]);
+                (x - 1, y - 1, image.width + 4, image.height + 4)
+            })
+            .collect_vec();
+        for (a, b) in cells.iter().tuple_combinations() {
+            let separate =
+                a.0 + a.2 <= b.0 || b.0 + b.2 <= a.0 || a.1 + a.3 <= b.1 || b.1 + b.3 <= a.1;
+            assert!(separate);
+        }
+        assert!(build_atlas(&images, &settings.with_max_size(8)).is_err());
+    }
+
+    #[test]
+    fn meshtool_test() {
+        use crate::utils::file::CUBE_MESH;
+        use crate::utils::meshtool::*;
+
+        let dir = std::env::temp_dir().join("leafy_meshtool_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        std::fs::write(dir.join("models/cube.obj"), CUBE_MESH).unwrap();
+        let arg = |path: &str| dir.join(path).to_string_lossy().into_owned();
+
+        let args = [
+            "--lods",
+            "--hulls",
+            "-q",
+            "--levels",
+            "0.5",
+            "-o",
+            &arg("out"),
+            "--cache",
+            &arg("cache"),
+            "--report",
+            &arg("report.txt"),
+            &arg("models"),
+        ];
+        assert_eq!(run(args.map(String::from)), EXIT_SUCCESS);
+        assert!(dir.join("out/cube_Cube_lod1.obj").is_file());
+        assert!(!dir.join("out/cube_Cube_lod2.obj").exists());
+        assert!(dir.join("out/cube_Cube_simplified_hull.obj").is_file());
+        assert_eq!(std::fs::read_dir(dir.join("cache")).unwrap().count(), 1);
+        let report = std::fs::read_to_string(dir.join("report.txt")).unwrap();
+        assert!(report.contains("12 triangles") && report.contains("validation: valid"));
+
+        std::fs::create_dir_all(dir.join("other")).unwrap();
+        std::fs::write(dir.join("other/cube.obj"), CUBE_MESH).unwrap();
+        let colliding = [
+            "--hulls",
+            "-q",
+            "-o",
+            &arg("out"),
+            &arg("models"),
+            &arg("other"),
+        ];
+        assert_eq!(run(colliding.map(String::from)), EXIT_FAILURE);
+
+        assert_eq!(run(["--levels".to_string()]), EXIT_USAGE);
+        assert_eq!(run([arg("missing.obj"), "-q".into()]), EXIT_FAILURE);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn convex_decomposition_test() {
+        let mut ecs = EntityManager::new();
+        // an L-shape made of two overlapping boxes
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for max in [vec3(4.0, 1.0, 1.0), vec3(1.0, 4.0, 1.0)] {
+            let offset = positions.len() as u32;
+            positions.extend((0..8).map(|i| {
+                vec3(
+                    if i & 1 == 0 { 0.0 } else { max.x },
+                    if i & 2 == 0 { 0.0 } else { max.y },
+                    if i & 4 == 0 { 0.0 } else { max.z },
+                )
+            }));
+            indices.extend(
+                [
+                    0, 2, 1, 1, 2, 3, 4, 5, 6, 5, 7, 6, 0, 1, 4, 1, 5, 4, 2, 6, 3, 3, 6, 7, 0, 4,
+                    2, 2, 4, 6, 1, 3, 5, 3, 7, 5,
+                ]
+                .map(|index| index + offset),
+            );
+        }
+        let handle = ecs
+            .load_mesh_data(MeshData::new("l_shape", positions, indices))
+            .unwrap();
+        let settings = DecompositionSettings::default().with_max_parts(4);
+        assert_eq!(
+            settings.with_resolution(1 << 40).resolution,
+            MAX_DECOMPOSITION_RESOLUTION
+        );
+        let hitbox_type = HitboxType::ConvexDecomposition(settings);
+        assert!(ecs.load_hitbox(hitbox_type, Some(handle)));
+
+        match ecs.hitbox_from_data(hitbox_type, Some(handle)) {
+            Some(Hitbox::Compound(parts)) => {
+                assert!((2..=4).contains(&parts.len()));
+                // no part may cover the empty corner of the L
+                for part in parts.iter() {
+                    let max = part
+                        .vertices
+                        .iter()
+                        .fold(vec3(0.0, 0.0, 0.0), |max, v| max.sup(v));
+                    assert!(max.x < 2.5 || max.y < 2.5);
+                }
+            }
+            _ => panic!("hitbox is not a convex decomposition"),
+        }
+        let path = std::env::temp_dir().join("leafy_decomposition_test.obj");
+        assert!(ecs.export_hitbox(hitbox_type, Some(handle), &path));
+        let obj = std::fs::read_to_string(&path).unwrap();
+        assert!(obj.contains("o ConvexDecomposition_1"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn mesh_slicing_test() {
+        let mut ecs = EntityManager::new();
+        // the horizontal cut through the torus has a hole in its cap
+        let shapes = [
+            (
+                ProceduralMesh::Cylinder { segments: 12 },
+                SlicePlane::new(vec3(0.2, 0.0, 0.0), vec3(1.0, 1.0, 0.0)),
+            ),
+            (
+                ProceduralMesh::Torus {
+                    major_segments: 24,
+                    minor_segments: 12,
+                    minor_radius: 0.25,
+                },
+                SlicePlane::new(vec3(0.0, 0.05, 0.0), Y_AXIS),
+            ),
+        ];
+        for (shape, plane) in shapes {
+            let handle = ecs.load_procedural_mesh(shape);
+            let weld = MeshOperation::WeldVertices {
+                tolerance: 1e-4,
+                positions_only: true,
+            };
+            assert!(ecs.process_mesh(handle, &[weld]));
+            assert!(ecs.validate_mesh(handle, LOD::None).unwrap().is_closed());
+            let (front, back) = ecs.slice_mesh(handle, &plane).unwrap();
+            for half in [front, back] {
+                let report = ecs.validate_mesh(half, LOD::None).unwrap();
+                assert!(
+                    report.is_closed() && report.has_consistent_winding(),
+                    "{shape:?}"
+                );
+            }
+            let front_mesh = ecs.mesh_from_handle(front, LOD::None).unwrap();
+            assert!(front_mesh
+                .positions
+                .iter()
+                .all(|p| (p - plane.point).dot(&plane.normal) > -1e-4));
+            let miss = SlicePlane::new(vec3(0.0, 5.0, 0.0), Y_AXIS);
+            assert!(ecs.slice_mesh(handle, &miss).is_none());
+        }
+
+        let cylinder = ecs.load_procedural_mesh(ProceduralMesh::Cylinder { segments: 16 });
+        let entity = ecs.create_entity(components!(
+            Position::new(1.0, 2.0, 3.0),
+            Renderable {
+                mesh_type: MeshType::Custom(cylinder),
+                ..Default::default()
+            },
+            RigidBody::default(),
+            Collider::from_type(HitboxType::ConvexHull)
+        ));
+        let mass = ecs.get_component::<RigidBody>(entity).unwrap().mass;
+        let plane = SlicePlane::new(vec3(1.0, 2.5, 3.0), Y_AXIS);
+        let (top, bottom) = ecs.slice_entity(entity, &plane).unwrap();
+        let top_body = ecs.get_component::<RigidBody>(top).unwrap();
+        let bottom_body = ecs.get_component::<RigidBody>(bottom).unwrap();
+        assert!((top_body.mass + bottom_body.mass - mass).abs() < mass * 0.01);
+        assert!(top_body.mass < bottom_body.mass);
+        for half in [top, bottom] {
+            let renderable = ecs.get_component::<Renderable>(half).unwrap();
+            let handle = renderable.mesh_type.mesh_handle();
+            assert!(ecs.is_asset_loaded(&AssetID::Hitbox(HitboxType::ConvexHull, Some(handle))));
+        }
+    }
+
+    #[test]
+    fn skeletal_animation_test() {
+        use base64::Engine;
+        use std::rc::Rc;
+
+        let mut buffer = Vec::new();
+        let push_floats = |values: &[f32], buffer: &mut Vec<u8>| {
+            values
+                .iter()
+                .for_each(|v| buffer.extend_from_slice(&v.to_le_bytes()))
+        };
+        push_floats(&[1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0], &mut buffer);
+        for joint in [1u16, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0] {
+            buffer.extend_from_slice(&joint.to_le_bytes());
+        }
+        push_floats(
+            &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 2.0, 0.0, 0.0],
+            &mut buffer,
+        );
+        push_floats(Mat4::identity().as_slice(), &mut buffer);
+        push_floats(
+            glm::translation(&vec3(0.0, -1.0, 0.0)).as_slice(),
+            &mut buffer,
+        );
+        push_floats(&[0.0, 1.0], &mut buffer);
+        let half_sqrt = std::f32::consts::FRAC_1_SQRT_2;
+        push_floats(
+            &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, half_sqrt, half_sqrt],
+            &mut buffer,
+        );
+        let data = base64::engine::general_purpose::STANDARD.encode(&buffer);
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scenes": [{{ "nodes": [0, 2] }}],
+                "nodes": [
+                    {{ "name": "root", "children": [1] }},
+                    {{ "name": "arm", "translation": [0, 1, 0] }},
+                    {{ "mesh": 0, "skin": 0, "translation": [5, 0, 0] }}
+                ],
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "JOINTS_0": 1, "WEIGHTS_0": 2 }} }}] }}],
+                "skins": [{{ "name": "rig", "joints": [0, 1], "inverseBindMatrices": 3 }}],
+                "animations": [{{
+                    "name": "wave",
+                    "samplers": [{{ "input": 4, "output": 5 }}],
+                    "channels": [{{ "sampler": 0, "target": {{ "node": 1, "path": "rotation" }} }}]
+                }}],
+                "buffers": [{{ "byteLength": {}, "uri": "data:application/octet-stream;base64,{data}" }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 24 }},
+                    {{ "buffer": 0, "byteOffset": 60, "byteLength": 48 }},
+                    {{ "buffer": 0, "byteOffset": 108, "byteLength": 128 }},
+                    {{ "buffer": 0, "byteOffset": 236, "byteLength": 8 }},
+                    {{ "buffer": 0, "byteOffset": 244, "byteLength": 32 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "VEC4" }},
+                    {{ "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4" }},
+                    {{ "bufferView": 3, "componentType": 5126, "count": 2, "type": "MAT4" }},
+                    {{ "bufferView": 4, "componentType": 5126, "count": 2, "type": "SCALAR" }},
+                    {{ "bufferView": 5, "componentType": 5126, "count": 2, "type": "VEC4" }}
+                ]
+            }}"#,
+            buffer.len()
+        );
+        let path = std::env::temp_dir().join("leafy_skinning_test.gltf");
+        std::fs::write(
+            &path,
+            json.replace(r#""joints": [0, 1]"#, r#""joints": [0]"#),
+        )
+        .unwrap();
+        assert!(EntityManager::new().load_gltf(&path, false).0.is_empty());
+        std::fs::write(&path, json).unwrap();
+        let mut ecs = EntityManager::new();
+        let (handles, entities) = ecs.load_gltf(&path, true);
+        std::fs::remove_file(&path).unwrap();
+
+        let skin = ecs
+            .mesh_from_handle(handles[0], LOD::None)
+            .unwrap()
+            .skin
+            .clone()
+            .unwrap();
+        assert_eq!(skin.weights[2], vec4(0.5, 0.5, 0.0, 0.0));
+        assert_eq!(ecs.skeleton("rig").unwrap().joint_count(), 2);
+        let clip = ecs.skeletal_clip("wave").unwrap();
+        assert_eq!(clip.duration(), 1.0);
+        // the node transform of skinned meshes is ignored
+        assert_eq!(
+            ecs.get_component::<Position>(entities[0]),
+            Some(&Position::default())
+        );
+
+        let skinned_position = |animator: &SkeletalAnimator| {
+            let matrices = animator.skinning_matrices();
+            mult_mat4_vec3(&matrices[1], &vec3(1.0, 1.0, 0.0))
+        };
+        let animator = ecs
+            .get_component_mut::<SkeletalAnimator>(entities[0])
+            .unwrap();
+        assert!((skinned_position(animator) - vec3(1.0, 1.0, 0.0)).norm() < 1e-5);
+        animator.play(clip.clone());
+        animator.advance(0.5);
+        let expected = vec3(half_sqrt, 1.0 + half_sqrt, 0.0);
+        assert!((skinned_position(animator) - expected).norm() < 1e-5);
+        // looping wraps the time around
+        animator.advance(0.75);
+        assert!((animator.time() - 0.25).abs() < 1e-5);
+
+        // fading from the end of the clip to the rest pose blends the rotation halfway
+        animator.looping = false;
+        animator.set_time(1.0);
+        assert!(animator.is_finished());
+        animator.cross_fade(Rc::new(SkeletalClip::new("rest")), 1.0);
+        animator.advance(0.5);
+        assert!((skinned_position(animator) - expected).norm() < 1e-5);
+        animator.advance(0.5);
+        assert!(animator.fade_out.is_none());
+        assert!((skinned_position(animator) - vec3(1.0, 1.0, 0.0)).norm() < 1e-5);
+
+        ecs.clear();
+        assert!(ecs.skeleton("rig").is_none() && ecs.skeletal_clip("wave").is_none());
+    }
+
+    #[test]
+    fn morph_target_test() {
+        let mut ecs = EntityManager::new();
+        let positions = vec![Vec3::zeros(), X_AXIS, Y_AXIS, Y_AXIS];
+        let indices = vec![0, 1, 2, 0, 1, 3];
+        assert!(ecs
+            .load_mesh_data(
+                MeshData::new("broken", positions.clone(), indices.clone()).with_morph_target(
+                    "smile",
+                    vec![Z_AXIS; 3],
+                    None
+                )
+            )
+            .is_none());
+
+        // the two coincident vertices are moved apart by the target and must not be welded
+        let deltas = vec![Vec3::zeros(), Vec3::zeros(), Z_AXIS * 2.0, Vec3::zeros()];
+        let data =
+            MeshData::new("face", positions, indices).with_morph_target("smile", deltas, None);
+        let handle = ecs.load_mesh_data(data).unwrap();
+        let weld = MeshOperation::WeldVertices {
+            tolerance: 0.01,
+            positions_only: true,
+        };
+        assert!(ecs.process_mesh(handle, &[weld]));
+        assert_eq!(
+            ecs.mesh_morph_target_names_from_handle(handle),
+            Some(vec!["smile"])
+        );
+        let mesh = ecs.mesh_from_handle(handle, LOD::None).unwrap();
+        assert_eq!(mesh.num_vertices(), 4);
+        let (morphed, _) = mesh.morphed_vertices(&[0.5, 1.0]);
+        assert_eq!(morphed[2], vec3(0.0, 1.0, 1.0));
+        assert_eq!(morphed[3], Y_AXIS);
+
+        let mut weights = MorphWeights::new(vec![1.0]);
+        weights.blend_to(vec![0.0, 1.0], 1.0);
+        weights.advance(0.25);
+        assert_eq!(weights.weights, vec![0.75, 0.25]);
+        weights.advance(1.0);
+        assert_eq!(weights.weights, vec![0.0, 1.0]);
+        assert!(!weights.is_animating());
+        assert!(!weights.animate(
+            vec![1.0, 0.5],
+            vec![vec![0.0], vec![1.0]],
+            Interpolation::Step
+        ));
+    }
+
+    #[test]
+    fn keyframe_animation_test() {
+        use crate::ecs::keyframe_animation::AnimatedComponents;
+        use std::rc::Rc;
+
+        let clip = AnimationClip::new("door")
+            .with_positions(
+                vec![0.0, 2.0],
+                vec![ORIGIN, X_AXIS * 4.0],
+                Interpolation::Linear,
+            )
+            .with_light_intensities(vec![0.0, 1.0], vec![0.0, 2.0], Interpolation::Step)
+            .with_light_colors(vec![0.0], vec![Color32::RED; 2], Interpolation::Linear)
+            .with_marker(1.0, "halfway")
+            .with_marker(2.0, "closed");
+        assert_eq!(clip.duration(), 2.0);
+
+        let mut animator = Animator::new().with_clip(Rc::new(clip));
+        let (mut position, mut light) = (Position::origin(), PointLight::default());
+        let mut step = |animator: &mut Animator, delta: f32| {
+            let markers = animator.advance(delta);
+            animator.clip.as_ref().unwrap().apply(
+                animator.time(),
+                AnimatedComponents {
+                    position: Some(&mut position),
+                    orientation: None,
+                    scale: None,
+                    point_light: Some(&mut light),
+                    directional_light: None,
+                },
+            );
+            (markers, position, light)
+        };
+
+        let (markers, position, light) = step(&mut animator, 0.5);
+        assert!(markers.is_empty());
+        assert_eq!(position, Position::new(1.0, 0.0, 0.0));
+        assert_eq!(light.intensity, 0.0);
+        // markers are passed once the playback moves beyond them
+        assert!(step(&mut animator, 0.5).0.is_empty());
+        let (markers, _, light) = step(&mut animator, 0.1);
+        assert_eq!(markers, vec![Rc::from("halfway")]);
+        assert_eq!(light.intensity, 2.0);
+        let (markers, position, _) = step(&mut animator, 5.0);
+        assert_eq!(markers, vec![Rc::from("closed")]);
+        assert_eq!(position, Position::new(4.0, 0.0, 0.0));
+        assert!(animator.is_finished());
+        assert!(step(&mut animator, 1.0).0.is_empty());
+
+        // looping backwards wraps around the start and passes the end marker again
+        animator.looping = true;
+        animator.speed = -1.0;
+        animator.set_time(0.5);
+        let (markers, position, _) = step(&mut animator, 1.0);
+        assert_eq!(markers, vec![Rc::from("closed")]);
+        assert_eq!(position, Position::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn tween_test() {
+        use crate::ecs::tween::TweenRegister;
+
+        for easing in [
+            Easing::QuadInOut,
+            Easing::SineOut,
+            Easing::BackIn,
+            Easing::ElasticOut,
+            Easing::BounceInOut,
+        ] {
+            assert!(easing.apply(0.0).abs() < 1e-5);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5);
+        }
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+        assert_eq!(Easing::CubicOut.apply(0.5), 0.875);
+
+        let mut ecs = EntityManager::new();
+        let entity = ecs.create_entity(components!(Position::origin(), PointLight::default()));
+        let mut tweens = TweenRegister::default();
+        let intensity: fn(&mut PointLight) -> Option<&mut f32> = |light| Some(&mut light.intensity);
+        let first = tweens.add(
+            entity,
+            intensity,
+            Tween::new(3.0, 1.0)
+                .with_delay(0.5)
+                .with_repeat(TweenRepeat::Times(1))
+                .with_yoyo(true),
+            None,
+        );
+        let second = tweens.add(
+            entity,
+            |p: &mut Position| Some(p),
+            Tween::new(Position::new(0.0, 2.0, 0.0), 1.0),
+            Some(first),
+        );
+        // a sprite source that is not colored has no value to animate
+        let color: fn(&mut Sprite) -> Option<&mut Color32> = |sprite| match &mut sprite.source {
+            SpriteSource::Colored(color) => Some(color),
+            _ => None,
+        };
+        let missing = tweens.add(entity, color, Tween::new(Color32::RED, 1.0), None);
+
+        assert!(tweens.update(&mut ecs, 0.25).is_empty());
+        assert!(!tweens.contains(missing));
+        assert_eq!(
+            ecs.get_component::<PointLight>(entity).unwrap().intensity,
+            1.0
+        );
+        tweens.update(&mut ecs, 0.75);
+        assert_eq!(
+            ecs.get_component::<PointLight>(entity).unwrap().intensity,
+            2.0
+        );
+        tweens.update(&mut ecs, 1.0);
+        assert_eq!(
+            ecs.get_component::<PointLight>(entity).unwrap().intensity,
+            2.0
+        );
+        assert_eq!(tweens.update(&mut ecs, 0.5), vec![(first, entity)]);
+        assert_eq!(
+            ecs.get_component::<PointLight>(entity).unwrap().intensity,
+            1.0
+        );
+
+        tweens.update(&mut ecs, 0.5);
+        assert_eq!(
+            ecs.get_component::<Position>(entity),
+            Some(&Position::new(0.0, 1.0, 0.0))
+        );
+        assert_eq!(tweens.remove(|id, _| id == second), 1);
+        assert!(tweens.update(&mut ecs, 1.0).is_empty());
+    }
+
+    #[test]
+    fn sprite_animation_test() {
+        let mut animation = SpriteAnimation::new()
+            .with_sequence(SpriteSequence::new("walk", SpritePlayMode::Loop).with_row(
+                (0, 16),
+                (16, 16),
+                3,
+                0.1,
+            ))
+            .with_sequence(
+                SpriteSequence::new("bounce", SpritePlayMode::PingPong).with_row(
+                    (0, 32),
+                    (16, 16),
+                    3,
+                    0.1,
+                ),
+            )
+            .with_sequence(
+                SpriteSequence::new("die", SpritePlayMode::Once)
+                    .with_frame((0, 48), (16, 16), 0.1)
+                    .with_frame((16, 48), (16, 16), 0.0)
+                    .with_frame((32, 48), (32, 16), 0.3),
+            );
+        assert_eq!(animation.sequence("die").unwrap().frames().len(), 2);
+        assert!(animation.current_frame().is_none());
+        assert!(!animation.play("jump"));
+
+        let frames = |animation: &mut SpriteAnimation, steps: usize| {
+            (0..steps)
+                .map(|_| {
+                    animation.advance(0.1);
+                    animation.frame_index()
+                })
+                .collect_vec()
+        };
+        assert!(animation.play("walk"));
+        assert_eq!(
+            animation.current_frame().map(|frame| frame.pixel_index),
+            Some((0, 16))
+        );
+        assert_eq!(frames(&mut animation, 5), vec![1, 2, 0, 1, 2]);
+        assert_eq!(
+            animation.current_frame().map(|frame| frame.pixel_index),
+            Some((32, 16))
+        );
+
+        animation.play("bounce");
+        assert_eq!(frames(&mut animation, 6), vec![1, 2, 1, 0, 1, 2]);
+
+        animation.play("die");
+        animation.speed = 0.5;
+        assert!(animation.advance(0.1).is_none());
+        assert_eq!(animation.frame_index(), 0);
+        assert!(animation.advance(0.1).is_none());
+        assert_eq!(animation.frame_index(), 1);
+        assert_eq!(animation.advance(0.6).as_deref(), Some("die"));
+        assert!(animation.is_finished());
+        assert_eq!(
+            animation.current_frame().map(|frame| frame.pixel_size),
+            Some((32, 16))
+        );
+        assert!(animation.advance(1.0).is_none());
+        animation.stop();
+        assert!(animation.current_sequence().is_none());
+    }
+
+    #[test]
+    fn pbr_material_test() {
+        let path = std::env::temp_dir().join("leafy_pbr_material_test.mtl");
+        let source = "newmtl metal\nKd 1 0 0\nPr 0.25\nPm 1\nKe 2 1 0\nmap_Pr rough.png\n\nnewmtl plain\nKd 0 1 0\nKe 1 1 1\n";
+        std::fs::write(&path, source).unwrap();
+        let (materials, _) = tobj::load_mtl(&path).unwrap();
+        let metal = Material::from_mtl(&materials[0]);
+        assert!(Material::from_mtl(&materials[1]).pbr.is_none());
+
+        let pbr = metal.pbr.as_ref().unwrap();
+        assert_eq!(pbr.base_color, Color32::RED);
+        assert_eq!((pbr.metallic, pbr.roughness), (1.0, 0.25));
+        assert_eq!(pbr.roughness_texture.as_deref(), Some("rough.png"));
+        assert_eq!(pbr.emissive_strength, 2.0);
+        assert_eq!(pbr.emissive, Color32::from_float_rgb(1.0, 0.5, 0.0));
+        assert_eq!(
+            metal.pbr_textures(true),
+            [None, None, Some("rough.png"), None, None]
+        );
+        assert_eq!(metal.pbr_textures(false), [None; 5]);
+
+        std::fs::write(&path, metal.to_mtl("metal")).unwrap();
+        let (exported, _) = tobj::load_mtl(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Material::from_mtl(&exported[0]).pbr, metal.pbr);
+    }
 }
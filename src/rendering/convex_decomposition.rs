@@ -0,0 +1,252 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::HitboxMesh;
+use std::collections::VecDeque;
+
+/// integer coordinates of a voxel (or of a voxel corner) in the decomposition grid
+type Voxel = [i32; 3];
+
+/// upper limit of candidate split planes that are evaluated per axis
+const MAX_PLANES_PER_AXIS: usize = 16;
+
+/// a set of voxels that will be approximated by one convex hull
+struct Part {
+    voxels: Vec<Voxel>,
+    concavity: f32,
+    splittable: bool,
+}
+
+impl Part {
+    /// creates a part and measures how much volume its convex hull would add
+    fn new(voxels: Vec<Voxel>) -> Self {
+        let concavity = concavity(&voxels);
+        Self {
+            voxels,
+            concavity,
+            splittable: true,
+        }
+    }
+
+    /// splits the part along the axis aligned plane that leaves the least concavity in both halves
+    fn split(&self) -> Option<(Part, Part)> {
+        let mut best: Option<(f32, usize, i32)> = None;
+        for axis in 0..3 {
+            let (min, max) = self
+                .voxels
+                .iter()
+                .map(|voxel| voxel[axis])
+                .minmax()
+                .into_option()?;
+            let step = ((max - min) as usize).div_ceil(MAX_PLANES_PER_AXIS).max(1);
+            for plane in (min + 1..=max).step_by(step) {
+                let (left, right): (Vec<Voxel>, Vec<Voxel>) =
+                    self.voxels.iter().partition(|voxel| voxel[axis] < plane);
+                let cost = concavity(&left) + concavity(&right);
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, plane));
+                }
+            }
+        }
+        let (_, axis, plane) = best?;
+        let (left, right) = self.voxels.iter().partition(|voxel| voxel[axis] < plane);
+        Some((Part::new(left), Part::new(right)))
+    }
+}
+
+/// splits a triangle mesh into convex parts by recursively cutting a voxel approximation of its volume, each part is returned as its own convex hull
+pub(crate) fn convex_decomposition(
+    mesh: &HitboxMesh,
+    settings: &DecompositionSettings,
+) -> Vec<HitboxMesh> {
+    if mesh.faces.is_empty() {
+        return Vec::new();
+    }
+    let resolution = settings.resolution.clamp(1, MAX_DECOMPOSITION_RESOLUTION);
+    let opt_grid = VoxelGrid::from_mesh(mesh, resolution);
+    if opt_grid.as_ref().is_none_or(|grid| grid.voxels.is_empty()) {
+        return Vec::new();
+    }
+    let grid = opt_grid.unwrap();
+    let threshold = settings.tolerance * grid.voxels.len() as f32;
+    let mut parts = vec![Part::new(grid.voxels.clone())];
+
+    // always split the part with the highest concavity next
+    while parts.len() < settings.max_parts.max(1) {
+        let next = parts
+            .iter()
+            .enumerate()
+            .filter(|(_, part)| part.splittable && part.concavity > threshold)
+            .max_by(|(_, part1), (_, part2)| part1.concavity.total_cmp(&part2.concavity))
+            .map(|(i, _)| i);
+        if next.is_none() {
+            break;
+        }
+        let mut part = parts.swap_remove(next.unwrap());
+        if let Some((left, right)) = part.split() {
+            parts.push(left);
+            parts.push(right);
+        } else {
+            part.splittable = false;
+            parts.push(part);
+        }
+    }
+    parts
+        .iter()
+        .map(|part| grid.part_hull(&part.voxels))
+        .collect_vec()
+}
+
+/// volume of a closed triangle mesh
+pub(crate) fn mesh_volume(mesh: &HitboxMesh) -> f32 {
+    mesh.faces
+        .iter()
+        .map(|&[a, b, c]| {
+            let (a, b, c) = (mesh.vertices[a], mesh.vertices[b], mesh.vertices[c]);
+            a.dot(&b.cross(&c))
+        })
+        .sum::<f32>()
+        .abs()
+        / 6.0
+}
+
+/// voxel volume by how much the convex hull of the voxel centers exceeds the voxels themselves
+fn concavity(voxels: &[Voxel]) -> f32 {
+    if voxels.len() < 4 {
+        return 0.0;
+    }
+    // flat parts do not have a volume to measure
+    let flat = (0..3).any(|axis| voxels.iter().map(|voxel| voxel[axis]).all_equal());
+    if flat {
+        return 0.0;
+    }
+    let hull = HitboxMesh {
+        vertices: hull_candidates(voxels.iter().copied()),
+        faces: Vec::new(),
+    }
+    .convex_hull();
+    (mesh_volume(&hull) - voxels.len() as f32).max(0.0)
+}
+
+/// reduces the points to the ones that can be vertices of their convex hull, a hull vertex is always the first or last point on each axis aligned line through it
+fn hull_candidates(points: impl Iterator<Item = Voxel>) -> Vec<Vec3> {
+    let mut points = points.collect_vec();
+    for axis in 0..3 {
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut rows: AHashMap<(i32, i32), (i32, i32)> = AHashMap::new();
+        for point in points.iter() {
+            let row = rows
+                .entry((point[a], point[b]))
+                .or_insert((point[axis], point[axis]));
+            row.0 = row.0.min(point[axis]);
+            row.1 = row.1.max(point[axis]);
+        }
+        points.retain(|point| {
+            let (min, max) = rows[&(point[a], point[b])];
+            point[axis] == min || point[axis] == max
+        });
+    }
+    points
+        .into_iter()
+        .map(|[x, y, z]| vec3(x as f32, y as f32, z as f32))
+        .collect_vec()
+}
+
+/// the solid voxels of a mesh on a regular grid
+struct VoxelGrid {
+    origin: Vec3,
+    voxel_size: f32,
+    voxels: Vec<Voxel>,
+}
+
+impl VoxelGrid {
+    /// voxelizes the surface of the mesh and fills the enclosed space, open meshes only keep their surface voxels (``None`` if the grid is too large)
+    fn from_mesh(mesh: &HitboxMesh, resolution: usize) -> Option<Self> {
+        let (min, max) = mesh.vertices.iter().fold(
+            (Vec3::from_element(f32::MAX), Vec3::from_element(f32::MIN)),
+            |(min, max), vertex| (min.inf(vertex), max.sup(vertex)),
+        );
+        let extent = max - min;
+        let voxel_size = (extent.max() / resolution as f32).max(f32::EPSILON);
+        let dims = extent.map(|side| ((side / voxel_size) as i32).min(resolution as i32) + 1);
+        let to_voxel = |point: Vec3| -> Voxel {
+            let scaled = (point - min) / voxel_size;
+            [0, 1, 2].map(|axis| (scaled[axis] as i32).clamp(0, dims[axis] - 1))
+        };
+
+        // the grid is padded by one voxel on each side so the outside is connected
+        let padded = dims.add_scalar(2).map(|side| side as usize);
+        let opt_size = padded
+            .x
+            .checked_mul(padded.y)
+            .and_then(|area| area.checked_mul(padded.z));
+        if opt_size.is_none() {
+            log::warn!("Voxel grid of the convex decomposition is too large.");
+            return None;
+        }
+        let index = |[x, y, z]: Voxel| {
+            (z + 1) as usize * padded.y * padded.x + (y + 1) as usize * padded.x + (x + 1) as usize
+        };
+        let mut solid = vec![true; opt_size.unwrap()];
+        let mut surface = vec![false; solid.len()];
+
+        // sample every triangle densely enough to hit each voxel it passes through
+        for &[a, b, c] in mesh.faces.iter() {
+            let (a, b, c) = (mesh.vertices[a], mesh.vertices[b], mesh.vertices[c]);
+            let longest = (b - a).norm().max((c - a).norm()).max((c - b).norm());
+            let samples = ((longest / voxel_size * 2.0).ceil() as usize).max(1);
+            for i in 0..=samples {
+                for j in 0..=samples - i {
+                    let (u, v) = (i as f32 / samples as f32, j as f32 / samples as f32);
+                    surface[index(to_voxel(a + (b - a) * u + (c - a) * v))] = true;
+                }
+            }
+        }
+
+        // flood fill the outside starting in a padding corner
+        let mut queue = VecDeque::from([[-1, -1, -1]]);
+        solid[index([-1, -1, -1])] = false;
+        while let Some([x, y, z]) = queue.pop_front() {
+            for neighbor in [
+                [x - 1, y, z],
+                [x + 1, y, z],
+                [x, y - 1, z],
+                [x, y + 1, z],
+                [x, y, z - 1],
+                [x, y, z + 1],
+            ] {
+                let inside = (0..3).all(|axis| (-1..=dims[axis]).contains(&neighbor[axis]));
+                if inside && solid[index(neighbor)] && !surface[index(neighbor)] {
+                    solid[index(neighbor)] = false;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let voxels = (0..dims.z)
+            .cartesian_product(0..dims.y)
+            .cartesian_product(0..dims.x)
+            .map(|((z, y), x)| [x, y, z])
+            .filter(|&voxel| solid[index(voxel)])
+            .collect_vec();
+        Some(Self {
+            origin: min,
+            voxel_size,
+            voxels,
+        })
+    }
+
+    /// the convex hull around all of the voxels in mesh space
+    fn part_hull(&self, voxels: &[Voxel]) -> HitboxMesh {
+        let corners = voxels.iter().flat_map(|&[x, y, z]| {
+            (0..8).map(move |corner| [x + (corner & 1), y + ((corner >> 1) & 1), z + (corner >> 2)])
+        });
+        let mut hull = HitboxMesh {
+            vertices: hull_candidates(corners.unique()),
+            faces: Vec::new(),
+        }
+        .convex_hull();
+        for vertex in hull.vertices.iter_mut() {
+            *vertex = self.origin + *vertex * self.voxel_size;
+        }
+        hull
+    }
+}
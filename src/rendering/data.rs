@@ -1,19 +1,106 @@
 use crate::internal_prelude::*;
 use crate::rendering::shader::ShaderProgram;
+use crate::rendering::sprite_atlas::{load_atlas, SpriteAtlas, SpriteAtlasSettings};
 use crate::rendering::sprite_renderer::SpriteSheet;
+use crate::rendering::texture_formats::{load_texture_data, TextureData};
 use stb_image::image::Image;
 use std::ptr;
 
 /// generates a gl texture from given image data, filtering and wrapping
 #[rustfmt::skip]
 fn generate_texture(data: &Image<u8>, filtering: Filtering, wrapping: Wrapping, color_space: ColorSpace) -> GLuint {
+    let mut tex_id = 0;
+    unsafe {
+        gl::GenTextures(1, &mut tex_id);
+    }
+    reupload_texture(tex_id, data, color_space);
+    set_sampling_parameters(filtering, wrapping, true);
+    tex_id
+}
+
+/// sets the filtering and wrapping parameters of the currently bound texture
+#[rustfmt::skip]
+fn set_sampling_parameters(filtering: Filtering, wrapping: Wrapping, mipmapped: bool) {
+    let (mag_filter, min_filter) = match (filtering, mipmapped) {
+        (Filtering::Linear, true) => (gl::LINEAR, gl::LINEAR_MIPMAP_LINEAR),
+        (Filtering::Linear, false) => (gl::LINEAR, gl::LINEAR),
+        (Filtering::Nearest, true) => (gl::NEAREST, gl::NEAREST_MIPMAP_LINEAR),
+        (Filtering::Nearest, false) => (gl::NEAREST, gl::NEAREST),
+    };
+    let wrap = match wrapping {
+        Wrapping::Repeat => gl::REPEAT,
+        Wrapping::MirroredRepeat => gl::MIRRORED_REPEAT,
+        Wrapping::ClampToEdge => gl::CLAMP_TO_EDGE,
+        Wrapping::ClampToBorder => gl::CLAMP_TO_BORDER,
+    };
+    unsafe {
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap as GLint);
+    }
+}
+
+/// uploads decoded texture data with its mip chain into the given gl texture and sets all of the texture parameters
+#[rustfmt::skip]
+fn upload_texture_data(tex_id: GLuint, data: &TextureData, texture: &Texture) {
+    let (uploaded_levels, generate) = data.mip_chain(&texture.mipmaps);
+    let (internal_format, pixel_format, pixel_type) = data.format.gl_formats(texture.color_space, data.srgb);
+    let max_level = if generate {
+        texture.mipmaps.max_levels.map_or(1000, |max| max.max(1) as usize - 1)
+    } else {
+        uploaded_levels - 1
+    };
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_2D, tex_id);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        for (level, level_data) in data.levels.iter().take(uploaded_levels).enumerate() {
+            let (width, height) = data.level_dimensions(level);
+            if data.format.is_compressed() {
+                gl::CompressedTexImage2D(
+                    gl::TEXTURE_2D,
+                    level as GLint,
+                    internal_format,
+                    width as GLsizei,
+                    height as GLsizei,
+                    0,
+                    level_data.len() as GLsizei,
+                    level_data.as_ptr() as *const GLvoid,
+                );
+            } else {
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    level as GLint,
+                    internal_format as GLint,
+                    width as GLsizei,
+                    height as GLsizei,
+                    0,
+                    pixel_format,
+                    pixel_type,
+                    level_data.as_ptr() as *const GLvoid,
+                );
+            }
+        }
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, max_level as GLint);
+        gl::TexParameterf(gl::TEXTURE_2D, gl::TEXTURE_LOD_BIAS, texture.mipmaps.lod_bias);
+        if generate {
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+    }
+    set_sampling_parameters(texture.filtering, texture.wrapping, generate || uploaded_levels > 1);
+}
+
+/// replaces the image data of an existing gl texture and keeps the texture parameters
+fn reupload_texture(tex_id: GLuint, data: &Image<u8>, color_space: ColorSpace) {
     let gl_color_space_enum = match color_space {
         ColorSpace::SRGBA => gl::SRGB_ALPHA as GLint,
         ColorSpace::RGBA8 => gl::RGBA8 as GLint,
+        ColorSpace::RGBA16F => gl::RGBA16F as GLint,
+        ColorSpace::RGBA32F => gl::RGBA32F as GLint,
     };
-    let mut tex_id = 0;
     unsafe {
-        gl::GenTextures(1, &mut tex_id);
         gl::BindTexture(gl::TEXTURE_2D, tex_id);
         gl::TexImage2D(
             gl::TEXTURE_2D,
@@ -27,45 +114,17 @@ fn generate_texture(data: &Image<u8>, filtering: Filtering, wrapping: Wrapping,
             data.data.as_ptr() as *const GLvoid,
         );
         gl::GenerateMipmap(gl::TEXTURE_2D);
-
-        match filtering {
-            Filtering::Linear => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as GLint);
-            }
-            Filtering::Nearest => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_LINEAR as GLint);
-            }
-        }
-        match wrapping {
-            Wrapping::Repeat => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
-            }
-            Wrapping::MirroredRepeat => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::MIRRORED_REPEAT as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::MIRRORED_REPEAT as GLint);
-            }
-            Wrapping::ClampToEdge => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
-            }
-            Wrapping::ClampToBorder => {
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as GLint);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as GLint);
-            }
-        }
     }
-    tex_id
 }
 
 /// holds the texture ID's for the App
 pub(crate) struct TextureMap {
     textures: AHashMap<Texture, GLuint>,
     material_textures: AHashMap<String, GLuint>,
+    material_texture_sources: AHashMap<String, PathBuf>,
     sheets: AHashMap<Rc<Path>, SpriteSheet>,
     sprites: AHashMap<Rc<Path>, GLuint>,
+    atlases: AHashMap<Rc<str>, SpriteAtlas>,
 }
 
 impl TextureMap {
@@ -74,8 +133,10 @@ impl TextureMap {
         Self {
             textures: AHashMap::new(),
             material_textures: AHashMap::new(),
+            material_texture_sources: AHashMap::new(),
             sheets: AHashMap::new(),
             sprites: AHashMap::new(),
+            atlases: AHashMap::new(),
         }
     }
 
@@ -85,19 +146,19 @@ impl TextureMap {
             log::warn!("Texture data for {texture:?} already present.");
             return false;
         }
-        if let Some(image) = stbi_load_u8_rgba(&texture.path) {
-            let tex_id = generate_texture(
-                &image,
-                texture.filtering,
-                texture.wrapping,
-                texture.color_space,
-            );
-            self.textures.insert(texture.clone(), tex_id);
-            log::debug!("Loaded texture: {texture:?}");
-            true
-        } else {
-            log::error!("Error loading texture file data for {texture:?}");
-            false
+        match load_texture_data(texture) {
+            Ok(data) => {
+                let mut tex_id = 0;
+                unsafe { gl::GenTextures(1, &mut tex_id) };
+                upload_texture_data(tex_id, &data, texture);
+                self.textures.insert(texture.clone(), tex_id);
+                log::debug!("Loaded texture: {texture:?}");
+                true
+            }
+            Err(error) => {
+                log::error!("Error loading texture file data for {texture:?}: {error}");
+                false
+            }
         }
     }
 
@@ -117,15 +178,12 @@ impl TextureMap {
     pub(crate) fn add_material_texture(&mut self, path: impl AsRef<Path>) -> bool {
         let path = path.as_ref();
         if let Some(image) = stbi_load_u8_rgba(path) {
-            let tex_id = generate_texture(
+            self.add_material_texture_image(
+                path.file_name().unwrap().to_str().unwrap(),
                 &image,
-                Filtering::Nearest,
-                Wrapping::default(),
-                ColorSpace::RGBA8,
+                Some(path),
             );
             log::debug!("Loaded material texture: {path:?}.");
-            self.material_textures
-                .insert(path.file_name().unwrap().to_str().unwrap().into(), tex_id);
             true
         } else {
             log::error!("Error loading material texture file data from {path:?}.");
@@ -133,10 +191,53 @@ impl TextureMap {
         }
     }
 
+    /// loads a material texture from encoded image file data in memory (e.g. embedded in a model file) that is stored with the given name
+    pub(crate) fn add_embedded_material_texture(
+        &mut self,
+        name: impl AsRef<str>,
+        bytes: &[u8],
+    ) -> bool {
+        let name = name.as_ref();
+        if let Some(image) = stbi_load_u8_rgba_from_memory(bytes) {
+            self.add_material_texture_image(name, &image, None);
+            log::debug!("Loaded embedded material texture: {name:?}.");
+            true
+        } else {
+            log::error!("Error loading embedded material texture data for {name:?}.");
+            false
+        }
+    }
+
+    /// uploads already decoded material texture image data that is stored with the given name, the source file is used for reloading
+    pub(crate) fn add_material_texture_image(
+        &mut self,
+        name: impl AsRef<str>,
+        image: &Image<u8>,
+        source: Option<&Path>,
+    ) {
+        let name = name.as_ref();
+        let tex_id = generate_texture(
+            image,
+            Filtering::Nearest,
+            Wrapping::default(),
+            ColorSpace::RGBA8,
+        );
+        if let Some(old_id) = self.material_textures.insert(name.into(), tex_id) {
+            unsafe { gl::DeleteTextures(1, &old_id) };
+        }
+        match source {
+            Some(path) => self
+                .material_texture_sources
+                .insert(name.into(), path.to_path_buf()),
+            None => self.material_texture_sources.remove(name),
+        };
+    }
+
     /// delete a stored material texture
     pub(crate) fn delete_material_texture(&mut self, name: impl AsRef<str>) -> bool {
         let name = name.as_ref();
         if let Some(id) = self.material_textures.remove(name) {
+            self.material_texture_sources.remove(name);
             unsafe { gl::DeleteTextures(1, &id) };
             log::debug!("Deleted material texture: {name:?}.");
             true
@@ -242,6 +343,69 @@ impl TextureMap {
         }
     }
 
+    /// packs the sprite images into a new atlas that is stored under the given name
+    pub(crate) fn add_atlas(
+        &mut self,
+        name: &Rc<str>,
+        paths: &[Rc<Path>],
+        settings: SpriteAtlasSettings,
+    ) -> bool {
+        if self.atlases.contains_key(name) {
+            log::warn!("Sprite atlas data already present for name: {name:?}.");
+            return false;
+        }
+        match load_atlas(paths, &settings) {
+            Ok(packed) => {
+                let tex_id = generate_texture(
+                    &packed.image,
+                    Filtering::Nearest,
+                    Wrapping::ClampToEdge,
+                    ColorSpace::RGBA8,
+                );
+                let atlas = SpriteAtlas {
+                    texture_id: tex_id,
+                    width: packed.image.width,
+                    height: packed.image.height,
+                    regions: packed.regions,
+                    settings,
+                };
+                log::debug!(
+                    "Loaded sprite atlas {name:?} with {} sprites and size {}x{}.",
+                    atlas.regions.len(),
+                    atlas.width,
+                    atlas.height
+                );
+                self.atlases.insert(name.clone(), atlas);
+                true
+            }
+            Err(error) => {
+                log::error!("Error creating sprite atlas {name:?}: {error}");
+                false
+            }
+        }
+    }
+
+    /// deletes a stored sprite atlas
+    pub(crate) fn delete_atlas(&mut self, name: &Rc<str>) -> bool {
+        if let Some(atlas) = self.atlases.remove(name) {
+            unsafe { gl::DeleteTextures(1, &atlas.texture_id) };
+            log::debug!("Deleted sprite atlas: {name:?}.");
+            true
+        } else {
+            log::warn!("Sprite atlas data not present for name: {name:?}.");
+            false
+        }
+    }
+
+    /// yields an atlas reference for given name
+    pub(crate) fn get_atlas(&self, name: &Rc<str>) -> Option<&SpriteAtlas> {
+        let result = self.atlases.get(name);
+        if result.is_none() {
+            log::warn!("Sprite atlas {name:?} is not loaded!");
+        }
+        result
+    }
+
     /// yields a sheet reference for given path
     pub(crate) fn get_sheet(&self, path: &Rc<Path>) -> Option<&SpriteSheet> {
         let result = self.sheets.get(path);
@@ -260,6 +424,143 @@ impl TextureMap {
         result
     }
 
+    /// iterator over all of the loaded textures
+    pub(crate) fn all_textures(&self) -> impl Iterator<Item = &Texture> {
+        self.textures.keys()
+    }
+
+    /// iterator over all of the loaded sprite sheet paths
+    pub(crate) fn all_sheets(&self) -> impl Iterator<Item = &Rc<Path>> {
+        self.sheets.keys()
+    }
+
+    /// iterator over all of the loaded sprite paths
+    pub(crate) fn all_sprites(&self) -> impl Iterator<Item = &Rc<Path>> {
+        self.sprites.keys()
+    }
+
+    /// iterator over all of the loaded sprite atlas names
+    pub(crate) fn all_atlases(&self) -> impl Iterator<Item = &Rc<str>> {
+        self.atlases.keys()
+    }
+
+    /// all the source files of the stored textures
+    pub(crate) fn source_files(&self) -> Vec<PathBuf> {
+        self.textures
+            .keys()
+            .flat_map(|texture| {
+                let custom_levels = match &texture.mipmaps.mode {
+                    MipmapMode::Custom(paths) => paths.as_slice(),
+                    _ => &[],
+                };
+                std::iter::once(&texture.path)
+                    .chain(custom_levels)
+                    .map(|path| path.to_path_buf())
+            })
+            .chain(self.material_texture_sources.values().cloned())
+            .chain(self.sheets.keys().map(|path| path.to_path_buf()))
+            .chain(self.sprites.keys().map(|path| path.to_path_buf()))
+            .chain(
+                self.atlases
+                    .values()
+                    .flat_map(|atlas| atlas.sources().map(|path| path.to_path_buf())),
+            )
+            .unique()
+            .collect()
+    }
+
+    /// re-uploads the image data of all textures that use the source file without changing the texture ids, keeps the previous data if the file can not be loaded
+    pub(crate) fn reload_file(&mut self, path: &Path) -> bool {
+        let mut success = true;
+        for (texture, tex_id) in self
+            .textures
+            .iter()
+            .filter(|(texture, _)| texture.uses_file(path))
+        {
+            match load_texture_data(texture) {
+                Ok(data) => upload_texture_data(*tex_id, &data, texture),
+                Err(error) => {
+                    log::warn!("Error reloading texture {texture:?}, keeping the previous version: {error}");
+                    success = false;
+                }
+            }
+        }
+        let is_image_source = self
+            .material_texture_sources
+            .values()
+            .any(|source| source == path)
+            || self
+                .sheets
+                .keys()
+                .any(|sheet_path| sheet_path.as_ref() == path)
+            || self
+                .sprites
+                .keys()
+                .any(|sprite_path| sprite_path.as_ref() == path);
+        if is_image_source {
+            success &= self.reload_image_file(path);
+        }
+        for (name, atlas) in self
+            .atlases
+            .iter_mut()
+            .filter(|(_, atlas)| atlas.regions.contains_key(path))
+        {
+            let paths = atlas.sources().cloned().collect_vec();
+            match load_atlas(&paths, &atlas.settings) {
+                Ok(packed) => {
+                    reupload_texture(atlas.texture_id, &packed.image, ColorSpace::RGBA8);
+                    atlas.width = packed.image.width;
+                    atlas.height = packed.image.height;
+                    atlas.regions = packed.regions;
+                }
+                Err(error) => {
+                    log::warn!("Error repacking sprite atlas {name:?}, keeping the previous version: {error}");
+                    success = false;
+                }
+            }
+        }
+        if success {
+            log::debug!("Reloaded texture file {path:?}.");
+        }
+        success
+    }
+
+    /// re-uploads the 8-bit image data of the material textures, sprite sheets and sprites that use the source file
+    fn reload_image_file(&mut self, path: &Path) -> bool {
+        let image = stbi_load_u8_rgba(path);
+        if image.is_none() {
+            log::warn!(
+                "Error reloading texture file data from {path:?}, keeping the previous version."
+            );
+            return false;
+        }
+        let image = image.unwrap();
+        for (_, tex_id) in self.material_textures.iter().filter(|(name, _)| {
+            self.material_texture_sources
+                .get(*name)
+                .is_some_and(|source| source == path)
+        }) {
+            reupload_texture(*tex_id, &image, ColorSpace::RGBA8);
+        }
+        for (_, sheet) in self
+            .sheets
+            .iter_mut()
+            .filter(|(sheet_path, _)| sheet_path.as_ref() == path)
+        {
+            reupload_texture(sheet.texture_id, &image, ColorSpace::RGBA8);
+            sheet.width = image.width;
+            sheet.height = image.height;
+        }
+        for (_, tex_id) in self
+            .sprites
+            .iter()
+            .filter(|(sprite_path, _)| sprite_path.as_ref() == path)
+        {
+            reupload_texture(*tex_id, &image, ColorSpace::RGBA8);
+        }
+        true
+    }
+
     /// clears the texture map and deletes all of the stored textures
     pub(crate) fn clear(&mut self) {
         for texture in self.textures.values() {
@@ -270,6 +571,7 @@ impl TextureMap {
         }
         self.textures.clear();
         self.material_textures.clear();
+        self.material_texture_sources.clear();
 
         for tex_id in self.sheets.values().map(|sheet| sheet.texture_id) {
             unsafe { gl::DeleteTextures(1, &tex_id) };
@@ -279,6 +581,11 @@ impl TextureMap {
         }
         self.sheets.clear();
         self.sprites.clear();
+
+        for atlas in self.atlases.values() {
+            unsafe { gl::DeleteTextures(1, &atlas.texture_id) };
+        }
+        self.atlases.clear();
     }
 }
 
@@ -297,6 +604,9 @@ impl Drop for TextureMap {
         for tex_id in self.sprites.values() {
             unsafe { gl::DeleteTextures(1, tex_id) };
         }
+        for atlas in self.atlases.values() {
+            unsafe { gl::DeleteTextures(1, &atlas.texture_id) };
+        }
     }
 }
 
@@ -746,33 +1056,42 @@ pub struct Skybox {
 
 impl Skybox {
     /// Trys to create a new skybox cube map from input texture paths ``[right, left, top, bottom, front, back]``. Returns ``None`` if the texture loading from the given paths was unsuccessful.
-    #[rustfmt::skip]
     pub fn try_new(paths: [impl AsRef<Path>; 6]) -> Option<Self> {
+        let mut images = Vec::with_capacity(6);
+        for path in paths.iter() {
+            let path = path.as_ref();
+            if let Some(image) = stbi_load_u8_rgba(path) {
+                images.push(image);
+            } else {
+                log::warn!("Error loading skybox texture from {path:?}.");
+                return None;
+            }
+        }
+        Some(Self::from_images(&images))
+    }
+
+    /// creates the cube map from already loaded images in the order ``[right, left, top, bottom, front, back]``
+    #[rustfmt::skip]
+    pub(crate) fn from_images(images: &[Image<u8>]) -> Self {
+        debug_assert_eq!(images.len(), 6);
         let mut cube_map = 0;
         unsafe {
             gl::GenTextures(1, &mut cube_map);
             gl::BindTexture(gl::TEXTURE_CUBE_MAP, cube_map);
         }
-        for i in 0..6 {
-            let path = paths[i as usize].as_ref();
-            if let Some(texture) = stbi_load_u8_rgba(path) {
-                unsafe {
-                    gl::TexImage2D(
-                        gl::TEXTURE_CUBE_MAP_POSITIVE_X + i,
-                        0,
-                        gl::RGBA8 as GLint,
-                        texture.width as GLint,
-                        texture.height as GLint,
-                        0,
-                        gl::RGBA,
-                        gl::UNSIGNED_BYTE,
-                        texture.data.as_ptr() as *const GLvoid,
-                    );
-                }
-            } else {
-                log::warn!("Error loading skybox texture from {path:?}.");
-                unsafe { gl::DeleteTextures(1, &cube_map) };
-                return None;
+        for (i, texture) in images.iter().enumerate() {
+            unsafe {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    0,
+                    gl::RGBA8 as GLint,
+                    texture.width as GLint,
+                    texture.height as GLint,
+                    0,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    texture.data.as_ptr() as *const GLvoid,
+                );
             }
         }
         unsafe {
@@ -801,7 +1120,7 @@ impl Skybox {
         }
         log::trace!("Created skybox.");
 
-        Some(Self { cube_map, vao, vbo })
+        Self { cube_map, vao, vbo }
     }
 
     /// renders the skybox
@@ -1093,9 +1412,9 @@ impl ScreenTexture {
         }
     }
 
-    /// unbind the screen texture and use the default frame buffer
+    /// unbind the screen texture and use the output frame buffer (0 is the default one)
     #[rustfmt::skip]
-    pub(crate) fn unbind(&self, bloom_shader: &ShaderProgram, use_bloom: bool, bloom_iterations: usize) {
+    pub(crate) fn unbind(&self, bloom_shader: &ShaderProgram, use_bloom: bool, bloom_iterations: usize, output_fbo: GLuint) {
         unsafe {
             // blit mulisampled texture if necessary
             if self.msaa {
@@ -1134,7 +1453,7 @@ impl ScreenTexture {
             }
 
             // reset frame buffer binding
-            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, output_fbo);
             gl::Viewport(
                 self.tmp_viewport[0],
                 self.tmp_viewport[1],
@@ -1191,6 +1510,96 @@ impl Drop for ScreenTexture {
     }
 }
 
+/// frame buffer that takes the place of the default one when rendering without a window
+pub(crate) struct OffscreenTarget {
+    fbo: GLuint,
+    color_rbo: GLuint,
+    depth_rbo: GLuint,
+    width: GLsizei,
+    height: GLsizei,
+}
+
+impl OffscreenTarget {
+    /// creates a new 8 bit rgba target with a depth + stencil buffer, fails if the frame buffer is incomplete
+    #[rustfmt::skip]
+    pub(crate) fn new(width: GLsizei, height: GLsizei) -> Result<Self, String> {
+        let mut fbo = 0;
+        let mut color_rbo = 0;
+        let mut depth_rbo = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut color_rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, color_rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width, height);
+
+            gl::GenRenderbuffers(1, &mut depth_rbo);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_rbo);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color_rbo);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth_rbo);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                gl::DeleteFramebuffers(1, &fbo);
+                gl::DeleteRenderbuffers(1, &color_rbo);
+                gl::DeleteRenderbuffers(1, &depth_rbo);
+                return Err(format!("Offscreen frame buffer of size {width}x{height} is incomplete (status {status:#x})."));
+            }
+        }
+
+        Ok(Self {
+            fbo,
+            color_rbo,
+            depth_rbo,
+            width,
+            height,
+        })
+    }
+
+    /// the gl frame buffer id
+    #[inline]
+    pub(crate) fn fbo(&self) -> GLuint {
+        self.fbo
+    }
+
+    /// reads back the rgba pixels with the top row first
+    pub(crate) fn read_pixels(&self) -> Vec<u8> {
+        let row_len = self.width as usize * 4;
+        let mut pixels = vec![0u8; row_len * self.height as usize];
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                self.width,
+                self.height,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut GLvoid,
+            );
+        }
+        // gl starts at the bottom row
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks_exact(row_len).rev() {
+            flipped.extend_from_slice(row);
+        }
+        flipped
+    }
+}
+
+impl Drop for OffscreenTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteRenderbuffers(1, &self.color_rbo);
+            gl::DeleteRenderbuffers(1, &self.depth_rbo);
+        }
+    }
+}
+
 /// Holds all parameters for post processing. This can be used to change the values of gamma, hue, saturation and brightness. For gamma, typical values are ``1.0`` (default) for linear color space and ``2.2`` for SRGB. The parameters of the HSV color space are all positive factors and are **not** absolute values. Hue is also in range [0, 1] inernally. You have to make shure the values are correct and valid yourself! The exposure parameter is an absolute value. The default value is 1.4. The ``use_bloom`` flag determines wether or not very bright parts of the scene should experience a bloom effect. This is turned on by default. There is also a ``bloom_threshold_shift`` setting that will control the brightness value at which bloom will be applied. This setting can also be controled for the skybox only. These values both default at 0. For bloom, you can also control the strength computation (iterations). The default value is 10 iterations. The ``background_as_scene_element`` flag determines wether or not the background clear color is used in the HDR color tone mapping of the engine. If this is set to true, the color will be affected by other settings like exposure and will be able to experience bloom. The default value is true.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct PostProcessingParams {
@@ -1230,4 +1639,28 @@ pub(crate) struct MaterialData {
     pub(crate) diffuse_color: Vec3,
     pub(crate) specular_color: Vec3,
     pub(crate) shininess: f32,
+    pub(crate) pbr: Option<PbrMaterialData>,
+}
+
+/// stores the physically based material values for the renderer
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct PbrMaterialData {
+    pub(crate) base_color: Vec4,
+    pub(crate) metallic: f32,
+    pub(crate) roughness: f32,
+    pub(crate) occlusion_strength: f32,
+    pub(crate) emissive_color: Vec3,
+}
+
+impl From<&PbrMaterial> for PbrMaterialData {
+    fn from(pbr: &PbrMaterial) -> Self {
+        Self {
+            base_color: pbr.base_color.to_vec4(),
+            metallic: pbr.metallic.clamp(0.0, 1.0),
+            // the highlights of point lights degenerate on perfectly smooth surfaces
+            roughness: pbr.roughness.clamp(0.04, 1.0),
+            occlusion_strength: pbr.occlusion_strength.clamp(0.0, 1.0),
+            emissive_color: pbr.emissive.to_vec4().xyz() * pbr.emissive_strength.max(0.0),
+        }
+    }
 }
@@ -0,0 +1,1056 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::{Mesh, MorphTarget, VertexSkin};
+use crate::rendering::skinning::MAX_JOINTS;
+use crate::utils::json::JsonValue;
+use crate::utils::vfs::read_file;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+
+const GLB_MAGIC: u32 = 0x46546C67;
+const GLB_JSON_CHUNK: u32 = 0x4E4F534A;
+const GLB_BIN_CHUNK: u32 = 0x004E4942;
+const TRIANGLES_MODE: usize = 4;
+
+/// image data that is referenced by gltf materials
+pub(crate) enum GltfImage {
+    External(PathBuf),
+    Embedded { name: String, bytes: Vec<u8> },
+}
+
+impl GltfImage {
+    /// the material texture name that the image is stored with
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Self::External(path) => path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            Self::Embedded { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// one node of the scene hierarchy that has a mesh attached to it with its global transform
+pub(crate) struct GltfNode {
+    pub(crate) name: Option<String>,
+    pub(crate) mesh: usize,
+    pub(crate) skin: Option<usize>,
+    /// the initial morph target weights of the node or its mesh
+    pub(crate) morph_weights: Vec<f32>,
+    pub(crate) transform: Mat4,
+}
+
+/// all of the data loaded from a ``.gltf`` or ``.glb`` file
+pub(crate) struct GltfData {
+    /// all of the triangle primitives for every gltf mesh
+    pub(crate) meshes: Vec<Vec<Mesh>>,
+    pub(crate) materials: Vec<(String, Material)>,
+    pub(crate) images: Vec<GltfImage>,
+    pub(crate) nodes: Vec<GltfNode>,
+    /// the skeleton of every skin, skins with an invalid joint hierarchy are ``None``
+    pub(crate) skeletons: Vec<Option<Skeleton>>,
+    pub(crate) clips: Vec<SkeletalClip>,
+}
+
+impl GltfData {
+    /// loads and parses a gltf file (both the text and binary format)
+    pub(crate) fn load(file_path: &Path) -> Result<Self, String> {
+        let bytes = read_file(file_path).map_err(|e| e.to_string())?;
+        let (json, glb_bin) = if read_u32(&bytes, 0) == Some(GLB_MAGIC) {
+            parse_glb(&bytes)?
+        } else {
+            let text = std::str::from_utf8(&bytes).map_err(|e| e.to_string())?;
+            (text.to_string(), None)
+        };
+        let root = JsonValue::parse(&json).ok_or("Invalid JSON data.")?;
+        let file_stem = file_path
+            .file_stem()
+            .map_or("gltf".into(), |stem| stem.to_string_lossy());
+
+        let mut buffers = Vec::new();
+        for (i, buffer) in root.array("buffers").iter().enumerate() {
+            let data = match buffer.get("uri").and_then(|uri| uri.as_str()) {
+                Some(uri) => load_uri(uri, file_path)?,
+                None if i == 0 => glb_bin.clone().ok_or("Missing binary chunk.")?,
+                None => return Err(format!("Buffer {i} has no data.")),
+            };
+            buffers.push(data);
+        }
+        let reader = AccessorReader {
+            root: &root,
+            buffers,
+        };
+
+        let mut images = Vec::new();
+        for (i, image) in root.array("images").iter().enumerate() {
+            let extension = match image.get("mimeType").and_then(|m| m.as_str()) {
+                Some("image/jpeg") => "jpg",
+                _ => "png",
+            };
+            let embedded_name = format!("{file_stem}_image{i}.{extension}");
+            let loaded = match image.get("uri").and_then(|uri| uri.as_str()) {
+                Some(uri) if uri.starts_with("data:") => GltfImage::Embedded {
+                    name: embedded_name,
+                    bytes: load_uri(uri, file_path)?,
+                },
+                Some(uri) => {
+                    let decoded = percent_decode(uri);
+                    let mut full_path = PathBuf::from(file_path);
+                    full_path.set_file_name(&decoded);
+                    if decoded.is_empty() || full_path.file_name().is_none() {
+                        return Err(format!("Image {i} has the invalid uri {uri:?}."));
+                    }
+                    GltfImage::External(full_path)
+                }
+                None => {
+                    let view = image
+                        .get("bufferView")
+                        .and_then(|v| v.as_usize())
+                        .ok_or(format!("Image {i} has no data."))?;
+                    GltfImage::Embedded {
+                        name: embedded_name,
+                        bytes: reader.buffer_view(view)?.to_vec(),
+                    }
+                }
+            };
+            images.push(loaded);
+        }
+
+        let texture_name = |texture_info: Option<&JsonValue>| -> Option<String> {
+            let texture = texture_info?.get("index")?.as_usize()?;
+            let source = root
+                .array("textures")
+                .get(texture)?
+                .get("source")?
+                .as_usize()?;
+            images.get(source).map(|image| image.name())
+        };
+
+        let materials = root
+            .array("materials")
+            .iter()
+            .enumerate()
+            .map(|(i, material)| {
+                let name = material
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .map_or_else(|| format!("{file_stem}_material{i}"), |n| n.to_string());
+                (name, convert_material(material, texture_name))
+            })
+            .collect_vec();
+
+        let mut meshes = Vec::new();
+        let mut primitive_count = 0;
+        for (i, mesh) in root.array("meshes").iter().enumerate() {
+            let mesh_name = mesh
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or_else(|| format!("{file_stem}_mesh{i}"), |n| n.to_string());
+            // target names are not part of the specification, but exporters store them in the extras
+            let target_names = mesh
+                .get("extras")
+                .map(|extras| extras.array("targetNames"))
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|n| n.as_str())
+                .map(|n| n.to_string())
+                .collect_vec();
+            let primitives = mesh.array("primitives");
+            let mut loaded = Vec::with_capacity(primitives.len());
+            for (j, primitive) in primitives.iter().enumerate() {
+                let mode = primitive.get("mode").and_then(|m| m.as_usize());
+                if mode.unwrap_or(TRIANGLES_MODE) != TRIANGLES_MODE {
+                    log::warn!("Skipped non-triangle primitive {j} of mesh {mesh_name:?}.");
+                    continue;
+                }
+                let name = if primitives.len() > 1 {
+                    format!("{mesh_name}_{j}")
+                } else {
+                    mesh_name.clone()
+                };
+                let material_name = primitive
+                    .get("material")
+                    .and_then(|m| m.as_usize())
+                    .and_then(|m| materials.get(m))
+                    .map(|(name, _)| name.clone());
+                let mut primitive_mesh = reader.primitive_mesh(
+                    primitive,
+                    name,
+                    file_path,
+                    material_name,
+                    &target_names,
+                )?;
+                primitive_mesh.source_index = primitive_count;
+                primitive_count += 1;
+                loaded.push(primitive_mesh);
+            }
+            meshes.push(loaded);
+        }
+
+        let nodes = collect_scene_nodes(&root)?;
+        let parents = node_parents(&root);
+
+        let mut skeletons = Vec::new();
+        for (i, skin) in root.array("skins").iter().enumerate() {
+            let name = skin
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or_else(|| format!("{file_stem}_skin{i}"), |n| n.to_string());
+            skeletons.push(reader.skeleton(skin, name, &parents, &file_stem)?);
+        }
+
+        // the joint indices of a skinned mesh refer to the joints of the skin of its node
+        for node in nodes.iter() {
+            let joint_count = match node.skin {
+                Some(skin) => root
+                    .array("skins")
+                    .get(skin)
+                    .map_or(0, |skin| skin.array("joints").len()),
+                None => continue,
+            };
+            for mesh in meshes.get(node.mesh).into_iter().flatten() {
+                let max_joint = mesh
+                    .skin
+                    .iter()
+                    .flat_map(|skin| skin.joints.iter().flatten())
+                    .max();
+                if max_joint.is_some_and(|joint| *joint as usize >= joint_count) {
+                    return Err(format!(
+                        "Mesh {:?} uses joint {} of a skin with {joint_count} joints.",
+                        mesh.name,
+                        max_joint.unwrap()
+                    ));
+                }
+            }
+        }
+
+        let mut clips = Vec::new();
+        for (i, animation) in root.array("animations").iter().enumerate() {
+            let name = animation
+                .get("name")
+                .and_then(|n| n.as_str())
+                .map_or_else(|| format!("{file_stem}_animation{i}"), |n| n.to_string());
+            clips.push(reader.clip(animation, name, &file_stem)?);
+        }
+
+        Ok(Self {
+            meshes,
+            materials,
+            images,
+            nodes,
+            skeletons,
+            clips,
+        })
+    }
+}
+
+/// splits a binary gltf file into the json chunk and the optional binary buffer chunk
+fn parse_glb(bytes: &[u8]) -> Result<(String, Option<Vec<u8>>), String> {
+    if read_u32(bytes, 4) != Some(2) {
+        return Err("Only GLB version 2 is supported.".into());
+    }
+    let total_length = (read_u32(bytes, 8).ok_or("Invalid GLB header.")? as usize).min(bytes.len());
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= total_length {
+        let chunk_length = read_u32(bytes, offset).unwrap() as usize;
+        let chunk_type = read_u32(bytes, offset + 4).unwrap();
+        let chunk = bytes
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or("Invalid GLB chunk length.")?;
+        match chunk_type {
+            GLB_JSON_CHUNK => json = Some(String::from_utf8_lossy(chunk).into_owned()),
+            GLB_BIN_CHUNK if bin.is_none() => bin = Some(chunk.to_vec()),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+    Ok((json.ok_or("Missing JSON chunk.")?, bin))
+}
+
+/// reads a little endian u32 at the given byte offset
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let slice = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(slice.try_into().unwrap()))
+}
+
+/// loads the data of a uri that is either a base64 data uri or a path relative to the gltf file
+fn load_uri(uri: &str, file_path: &Path) -> Result<Vec<u8>, String> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or("Only base64 data URIs are supported.")?;
+        BASE64.decode(encoded).map_err(|e| e.to_string())
+    } else {
+        let mut full_path = PathBuf::from(file_path);
+        full_path.set_file_name(percent_decode(uri));
+        read_file(&full_path).map_err(|e| format!("{full_path:?}: {e}"))
+    }
+}
+
+/// decodes percent encoded characters in relative uris
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// converts a pbr metallic roughness material, the phong parameters approximate it for exports to the ``.mtl`` format
+fn convert_material(
+    material: &JsonValue,
+    texture_name: impl Fn(Option<&JsonValue>) -> Option<String>,
+) -> Material {
+    let pbr = material.get("pbrMetallicRoughness");
+    let base_color = pbr
+        .and_then(|pbr| pbr.get("baseColorFactor"))
+        .and_then(|f| f.as_f32_vec())
+        .filter(|f| f.len() == 4)
+        .map_or(Color32::WHITE, |f| {
+            Color32::from_float_rgba(f[0], f[1], f[2], f[3])
+        });
+    let metallic = pbr
+        .and_then(|pbr| pbr.get("metallicFactor"))
+        .and_then(|f| f.as_f32())
+        .unwrap_or(1.0)
+        .clamp(0.0, 1.0);
+    let roughness = pbr
+        .and_then(|pbr| pbr.get("roughnessFactor"))
+        .and_then(|f| f.as_f32())
+        .unwrap_or(1.0)
+        .clamp(0.0, 1.0);
+    // the roughness is stored in the green and the metalness in the blue channel of the same texture
+    let metallic_roughness_texture =
+        texture_name(pbr.and_then(|pbr| pbr.get("metallicRoughnessTexture")));
+    let occlusion = material.get("occlusionTexture");
+    let emissive = material
+        .get("emissiveFactor")
+        .and_then(|f| f.as_f32_vec())
+        .filter(|f| f.len() == 3)
+        .map_or(Color32::BLACK, |f| {
+            Color32::from_float_rgb(f[0], f[1], f[2])
+        });
+    let emissive_strength = material
+        .get("extensions")
+        .and_then(|extensions| extensions.get("KHR_materials_emissive_strength"))
+        .and_then(|extension| extension.get("emissiveStrength"))
+        .and_then(|f| f.as_f32())
+        .unwrap_or(1.0)
+        .max(0.0);
+
+    let mut converted = Material::from_pbr(PbrMaterial {
+        base_color,
+        base_color_texture: texture_name(pbr.and_then(|pbr| pbr.get("baseColorTexture"))),
+        metallic,
+        metallic_texture: metallic_roughness_texture.clone(),
+        roughness,
+        roughness_texture: metallic_roughness_texture,
+        occlusion_strength: occlusion
+            .and_then(|o| o.get("strength"))
+            .and_then(|f| f.as_f32())
+            .unwrap_or(1.0)
+            .clamp(0.0, 1.0),
+        occlusion_texture: texture_name(occlusion),
+        emissive,
+        emissive_texture: texture_name(material.get("emissiveTexture")),
+        emissive_strength,
+    });
+
+    // metals reflect with their base color, dielectrics with a weak white highlight
+    let base = base_color.to_vec4().xyz();
+    let specular = glm::mix(&Vec3::from_element(0.04), &base, metallic) * (1.0 - roughness);
+    converted.specular =
+        Specular::Value(Color32::from_float_rgb(specular.x, specular.y, specular.z));
+    converted.shininess = (2.0 / roughness.powi(4).max(1e-4) - 2.0).clamp(1.0, 256.0);
+    converted.normal_texture = texture_name(material.get("normalTexture"));
+    converted
+}
+
+/// computes the global transforms of all nodes with meshes in the default scene
+fn collect_scene_nodes(root: &JsonValue) -> Result<Vec<GltfNode>, String> {
+    let nodes = root.array("nodes");
+    let scene_index = root.get("scene").and_then(|s| s.as_usize()).unwrap_or(0);
+    let roots = match root.array("scenes").get(scene_index) {
+        Some(scene) => scene
+            .array("nodes")
+            .iter()
+            .filter_map(|n| n.as_usize())
+            .collect_vec(),
+        None => {
+            let children = nodes
+                .iter()
+                .flat_map(|node| node.array("children"))
+                .filter_map(|c| c.as_usize())
+                .collect::<AHashSet<_>>();
+            (0..nodes.len())
+                .filter(|i| !children.contains(i))
+                .collect_vec()
+        }
+    };
+
+    let mut collected = Vec::new();
+    let mut visited = AHashSet::new();
+    let mut stack = roots
+        .into_iter()
+        .map(|index| (index, Mat4::identity()))
+        .collect_vec();
+
+    while let Some((index, parent_transform)) = stack.pop() {
+        if !visited.insert(index) {
+            return Err(format!(
+                "Node {index} is part of a cycle or has multiple parents."
+            ));
+        }
+        let node = nodes.get(index).ok_or(format!("Node {index} not found."))?;
+        let transform = parent_transform * local_transform(node);
+        if let Some(mesh) = node.get("mesh").and_then(|m| m.as_usize()) {
+            collected.push(GltfNode {
+                name: node
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .map(|n| n.to_string()),
+                mesh,
+                skin: node.get("skin").and_then(|s| s.as_usize()),
+                morph_weights: node
+                    .get("weights")
+                    .or_else(|| root.array("meshes").get(mesh)?.get("weights"))
+                    .and_then(|w| w.as_f32_vec())
+                    .unwrap_or_default(),
+                transform,
+            });
+        }
+        for child in node.array("children").iter().filter_map(|c| c.as_usize()) {
+            stack.push((child, transform));
+        }
+    }
+    Ok(collected)
+}
+
+/// the parent of every node
+fn node_parents(root: &JsonValue) -> Vec<Option<usize>> {
+    let nodes = root.array("nodes");
+    let mut parents = vec![None; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for child in node.array("children").iter().filter_map(|c| c.as_usize()) {
+            if let Some(parent) = parents.get_mut(child) {
+                *parent = Some(i);
+            }
+        }
+    }
+    parents
+}
+
+/// the transform of a node relative to the scene origin, an invalid hierarchy is cut off after visiting every node once
+fn global_transform(root: &JsonValue, parents: &[Option<usize>], index: usize) -> Mat4 {
+    let nodes = root.array("nodes");
+    let mut transform = Mat4::identity();
+    let mut current = Some(index);
+    for _ in 0..nodes.len() {
+        if current.is_none() {
+            break;
+        }
+        let node = current.unwrap();
+        transform = local_transform(&nodes[node]) * transform;
+        current = parents[node];
+    }
+    transform
+}
+
+/// the name that joints and animation tracks refer to a node with
+fn node_name(root: &JsonValue, index: usize, file_stem: &str) -> String {
+    root.array("nodes")
+        .get(index)
+        .and_then(|node| node.get("name"))
+        .and_then(|n| n.as_str())
+        .map_or_else(|| format!("{file_stem}_node{index}"), |n| n.to_string())
+}
+
+/// the transform of a node relative to its parent
+fn local_transform(node: &JsonValue) -> Mat4 {
+    if let Some(matrix) = node
+        .get("matrix")
+        .and_then(|m| m.as_f32_vec())
+        .filter(|m| m.len() == 16)
+    {
+        return Mat4::from_column_slice(&matrix);
+    }
+    let vector = |key: &str, default: Vec<f32>, len: usize| {
+        node.get(key)
+            .and_then(|v| v.as_f32_vec())
+            .filter(|v| v.len() == len)
+            .unwrap_or(default)
+    };
+    let t = vector("translation", vec![0.0, 0.0, 0.0], 3);
+    let r = vector("rotation", vec![0.0, 0.0, 0.0, 1.0], 4);
+    let s = vector("scale", vec![1.0, 1.0, 1.0], 3);
+
+    glm::translation(&vec3(t[0], t[1], t[2]))
+        * glm::quat_to_mat4(&glm::quat(r[0], r[1], r[2], r[3]))
+        * glm::scaling(&vec3(s[0], s[1], s[2]))
+}
+
+/// reads typed vertex data from the buffers described by accessors
+struct AccessorReader<'a> {
+    root: &'a JsonValue,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl AccessorReader<'_> {
+    /// the bytes of a buffer view
+    fn buffer_view(&self, index: usize) -> Result<&[u8], String> {
+        let view = self
+            .root
+            .array("bufferViews")
+            .get(index)
+            .ok_or(format!("Buffer view {index} not found."))?;
+        let buffer = view.get("buffer").and_then(|b| b.as_usize()).unwrap_or(0);
+        let offset = view
+            .get("byteOffset")
+            .and_then(|o| o.as_usize())
+            .unwrap_or(0);
+        let length = view
+            .get("byteLength")
+            .and_then(|l| l.as_usize())
+            .unwrap_or(0);
+        self.buffers
+            .get(buffer)
+            .zip(offset.checked_add(length))
+            .and_then(|(data, end)| data.get(offset..end))
+            .ok_or(format!("Buffer view {index} is out of bounds."))
+    }
+
+    /// reads the accessor data as floats (precise enough for all integer indices) and returns them with the number of components per element
+    fn read(&self, index: usize) -> Result<(Vec<f64>, usize), String> {
+        let accessor = self
+            .root
+            .array("accessors")
+            .get(index)
+            .ok_or(format!("Accessor {index} not found."))?;
+        let count = accessor
+            .get("count")
+            .and_then(|c| c.as_usize())
+            .unwrap_or(0);
+        let component_type = accessor
+            .get("componentType")
+            .and_then(|c| c.as_usize())
+            .ok_or(format!("Accessor {index} has no component type."))?;
+        let normalized = accessor
+            .get("normalized")
+            .and_then(|n| n.as_bool())
+            .unwrap_or(false);
+        let components = match accessor.get("type").and_then(|t| t.as_str()) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT4") => 16,
+            other => return Err(format!("Unsupported accessor type {other:?}.")),
+        };
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(format!("Unsupported component type {component_type}.")),
+        };
+
+        let out_of_bounds = || format!("Accessor {index} is out of bounds.");
+        let value_count = count.checked_mul(components).ok_or_else(out_of_bounds)?;
+        let opt_view_index = accessor.get("bufferView").and_then(|v| v.as_usize());
+
+        // the elements have to fit in the buffer view before anything is allocated
+        let mut opt_view = None;
+        if let Some(view_index) = opt_view_index {
+            let view = self.buffer_view(view_index)?;
+            let element_size = component_size * components;
+            let stride = self.root.array("bufferViews")[view_index]
+                .get("byteStride")
+                .and_then(|s| s.as_usize())
+                .unwrap_or(element_size);
+            let offset = accessor
+                .get("byteOffset")
+                .and_then(|o| o.as_usize())
+                .unwrap_or(0);
+            if count > 0 {
+                let end = (count - 1)
+                    .checked_mul(stride)
+                    .and_then(|last| last.checked_add(offset))
+                    .and_then(|last| last.checked_add(element_size))
+                    .ok_or_else(out_of_bounds)?;
+                if end > view.len() {
+                    return Err(out_of_bounds());
+                }
+            }
+            opt_view = Some((view, stride, offset));
+        }
+
+        let mut values = vec![0.0; value_count];
+        if let Some((view, stride, offset)) = opt_view {
+            for element in 0..count {
+                for component in 0..components {
+                    let start = offset + element * stride + component * component_size;
+                    let bytes = view
+                        .get(start..start + component_size)
+                        .ok_or_else(out_of_bounds)?;
+                    values[element * components + component] =
+                        decode_component(bytes, component_type, normalized);
+                }
+            }
+        }
+
+        if let Some(sparse) = accessor.get("sparse") {
+            let sparse_count = sparse.get("count").and_then(|c| c.as_usize()).unwrap_or(0);
+            let sparse_indices = sparse
+                .get("indices")
+                .ok_or("Sparse accessor without indices.")?;
+            let sparse_values = sparse
+                .get("values")
+                .ok_or("Sparse accessor without values.")?;
+            let index_type = sparse_indices
+                .get("componentType")
+                .and_then(|c| c.as_usize())
+                .unwrap_or(5125);
+            let index_size = if index_type == 5121 {
+                1
+            } else if index_type == 5123 {
+                2
+            } else {
+                4
+            };
+            let index_view = self.buffer_view(
+                sparse_indices
+                    .get("bufferView")
+                    .and_then(|v| v.as_usize())
+                    .unwrap_or(0),
+            )?;
+            let index_offset = sparse_indices
+                .get("byteOffset")
+                .and_then(|o| o.as_usize())
+                .unwrap_or(0);
+            let value_view = self.buffer_view(
+                sparse_values
+                    .get("bufferView")
+                    .and_then(|v| v.as_usize())
+                    .unwrap_or(0),
+            )?;
+            let value_offset = sparse_values
+                .get("byteOffset")
+                .and_then(|o| o.as_usize())
+                .unwrap_or(0);
+            if index_offset > index_view.len() || value_offset > value_view.len() {
+                return Err(format!("Sparse accessor {index} is out of bounds."));
+            }
+
+            for i in 0..sparse_count {
+                let start = index_offset + i * index_size;
+                let target = index_view
+                    .get(start..start + index_size)
+                    .map(|bytes| decode_component(bytes, index_type, false) as usize)
+                    .filter(|target| *target < count)
+                    .ok_or(format!("Sparse accessor {index} is out of bounds."))?;
+                for component in 0..components {
+                    let start = value_offset + (i * components + component) * component_size;
+                    let bytes = value_view
+                        .get(start..start + component_size)
+                        .ok_or(format!("Sparse accessor {index} is out of bounds."))?;
+                    values[target * components + component] =
+                        decode_component(bytes, component_type, normalized);
+                }
+            }
+        }
+
+        Ok((values, components))
+    }
+
+    /// reads a vertex attribute with an expected number of components
+    fn attribute(
+        &self,
+        primitive: &JsonValue,
+        name: &str,
+        components: &[usize],
+    ) -> Result<Option<(Vec<f32>, usize)>, String> {
+        let index = primitive
+            .get("attributes")
+            .and_then(|a| a.get(name))
+            .and_then(|i| i.as_usize());
+        if let Some(index) = index {
+            let (values, found) = self.read(index)?;
+            if !components.contains(&found) {
+                return Err(format!(
+                    "Attribute {name} has an invalid number of components."
+                ));
+            }
+            Ok(Some((
+                values.into_iter().map(|v| v as f32).collect(),
+                found,
+            )))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// converts a skin to a skeleton, joints whose parent node is no joint are root joints
+    fn skeleton(
+        &self,
+        skin: &JsonValue,
+        name: String,
+        parents: &[Option<usize>],
+        file_stem: &str,
+    ) -> Result<Option<Skeleton>, String> {
+        let nodes = self.root.array("nodes");
+        let joint_nodes = skin
+            .array("joints")
+            .iter()
+            .filter_map(|j| j.as_usize())
+            .collect_vec();
+        if joint_nodes.iter().any(|node| *node >= nodes.len()) {
+            return Err(format!("Skin {name:?} has invalid joints."));
+        }
+        if joint_nodes.len() > MAX_JOINTS {
+            return Err(format!(
+                "Skin {name:?} has {} joints, only {MAX_JOINTS} are supported.",
+                joint_nodes.len()
+            ));
+        }
+        let inverse_binds = match skin.get("inverseBindMatrices").and_then(|i| i.as_usize()) {
+            Some(index) => self
+                .read(index)?
+                .0
+                .into_iter()
+                .map(|v| v as f32)
+                .chunks(16)
+                .into_iter()
+                .map(Mat4::from_iterator)
+                .collect_vec(),
+            None => vec![Mat4::identity(); joint_nodes.len()],
+        };
+        if inverse_binds.len() < joint_nodes.len() {
+            return Err(format!("Skin {name:?} has too few inverse bind matrices."));
+        }
+
+        let joint_parent = |node: usize| {
+            parents[node].and_then(|parent| joint_nodes.iter().position(|j| *j == parent))
+        };
+        let joints = joint_nodes
+            .iter()
+            .zip(inverse_binds)
+            .map(|(&node, inverse_bind)| {
+                let (position, orientation, scale) =
+                    decompose_transform(&local_transform(&nodes[node]));
+                let rest = JointTransform::new(*position.data(), orientation.0, *scale.data());
+                Joint::new(
+                    node_name(self.root, node, file_stem),
+                    joint_parent(node),
+                    rest,
+                )
+                .with_inverse_bind_matrix(inverse_bind)
+            })
+            .collect_vec();
+
+        // the joint transforms include the nodes above the skeleton
+        let root_parent = joint_nodes
+            .iter()
+            .find(|node| joint_parent(**node).is_none())
+            .and_then(|node| parents[*node]);
+        Ok(Skeleton::new(name, joints).map(|mut skeleton| {
+            skeleton.root_transform = root_parent.map_or(Mat4::identity(), |parent| {
+                global_transform(self.root, parents, parent)
+            });
+            skeleton
+        }))
+    }
+
+    /// converts the node translation, rotation and scale channels of an animation to a clip
+    fn clip(
+        &self,
+        animation: &JsonValue,
+        name: String,
+        file_stem: &str,
+    ) -> Result<SkeletalClip, String> {
+        let samplers = animation.array("samplers");
+        let mut clip = SkeletalClip::new(name);
+        for channel in animation.array("channels") {
+            let target = channel.get("target");
+            let node = target
+                .and_then(|t| t.get("node"))
+                .and_then(|n| n.as_usize());
+            let path = target
+                .and_then(|t| t.get("path"))
+                .and_then(|p| p.as_str())
+                .unwrap_or_default();
+            let sampler = channel
+                .get("sampler")
+                .and_then(|s| s.as_usize())
+                .and_then(|s| samplers.get(s))
+                .ok_or(format!(
+                    "Animation {:?} has an invalid sampler.",
+                    clip.name()
+                ))?;
+            if node.is_none() || !["translation", "rotation", "scale"].contains(&path) {
+                log::debug!("Skipped {path:?} channel of animation {:?}.", clip.name());
+                continue;
+            }
+            let joint = node_name(self.root, node.unwrap(), file_stem);
+
+            let accessor = |key: &str| {
+                sampler
+                    .get(key)
+                    .and_then(|a| a.as_usize())
+                    .ok_or(format!("Animation {:?} has no {key} data.", clip.name()))
+            };
+            let times = self
+                .read(accessor("input")?)?
+                .0
+                .into_iter()
+                .map(|t| t as f32)
+                .collect_vec();
+            let (values, components) = self.read(accessor("output")?)?;
+            let values = values.into_iter().map(|v| v as f32).collect_vec();
+            let interpolation = match sampler.get("interpolation").and_then(|i| i.as_str()) {
+                Some("STEP") => Interpolation::Step,
+                Some("CUBICSPLINE") => Interpolation::CubicSpline,
+                _ => Interpolation::Linear,
+            };
+
+            clip = match (path, components) {
+                ("translation", 3) => clip.with_translations(
+                    joint,
+                    times,
+                    values
+                        .chunks_exact(3)
+                        .map(Vec3::from_column_slice)
+                        .collect(),
+                    interpolation,
+                ),
+                ("scale", 3) => clip.with_scales(
+                    joint,
+                    times,
+                    values
+                        .chunks_exact(3)
+                        .map(Vec3::from_column_slice)
+                        .collect(),
+                    interpolation,
+                ),
+                ("rotation", 4) => clip.with_rotations(
+                    joint,
+                    times,
+                    values
+                        .chunks_exact(4)
+                        .map(|r| glm::quat(r[0], r[1], r[2], r[3]))
+                        .collect(),
+                    interpolation,
+                ),
+                _ => {
+                    return Err(format!(
+                        "Animation {:?} has an invalid {path} channel.",
+                        clip.name()
+                    ))
+                }
+            };
+        }
+        Ok(clip)
+    }
+
+    /// converts a triangle primitive to a mesh
+    fn primitive_mesh(
+        &self,
+        primitive: &JsonValue,
+        name: String,
+        file_path: &Path,
+        material_name: Option<String>,
+        target_names: &[String],
+    ) -> Result<Mesh, String> {
+        let (positions, _) = self
+            .attribute(primitive, "POSITION", &[3])?
+            .ok_or(format!("Mesh {name:?} has no positions."))?;
+        let positions = positions
+            .into_iter()
+            .tuples()
+            .map(|(x, y, z)| vec3(x, y, z))
+            .collect_vec();
+
+        let normals = self
+            .attribute(primitive, "NORMAL", &[3])?
+            .map(|(normals, _)| {
+                normals
+                    .into_iter()
+                    .tuples()
+                    .map(|(x, y, z)| vec3(x, y, z))
+                    .collect_vec()
+            });
+        // gltf uses the top left image corner as the texture coordinate origin
+        let texture_coords = self
+            .attribute(primitive, "TEXCOORD_0", &[2])?
+            .map(|(uvs, _)| {
+                uvs.into_iter()
+                    .tuples()
+                    .map(|(u, v)| vec2(u, 1.0 - v))
+                    .collect_vec()
+            });
+        let colors = self
+            .attribute(primitive, "COLOR_0", &[3, 4])?
+            .map(|(colors, components)| {
+                colors
+                    .chunks_exact(components)
+                    .map(|c| vec4(c[0], c[1], c[2], c.get(3).copied().unwrap_or(1.0)))
+                    .collect_vec()
+            });
+
+        let indices = match primitive.get("indices").and_then(|i| i.as_usize()) {
+            Some(index) => self
+                .read(index)?
+                .0
+                .into_iter()
+                .map(|i| i as GLuint)
+                .collect_vec(),
+            None => (0..positions.len() as GLuint).collect_vec(),
+        };
+        let joints = self
+            .attribute(primitive, "JOINTS_0", &[4])?
+            .map(|(joints, _)| {
+                joints
+                    .chunks_exact(4)
+                    .map(|j| [j[0] as u16, j[1] as u16, j[2] as u16, j[3] as u16])
+                    .collect_vec()
+            });
+        let weights = self
+            .attribute(primitive, "WEIGHTS_0", &[4])?
+            .map(|(weights, _)| {
+                weights
+                    .chunks_exact(4)
+                    .map(Vec4::from_column_slice)
+                    .collect_vec()
+            });
+
+        if indices.len() % 3 != 0 || indices.iter().any(|i| *i as usize >= positions.len()) {
+            return Err(format!("Mesh {name:?} has invalid indices."));
+        }
+        if joints
+            .iter()
+            .flatten()
+            .flatten()
+            .any(|joint| *joint as usize >= MAX_JOINTS)
+        {
+            return Err(format!("Mesh {name:?} has invalid joint indices."));
+        }
+        let vertex_count = positions.len();
+        if [
+            normals.as_ref().map(|n| n.len()),
+            texture_coords.as_ref().map(|t| t.len()),
+            colors.as_ref().map(|c| c.len()),
+            joints.as_ref().map(|j| j.len()),
+            weights.as_ref().map(|w| w.len()),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|len| len != vertex_count)
+        {
+            return Err(format!(
+                "Mesh {name:?} has mismatching vertex attribute counts."
+            ));
+        }
+
+        let mut mesh = Mesh::from_vertex_data(
+            name,
+            Arc::from(file_path),
+            positions,
+            colors,
+            normals,
+            texture_coords,
+            indices,
+            material_name,
+        );
+        if let (Some(joints), Some(weights)) = (joints, weights) {
+            mesh.skin = Some(VertexSkin::new(joints, weights));
+        }
+        for (i, target) in primitive.array("targets").iter().enumerate() {
+            let mut deltas = [Vec::new(), Vec::new()];
+            for (attribute, deltas) in ["POSITION", "NORMAL"].into_iter().zip(deltas.iter_mut()) {
+                *deltas = match target.get(attribute).and_then(|a| a.as_usize()) {
+                    Some(index) => self
+                        .read(index)?
+                        .0
+                        .into_iter()
+                        .map(|d| d as f32)
+                        .tuples()
+                        .map(|(x, y, z)| vec3(x, y, z))
+                        .collect_vec(),
+                    None => vec![Vec3::zeros(); vertex_count],
+                };
+                if deltas.len() != vertex_count {
+                    return Err(format!(
+                        "Morph target {i} of mesh {:?} has an invalid {attribute} count.",
+                        mesh.name
+                    ));
+                }
+            }
+            let [position_deltas, normal_deltas] = deltas;
+            mesh.morph_targets.push(MorphTarget {
+                name: target_names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("target{i}")),
+                position_deltas,
+                normal_deltas,
+            });
+        }
+        Ok(mesh)
+    }
+}
+
+/// decodes a single little endian accessor component to a float
+fn decode_component(bytes: &[u8], component_type: usize, normalized: bool) -> f64 {
+    match component_type {
+        5120 => {
+            let value = bytes[0] as i8 as f64;
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        5121 => {
+            let value = bytes[0] as f64;
+            if normalized {
+                value / 255.0
+            } else {
+                value
+            }
+        }
+        5122 => {
+            let value = i16::from_le_bytes([bytes[0], bytes[1]]) as f64;
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        5123 => {
+            let value = u16::from_le_bytes([bytes[0], bytes[1]]) as f64;
+            if normalized {
+                value / 65535.0
+            } else {
+                value
+            }
+        }
+        5125 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+    }
+}
@@ -2,6 +2,7 @@ use super::data::*;
 use super::shader::*;
 use crate::internal_prelude::*;
 use crate::rendering::mesh::Mesh;
+use crate::rendering::skinning::{JOINT_BLOCK_BINDING, MAX_JOINTS};
 use crate::systems::rendering_system::RenderAttributes;
 use std::ptr;
 
@@ -18,6 +19,9 @@ pub(crate) struct InstanceRenderer {
     ocbo: GLuint,
     otbo: GLuint,
     ibo: GLuint,
+    skin_buffers: Option<(GLuint, GLuint)>,
+    joint_buffer: Option<UniformBuffer>,
+    morph_weights: Option<Vec<f32>>, // the weights that the uploaded positions and normals are morphed with
     index_count: GLsizei,
     models: Vec<Mat4>,
     normal_matrices: Vec<Mat3>,
@@ -44,6 +48,8 @@ impl InstanceRenderer {
         let mut ocbo = 0; // outline colors
         let mut otbo = 0; // outline thicknesses
         let mut ibo = 0; // indices
+        let mut jbo = 0; // joint indices
+        let mut wbo = 0; // joint weights
         let models = vec![Mat4::identity(); max_num_instances];
         let normal_matrices = vec![Mat3::identity(); max_num_instances];
         let outline_colors = vec![Vec4::default(); max_num_instances];
@@ -153,6 +159,29 @@ impl InstanceRenderer {
             );
             bind_instance_otbo();
 
+            // JOINT INDEX AND WEIGHT BUFFERS
+            if let Some(skin) = mesh.skin.as_ref() {
+                gl::GenBuffers(1, &mut jbo);
+                gl::BindBuffer(gl::ARRAY_BUFFER, jbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (mesh.num_vertices() * size_of::<[u16; 4]>()) as GLsizeiptr,
+                    skin.joints.as_ptr() as *const GLvoid,
+                    gl::STATIC_DRAW,
+                );
+                bind_instance_jbo();
+
+                gl::GenBuffers(1, &mut wbo);
+                gl::BindBuffer(gl::ARRAY_BUFFER, wbo);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (mesh.num_vertices() * size_of::<Vec4>()) as GLsizeiptr,
+                    skin.weights.as_ptr() as *const GLvoid,
+                    gl::STATIC_DRAW,
+                );
+                bind_instance_wbo();
+            }
+
             // INDECES
             gl::GenBuffers(1, &mut ibo);
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ibo);
@@ -178,6 +207,9 @@ impl InstanceRenderer {
             ocbo,
             otbo,
             ibo,
+            skin_buffers: mesh.skin.is_some().then_some((jbo, wbo)),
+            joint_buffer: None,
+            morph_weights: None,
             index_count: 0,
             models,
             normal_matrices,
@@ -189,6 +221,166 @@ impl InstanceRenderer {
         }
     }
 
+    /// replaces the vertex and index data in the existing buffers with new mesh data
+    pub(crate) fn update_mesh(&mut self, mesh: &Mesh) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            for (buffer, size, data) in [
+                (
+                    self.pbo,
+                    size_of::<Vec3>(),
+                    mesh.positions.as_ptr() as *const GLvoid,
+                ),
+                (
+                    self.ubo,
+                    size_of::<Vec2>(),
+                    mesh.texture_coords.as_ptr() as *const GLvoid,
+                ),
+                (
+                    self.nbo,
+                    size_of::<Vec3>(),
+                    mesh.normals.as_ptr() as *const GLvoid,
+                ),
+                (
+                    self.cbo,
+                    size_of::<Vec4>(),
+                    mesh.colors.as_ptr() as *const GLvoid,
+                ),
+                (
+                    self.tbo,
+                    size_of::<Vec3>(),
+                    mesh.tangents.as_ptr() as *const GLvoid,
+                ),
+            ] {
+                gl::BindBuffer(gl::ARRAY_BUFFER, buffer);
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (mesh.num_vertices() * size) as GLsizeiptr,
+                    data,
+                    gl::DYNAMIC_DRAW,
+                );
+            }
+            match (self.skin_buffers, mesh.skin.as_ref()) {
+                (Some((jbo, wbo)), Some(skin)) => {
+                    gl::BindBuffer(gl::ARRAY_BUFFER, jbo);
+                    gl::BufferData(
+                        gl::ARRAY_BUFFER,
+                        (mesh.num_vertices() * size_of::<[u16; 4]>()) as GLsizeiptr,
+                        skin.joints.as_ptr() as *const GLvoid,
+                        gl::DYNAMIC_DRAW,
+                    );
+                    gl::BindBuffer(gl::ARRAY_BUFFER, wbo);
+                    gl::BufferData(
+                        gl::ARRAY_BUFFER,
+                        (mesh.num_vertices() * size_of::<Vec4>()) as GLsizeiptr,
+                        skin.weights.as_ptr() as *const GLvoid,
+                        gl::DYNAMIC_DRAW,
+                    );
+                }
+                // the new mesh is skinned, so the joint attributes are added to the vertex array
+                (None, Some(skin)) => {
+                    let mut jbo = 0;
+                    let mut wbo = 0;
+                    gl::GenBuffers(1, &mut jbo);
+                    gl::BindBuffer(gl::ARRAY_BUFFER, jbo);
+                    gl::BufferData(
+                        gl::ARRAY_BUFFER,
+                        (mesh.num_vertices() * size_of::<[u16; 4]>()) as GLsizeiptr,
+                        skin.joints.as_ptr() as *const GLvoid,
+                        gl::DYNAMIC_DRAW,
+                    );
+                    bind_instance_jbo();
+
+                    gl::GenBuffers(1, &mut wbo);
+                    gl::BindBuffer(gl::ARRAY_BUFFER, wbo);
+                    gl::BufferData(
+                        gl::ARRAY_BUFFER,
+                        (mesh.num_vertices() * size_of::<Vec4>()) as GLsizeiptr,
+                        skin.weights.as_ptr() as *const GLvoid,
+                        gl::DYNAMIC_DRAW,
+                    );
+                    bind_instance_wbo();
+                    self.skin_buffers = Some((jbo, wbo));
+                }
+                // the new mesh is static, so the joint attributes are removed from the vertex array
+                (Some((jbo, wbo)), None) => {
+                    gl::DisableVertexAttribArray(14);
+                    gl::DisableVertexAttribArray(15);
+                    gl::DeleteBuffers(1, &jbo);
+                    gl::DeleteBuffers(1, &wbo);
+                    self.skin_buffers = None;
+                    self.joint_buffer = None;
+                }
+                (None, None) => {}
+            }
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ibo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                (mesh.num_indices() * size_of::<GLuint>()) as GLsizeiptr,
+                mesh.indices.as_ptr() as *const GLvoid,
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindVertexArray(0);
+        }
+        self.morph_weights = None;
+    }
+
+    /// the morph target weights of all rendered instances, ``None`` if the mesh is rendered undeformed
+    pub(crate) fn morph_weights(&self) -> Option<&[f32]> {
+        self.morph_weights.as_deref()
+    }
+
+    /// replaces the positions and normals with the morphed ones of the mesh if the weights changed
+    pub(crate) fn set_morph_weights(&mut self, mesh: &Mesh, weights: &[f32]) {
+        if self.morph_weights.as_deref() == Some(weights) {
+            return;
+        }
+        let (positions, normals) = mesh.morphed_vertices(weights);
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.pbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (positions.len() * size_of::<Vec3>()) as GLsizeiptr,
+                positions.as_ptr() as *const GLvoid,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.nbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (normals.len() * size_of::<Vec3>()) as GLsizeiptr,
+                normals.as_ptr() as *const GLvoid,
+            );
+        }
+        self.morph_weights = Some(weights.to_vec());
+    }
+
+    /// uploads the joint matrices that deform the mesh of the (single) rendered instance
+    pub(crate) fn set_joint_matrices(&mut self, matrices: &[Mat4]) {
+        if self.skin_buffers.is_none() {
+            return;
+        }
+        let count = matrices.len().min(MAX_JOINTS);
+        self.joint_buffer
+            .get_or_insert_with(|| UniformBuffer::new(size_of::<Mat4>() * MAX_JOINTS))
+            .upload_data(
+                0,
+                count * size_of::<Mat4>(),
+                matrices.as_ptr() as *const GLvoid,
+            );
+    }
+
+    /// binds the joint matrices and sets the skinning flag at the given uniform location of the active shader
+    unsafe fn bind_skinning(&self, location: GLint) {
+        match self.joint_buffer.as_ref() {
+            Some(buffer) => {
+                gl::BindBufferBase(gl::UNIFORM_BUFFER, JOINT_BLOCK_BINDING, buffer.ubo);
+                gl::Uniform1i(location, 1);
+            }
+            None => gl::Uniform1i(location, 0),
+        }
+    }
+
     /// resizes the internal buffer to hold more instances (erases all positions confirmed prior to this call)
     fn resize_buffer(&mut self) {
         let add_size: usize = self.max_num_instances * 2;
@@ -314,6 +506,7 @@ impl InstanceRenderer {
             // bind uniforms
             gl::Uniform4fv(4, 1, &self.color[0]);
             gl::Uniform1i(5, 0);
+            self.bind_skinning(6);
             // draw the instanced triangles corresponding to the index buffer
             gl::BindVertexArray(self.vao);
             gl::DrawElementsInstanced(
@@ -336,6 +529,7 @@ impl InstanceRenderer {
             // bind uniforms
             gl::Uniform4fv(24, 1, &self.color[0]);
             gl::Uniform1i(25, 0);
+            self.bind_skinning(26);
             // draw the instanced triangles corresponding to the index buffer
             gl::BindVertexArray(self.vao);
             gl::DrawElementsInstanced(
@@ -353,6 +547,7 @@ impl InstanceRenderer {
     pub(crate) fn render_stencil_outlines(&mut self) {
         // the scaling is done on the gpu
         unsafe {
+            self.bind_skinning(0);
             gl::BindVertexArray(self.vao);
             gl::DrawElementsInstanced(
                 gl::TRIANGLES,
@@ -366,6 +561,7 @@ impl InstanceRenderer {
     }
 
     /// draws the mesh at all the positions specified until the call of this and clears the positions
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn render<'a>(
         &self,
         attributes: &RenderAttributes,
@@ -398,10 +594,18 @@ impl InstanceRenderer {
             gl::BindTexture(gl::TEXTURE_2D, attributes.specular_tex_id);
             gl::ActiveTexture(gl::TEXTURE14);
             gl::BindTexture(gl::TEXTURE_2D, attributes.normal_tex_id);
+            // the pbr texture units might not be available if pbr is not supported
+            if attributes.material_data.pbr.is_some() {
+                for (i, tex_id) in attributes.pbr_tex_ids.iter().enumerate() {
+                    gl::ActiveTexture(gl::TEXTURE15 + i as GLenum);
+                    gl::BindTexture(gl::TEXTURE_2D, *tex_id);
+                }
+            }
 
             // bind uniforms
             gl::Uniform4fv(0, 1, &self.color[0]);
             gl::Uniform1i(1, 0);
+            self.bind_skinning(22);
 
             if shader_type != ShaderType::Passthrough {
                 for i in 0..MAX_DIR_LIGHT_MAPS {
@@ -420,6 +624,17 @@ impl InstanceRenderer {
                 gl::Uniform1i(19, 14);
                 let use_normal_map = attributes.normal_tex_id != white_texture;
                 gl::Uniform1i(20, use_normal_map as GLint);
+                gl::Uniform1i(23, attributes.material_data.pbr.is_some() as GLint);
+                if let Some(pbr) = attributes.material_data.pbr.as_ref() {
+                    gl::Uniform4fv(24, 1, &pbr.base_color[0]);
+                    gl::Uniform1f(25, pbr.metallic);
+                    gl::Uniform1f(26, pbr.roughness);
+                    gl::Uniform1f(27, pbr.occlusion_strength);
+                    gl::Uniform3fv(28, 1, &pbr.emissive_color[0]);
+                    for i in 0..attributes.pbr_tex_ids.len() {
+                        gl::Uniform1i(29 + i as GLint, 15 + i as GLint);
+                    }
+                }
             } else {
                 gl::Uniform1i(21, is_light_source as GLint); // only used in the passthrough shader
             }
@@ -463,6 +678,10 @@ impl Drop for InstanceRenderer {
             gl::DeleteBuffers(1, &self.ocbo);
             gl::DeleteBuffers(1, &self.otbo);
             gl::DeleteBuffers(1, &self.ibo);
+            if let Some((jbo, wbo)) = self.skin_buffers {
+                gl::DeleteBuffers(1, &jbo);
+                gl::DeleteBuffers(1, &wbo);
+            }
             gl::DeleteVertexArrays(1, &self.vao);
         }
     }
@@ -1,4 +1,5 @@
 use crate::internal_prelude::*;
+use crate::rendering::convex_decomposition::convex_decomposition;
 use petgraph::stable_graph::{NodeIndex, StableUnGraph};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
@@ -13,9 +14,10 @@ type TriangleID = u64;
 #[derive(Debug, Clone)]
 struct AlgorithmMesh {
     name: String,
-    source_file: Rc<Path>,
+    source_file: Arc<Path>,
     material_name: Option<String>,
     vertices: Vec<Vec3>,
+    locked: Vec<bool>,
     faces: Vec<[usize; 3]>,
     triangle_map: AHashMap<usize, AHashSet<TriangleID>>,
     windings: AHashMap<TriangleID, [usize; 3]>,
@@ -47,16 +49,7 @@ impl AlgorithmMesh {
                 (positions.len() - 1) as GLuint,
             ]);
         }
-        let max_reach = self
-            .vertices
-            .iter()
-            .map(|v| v.abs())
-            .fold(ORIGIN, |mut current, p| {
-                current.x = current.x.max(p.x);
-                current.y = current.y.max(p.y);
-                current.z = current.z.max(p.z);
-                current
-            });
+        let max_reach = max_reach(&self.vertices);
 
         // TODO: we might want to keep track of these vertex attributes in the future in mesh manipulation
         let texture_coords = vec![vec2(0.0, 0.0); positions.len()];
@@ -66,6 +59,7 @@ impl AlgorithmMesh {
         Mesh {
             name: self.name,
             source_file: self.source_file,
+            source_index: 0,
             positions,
             colors,
             normals,
@@ -74,6 +68,9 @@ impl AlgorithmMesh {
             tangents,
             max_reach,
             material_name: self.material_name,
+            // the simplified geometry has no joint weights or morph deltas, so LODs of deformable meshes are static
+            skin: None,
+            morph_targets: Vec::new(),
         }
     }
 
@@ -85,8 +82,8 @@ impl AlgorithmMesh {
         }
     }
 
-    /// creates a simplified version of the mesh that is used for LOD and hitboxes
-    fn simplified(mut self) -> Self {
+    /// creates a simplified version of the mesh with at most ``target_triangle_count`` triangles that is used for LOD and hitboxes, stops early if no contraction with an error below ``max_error`` is left
+    fn simplified(mut self, target_triangle_count: usize, max_error: f32) -> Self {
         // SELECTING VALID PAIRS FOR THE CONSTRACTIONS
         // -> one of two cases: v1->v2 is edge or distance(v1, v2) < t with t being a threshold parameter
         // -> t = 0 would be equivalent to a regular edge contraction algo
@@ -125,8 +122,6 @@ impl AlgorithmMesh {
         // 4. put all the pairs in a heap keyed on cost with the minimum cost pair at the top
         // 5. iteratively remove the pair v1 v2 of least cost from the heap, contract this pair, and update the costs of all valid pairs involving v1
 
-        let target_node_count = (self.vertices.len() / 2).max(4);
-
         let edges = self
             .faces
             .iter()
@@ -140,10 +135,11 @@ impl AlgorithmMesh {
         let mut mesh_graph = MeshErrorGraph::default();
 
         // add vertices with positions
-        for vertex in self.vertices.iter() {
+        for (vertex, locked) in self.vertices.iter().zip(&self.locked) {
             mesh_graph.add_node(ErrorVertex {
                 position: *vertex,
                 error_matrix: Mat4::default(),
+                locked: *locked,
             });
         }
 
@@ -166,18 +162,38 @@ impl AlgorithmMesh {
         let error_threshold = 0.02;
         let mut valid_pairs = find_all_valid_pairs(error_threshold, &mesh_graph);
 
-        while mesh_graph.node_count() > target_node_count && !valid_pairs.is_empty() {
-            let pair_to_contract = valid_pairs.pop().unwrap();
-            self.contract_pair(pair_to_contract, &mut mesh_graph, &mut valid_pairs);
+        while self.windings.len() > target_triangle_count {
+            match valid_pairs.pop() {
+                Some(pair) if pair.error <= max_error => {
+                    self.contract_pair(pair, &mut mesh_graph, &mut valid_pairs)
+                }
+                _ => break,
+            }
         }
 
         // reconstruct the mesh from the final graph data
         self.convert_graph_edges_to_triangles(&mesh_graph);
         self.convert_graph_nodes_to_vertices(&mesh_graph);
         self.remove_unused_vertices();
+        self.rebuild_triangle_data();
         self
     }
 
+    /// rebuilds the triangle lookup data from the current faces so the mesh can be simplified further
+    fn rebuild_triangle_data(&mut self) {
+        self.triangle_map.clear();
+        self.windings.clear();
+        for (i, face) in self.faces.iter().enumerate() {
+            for index in face {
+                self.triangle_map
+                    .entry(*index)
+                    .or_default()
+                    .insert(i as TriangleID);
+            }
+            self.windings.insert(i as TriangleID, *face);
+        }
+    }
+
     /// removes vertices that are not used by a face and corrects the face indices
     fn remove_unused_vertices(&mut self) {
         let mut used_indices = self
@@ -189,6 +205,7 @@ impl AlgorithmMesh {
         for i in 0..self.vertices.len() {
             while !used_indices.contains(&i) && i < self.vertices.len() - 1 {
                 self.vertices.swap_remove(i);
+                self.locked.swap_remove(i);
                 self.faces
                     .iter_mut()
                     .flatten()
@@ -213,8 +230,9 @@ impl AlgorithmMesh {
     /// converts the nodes currently stored in the graph to the mesh vertex positions
     fn convert_graph_nodes_to_vertices(&mut self, mesh_graph: &MeshErrorGraph) {
         for node_idx in mesh_graph.node_indices() {
-            let vertex = mesh_graph.index(node_idx).position;
-            self.vertices[node_idx.index()] = vertex;
+            let vertex = mesh_graph.index(node_idx);
+            self.vertices[node_idx.index()] = vertex.position;
+            self.locked[node_idx.index()] = vertex.locked;
         }
     }
 
@@ -312,6 +330,7 @@ impl AlgorithmMesh {
 
         v1_ref.position = pair.v_new;
         v1_ref.error_matrix = vertex1.error_matrix + vertex2.error_matrix;
+        v1_ref.locked = vertex1.locked || vertex2.locked;
 
         // exchange v2 for v1 in the windings of triangles that it was a part of
         for id in self.triangle_map.get(&pair.v2.index()).unwrap().iter() {
@@ -371,7 +390,12 @@ fn calculate_error_matrix(mesh_graph: &MeshErrorGraph, index: NodeIndex<usize>)
         let vertex1 = mesh_graph.index(triangle[0]).position;
         let vertex2 = mesh_graph.index(triangle[1]).position;
         let vertex3 = mesh_graph.index(triangle[2]).position;
-        let plane_normal = (vertex2 - vertex1).cross(&(vertex3 - vertex1)).normalize();
+        let plane_normal = (vertex2 - vertex1).cross(&(vertex3 - vertex1));
+        // degenerate triangles (e.g. along preserved straight borders) do not span a plane
+        if plane_normal.norm() <= f32::EPSILON {
+            continue;
+        }
+        let plane_normal = plane_normal.normalize();
         let distance_from_origin = -plane_normal.dot(&vertex1);
         let mut p = to_vec4(&plane_normal);
         p.w = distance_from_origin;
@@ -423,7 +447,14 @@ fn add_valid_vertex_pair(
         vec4(q_new.m14, q_new.m24, q_new.m34, 1.0),
     ]);
 
-    let v_new = if let Some(inv_deriv_mat) = partial_derivative_mat.try_inverse() {
+    // preserved vertices stay in place and can not be merged with each other
+    let v_new = if vertex1.locked && vertex2.locked {
+        return;
+    } else if vertex1.locked {
+        to_vec4(&vertex1.position)
+    } else if vertex2.locked {
+        to_vec4(&vertex2.position)
+    } else if let Some(inv_deriv_mat) = partial_derivative_mat.try_inverse() {
         inv_deriv_mat * vec4(0.0, 0.0, 0.0, 1.0)
     } else {
         // fall back on choosing v_new as the midpoint
@@ -449,6 +480,7 @@ type MeshErrorGraph = StableUnGraph<ErrorVertex, (), usize>;
 struct ErrorVertex {
     position: Vec3,
     error_matrix: Mat4,
+    locked: bool,
 }
 
 /// stores one vertex pair with error data that is used in the mesh simplification algorithm
@@ -476,11 +508,236 @@ impl Ord for ErrorVertexPair {
     }
 }
 
+/// Raw vertex data for meshes that are built at runtime, e.g. voxel chunks or deformable terrain. Register the data with ``EntityManager::load_mesh_data`` and replace it with ``EntityManager::update_mesh``. Every three indices form a counter-clockwise triangle. Missing normals are computed per face, missing colors default to white and missing texture coordinates to ``(0, 0)``. Tangents and the bounding extents are always computed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MeshData {
+    name: String,
+    positions: Vec<Vec3>,
+    indices: Vec<GLuint>,
+    normals: Option<Vec<Vec3>>,
+    texture_coords: Option<Vec<Vec2>>,
+    colors: Option<Vec<Vec4>>,
+    material_name: Option<String>,
+    skin: Option<(Vec<[u16; 4]>, Vec<Vec4>)>,
+    morph_targets: Vec<(String, Vec<Vec3>, Option<Vec<Vec3>>)>,
+}
+
+impl MeshData {
+    /// Creates new mesh data from vertex positions and triangle indices.
+    pub fn new(name: impl Into<String>, positions: Vec<Vec3>, indices: Vec<GLuint>) -> Self {
+        Self {
+            name: name.into(),
+            positions,
+            indices,
+            ..Default::default()
+        }
+    }
+
+    /// Adds one normal vector per vertex.
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Adds one texture coordinate per vertex.
+    pub fn with_texture_coords(mut self, texture_coords: Vec<Vec2>) -> Self {
+        self.texture_coords = Some(texture_coords);
+        self
+    }
+
+    /// Adds one RGBA vertex color per vertex.
+    pub fn with_colors(mut self, colors: Vec<Vec4>) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Binds every vertex to up to four joints of a ``Skeleton`` with the given weights, which makes the mesh deformable by a ``SkeletalAnimator``. The weights are normalized.
+    pub fn with_skin(mut self, joints: Vec<[u16; 4]>, weights: Vec<Vec4>) -> Self {
+        self.skin = Some((joints, weights));
+        self
+    }
+
+    /// Adds a morph target (blend shape) with one position offset and optionally one normal offset per vertex. The offsets are added to the base mesh scaled by the weight of the target in the ``MorphWeights`` of an entity. Targets are indexed in the order they are added.
+    pub fn with_morph_target(
+        mut self,
+        name: impl Into<String>,
+        position_deltas: Vec<Vec3>,
+        normal_deltas: Option<Vec<Vec3>>,
+    ) -> Self {
+        self.morph_targets
+            .push((name.into(), position_deltas, normal_deltas));
+        self
+    }
+
+    /// Sets the name of the material that is used by renderables with ``MaterialSource::Inherit``.
+    pub fn with_material_name(mut self, name: impl Into<String>) -> Self {
+        self.material_name = Some(name.into());
+        self
+    }
+
+    /// checks the data for consistency and converts it to a renderable mesh
+    pub(crate) fn into_mesh(self) -> Result<Mesh, String> {
+        let vertex_count = self.positions.len();
+        if self.indices.is_empty() || self.indices.len() % 3 != 0 {
+            return Err(format!(
+                "the index count {} is not a positive multiple of 3",
+                self.indices.len()
+            ));
+        }
+        if let Some(index) = self.indices.iter().find(|i| **i as usize >= vertex_count) {
+            return Err(format!(
+                "index {index} is out of bounds for {vertex_count} vertices"
+            ));
+        }
+        let attribute_lengths = [
+            ("normals", self.normals.as_ref().map(Vec::len)),
+            (
+                "texture coordinates",
+                self.texture_coords.as_ref().map(Vec::len),
+            ),
+            ("colors", self.colors.as_ref().map(Vec::len)),
+            ("joints", self.skin.as_ref().map(|(joints, _)| joints.len())),
+            (
+                "weights",
+                self.skin.as_ref().map(|(_, weights)| weights.len()),
+            ),
+        ];
+        for (attribute, len) in attribute_lengths {
+            if len.is_some_and(|len| len != vertex_count) {
+                return Err(format!(
+                    "the number of {attribute} does not match the {vertex_count} vertices"
+                ));
+            }
+        }
+        for (name, position_deltas, normal_deltas) in self.morph_targets.iter() {
+            if position_deltas.len() != vertex_count
+                || normal_deltas
+                    .as_ref()
+                    .is_some_and(|deltas| deltas.len() != vertex_count)
+            {
+                return Err(format!(
+                    "the offsets of morph target {name:?} do not match the {vertex_count} vertices"
+                ));
+            }
+        }
+
+        let mut mesh = Mesh::from_vertex_data(
+            self.name,
+            Arc::from(Path::new("internal")),
+            self.positions,
+            self.colors,
+            self.normals,
+            self.texture_coords,
+            self.indices,
+            self.material_name,
+        );
+        mesh.skin = self
+            .skin
+            .map(|(joints, weights)| VertexSkin::new(joints, weights));
+        mesh.morph_targets = self
+            .morph_targets
+            .into_iter()
+            .map(|(name, position_deltas, normal_deltas)| MorphTarget {
+                name,
+                normal_deltas: normal_deltas.unwrap_or_else(|| vec![Vec3::zeros(); vertex_count]),
+                position_deltas,
+            })
+            .collect();
+        Ok(mesh)
+    }
+}
+
+/// The amount of triangles that a simplified LOD level is reduced to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimplificationTarget {
+    /// Fraction of the triangle count of the original mesh (in ``(0, 1]``).
+    Ratio(f32),
+    /// Absolute number of triangles.
+    TriangleCount(usize),
+}
+
+impl SimplificationTarget {
+    /// the target number of triangles for a mesh with ``triangle_count`` triangles
+    fn triangle_count(&self, triangle_count: usize) -> usize {
+        match *self {
+            Self::Ratio(ratio) => (triangle_count as f32 * ratio.clamp(0.0, 1.0)) as usize,
+            Self::TriangleCount(count) => count,
+        }
+        .max(4)
+    }
+}
+
+/// Settings for the quadric error mesh simplification that is used for generating LODs with ``EntityManager::load_lods``. Every target in ``levels`` produces one LOD level, starting with ``LOD::from_level(1)``. Targets past ``MAX_LOD_LEVEL`` are ignored. The levels are simplified one after another, so the targets should be decreasing. Preserved vertices (boundaries, UV seams and vertex color discontinuities) keep their position and are never merged with each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplificationSettings {
+    /// The triangle target of each LOD level (default is halving the triangles for 4 levels).
+    pub levels: Vec<SimplificationTarget>,
+    /// The maximum quadric error (roughly the squared distance to the original surface) a contraction may introduce, simplification stops early once it is reached (default is ``None``).
+    pub max_error: Option<f32>,
+    /// Keeps the vertices on open mesh borders in place (default is ``false``).
+    pub preserve_boundaries: bool,
+    /// Keeps vertices with multiple texture coordinates in place (default is ``false``).
+    pub preserve_uv_seams: bool,
+    /// Keeps vertices with multiple vertex colors in place (default is ``false``).
+    pub preserve_color_seams: bool,
+}
+
+impl SimplificationSettings {
+    /// Changes the targets of the LOD levels.
+    pub fn with_levels(mut self, levels: Vec<SimplificationTarget>) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Changes the maximum quadric error of a contraction.
+    pub fn with_max_error(mut self, max_error: Option<f32>) -> Self {
+        self.max_error = max_error;
+        self
+    }
+
+    /// Enables or disables the preservation of open mesh borders.
+    pub fn with_preserve_boundaries(mut self, flag: bool) -> Self {
+        self.preserve_boundaries = flag;
+        self
+    }
+
+    /// Enables or disables the preservation of UV seams.
+    pub fn with_preserve_uv_seams(mut self, flag: bool) -> Self {
+        self.preserve_uv_seams = flag;
+        self
+    }
+
+    /// Enables or disables the preservation of vertex color discontinuities.
+    pub fn with_preserve_color_seams(mut self, flag: bool) -> Self {
+        self.preserve_color_seams = flag;
+        self
+    }
+}
+
+impl Default for SimplificationSettings {
+    fn default() -> Self {
+        Self {
+            levels: vec![
+                SimplificationTarget::Ratio(0.5),
+                SimplificationTarget::Ratio(0.25),
+                SimplificationTarget::Ratio(0.125),
+                SimplificationTarget::Ratio(0.0625),
+            ],
+            max_error: None,
+            preserve_boundaries: false,
+            preserve_uv_seams: false,
+            preserve_color_seams: false,
+        }
+    }
+}
+
 /// a mesh that can be rendered in gl
 #[derive(Clone)]
 pub(crate) struct Mesh {
     pub(crate) name: String,
-    pub(crate) source_file: Rc<Path>,
+    pub(crate) source_file: Arc<Path>,
+    /// the position of the mesh in its source file that identifies it in the mesh cache
+    pub(crate) source_index: usize,
     pub(crate) positions: Vec<Vec3>,
     pub(crate) colors: Vec<Vec4>,
     pub(crate) normals: Vec<Vec3>,
@@ -489,6 +746,42 @@ pub(crate) struct Mesh {
     pub(crate) tangents: Vec<Vec3>,
     pub(crate) max_reach: Vec3,
     pub(crate) material_name: Option<String>, // the presence of this means the material source can be inherited
+    pub(crate) skin: Option<VertexSkin>,
+    pub(crate) morph_targets: Vec<MorphTarget>,
+}
+
+/// the joints that influence each vertex of a skinned mesh with their weights
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct VertexSkin {
+    pub(crate) joints: Vec<[u16; 4]>,
+    pub(crate) weights: Vec<Vec4>,
+}
+
+impl VertexSkin {
+    /// creates the skin data and normalizes the weights so they sum up to one, vertices without weights are bound to the first joint
+    pub(crate) fn new(joints: Vec<[u16; 4]>, weights: Vec<Vec4>) -> Self {
+        let weights = weights
+            .into_iter()
+            .map(|weights| {
+                let weights = weights.map(|weight| weight.max(0.0));
+                let sum = weights.sum();
+                if sum > f32::EPSILON {
+                    weights / sum
+                } else {
+                    vec4(1.0, 0.0, 0.0, 0.0)
+                }
+            })
+            .collect();
+        Self { joints, weights }
+    }
+}
+
+/// per vertex offsets of a blend shape that are added to the base mesh scaled by the weight of the target
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MorphTarget {
+    pub(crate) name: String,
+    pub(crate) position_deltas: Vec<Vec3>,
+    pub(crate) normal_deltas: Vec<Vec3>,
 }
 
 impl Mesh {
@@ -496,88 +789,60 @@ impl Mesh {
     pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
         let mut data = BufReader::new(bytes);
         let (models, _) = load_obj_buf(&mut data, &GPU_LOAD_OPTIONS, |_| unreachable!()).unwrap();
-        Self::from_obj_data(&models[0], Path::new("internal").into(), None)
+        Self::from_obj_data(&models[0], Arc::from(Path::new("internal")), None)
     }
 
     /// loads a mesh from loaded object file data
     #[rustfmt::skip]
-    pub(crate) fn from_obj_data(model: &tobj::Model, source_file: Rc<Path>, material_name: Option<String>) -> Self {
+    pub(crate) fn from_obj_data(model: &tobj::Model, source_file: Arc<Path>, material_name: Option<String>) -> Self {
         let obj = &model.mesh;
 
         let positions = obj.positions.iter().copied().tuples().map(|(x, y, z)| vec3(x, y, z)).collect_vec();
-        let indices = obj.indices.clone();
-
-        let colors = if obj.vertex_color.is_empty() {
-            vec![vec4(1.0, 1.0, 1.0, 1.0); positions.len()]
-        } else {
+        let colors = (!obj.vertex_color.is_empty()).then(|| {
             obj.vertex_color.iter().copied().tuples().map(|(r, g, b)| vec4(r, g, b, 1.0)).collect_vec()
-        };
-
-        let normals = if obj.normals.is_empty() {
-            let mut computed = vec![ORIGIN; positions.len()];
-            for (a, b, c) in indices.iter().copied().tuples() {
-                let p1 = positions[a as usize];
-                let p2 = positions[b as usize];
-                let p3 = positions[c as usize];
-                let normal = (p2 - p1).cross(&(p3 - p1)).normalize();
-                computed[a as usize] = normal;
-                computed[b as usize] = normal;
-                computed[c as usize] = normal;
-            }
-            computed
-        } else {
+        });
+        let normals = (!obj.normals.is_empty()).then(|| {
             obj.normals.iter().copied().tuples().map(|(x, y, z)| vec3(x, y, z)).collect_vec()
-        };
-
-        let texture_coords = if obj.texcoords.is_empty() {
-            vec![vec2(0.0, 0.0); positions.len()]
-        } else {
+        });
+        let texture_coords = (!obj.texcoords.is_empty()).then(|| {
             obj.texcoords.iter().copied().tuples().map(|(u, v)| vec2(u, v)).collect_vec()
-        };
-
-        let mut tangents = vec![vec3(0.0, 0.0, 0.0); positions.len()];
-        for (a, b, c) in indices.iter().copied().tuples() {
-            let p1 = positions[a as usize];
-            let p2 = positions[b as usize];
-            let p3 = positions[c as usize];
-            let uv1 = texture_coords[a as usize];
-            let uv2 = texture_coords[b as usize];
-            let uv3 = texture_coords[c as usize];
+        });
 
-            let edge1 = p2 - p1;
-            let edge2 = p3 - p1;
-            let delta_uv1 = uv2 - uv1;
-            let delta_uv2 = uv3 - uv1;
-
-            let f = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y);
-
-            let mut tangent = vec3(0.0, 0.0, 0.0);
-            tangent.x = f * (delta_uv2.y * edge1.x - delta_uv1.y * edge2.x);
-            tangent.y = f * (delta_uv2.y * edge1.y - delta_uv1.y * edge2.y);
-            tangent.z = f * (delta_uv2.y * edge1.z - delta_uv1.y * edge2.z);
-
-            tangents[a as usize] = tangent;
-            tangents[b as usize] = tangent;
-            tangents[c as usize] = tangent;
-        }
-
-        let max_reach =
-            positions
-                .iter()
-                .copied()
-                .map(|p| p.abs())
-                .fold(ORIGIN, |mut current, p| {
-                    current.x = current.x.max(p.x);
-                    current.y = current.y.max(p.y);
-                    current.z = current.z.max(p.z);
-                    current
-                });
+        Self::from_vertex_data(
+            model.name.clone(),
+            source_file,
+            positions,
+            colors,
+            normals,
+            texture_coords,
+            obj.indices.clone(),
+            material_name,
+        )
+    }
 
-        let name = model.name.clone();
+    /// creates a mesh from raw vertex data, missing vertex attributes are filled with default values or computed
+    #[rustfmt::skip]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn from_vertex_data(
+        name: String,
+        source_file: Arc<Path>,
+        positions: Vec<Vec3>,
+        colors: Option<Vec<Vec4>>,
+        normals: Option<Vec<Vec3>>,
+        texture_coords: Option<Vec<Vec2>>,
+        indices: Vec<GLuint>,
+        material_name: Option<String>,
+    ) -> Self {
+        let colors = colors.unwrap_or_else(|| vec![vec4(1.0, 1.0, 1.0, 1.0); positions.len()]);
+        let normals = normals.unwrap_or_else(|| flat_normals(&positions, &indices));
+        let texture_coords = texture_coords.unwrap_or_else(|| vec![vec2(0.0, 0.0); positions.len()]);
+        let tangents = triangle_tangents(&positions, &texture_coords, &indices);
+        let max_reach = max_reach(&positions);
 
         Self {
             name,
             source_file,
+            source_index: 0,
             positions,
             colors,
             normals,
@@ -585,7 +850,9 @@ impl Mesh {
             indices,
             tangents,
             max_reach,
-            material_name
+            material_name,
+            skin: None,
+            morph_targets: Vec::new(),
         }
     }
 
@@ -601,14 +868,37 @@ impl Mesh {
         self.indices.len()
     }
 
-    /// generates the AOS based algorithm mesh for easier data parsing
-    fn algorithm_mesh(&self) -> AlgorithmMesh {
+    /// the vertex positions and normals with all morph targets applied, weights without a target and targets without a weight are ignored
+    pub(crate) fn morphed_vertices(&self, weights: &[f32]) -> (Vec<Vec3>, Vec<Vec3>) {
+        let mut positions = self.positions.clone();
+        let mut normals = self.normals.clone();
+        for (target, weight) in self.morph_targets.iter().zip(weights) {
+            if *weight == 0.0 {
+                continue;
+            }
+            for (position, delta) in positions.iter_mut().zip(target.position_deltas.iter()) {
+                *position += delta * *weight;
+            }
+            for (normal, delta) in normals.iter_mut().zip(target.normal_deltas.iter()) {
+                *normal += delta * *weight;
+            }
+        }
+        normals
+            .iter_mut()
+            .for_each(|normal| *normal = normal.try_normalize(f32::EPSILON).unwrap_or(*normal));
+        (positions, normals)
+    }
+
+    /// generates the AOS based algorithm mesh for easier data parsing, vertices are locked according to the preservation options of the settings
+    fn algorithm_mesh(&self, settings: &SimplificationSettings) -> AlgorithmMesh {
         let mut original_mesh_faces = vec![[0, 0, 0]; self.indices.len() / 3];
         for (i, index) in self.indices.iter().enumerate() {
             original_mesh_faces[i / 3][i % 3] = *index as usize;
         }
 
         let mut vertices: Vec<Vec3> = Vec::new();
+        let mut locked: Vec<bool> = Vec::new();
+        let mut first_indices: Vec<usize> = Vec::new();
         let mut faces = Vec::with_capacity(original_mesh_faces.len());
         let mut triangle_map: AHashMap<usize, AHashSet<TriangleID>> = AHashMap::new();
         let mut windings: AHashMap<TriangleID, [usize; 3]> = AHashMap::new();
@@ -627,9 +917,18 @@ impl Mesh {
                     .map(|(i, _)| i)
                     .unwrap_or_else(|| {
                         vertices.push(position);
+                        locked.push(false);
+                        first_indices.push(index);
                         vertices.len() - 1
                     });
 
+                // vertices at the same position with different attributes lie on a seam
+                let first_index = first_indices[vertex_index];
+                locked[vertex_index] |= (settings.preserve_uv_seams
+                    && self.texture_coords[first_index] != self.texture_coords[index])
+                    || (settings.preserve_color_seams
+                        && self.colors[first_index] != self.colors[index]);
+
                 aos_indices[i] = vertex_index;
 
                 triangle_map
@@ -641,11 +940,25 @@ impl Mesh {
             faces.push(aos_indices);
         }
 
+        if settings.preserve_boundaries {
+            let mut edge_counts: AHashMap<(usize, usize), usize> = AHashMap::new();
+            for face in faces.iter() {
+                for (a, b) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+                    *edge_counts.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+            for ((a, b), _) in edge_counts.into_iter().filter(|(_, count)| *count == 1) {
+                locked[a] = true;
+                locked[b] = true;
+            }
+        }
+
         AlgorithmMesh {
             name: self.name.clone(),
             source_file: self.source_file.clone(),
             material_name: self.material_name.clone(),
             vertices,
+            locked,
             faces,
             triangle_map,
             windings,
@@ -654,6 +967,12 @@ impl Mesh {
 
     /// generates the inverse inertia tensor matrix, center of mass and the mass
     pub(crate) fn intertia_data(&self, density: f32, scale: &Scale) -> (Mat3, Vec3, f32) {
+        let (inertia_tensor, center_of_mass, mass) = self.inertia_tensor(density, scale);
+        (inertia_tensor.try_inverse().unwrap(), center_of_mass, mass)
+    }
+
+    /// generates the inertia tensor matrix, center of mass and the mass (the tensor is not invertible for open or flat meshes)
+    pub(crate) fn inertia_tensor(&self, density: f32, scale: &Scale) -> (Mat3, Vec3, f32) {
         // inertia matrix entries
         let (mut ia, mut ib, mut ic, mut iap, mut ibp, mut icp) =
             (0f32, 0f32, 0f32, 0f32, 0f32, 0f32);
@@ -700,35 +1019,141 @@ impl Mesh {
                 vec3(ia, -ibp, -icp),
                 vec3(-ibp, ib, -iap),
                 vec3(-icp, -iap, ic),
-            ])
-            .try_inverse()
-            .unwrap(),
+            ]),
             center_of_mass,
             mass,
         )
     }
 
-    /// generates all the simpified meshes for the lod levels
-    pub(crate) fn generate_lods(&self) -> [Mesh; 4] {
-        let lod1 = self.algorithm_mesh().simplified().into_mesh();
-        let lod2 = lod1.algorithm_mesh().simplified().into_mesh();
-        let lod3 = lod2.algorithm_mesh().simplified().into_mesh();
-        let lod4 = lod3.algorithm_mesh().simplified().into_mesh();
-        [lod1, lod2, lod3, lod4]
+    /// generates the simpified meshes for all the lod levels of the settings, every level continues simplifying the previous one
+    pub(crate) fn generate_lods(&self, settings: &SimplificationSettings) -> Vec<Mesh> {
+        let triangle_count = self.num_indices() / 3;
+        let max_error = settings.max_error.unwrap_or(f32::INFINITY);
+        let mut algorithm_mesh = self.algorithm_mesh(settings);
+        settings
+            .levels
+            .iter()
+            .take(MAX_LOD_LEVEL)
+            .map(|target| {
+                algorithm_mesh = algorithm_mesh
+                    .clone()
+                    .simplified(target.triangle_count(triangle_count), max_error);
+                algorithm_mesh.clone().into_mesh()
+            })
+            .collect()
+    }
+
+    /// converts the mesh to the ``.obj`` file format, optionally referencing a material library and material
+    pub(crate) fn to_obj(&self, material: Option<(&str, &str)>) -> String {
+        let mut obj = String::new();
+        if let Some((mtl_file, _)) = material {
+            obj += &format!("mtllib {mtl_file}\n");
+        }
+        obj += &format!("o {}\n", self.name);
+        let colored = self
+            .colors
+            .iter()
+            .any(|color| *color != vec4(1.0, 1.0, 1.0, 1.0));
+        for (position, color) in self.positions.iter().zip(self.colors.iter()) {
+            obj += &format!("v {} {} {}", position.x, position.y, position.z);
+            if colored {
+                obj += &format!(" {} {} {}", color.x, color.y, color.z);
+            }
+            obj += "\n";
+        }
+        for uv in self.texture_coords.iter() {
+            obj += &format!("vt {} {}\n", uv.x, uv.y);
+        }
+        for normal in self.normals.iter() {
+            obj += &format!("vn {} {} {}\n", normal.x, normal.y, normal.z);
+        }
+        if let Some((_, mtl_name)) = material {
+            obj += &format!("usemtl {mtl_name}\n");
+        }
+        for (a, b, c) in self.indices.iter().map(|index| index + 1).tuples() {
+            obj += &format!("f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}\n");
+        }
+        obj
     }
 
     /// generates the meshes' hitbox for the given hitbox type
     #[rustfmt::skip]
     pub(crate) fn generate_hitbox(&self, hitbox: &HitboxType) -> Hitbox {
         match hitbox {
-            HitboxType::ConvexHull => Hitbox::ConvexMesh(self.algorithm_mesh().hitbox_mesh().convex_hull()),
-            HitboxType::SimplifiedConvexHull => Hitbox::ConvexMesh(self.algorithm_mesh().simplified().hitbox_mesh().convex_hull()),
+            HitboxType::ConvexHull => Hitbox::ConvexMesh(self.algorithm_mesh(&SimplificationSettings::default()).hitbox_mesh().convex_hull()),
+            HitboxType::SimplifiedConvexHull => {
+                let target = SimplificationTarget::Ratio(0.5).triangle_count(self.num_indices() / 3);
+                Hitbox::ConvexMesh(self.algorithm_mesh(&SimplificationSettings::default()).simplified(target, f32::INFINITY).hitbox_mesh().convex_hull())
+            }
             HitboxType::Sphere => Hitbox::Sphere(self.max_reach.max()),
             HitboxType::Box => Hitbox::ConvexMesh(HitboxMesh::box_from_dims(&self.max_reach)),
+            HitboxType::ConvexDecomposition(settings) => Hitbox::Compound(convex_decomposition(&self.algorithm_mesh(&SimplificationSettings::default()).hitbox_mesh(), settings)),
         }
     }
 }
 
+/// computes flat per-triangle normals for indexed positions
+pub(crate) fn flat_normals(positions: &[Vec3], indices: &[GLuint]) -> Vec<Vec3> {
+    let mut computed = vec![ORIGIN; positions.len()];
+    for (a, b, c) in indices.iter().copied().tuples() {
+        let p1 = positions[a as usize];
+        let p2 = positions[b as usize];
+        let p3 = positions[c as usize];
+        let normal = (p2 - p1).cross(&(p3 - p1)).normalize();
+        computed[a as usize] = normal;
+        computed[b as usize] = normal;
+        computed[c as usize] = normal;
+    }
+    computed
+}
+
+/// computes the tangents for normal mapping per triangle from the texture coordinates
+pub(crate) fn triangle_tangents(
+    positions: &[Vec3],
+    texture_coords: &[Vec2],
+    indices: &[GLuint],
+) -> Vec<Vec3> {
+    let mut tangents = vec![vec3(0.0, 0.0, 0.0); positions.len()];
+    for (a, b, c) in indices.iter().copied().tuples() {
+        let p1 = positions[a as usize];
+        let p2 = positions[b as usize];
+        let p3 = positions[c as usize];
+        let uv1 = texture_coords[a as usize];
+        let uv2 = texture_coords[b as usize];
+        let uv3 = texture_coords[c as usize];
+
+        let edge1 = p2 - p1;
+        let edge2 = p3 - p1;
+        let delta_uv1 = uv2 - uv1;
+        let delta_uv2 = uv3 - uv1;
+
+        let f = 1.0 / (delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y);
+
+        let mut tangent = vec3(0.0, 0.0, 0.0);
+        tangent.x = f * (delta_uv2.y * edge1.x - delta_uv1.y * edge2.x);
+        tangent.y = f * (delta_uv2.y * edge1.y - delta_uv1.y * edge2.y);
+        tangent.z = f * (delta_uv2.y * edge1.z - delta_uv1.y * edge2.z);
+
+        tangents[a as usize] = tangent;
+        tangents[b as usize] = tangent;
+        tangents[c as usize] = tangent;
+    }
+    tangents
+}
+
+/// computes the maximum absolute coordinate values in all dimensions
+pub(crate) fn max_reach(positions: &[Vec3]) -> Vec3 {
+    positions
+        .iter()
+        .map(|p| p.abs())
+        .fold(ORIGIN, |mut current, p| {
+            current.x = current.x.max(p.x);
+            current.y = current.y.max(p.y);
+            current.z = current.z.max(p.z);
+            current
+        })
+}
+
 /// computes the inertia moment for a given traingle and index
 fn inertia_moment(triangle: &(Vec3, Vec3, Vec3), i: usize) -> f32 {
     triangle.0[i] * triangle.0[i]
@@ -753,9 +1178,12 @@ fn inertia_product(triangle: &(Vec3, Vec3, Vec3), i: usize, j: usize) -> f32 {
 }
 
 /// all possible versions of hitboxes
+#[derive(Clone)]
 pub(crate) enum Hitbox {
     ConvexMesh(HitboxMesh),
     Sphere(f32),
+    /// multiple convex parts of a concave mesh
+    Compound(Vec<HitboxMesh>),
 }
 
 impl Hitbox {
@@ -766,9 +1194,19 @@ impl Hitbox {
             _ => Self::ConvexMesh(HitboxMesh::box_from_dims(&Vec3::from_element(1.0))),
         }
     }
+
+    /// all convex meshes the hitbox consists of, sphere hitboxes have none
+    pub(crate) fn convex_parts(&self) -> &[HitboxMesh] {
+        match self {
+            Self::ConvexMesh(mesh) => std::slice::from_ref(mesh),
+            Self::Compound(parts) => parts,
+            Self::Sphere(_) => &[],
+        }
+    }
 }
 
 /// contains all of the hitbox vertex data
+#[derive(Clone)]
 pub(crate) struct HitboxMesh {
     pub(crate) vertices: Vec<Vec3>,
     pub(crate) faces: Vec<[usize; 3]>,
@@ -805,92 +1243,131 @@ impl HitboxMesh {
         }
     }
 
+    /// converts the hitbox mesh to the ``.obj`` file format, the vertex offset is the number of vertices written before this object in the same file
+    pub(crate) fn to_obj(&self, name: &str, vertex_offset: usize) -> String {
+        let mut obj = format!("o {name}\n");
+        for vertex in self.vertices.iter() {
+            obj += &format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z);
+        }
+        for face in self
+            .faces
+            .iter()
+            .map(|face| face.map(|i| i + vertex_offset + 1))
+        {
+            obj += &format!("f {} {} {}\n", face[0], face[1], face[2]);
+        }
+        obj
+    }
+
     /// creates a hitbox in the form of a convex hull of the mesh
-    fn convex_hull(mut self) -> Self {
+    pub(crate) fn convex_hull(mut self) -> Self {
         assert!(
             self.vertices.len() >= 4,
             "mesh must be at least as complex as a tetrahedron for it to have a convex hull hitbox"
         );
         let (a, b, c, d) = self.find_initial_tetrahedron();
         self.faces = vec![[a, b, c], [b, a, d], [d, a, c], [b, d, c]];
+        // make sure all of the face normals point outwards
+        let center =
+            (self.vertices[a] + self.vertices[b] + self.vertices[c] + self.vertices[d]) / 4.0;
+        for i in 0..self.faces.len() {
+            if self.face_distance(&self.faces[i], &center) > 0.0 {
+                self.faces[i].swap(0, 1);
+            }
+        }
+        let extent = self
+            .vertices
+            .iter()
+            .map(|v| v.abs().max())
+            .fold(0.0, f32::max);
+        let epsilon = extent.max(1.0) * 1e-5;
 
         // find the points outside the initial tetrahedron
         let mut outside_points =
-            self.find_outside_points(self.faces.iter(), (0..self.vertices.len()).collect());
-
-        while outside_points.iter().any(|p| !p.is_empty()) {
-            let mut new_faces = vec![None; outside_points.len()]; // indices are face indices to delete after every iteration
-            for (i, points) in outside_points.iter().enumerate() {
-                if !points.is_empty() {
-                    let [a, b, c] = self.faces[i];
-                    let (v1, v2, v3) = (self.vertices[a], self.vertices[b], self.vertices[c]);
-                    let face_normal = (v2 - v1).cross(&(v3 - v1));
-                    // find furthest point
-                    let point = points
-                        .iter()
-                        .map(|j| (j, self.vertices[*j] - v1))
-                        .map(|(j, v)| (j, face_normal.dot(&v)))
-                        .filter(|(_, dotp)| *dotp > 0.0)
-                        .max_by(|(_, dp1), (_, dp2)| dp1.partial_cmp(dp2).unwrap())
-                        .map(|(j, _)| *j)
-                        .unwrap();
-                    // create new faces with the point
-                    new_faces[i] = Some([[c, point, a], [b, point, c], [a, point, b]]);
-                }
-            }
-            // the order of the outside sets and the faces is preserved as they are both mutated the same way
-            for idx in (0..new_faces.len()).rev() {
-                if let Some(faces) = new_faces[idx] {
-                    // delete old faces
-                    self.faces.swap_remove(idx);
-                    let mut outside_set = outside_points.swap_remove(idx);
-                    outside_set.retain(|p| faces.iter().flatten().all(|fp| fp != p));
-                    // add new faces
-                    self.faces.push(faces[0]);
-                    self.faces.push(faces[1]);
-                    self.faces.push(faces[2]);
-                    // construct 3 outside sets from a given point cloud the same way it was done in the beginning
-                    let new_outside_points = self.find_outside_points(faces.iter(), outside_set);
-                    debug_assert_eq!(new_outside_points.len(), 3);
-                    outside_points.extend(new_outside_points);
+            self.find_outside_points(&self.faces, (0..self.vertices.len()).collect(), epsilon);
+
+        while let Some(i) = outside_points.iter().position(|points| !points.is_empty()) {
+            // find furthest point
+            let point = *outside_points[i]
+                .iter()
+                .max_by(|&&j, &&k| {
+                    let dist1 = self.face_distance(&self.faces[i], &self.vertices[j]);
+                    dist1.total_cmp(&self.face_distance(&self.faces[i], &self.vertices[k]))
+                })
+                .unwrap();
+            let visible = self
+                .faces
+                .iter()
+                .map(|face| self.face_distance(face, &self.vertices[point]) > epsilon)
+                .collect_vec();
+
+            // the horizon consists of the edges of visible faces that are shared with a hidden face
+            let visible_edges = self
+                .faces
+                .iter()
+                .zip(visible.iter())
+                .filter(|(_, visible)| **visible)
+                .flat_map(|(&[a, b, c], _)| [(a, b), (b, c), (c, a)])
+                .collect::<AHashSet<_>>();
+            let new_faces = visible_edges
+                .iter()
+                .filter(|(a, b)| !visible_edges.contains(&(*b, *a)))
+                .map(|&(a, b)| [a, b, point])
+                .collect_vec();
+
+            // delete the visible faces and redistribute their outside points to the new faces
+            let mut orphans = Vec::new();
+            let mut index = 0;
+            self.faces.retain(|_| {
+                index += 1;
+                !visible[index - 1]
+            });
+            let mut index = 0;
+            outside_points.retain_mut(|points| {
+                index += 1;
+                if visible[index - 1] {
+                    orphans.append(points);
                 }
-            }
+                !visible[index - 1]
+            });
+            orphans.retain(|&j| j != point);
+            outside_points.extend(self.find_outside_points(&new_faces, orphans, epsilon));
+            self.faces.extend(new_faces);
         }
         self.remove_unused_vertices();
         self
     }
 
-    /// find the points outside of each face
-    fn find_outside_points<'a>(
+    /// signed distance of a point to the plane of a face, positive values are in front of the face
+    fn face_distance(&self, face: &[usize; 3], point: &Vec3) -> f32 {
+        let (v1, v2, v3) = (
+            self.vertices[face[0]],
+            self.vertices[face[1]],
+            self.vertices[face[2]],
+        );
+        let face_normal = (v2 - v1).cross(&(v3 - v1));
+        let length = face_normal.norm();
+        if length == 0.0 {
+            return 0.0;
+        }
+        face_normal.dot(&(point - v1)) / length
+    }
+
+    /// assigns every point to the first face it is in front of, points that are not in front of any face are dropped
+    fn find_outside_points(
         &self,
-        faces: impl Iterator<Item = &'a [usize; 3]>,
+        faces: &[[usize; 3]],
         point_cloud: Vec<usize>,
+        epsilon: f32,
     ) -> Vec<Vec<usize>> {
-        let faces = faces.collect_vec();
-        let mut point_sets = Vec::with_capacity(faces.len());
-        let mut points_used = AHashSet::new();
-
-        for face in faces {
-            let (v1, v2, v3) = (
-                self.vertices[face[0]],
-                self.vertices[face[1]],
-                self.vertices[face[2]],
-            );
-            let face_normal = (v2 - v1).cross(&(v3 - v1));
-
-            let fitting_points = point_cloud
+        let mut point_sets = vec![Vec::new(); faces.len()];
+        for i in point_cloud {
+            let face = faces
                 .iter()
-                .filter(|i| !points_used.contains(*i))
-                .map(|i| (i, self.vertices[*i] - v1))
-                .map(|(i, v)| (i, face_normal.dot(&v)))
-                .filter(|(_, dotp)| *dotp > 0.0)
-                .map(|(i, _)| *i)
-                .collect_vec();
-
-            for fitting in fitting_points.iter() {
-                points_used.insert(*fitting);
+                .position(|face| self.face_distance(face, &self.vertices[i]) > epsilon);
+            if let Some(face) = face {
+                point_sets[face].push(i);
             }
-            point_sets.push(fitting_points);
         }
         point_sets
     }
@@ -0,0 +1,514 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::{Hitbox, HitboxMesh, Mesh, MorphTarget, VertexSkin};
+use crate::utils::vfs::read_file;
+
+/// the magic bytes at the start of every cache file
+const CACHE_MAGIC: &[u8; 8] = b"LEAFYMSH";
+/// the current version of the cache format, cache files with other versions are regenerated
+const CACHE_VERSION: u32 = 6;
+
+/// binary cache for processed mesh data (meshes, LODs and hitboxes) of model files that is stored in a directory
+#[derive(Debug, Clone)]
+pub(crate) struct MeshCache {
+    dir: PathBuf,
+}
+
+impl MeshCache {
+    /// creates a new cache that stores the files in the given directory
+    pub(crate) fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// the cached meshes of a source file if the cache entry is valid
+    pub(crate) fn meshes(&self, source: &Path) -> Option<Vec<Mesh>> {
+        self.read(source)
+            .map(|file| file.meshes)
+            .filter(|meshes| !meshes.is_empty())
+    }
+
+    /// stores the meshes of a source file and invalidates the LODs and hitboxes of the previous version
+    pub(crate) fn store_meshes(&self, source: &Path, meshes: &[Mesh]) {
+        let mut file = self.read(source).unwrap_or_else(|| CacheFile::new(source));
+        file.meshes = meshes.to_vec();
+        self.write(source, &file);
+    }
+
+    /// the LODs of a mesh from the cache or generates and stores them if not present, LODs generated with other settings are replaced
+    pub(crate) fn lods_or_generate(
+        cache: Option<&Self>,
+        mesh: &Mesh,
+        settings: &SimplificationSettings,
+    ) -> Vec<Mesh> {
+        let cache = cache.filter(|_| mesh.source_file.as_ref() != Path::new("internal"));
+        let settings_hash = settings_hash(settings);
+        let mut file = cache.and_then(|cache| cache.read(&mesh.source_file));
+        if let Some(lods) = file.as_ref().and_then(|file| {
+            file.lods
+                .iter()
+                .find(|(index, hash, _)| *index == mesh.source_index && *hash == settings_hash)
+                .map(|(.., lods)| lods.clone())
+        }) {
+            log::debug!("Loaded LODs for mesh {:?} from the cache.", mesh.name);
+            return lods;
+        }
+        let lods = mesh.generate_lods(settings);
+        if let Some(cache) = cache {
+            let mut file = file
+                .take()
+                .unwrap_or_else(|| CacheFile::new(&mesh.source_file));
+            file.lods.retain(|(index, ..)| *index != mesh.source_index);
+            file.lods
+                .push((mesh.source_index, settings_hash, lods.clone()));
+            cache.write(&mesh.source_file, &file);
+        }
+        lods
+    }
+
+    /// the hitbox of a mesh from the cache or generates and stores it if not present
+    pub(crate) fn hitbox_or_generate(
+        cache: Option<&Self>,
+        mesh: &Mesh,
+        hitbox_type: HitboxType,
+    ) -> Hitbox {
+        let cache = cache.filter(|_| mesh.source_file.as_ref() != Path::new("internal"));
+        let mut file = cache.and_then(|cache| cache.read(&mesh.source_file));
+        if let Some(hitbox) = file.as_ref().and_then(|file| {
+            file.hitboxes
+                .iter()
+                .find(|(index, ty, _)| *index == mesh.source_index && *ty == hitbox_type)
+                .map(|(.., hitbox)| hitbox.clone())
+        }) {
+            log::debug!(
+                "Loaded hitbox {hitbox_type:?} for mesh {:?} from the cache.",
+                mesh.name
+            );
+            return hitbox;
+        }
+        let hitbox = mesh.generate_hitbox(&hitbox_type);
+        if let Some(cache) = cache {
+            let mut file = file
+                .take()
+                .unwrap_or_else(|| CacheFile::new(&mesh.source_file));
+            file.hitboxes
+                .push((mesh.source_index, hitbox_type, hitbox.clone()));
+            cache.write(&mesh.source_file, &file);
+        }
+        hitbox
+    }
+
+    /// the path of the cache file for a source file
+    fn cache_path(&self, source: &Path) -> PathBuf {
+        let stem = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        self.dir.join(format!(
+            "{stem}_{:016x}.meshcache",
+            fnv1a(source.to_string_lossy().as_bytes())
+        ))
+    }
+
+    /// reads the cache file of a source file, returns ``None`` if it is missing, invalid or the source file changed
+    fn read(&self, source: &Path) -> Option<CacheFile> {
+        let source_hash = fnv1a(&read_file(source).ok()?);
+        let bytes = std::fs::read(self.cache_path(source)).ok()?;
+        let mut reader = ByteReader { bytes: &bytes };
+        if reader.take(CACHE_MAGIC.len())? != CACHE_MAGIC
+            || reader.u32()? != CACHE_VERSION
+            || reader.u64()? != source_hash
+        {
+            log::debug!("Mesh cache entry for {source:?} is outdated.");
+            return None;
+        }
+        let source_file: Arc<Path> = Arc::from(source);
+        let mut meshes = reader.vec(|r| r.mesh(&source_file))?;
+        for (i, mesh) in meshes.iter_mut().enumerate() {
+            mesh.source_index = i;
+        }
+        let lods = reader.vec(|r| {
+            Some((
+                r.u32()? as usize,
+                r.u64()?,
+                r.vec(|r| r.mesh(&source_file))?,
+            ))
+        })?;
+        let hitboxes = reader.vec(|r| Some((r.u32()? as usize, r.hitbox_type()?, r.hitbox()?)))?;
+        Some(CacheFile {
+            source_hash,
+            meshes,
+            lods,
+            hitboxes,
+        })
+    }
+
+    /// writes the cache file of a source file (atomically replaces the previous one)
+    fn write(&self, source: &Path, file: &CacheFile) {
+        let mut writer = ByteWriter { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(CACHE_MAGIC);
+        writer.u32(CACHE_VERSION);
+        writer.u64(file.source_hash);
+        writer.vec(&file.meshes, |w, mesh| w.mesh(mesh));
+        writer.vec(&file.lods, |w, (index, settings_hash, lods)| {
+            w.u32(*index as u32);
+            w.u64(*settings_hash);
+            w.vec(lods, |w, lod| w.mesh(lod));
+        });
+        writer.vec(&file.hitboxes, |w, (index, hitbox_type, hitbox)| {
+            w.u32(*index as u32);
+            w.hitbox_type(*hitbox_type);
+            w.hitbox(hitbox);
+        });
+
+        let path = self.cache_path(source);
+        let tmp_path = path.with_extension(format!("tmp{:?}", std::thread::current().id()));
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&tmp_path, &writer.bytes))
+            .and_then(|_| std::fs::rename(&tmp_path, &path));
+        match result {
+            Ok(_) => log::debug!("Updated mesh cache entry for {source:?}."),
+            Err(msg) => log::warn!("Error writing mesh cache file {path:?}: {msg}."),
+        }
+    }
+}
+
+/// the cached data of one source file
+struct CacheFile {
+    source_hash: u64,
+    meshes: Vec<Mesh>,
+    /// the LODs with the source index of the mesh and the simplification settings hash
+    lods: Vec<(usize, u64, Vec<Mesh>)>,
+    /// the hitboxes with the source index of the mesh
+    hitboxes: Vec<(usize, HitboxType, Hitbox)>,
+}
+
+impl CacheFile {
+    /// creates an empty cache file for the current version of a source file
+    fn new(source: &Path) -> Self {
+        Self {
+            source_hash: read_file(source)
+                .map(|bytes| fnv1a(&bytes))
+                .unwrap_or_default(),
+            meshes: Vec::new(),
+            lods: Vec::new(),
+            hitboxes: Vec::new(),
+        }
+    }
+}
+
+/// 64 bit FNV-1a hash that is stable across runs and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// identifies the simplification settings that LODs were generated with
+fn settings_hash(settings: &SimplificationSettings) -> u64 {
+    let mut writer = ByteWriter { bytes: Vec::new() };
+    writer.vec(&settings.levels, |w, target| match target {
+        SimplificationTarget::Ratio(ratio) => {
+            w.bytes.push(0);
+            w.f32s([ratio]);
+        }
+        SimplificationTarget::TriangleCount(count) => {
+            w.bytes.push(1);
+            w.u64(*count as u64);
+        }
+    });
+    writer.f32s(settings.max_error.as_ref());
+    writer.bytes.extend_from_slice(&[
+        settings.preserve_boundaries as u8,
+        settings.preserve_uv_seams as u8,
+        settings.preserve_color_seams as u8,
+    ]);
+    fnv1a(&writer.bytes)
+}
+
+/// little endian binary serialization of the cache data
+struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32s<'v>(&mut self, values: impl IntoIterator<Item = &'v f32>) {
+        let values = values.into_iter().collect_vec();
+        self.u32(values.len() as u32);
+        values
+            .iter()
+            .for_each(|value| self.bytes.extend_from_slice(&value.to_le_bytes()));
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn vec<T>(&mut self, values: &[T], write: impl Fn(&mut Self, &T)) {
+        self.u32(values.len() as u32);
+        values.iter().for_each(|value| write(self, value));
+    }
+
+    fn mesh(&mut self, mesh: &Mesh) {
+        self.string(&mesh.name);
+        self.f32s(mesh.positions.iter().flatten());
+        self.f32s(mesh.colors.iter().flatten());
+        self.f32s(mesh.normals.iter().flatten());
+        self.f32s(mesh.texture_coords.iter().flatten());
+        self.f32s(mesh.tangents.iter().flatten());
+        self.vec(&mesh.indices, |w, index| w.u32(*index));
+        self.f32s(mesh.max_reach.iter());
+        match &mesh.material_name {
+            Some(name) => {
+                self.bytes.push(1);
+                self.string(name);
+            }
+            None => self.bytes.push(0),
+        }
+        match &mesh.skin {
+            Some(skin) => {
+                self.bytes.push(1);
+                self.vec(&skin.joints, |w, joints| {
+                    joints.iter().for_each(|joint| w.u32(*joint as u32))
+                });
+                self.f32s(skin.weights.iter().flatten());
+            }
+            None => self.bytes.push(0),
+        }
+        self.vec(&mesh.morph_targets, |w, target| {
+            w.string(&target.name);
+            w.f32s(target.position_deltas.iter().flatten());
+            w.f32s(target.normal_deltas.iter().flatten());
+        });
+    }
+
+    fn hitbox_type(&mut self, hitbox_type: HitboxType) {
+        self.bytes.push(match hitbox_type {
+            HitboxType::ConvexHull => 0,
+            HitboxType::SimplifiedConvexHull => 1,
+            HitboxType::Sphere => 2,
+            HitboxType::Box => 3,
+            HitboxType::ConvexDecomposition(_) => 4,
+        });
+        if let HitboxType::ConvexDecomposition(settings) = hitbox_type {
+            self.u32(settings.max_parts as u32);
+            self.f32s([&settings.tolerance]);
+            self.u32(settings.resolution as u32);
+        }
+    }
+
+    fn hitbox(&mut self, hitbox: &Hitbox) {
+        match hitbox {
+            Hitbox::ConvexMesh(mesh) => {
+                self.bytes.push(0);
+                self.hitbox_mesh(mesh);
+            }
+            Hitbox::Sphere(radius) => {
+                self.bytes.push(1);
+                self.f32s([radius]);
+            }
+            Hitbox::Compound(parts) => {
+                self.bytes.push(2);
+                self.vec(parts, |w, part| w.hitbox_mesh(part));
+            }
+        }
+    }
+
+    fn hitbox_mesh(&mut self, mesh: &HitboxMesh) {
+        self.f32s(mesh.vertices.iter().flatten());
+        self.vec(&mesh.faces, |w, face| {
+            face.iter().for_each(|index| w.u32(*index as u32))
+        });
+    }
+}
+
+/// reads the data written by the ``ByteWriter``, all functions return ``None`` if the data is incomplete or invalid
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if count > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn f32s(&mut self) -> Option<Vec<f32>> {
+        let len = self.u32()? as usize;
+        self.take(len.checked_mul(4)?).map(|bytes| {
+            bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect()
+        })
+    }
+
+    fn vec3s(&mut self) -> Option<Vec<Vec3>> {
+        let values = self.f32s()?;
+        (values.len() % 3 == 0).then(|| {
+            values
+                .chunks_exact(3)
+                .map(Vec3::from_column_slice)
+                .collect()
+        })
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        self.take(len)
+            .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+    }
+
+    fn vec<T>(&mut self, read: impl Fn(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let len = self.u32()? as usize;
+        let mut values = Vec::with_capacity(len.min(self.bytes.len()));
+        for _ in 0..len {
+            values.push(read(self)?);
+        }
+        Some(values)
+    }
+
+    fn mesh(&mut self, source_file: &Arc<Path>) -> Option<Mesh> {
+        let name = self.string()?;
+        let positions = self.vec3s()?;
+        let colors = self.f32s()?;
+        let normals = self.vec3s()?;
+        let texture_coords = self.f32s()?;
+        let tangents = self.vec3s()?;
+        let indices = self.vec(|r| r.u32())?;
+        let max_reach = self.vec3s()?.first().copied()?;
+        let material_name = match self.u8()? {
+            0 => None,
+            _ => Some(self.string()?),
+        };
+        let skin = match self.u8()? {
+            0 => None,
+            _ => {
+                let joints = self.vec(|r| {
+                    Some([
+                        r.u32()? as u16,
+                        r.u32()? as u16,
+                        r.u32()? as u16,
+                        r.u32()? as u16,
+                    ])
+                })?;
+                let weights = self.f32s()?;
+                if weights.len() != joints.len() * 4 {
+                    return None;
+                }
+                Some(VertexSkin {
+                    joints,
+                    weights: weights
+                        .chunks_exact(4)
+                        .map(Vec4::from_column_slice)
+                        .collect(),
+                })
+            }
+        };
+        let morph_targets = self.vec(|r| {
+            Some(MorphTarget {
+                name: r.string()?,
+                position_deltas: r.vec3s()?,
+                normal_deltas: r.vec3s()?,
+            })
+        })?;
+        let vertex_count = positions.len();
+        if colors.len() != vertex_count * 4
+            || texture_coords.len() != vertex_count * 2
+            || normals.len() != vertex_count
+            || tangents.len() != vertex_count
+            || indices.iter().any(|index| *index as usize >= vertex_count)
+            || skin
+                .as_ref()
+                .is_some_and(|skin| skin.joints.len() != vertex_count)
+            || morph_targets.iter().any(|target| {
+                target.position_deltas.len() != vertex_count
+                    || target.normal_deltas.len() != vertex_count
+            })
+        {
+            return None;
+        }
+        Some(Mesh {
+            name,
+            source_file: source_file.clone(),
+            source_index: 0,
+            positions,
+            colors: colors
+                .chunks_exact(4)
+                .map(Vec4::from_column_slice)
+                .collect(),
+            normals,
+            texture_coords: texture_coords
+                .chunks_exact(2)
+                .map(Vec2::from_column_slice)
+                .collect(),
+            indices,
+            tangents,
+            max_reach,
+            material_name,
+            skin,
+            morph_targets,
+        })
+    }
+
+    fn hitbox_type(&mut self) -> Option<HitboxType> {
+        match self.u8()? {
+            0 => Some(HitboxType::ConvexHull),
+            1 => Some(HitboxType::SimplifiedConvexHull),
+            2 => Some(HitboxType::Sphere),
+            3 => Some(HitboxType::Box),
+            4 => Some(HitboxType::ConvexDecomposition(DecompositionSettings {
+                max_parts: self.u32()? as usize,
+                tolerance: self.f32s()?.first().copied()?,
+                resolution: self.u32()? as usize,
+            })),
+            _ => None,
+        }
+    }
+
+    fn hitbox(&mut self) -> Option<Hitbox> {
+        match self.u8()? {
+            0 => self.hitbox_mesh().map(Hitbox::ConvexMesh),
+            1 => self.f32s()?.first().copied().map(Hitbox::Sphere),
+            2 => self.vec(|r| r.hitbox_mesh()).map(Hitbox::Compound),
+            _ => None,
+        }
+    }
+
+    fn hitbox_mesh(&mut self) -> Option<HitboxMesh> {
+        let vertices = self.vec3s()?;
+        let faces =
+            self.vec(|r| Some([r.u32()? as usize, r.u32()? as usize, r.u32()? as usize]))?;
+        if faces.iter().flatten().any(|index| *index >= vertices.len()) {
+            return None;
+        }
+        Some(HitboxMesh { vertices, faces })
+    }
+}
@@ -0,0 +1,382 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::{max_reach, Mesh};
+
+/// Processing operations that can be applied to loaded meshes with ``EntityManager::process_mesh`` to repair imported geometry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeshOperation {
+    /// Merges vertices whose positions are closer than ``tolerance``. Unless ``positions_only`` is set, the normals, texture coordinates, colors, joint weights and morph target normal offsets also have to match within the tolerance, so hard edges and UV seams are kept. Vertices that are moved differently by a morph target are never merged. Triangles that collapse are removed.
+    WeldVertices {
+        tolerance: f32,
+        positions_only: bool,
+    },
+    /// Recomputes the vertex normals from the area weighted face normals. Faces are smoothed together if the angle between them is at most ``smoothing_angle`` degrees (``0`` yields flat shading, ``180`` fully smooth shading), vertices are split where necessary.
+    RecomputeNormals { smoothing_angle: f32 },
+    /// Recomputes the tangents from the texture coordinates, averaged over the adjacent faces and orthogonal to the normals.
+    RecomputeTangents,
+    /// Removes triangles with repeated vertices or without area and all vertices that are no longer used.
+    RemoveDegenerateFaces,
+}
+
+/// Result of the validation of a loaded mesh with ``EntityManager::validate_mesh``. Topology is evaluated on vertex positions, so vertices that are only split for shading purposes count as connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MeshReport {
+    /// The number of vertices.
+    pub vertex_count: usize,
+    /// The number of triangles.
+    pub triangle_count: usize,
+    /// The number of vertices that are exact duplicates of another vertex (all attributes equal).
+    pub duplicate_vertices: usize,
+    /// The number of triangles with repeated vertices or without area.
+    pub degenerate_faces: usize,
+    /// The number of edges that only belong to one triangle (open borders).
+    pub boundary_edges: usize,
+    /// The number of edges that belong to more than two triangles.
+    pub non_manifold_edges: usize,
+    /// The number of edges whose two triangles traverse them in the same direction (inconsistent winding).
+    pub inconsistent_edges: usize,
+    /// The number of triangles whose winding disagrees with their vertex normals.
+    pub flipped_faces: usize,
+}
+
+impl MeshReport {
+    /// Checks wether or not every edge belongs to exactly one or two triangles.
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges == 0
+    }
+
+    /// Checks wether or not the mesh is a closed surface without open borders.
+    pub fn is_closed(&self) -> bool {
+        self.boundary_edges == 0 && self.is_manifold()
+    }
+
+    /// Checks wether or not the neighboring triangles have a consistent winding.
+    pub fn has_consistent_winding(&self) -> bool {
+        self.inconsistent_edges == 0
+    }
+
+    /// Checks wether or not no problems were found, i.e. the mesh is closed, consistently wound and has no duplicates, degenerate triangles or flipped triangles.
+    pub fn is_valid(&self) -> bool {
+        self.is_closed()
+            && self.has_consistent_winding()
+            && self.duplicate_vertices == 0
+            && self.degenerate_faces == 0
+            && self.flipped_faces == 0
+    }
+}
+
+impl Mesh {
+    /// applies a processing operation and updates the extents of the mesh
+    pub(crate) fn apply_operation(&mut self, operation: MeshOperation) {
+        match operation {
+            MeshOperation::WeldVertices {
+                tolerance,
+                positions_only,
+            } => self.weld_vertices(tolerance, positions_only),
+            MeshOperation::RecomputeNormals { smoothing_angle } => {
+                self.recompute_normals(smoothing_angle)
+            }
+            MeshOperation::RecomputeTangents => self.recompute_tangents(),
+            MeshOperation::RemoveDegenerateFaces => self.remove_degenerate_faces(),
+        }
+        self.max_reach = max_reach(&self.positions);
+    }
+
+    /// merges vertices that are within the tolerance and drops collapsed triangles
+    fn weld_vertices(&mut self, tolerance: f32, positions_only: bool) {
+        let tolerance = tolerance.max(0.0);
+        let cell_size = tolerance.max(f32::EPSILON);
+        let cell = |p: &Vec3| (p / cell_size).map(|x| x.floor() as i64);
+        let mut grid: AHashMap<(i64, i64, i64), Vec<usize>> = AHashMap::new();
+        let mut remap: Vec<usize> = Vec::with_capacity(self.num_vertices());
+        let mut kept: Vec<usize> = Vec::new();
+
+        for i in 0..self.num_vertices() {
+            let c = cell(&self.positions[i]);
+            let matching = (-1..=1)
+                .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
+                .filter_map(|(x, y, z)| grid.get(&(c.x + x, c.y + y, c.z + z)))
+                .flatten()
+                .copied()
+                .find(|j| {
+                    let j = kept[*j];
+                    (self.positions[i] - self.positions[j]).norm() <= tolerance
+                        && self.morph_targets.iter().all(|target| {
+                            (target.position_deltas[i] - target.position_deltas[j]).norm()
+                                <= tolerance
+                        })
+                        && (positions_only
+                            || ((self.normals[i] - self.normals[j]).norm() <= tolerance
+                                && (self.texture_coords[i] - self.texture_coords[j]).norm()
+                                    <= tolerance
+                                && (self.colors[i] - self.colors[j]).norm() <= tolerance
+                                && self.skin.as_ref().is_none_or(|skin| {
+                                    skin.joints[i] == skin.joints[j]
+                                        && (skin.weights[i] - skin.weights[j]).norm() <= tolerance
+                                })
+                                && self.morph_targets.iter().all(|target| {
+                                    (target.normal_deltas[i] - target.normal_deltas[j]).norm()
+                                        <= tolerance
+                                })))
+                });
+            match matching {
+                Some(new_index) => remap.push(new_index),
+                None => {
+                    grid.entry((c.x, c.y, c.z)).or_default().push(kept.len());
+                    remap.push(kept.len());
+                    kept.push(i);
+                }
+            }
+        }
+
+        let welded = self.num_vertices() - kept.len();
+        self.select_vertices(&kept);
+        self.indices = self
+            .indices
+            .iter()
+            .map(|i| remap[*i as usize] as GLuint)
+            .tuples()
+            .filter(|(a, b, c)| a != b && b != c && a != c)
+            .flat_map(|(a, b, c)| [a, b, c])
+            .collect();
+        log::debug!("Welded {welded} vertices of mesh {:?}.", self.name);
+    }
+
+    /// recomputes the normals with a smoothing angle in degrees and splits vertices with multiple normals
+    fn recompute_normals(&mut self, smoothing_angle: f32) {
+        let min_cos = smoothing_angle.clamp(0.0, 180.0).to_radians().cos() - 1e-4;
+        let faces = self
+            .indices
+            .iter()
+            .map(|i| *i as usize)
+            .tuples()
+            .collect_vec();
+        let weighted_normals = faces
+            .iter()
+            .map(|(a, b, c)| {
+                (self.positions[*b] - self.positions[*a])
+                    .cross(&(self.positions[*c] - self.positions[*a]))
+            })
+            .collect_vec();
+        let mut faces_at_position: AHashMap<[u32; 3], Vec<usize>> = AHashMap::new();
+        for (f, (a, b, c)) in faces.iter().enumerate() {
+            for vertex in [a, b, c] {
+                faces_at_position
+                    .entry(position_key(&self.positions[*vertex]))
+                    .or_default()
+                    .push(f);
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut vertex_lookup: AHashMap<(usize, [u32; 3]), GLuint> = AHashMap::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::with_capacity(self.indices.len());
+        for (f, (a, b, c)) in faces.iter().enumerate() {
+            let face_normal = weighted_normals[f].normalize();
+            for vertex in [*a, *b, *c] {
+                let sum: Vec3 = faces_at_position[&position_key(&self.positions[vertex])]
+                    .iter()
+                    .unique()
+                    .map(|g| weighted_normals[*g])
+                    .filter(|n| {
+                        n.norm() > f32::EPSILON && n.normalize().dot(&face_normal) >= min_cos
+                    })
+                    .sum();
+                let normal = if sum.norm() > f32::EPSILON {
+                    sum.normalize()
+                } else {
+                    self.normals[vertex]
+                };
+                let index = *vertex_lookup
+                    .entry((vertex, position_key(&normal)))
+                    .or_insert_with(|| {
+                        vertices.push(vertex);
+                        normals.push(normal);
+                        (vertices.len() - 1) as GLuint
+                    });
+                indices.push(index);
+            }
+        }
+
+        self.select_vertices(&vertices);
+        self.normals = normals;
+        self.indices = indices;
+        log::debug!(
+            "Recomputed the normals of mesh {:?} with a smoothing angle of {smoothing_angle}°.",
+            self.name
+        );
+    }
+
+    /// recomputes the tangents averaged over all adjacent faces and orthogonalized against the normals
+    fn recompute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::zeros(); self.num_vertices()];
+        for (a, b, c) in self.indices.iter().map(|i| *i as usize).tuples() {
+            let edge1 = self.positions[b] - self.positions[a];
+            let edge2 = self.positions[c] - self.positions[a];
+            let delta_uv1 = self.texture_coords[b] - self.texture_coords[a];
+            let delta_uv2 = self.texture_coords[c] - self.texture_coords[a];
+            let det = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+            if det.abs() <= f32::EPSILON {
+                continue;
+            }
+            let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) / det;
+            for vertex in [a, b, c] {
+                tangents[vertex] += tangent;
+            }
+        }
+        self.tangents = tangents
+            .into_iter()
+            .zip(self.normals.iter())
+            .map(|(tangent, normal)| {
+                let orthogonal = tangent - normal * normal.dot(&tangent);
+                if orthogonal.norm() > f32::EPSILON {
+                    orthogonal.normalize()
+                } else {
+                    // any direction in the tangent plane for vertices without usable texture coordinates
+                    let helper = if normal.x.abs() < 0.9 { X_AXIS } else { Y_AXIS };
+                    normal
+                        .cross(&helper)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(X_AXIS)
+                }
+            })
+            .collect();
+        log::debug!("Recomputed the tangents of mesh {:?}.", self.name);
+    }
+
+    /// removes triangles without area and the vertices that are not used anymore
+    fn remove_degenerate_faces(&mut self) {
+        let triangle_count = self.num_indices() / 3;
+        self.indices = self
+            .indices
+            .iter()
+            .copied()
+            .tuples()
+            .filter(|(a, b, c)| !is_degenerate(&self.positions, [*a, *b, *c]))
+            .flat_map(|(a, b, c)| [a, b, c])
+            .collect();
+
+        let used = self
+            .indices
+            .iter()
+            .map(|i| *i as usize)
+            .unique()
+            .sorted()
+            .collect_vec();
+        let mut remap = vec![0; self.num_vertices()];
+        for (new_index, old_index) in used.iter().enumerate() {
+            remap[*old_index] = new_index as GLuint;
+        }
+        self.select_vertices(&used);
+        self.indices
+            .iter_mut()
+            .for_each(|i| *i = remap[*i as usize]);
+        log::debug!(
+            "Removed {} degenerate faces of mesh {:?}.",
+            triangle_count - self.num_indices() / 3,
+            self.name
+        );
+    }
+
+    /// keeps only the given vertices in the given order in all the vertex attributes
+    fn select_vertices(&mut self, vertices: &[usize]) {
+        self.positions = vertices.iter().map(|i| self.positions[*i]).collect();
+        self.colors = vertices.iter().map(|i| self.colors[*i]).collect();
+        self.normals = vertices.iter().map(|i| self.normals[*i]).collect();
+        self.texture_coords = vertices.iter().map(|i| self.texture_coords[*i]).collect();
+        self.tangents = vertices.iter().map(|i| self.tangents[*i]).collect();
+        if let Some(skin) = self.skin.as_mut() {
+            skin.joints = vertices.iter().map(|i| skin.joints[*i]).collect();
+            skin.weights = vertices.iter().map(|i| skin.weights[*i]).collect();
+        }
+        for target in self.morph_targets.iter_mut() {
+            target.position_deltas = vertices
+                .iter()
+                .map(|i| target.position_deltas[*i])
+                .collect();
+            target.normal_deltas = vertices.iter().map(|i| target.normal_deltas[*i]).collect();
+        }
+    }
+
+    /// checks the mesh for common problems of imported geometry
+    pub(crate) fn validate(&self) -> MeshReport {
+        let faces = self.indices.iter().copied().tuples().collect_vec();
+        let duplicate_vertices = self.num_vertices()
+            - (0..self.num_vertices())
+                .map(|i| {
+                    [
+                        self.positions[i].as_slice(),
+                        self.normals[i].as_slice(),
+                        self.texture_coords[i].as_slice(),
+                        self.colors[i].as_slice(),
+                    ]
+                    .concat()
+                    .iter()
+                    .map(|x| x.to_bits())
+                    .collect_vec()
+                })
+                .unique()
+                .count();
+
+        let mut edges: AHashMap<([u32; 3], [u32; 3]), (usize, usize)> = AHashMap::new();
+        let mut degenerate_faces = 0;
+        let mut flipped_faces = 0;
+        for (a, b, c) in faces.iter().copied() {
+            if is_degenerate(&self.positions, [a, b, c]) {
+                degenerate_faces += 1;
+                continue;
+            }
+            let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i as usize]);
+            let vertex_normals = [a, b, c]
+                .map(|i| self.normals[i as usize])
+                .iter()
+                .sum::<Vec3>();
+            if (pb - pa).cross(&(pc - pa)).dot(&vertex_normals) < 0.0 {
+                flipped_faces += 1;
+            }
+            for (from, to) in [(pa, pb), (pb, pc), (pc, pa)] {
+                let (from, to) = (position_key(&from), position_key(&to));
+                // count how often an edge is traversed in each direction
+                let entry = edges.entry((from.min(to), from.max(to))).or_default();
+                if from < to {
+                    entry.0 += 1;
+                } else {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        let (mut boundary_edges, mut non_manifold_edges, mut inconsistent_edges) = (0, 0, 0);
+        for (forward, backward) in edges.into_values() {
+            match forward + backward {
+                1 => boundary_edges += 1,
+                2 if forward != backward => inconsistent_edges += 1,
+                2 => {}
+                _ => non_manifold_edges += 1,
+            }
+        }
+
+        MeshReport {
+            vertex_count: self.num_vertices(),
+            triangle_count: faces.len(),
+            duplicate_vertices,
+            degenerate_faces,
+            boundary_edges,
+            non_manifold_edges,
+            inconsistent_edges,
+            flipped_faces,
+        }
+    }
+}
+
+/// checks wether or not a triangle has repeated vertices or no area
+fn is_degenerate(positions: &[Vec3], [a, b, c]: [GLuint; 3]) -> bool {
+    let [pa, pb, pc] = [a, b, c].map(|i| positions[i as usize]);
+    a == b || b == c || a == c || (pb - pa).cross(&(pc - pa)).norm() <= f32::EPSILON
+}
+
+/// exact hashable representation of a vector
+fn position_key(v: &Vec3) -> [u32; 3] {
+    // adding zero maps negative zero to positive zero
+    [v.x + 0.0, v.y + 0.0, v.z + 0.0].map(f32::to_bits)
+}
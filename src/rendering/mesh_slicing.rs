@@ -0,0 +1,533 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::{max_reach, Mesh};
+
+/// A plane that meshes are cut along with ``EntityManager::slice_mesh`` or ``EntityManager::slice_entity``. The half of the mesh that the normal points to is the front half.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlicePlane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// Texture coordinate units per mesh unit on the cap faces that close the cut.
+    pub cap_uv_scale: f32,
+}
+
+impl SlicePlane {
+    /// Creates a plane through ``point`` with the given normal.
+    pub fn new(point: Vec3, normal: Vec3) -> Self {
+        Self {
+            point,
+            normal,
+            cap_uv_scale: 1.0,
+        }
+    }
+
+    /// Sets the texture scale of the cap faces.
+    pub fn with_cap_uv_scale(mut self, cap_uv_scale: f32) -> Self {
+        self.cap_uv_scale = cap_uv_scale;
+        self
+    }
+}
+
+/// exact hashable representation of a position
+type PositionKey = [u32; 3];
+
+fn position_key(position: &Vec3) -> PositionKey {
+    [position.x, position.y, position.z].map(f32::to_bits)
+}
+
+/// identifies a vertex of a sliced half so vertices that are shared by triangles are only stored once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum VertexKey {
+    Original(usize),
+    /// intersection of the plane with the edge between two vertices
+    Edge(usize, usize),
+}
+
+/// a vertex with all of its attributes
+#[derive(Debug, Clone, Copy)]
+struct SliceVertex {
+    position: Vec3,
+    color: Vec4,
+    normal: Vec3,
+    texture_coord: Vec2,
+    tangent: Vec3,
+}
+
+impl SliceVertex {
+    /// copies the attributes of a mesh vertex
+    fn of(mesh: &Mesh, index: usize) -> Self {
+        Self {
+            position: mesh.positions[index],
+            color: mesh.colors[index],
+            normal: mesh.normals[index],
+            texture_coord: mesh.texture_coords[index],
+            tangent: mesh.tangents[index],
+        }
+    }
+
+    /// interpolates all attributes between two vertices
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let direction = |a: &Vec3, b: &Vec3| {
+            let mixed = a.lerp(b, t);
+            mixed.try_normalize(f32::EPSILON).unwrap_or(mixed)
+        };
+        Self {
+            position: self.position.lerp(&other.position, t),
+            color: self.color.lerp(&other.color, t),
+            normal: direction(&self.normal, &other.normal),
+            texture_coord: self.texture_coord.lerp(&other.texture_coord, t),
+            tangent: direction(&self.tangent, &other.tangent),
+        }
+    }
+}
+
+/// a corner of a triangle after clipping it against the plane
+#[derive(Clone, Copy)]
+struct ClippedCorner {
+    key: VertexKey,
+    vertex: SliceVertex,
+    on_plane: bool,
+}
+
+/// collects the geometry of one half of a sliced mesh
+#[derive(Default)]
+struct HalfBuilder {
+    vertices: Vec<SliceVertex>,
+    indices: Vec<GLuint>,
+    lookup: AHashMap<VertexKey, GLuint>,
+}
+
+impl HalfBuilder {
+    /// adds a convex polygon as a triangle fan
+    fn add_polygon(&mut self, corners: &[ClippedCorner]) {
+        let indices = corners
+            .iter()
+            .map(|corner| {
+                *self.lookup.entry(corner.key).or_insert_with(|| {
+                    self.vertices.push(corner.vertex);
+                    (self.vertices.len() - 1) as GLuint
+                })
+            })
+            .collect_vec();
+        for i in 1..indices.len().saturating_sub(1) {
+            self.indices
+                .extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+        }
+    }
+
+    /// adds the triangles of a cap that are not shared with the rest of the half
+    fn add_cap(&mut self, triangles: &[[Vec3; 3]], plane: &SlicePlane, normal: Vec3, flip: bool) {
+        let (u_axis, v_axis) = plane_basis(&normal);
+        for triangle in triangles {
+            let corners = if flip {
+                [triangle[0], triangle[2], triangle[1]]
+            } else {
+                *triangle
+            };
+            for position in corners {
+                self.vertices.push(SliceVertex {
+                    position,
+                    color: vec4(1.0, 1.0, 1.0, 1.0),
+                    normal,
+                    texture_coord: vec2(position.dot(&u_axis), position.dot(&v_axis))
+                        * plane.cap_uv_scale,
+                    tangent: u_axis,
+                });
+                self.indices.push((self.vertices.len() - 1) as GLuint);
+            }
+        }
+    }
+
+    /// converts the collected geometry to a mesh
+    fn into_mesh(self, original: &Mesh, suffix: &str) -> Mesh {
+        let positions = self.vertices.iter().map(|v| v.position).collect_vec();
+        Mesh {
+            name: format!("{}_{suffix}", original.name),
+            source_file: Arc::from(Path::new("internal")),
+            source_index: 0,
+            max_reach: max_reach(&positions),
+            positions,
+            colors: self.vertices.iter().map(|v| v.color).collect(),
+            normals: self.vertices.iter().map(|v| v.normal).collect(),
+            texture_coords: self.vertices.iter().map(|v| v.texture_coord).collect(),
+            tangents: self.vertices.iter().map(|v| v.tangent).collect(),
+            indices: self.indices,
+            material_name: original.material_name.clone(),
+            // the cap faces are not bound to any joint or morph target, so the halves are static meshes
+            skin: None,
+            morph_targets: Vec::new(),
+        }
+    }
+}
+
+impl Mesh {
+    /// cuts the mesh into the front and back half of the plane and closes both cuts with caps, returns ``None`` if the plane does not intersect the mesh
+    pub(crate) fn sliced(&self, plane: &SlicePlane) -> Option<(Mesh, Mesh)> {
+        let normal = plane.normal.try_normalize(f32::EPSILON)?;
+        let epsilon = self.max_reach.max().max(1.0) * 1e-5;
+        let distances = self
+            .positions
+            .iter()
+            .map(|position| normal.dot(&(position - plane.point)))
+            .map(|distance| {
+                if distance.abs() <= epsilon {
+                    0.0
+                } else {
+                    distance
+                }
+            })
+            .collect_vec();
+
+        let mut front = HalfBuilder::default();
+        let mut back = HalfBuilder::default();
+        // snapping stays below the distance threshold, so intersection points never merge with the vertices next to them
+        let mut segments = CapSegments::new(epsilon * 0.1);
+        for (a, b, c) in self.indices.iter().map(|i| *i as usize).tuples() {
+            let triangle = [a, b, c];
+            if triangle.iter().all(|i| distances[*i] == 0.0) {
+                // triangles inside of the plane belong to the half that is behind them
+                let (p1, p2, p3) = (self.positions[a], self.positions[b], self.positions[c]);
+                let facing = (p2 - p1).cross(&(p3 - p1)).dot(&normal);
+                let corners = self.clip_triangle(triangle, &distances, 1.0);
+                if facing < 0.0 {
+                    front.add_polygon(&corners);
+                } else {
+                    back.add_polygon(&corners);
+                }
+                continue;
+            }
+            let front_corners = self.clip_triangle(triangle, &distances, 1.0);
+            if front_corners.len() >= 3 {
+                front.add_polygon(&front_corners);
+                // edges of the clipped polygon that lie in the plane are part of the cut outline
+                for (corner1, corner2) in front_corners.iter().circular_tuple_windows() {
+                    if corner1.on_plane && corner2.on_plane {
+                        segments.add(corner2.vertex.position, corner1.vertex.position);
+                    }
+                }
+            }
+            back.add_polygon(&self.clip_triangle(triangle, &distances, -1.0));
+        }
+        if front.indices.is_empty() || back.indices.is_empty() {
+            return None;
+        }
+
+        // the cap of the front half faces against the plane normal
+        let cap = triangulate_cap(segments.into_loops(), &-normal);
+        front.add_cap(&cap, plane, -normal, false);
+        back.add_cap(&cap, plane, normal, true);
+        log::debug!(
+            "Sliced mesh {:?} with {} cap triangles.",
+            self.name,
+            cap.len()
+        );
+        Some((front.into_mesh(self, "front"), back.into_mesh(self, "back")))
+    }
+
+    /// clips a triangle against the plane and keeps the polygon on the side of the sign
+    fn clip_triangle(
+        &self,
+        triangle: [usize; 3],
+        distances: &[f32],
+        sign: f32,
+    ) -> SmallVec<[ClippedCorner; 4]> {
+        let mut corners = SmallVec::new();
+        for (&i, &j) in triangle.iter().circular_tuple_windows() {
+            let (distance_i, distance_j) = (distances[i] * sign, distances[j] * sign);
+            if distance_i >= 0.0 {
+                corners.push(ClippedCorner {
+                    key: VertexKey::Original(i),
+                    vertex: SliceVertex::of(self, i),
+                    on_plane: distance_i == 0.0,
+                });
+            }
+            if distance_i * distance_j < 0.0 {
+                // interpolate in a fixed order so both halves and neighboring triangles get the same position
+                let (first, second) =
+                    if position_key(&self.positions[i]) < position_key(&self.positions[j]) {
+                        (i, j)
+                    } else {
+                        (j, i)
+                    };
+                let t = distances[first] / (distances[first] - distances[second]);
+                corners.push(ClippedCorner {
+                    key: VertexKey::Edge(first.min(second), first.max(second)),
+                    vertex: SliceVertex::of(self, first).lerp(&SliceVertex::of(self, second), t),
+                    on_plane: true,
+                });
+            }
+        }
+        corners
+    }
+}
+
+/// the directed outline edges of a cut, edges that are traversed in both directions cancel each other out
+struct CapSegments {
+    tolerance: f32,
+    segments: AHashMap<(SnappedKey, SnappedKey), (Vec3, Vec3)>,
+}
+
+/// position rounded to the tolerance, so outlines of meshes that are not exactly welded still connect
+type SnappedKey = [i64; 3];
+
+impl CapSegments {
+    fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance,
+            segments: AHashMap::new(),
+        }
+    }
+
+    fn key(&self, position: &Vec3) -> SnappedKey {
+        [position.x, position.y, position.z].map(|x| (x / self.tolerance).round() as i64)
+    }
+
+    fn add(&mut self, start: Vec3, end: Vec3) {
+        let (start_key, end_key) = (self.key(&start), self.key(&end));
+        if start_key == end_key {
+            return;
+        }
+        if self.segments.remove(&(end_key, start_key)).is_none() {
+            self.segments.insert((start_key, end_key), (start, end));
+        }
+    }
+
+    /// chains the edges to closed loops, open chains of meshes with holes are dropped
+    fn into_loops(self) -> Vec<Vec<Vec3>> {
+        let mut outgoing: AHashMap<SnappedKey, Vec<Vec3>> = AHashMap::new();
+        let mut starts = AHashMap::new();
+        for ((start_key, _), (start, end)) in self.segments.iter() {
+            outgoing.entry(*start_key).or_default().push(*end);
+            starts.insert(*start_key, *start);
+        }
+        let mut loops = Vec::new();
+        for key in starts.keys().copied().sorted() {
+            while let Some(end) = outgoing.get_mut(&key).and_then(Vec::pop) {
+                let mut outline = vec![starts[&key]];
+                let mut current = end;
+                let closed = loop {
+                    let current_key = self.key(&current);
+                    if current_key == key {
+                        break true;
+                    }
+                    outline.push(current);
+                    match outgoing.get_mut(&current_key).and_then(Vec::pop) {
+                        Some(next) => current = next,
+                        None => break false,
+                    }
+                };
+                if closed && outline.len() >= 3 {
+                    loops.push(outline);
+                }
+            }
+        }
+        loops
+    }
+}
+
+/// two axes that span the plane with the given normal, counter clockwise in these axes is counter clockwise around the normal
+fn plane_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let helper = if normal.x.abs() < 0.9 { X_AXIS } else { Y_AXIS };
+    let u_axis = (helper - normal * normal.dot(&helper)).normalize();
+    (u_axis, normal.cross(&u_axis))
+}
+
+/// twice the signed area of the triangle, positive for counter clockwise corners
+fn orientation(a: &Vec2, b: &Vec2, c: &Vec2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// twice the signed area of a polygon
+fn polygon_area(points: &[Vec2], polygon: &[usize]) -> f32 {
+    polygon
+        .iter()
+        .circular_tuple_windows()
+        .map(|(&a, &b)| points[a].x * points[b].y - points[b].x * points[a].y)
+        .sum()
+}
+
+/// checks wether or not a point is inside of a polygon with the even-odd rule
+fn polygon_contains(points: &[Vec2], polygon: &[usize], point: &Vec2) -> bool {
+    let mut inside = false;
+    for (&a, &b) in polygon.iter().circular_tuple_windows() {
+        let (a, b) = (points[a], points[b]);
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// checks wether or not two segments cross each other in a single point that is not one of their ends
+fn segments_cross(a: &Vec2, b: &Vec2, c: &Vec2, d: &Vec2) -> bool {
+    orientation(a, b, c) * orientation(a, b, d) < 0.0
+        && orientation(c, d, a) * orientation(c, d, b) < 0.0
+}
+
+/// triangulates the outlines of a cut, outlines inside of other outlines are holes
+fn triangulate_cap(loops: Vec<Vec<Vec3>>, normal: &Vec3) -> Vec<[Vec3; 3]> {
+    let (u_axis, v_axis) = plane_basis(normal);
+    let positions = loops.iter().flatten().copied().collect_vec();
+    let points = positions
+        .iter()
+        .map(|position| vec2(position.dot(&u_axis), position.dot(&v_axis)))
+        .collect_vec();
+    let mut offset = 0;
+    let mut polygons = loops
+        .iter()
+        .map(|outline| {
+            offset += outline.len();
+            (offset - outline.len()..offset).collect_vec()
+        })
+        .collect_vec();
+
+    // outer outlines wind counter clockwise, but meshes with flipped winding result in flipped outlines
+    let mut areas = polygons
+        .iter()
+        .map(|polygon| polygon_area(&points, polygon))
+        .collect_vec();
+    if areas.iter().sum::<f32>() < 0.0 {
+        polygons.iter_mut().for_each(|polygon| polygon.reverse());
+        areas.iter_mut().for_each(|area| *area = -*area);
+    }
+    let mut outers = (0..polygons.len())
+        .filter(|i| areas[*i] > 0.0)
+        .map(|i| (i, Vec::new()))
+        .collect_vec();
+    for hole in (0..polygons.len()).filter(|i| areas[*i] < 0.0) {
+        let point = points[polygons[hole][0]];
+        let outer = outers
+            .iter_mut()
+            .filter(|(outer, _)| polygon_contains(&points, &polygons[*outer], &point))
+            .min_by(|(outer1, _), (outer2, _)| areas[*outer1].total_cmp(&areas[*outer2]));
+        if let Some((_, holes)) = outer {
+            holes.push(hole);
+        }
+    }
+
+    let extent = points
+        .iter()
+        .map(|point| point.abs().max())
+        .fold(1.0, f32::max);
+    let epsilon = extent * extent * 1e-6;
+    let mut triangles = Vec::new();
+    for (outer, mut holes) in outers {
+        let mut polygon = polygons[outer].clone();
+        // connect the holes from right to left to the outline
+        holes.sort_by(|hole1, hole2| {
+            let max_x = |hole: &usize| {
+                polygons[*hole]
+                    .iter()
+                    .map(|i| points[*i].x)
+                    .fold(f32::MIN, f32::max)
+            };
+            max_x(hole2).total_cmp(&max_x(hole1))
+        });
+        for (i, hole) in holes.iter().enumerate() {
+            bridge_hole(
+                &points,
+                &mut polygon,
+                &polygons[*hole],
+                holes[i + 1..].iter().map(|hole| &polygons[*hole]),
+            );
+        }
+        triangles.extend(ear_clip(&points, polygon, epsilon));
+    }
+    triangles
+        .into_iter()
+        .map(|triangle| triangle.map(|i| positions[i]))
+        .collect_vec()
+}
+
+/// merges a hole into the outline with a pair of edges to a visible vertex of the outline
+fn bridge_hole<'a>(
+    points: &[Vec2],
+    polygon: &mut Vec<usize>,
+    hole: &[usize],
+    other_holes: impl Iterator<Item = &'a Vec<usize>> + Clone,
+) {
+    let hole_start = (0..hole.len())
+        .max_by(|i, j| points[hole[*i]].x.total_cmp(&points[hole[*j]].x))
+        .unwrap();
+    let start = points[hole[hole_start]];
+    let blocked = |end: &Vec2| {
+        polygon
+            .iter()
+            .circular_tuple_windows()
+            .chain(
+                other_holes
+                    .clone()
+                    .flat_map(|hole| hole.iter().circular_tuple_windows()),
+            )
+            .chain(hole.iter().circular_tuple_windows())
+            .any(|(a, b)| segments_cross(&start, end, &points[*a], &points[*b]))
+    };
+    let candidates = (0..polygon.len())
+        .sorted_by(|i, j| {
+            let distance = |k: &usize| (points[polygon[*k]] - start).norm_squared();
+            distance(i).total_cmp(&distance(j))
+        })
+        .collect_vec();
+    let target = candidates
+        .iter()
+        .copied()
+        .find(|i| !blocked(&points[polygon[*i]]))
+        .unwrap_or(candidates[0]);
+
+    let mut bridged = polygon[..=target].to_vec();
+    bridged.extend(hole[hole_start..].iter().chain(hole[..=hole_start].iter()));
+    bridged.extend_from_slice(&polygon[target..]);
+    *polygon = bridged;
+}
+
+/// triangulates a simple counter clockwise polygon by cutting off convex corners that contain no other vertex
+fn ear_clip(points: &[Vec2], mut polygon: Vec<usize>, epsilon: f32) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::new();
+    let mut cursor = 0;
+    while polygon.len() >= 3 {
+        let n = polygon.len();
+        let corner = |i: usize| {
+            [
+                polygon[(i + n - 1) % n],
+                polygon[i % n],
+                polygon[(i + 1) % n],
+            ]
+        };
+        let area = |[a, b, c]: [usize; 3]| orientation(&points[a], &points[b], &points[c]);
+        let is_ear = |i: usize| {
+            let [a, b, c] = corner(i);
+            area([a, b, c]) > epsilon
+                && polygon.iter().all(|p| {
+                    let p = &points[*p];
+                    [a, b, c].iter().any(|corner| points[*corner] == *p)
+                        || orientation(&points[a], &points[b], p) < 0.0
+                        || orientation(&points[b], &points[c], p) < 0.0
+                        || orientation(&points[c], &points[a], p) < 0.0
+                })
+        };
+        // duplicate points and spikes are removed without a triangle, collinear points on straight edges are kept to avoid gaps to the neighboring faces
+        let spike = (0..n).find(|i| {
+            let [a, b, c] = corner(*i);
+            let (edge1, edge2) = (points[b] - points[a], points[c] - points[b]);
+            area([a, b, c]).abs() <= epsilon && edge1.dot(&edge2) <= 0.0
+        });
+        if let Some(i) = spike {
+            polygon.remove(i);
+            continue;
+        }
+        let ear = (cursor..cursor + n).map(|i| i % n).find(|i| is_ear(*i));
+        // fall back to the most convex corner if rounding errors leave no ear
+        let i = ear.unwrap_or_else(|| {
+            (0..n)
+                .max_by(|i, j| area(corner(*i)).total_cmp(&area(corner(*j))))
+                .unwrap()
+        });
+        if area(corner(i)) > epsilon {
+            triangles.push(corner(i));
+        }
+        polygon.remove(i);
+        cursor = i;
+    }
+    triangles
+}
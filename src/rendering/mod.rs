@@ -1,5 +1,14 @@
+pub(crate) mod convex_decomposition;
 pub mod data;
+pub(crate) mod gltf;
 pub mod instance_renderer;
 pub mod mesh;
+pub(crate) mod mesh_cache;
+pub mod mesh_processing;
+pub mod mesh_slicing;
+pub mod procedural;
 pub mod shader;
+pub mod skinning;
+pub mod sprite_atlas;
 pub mod sprite_renderer;
+pub(crate) mod texture_formats;
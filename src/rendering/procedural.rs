@@ -0,0 +1,256 @@
+use crate::internal_prelude::*;
+use crate::rendering::mesh::Mesh;
+use std::f32::consts::{PI, TAU};
+
+/// the maximum number of segments, rings or subdivisions along one direction of a grid, higher values are clamped
+const MAX_GRID_SEGMENTS: u32 = 1024;
+
+/// Parameterized primitive shapes that can be generated as meshes with ``EntityManager::load_procedural_mesh``. All shapes are centered at the origin and have the same extents as the built-in meshes (radius ``1.0`` and height ``2.0`` along the y-axis). The segment and ring counts are clamped so that a shape has at most ``1024`` quads along each direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProceduralMesh {
+    /// Sphere made of ``segments`` around the y-axis and ``rings`` from pole to pole.
+    UVSphere { segments: u32, rings: u32 },
+    /// Sphere made of a subdivided icosahedron with evenly distributed vertices.
+    IcoSphere { subdivisions: u32 },
+    /// Closed cylinder with ``segments`` around the y-axis.
+    Cylinder { segments: u32 },
+    /// Cylinder with hemispherical caps, ``length`` is the length of the cylindrical part and ``rings`` the number of rings per hemisphere.
+    Capsule {
+        segments: u32,
+        rings: u32,
+        length: f32,
+    },
+    /// Closed cone with the tip pointing in the positive y-direction.
+    Cone { segments: u32 },
+    /// Torus around the y-axis with a major radius of ``1.0``.
+    Torus {
+        major_segments: u32,
+        minor_segments: u32,
+        minor_radius: f32,
+    },
+    /// Plane in the xz-plane facing up that is subdivided into ``subdivisions`` x ``subdivisions`` quads.
+    Plane { subdivisions: u32 },
+}
+
+impl ProceduralMesh {
+    /// generates the mesh data with normals, texture coordinates and tangents
+    pub(crate) fn generate(&self) -> Mesh {
+        let mut builder = MeshBuilder::default();
+        match *self {
+            Self::UVSphere { segments, rings } => {
+                let segments = segments.clamp(3, MAX_GRID_SEGMENTS);
+                builder.grid(segments, rings.clamp(2, MAX_GRID_SEGMENTS), |u, v| {
+                    let normal = sphere_normal(TAU * u, PI * v);
+                    (normal, normal)
+                });
+            }
+            Self::IcoSphere { subdivisions } => builder.ico_sphere(subdivisions),
+            Self::Cylinder { segments } => {
+                let segments = segments.clamp(3, MAX_GRID_SEGMENTS);
+                builder.grid(segments, 1, |u, v| {
+                    let normal = vec3((TAU * u).cos(), 0.0, (TAU * u).sin());
+                    (normal + vec3(0.0, 1.0 - 2.0 * v, 0.0), normal)
+                });
+                builder.disc(segments, 1.0);
+                builder.disc(segments, -1.0);
+            }
+            Self::Capsule {
+                segments,
+                rings,
+                length,
+            } => {
+                let rings = rings.clamp(1, MAX_GRID_SEGMENTS / 2);
+                let half_length = length.max(0.0) / 2.0;
+                let segments = segments.clamp(3, MAX_GRID_SEGMENTS);
+                builder.grid(segments, 2 * rings + 1, |u, v| {
+                    let row = (v * (2 * rings + 1) as f32).round() as u32;
+                    let (theta, center_y) = if row <= rings {
+                        (PI / 2.0 * row as f32 / rings as f32, half_length)
+                    } else {
+                        (
+                            PI / 2.0 * (1.0 + (row - rings - 1) as f32 / rings as f32),
+                            -half_length,
+                        )
+                    };
+                    let normal = sphere_normal(TAU * u, theta);
+                    (normal + vec3(0.0, center_y, 0.0), normal)
+                });
+            }
+            Self::Cone { segments } => {
+                let segments = segments.clamp(3, MAX_GRID_SEGMENTS);
+                builder.grid(segments, 1, |u, v| {
+                    let (cos, sin) = ((TAU * u).cos(), (TAU * u).sin());
+                    let normal = vec3(2.0 * cos, 1.0, 2.0 * sin).normalize();
+                    let radius = v;
+                    (vec3(radius * cos, 1.0 - 2.0 * v, radius * sin), normal)
+                });
+                builder.disc(segments, -1.0);
+            }
+            Self::Torus {
+                major_segments,
+                minor_segments,
+                minor_radius,
+            } => {
+                let major_segments = major_segments.clamp(3, MAX_GRID_SEGMENTS);
+                let minor_segments = minor_segments.clamp(3, MAX_GRID_SEGMENTS);
+                builder.grid(major_segments, minor_segments, |u, v| {
+                    let (phi, psi) = (TAU * u, TAU * v);
+                    let center = vec3(phi.cos(), 0.0, phi.sin());
+                    let normal = vec3(psi.cos() * phi.cos(), psi.sin(), psi.cos() * phi.sin());
+                    (center + normal * minor_radius, normal)
+                });
+            }
+            Self::Plane { subdivisions } => {
+                let subdivisions = subdivisions.clamp(1, MAX_GRID_SEGMENTS);
+                builder.grid(subdivisions, subdivisions, |u, v| {
+                    (vec3(2.0 * u - 1.0, 0.0, 2.0 * v - 1.0), Y_AXIS)
+                });
+            }
+        }
+
+        Mesh::from_vertex_data(
+            format!("{self:?}"),
+            Arc::from(Path::new("internal")),
+            builder.positions,
+            None,
+            Some(builder.normals),
+            Some(builder.texture_coords),
+            builder.indices,
+            None,
+        )
+    }
+}
+
+/// the unit sphere normal for the azimuth ``phi`` and the polar angle ``theta`` measured from the positive y-axis
+fn sphere_normal(phi: f32, theta: f32) -> Vec3 {
+    vec3(
+        theta.sin() * phi.cos(),
+        theta.cos(),
+        theta.sin() * phi.sin(),
+    )
+}
+
+/// collects the vertex data of procedural meshes
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    texture_coords: Vec<Vec2>,
+    indices: Vec<GLuint>,
+}
+
+impl MeshBuilder {
+    /// adds a vertex and returns its index
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> GLuint {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.texture_coords.push(uv);
+        (self.positions.len() - 1) as GLuint
+    }
+
+    /// adds a triangle that faces in the direction of the vertex normals (counter-clockwise winding), degenerate triangles are skipped
+    fn triangle(&mut self, a: GLuint, b: GLuint, c: GLuint) {
+        let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i as usize]);
+        let face_normal = (pb - pa).cross(&(pc - pa));
+        if face_normal.norm() <= f32::EPSILON {
+            return;
+        }
+        let normal = self.normals[a as usize] + self.normals[b as usize] + self.normals[c as usize];
+        if face_normal.dot(&normal) >= 0.0 {
+            self.indices.extend_from_slice(&[a, b, c]);
+        } else {
+            self.indices.extend_from_slice(&[a, c, b]);
+        }
+    }
+
+    /// adds a parametric surface with texture coordinates ``(u, v)`` in ``[0, 1]`` that is split into ``cols`` x ``rows`` quads, ``f`` maps the coordinates to the position and normal
+    fn grid(&mut self, cols: u32, rows: u32, f: impl Fn(f32, f32) -> (Vec3, Vec3)) {
+        let start = self.positions.len() as GLuint;
+        for row in 0..=rows {
+            for col in 0..=cols {
+                let (u, v) = (col as f32 / cols as f32, row as f32 / rows as f32);
+                let (position, normal) = f(u, v);
+                self.vertex(position, normal, vec2(u, 1.0 - v));
+            }
+        }
+        for row in 0..rows {
+            for col in 0..cols {
+                let top_left = start + row * (cols + 1) + col;
+                let bottom_left = top_left + cols + 1;
+                self.triangle(top_left, bottom_left, top_left + 1);
+                self.triangle(top_left + 1, bottom_left, bottom_left + 1);
+            }
+        }
+    }
+
+    /// adds a flat disc with radius ``1.0`` at the height ``y`` that faces away from the origin
+    fn disc(&mut self, segments: u32, y: f32) {
+        let normal = vec3(0.0, y.signum(), 0.0);
+        let center = self.vertex(vec3(0.0, y, 0.0), normal, vec2(0.5, 0.5));
+        let ring = (0..=segments)
+            .map(|i| {
+                let (cos, sin) = (
+                    (TAU * i as f32 / segments as f32).cos(),
+                    (TAU * i as f32 / segments as f32).sin(),
+                );
+                self.vertex(
+                    vec3(cos, y, sin),
+                    normal,
+                    vec2(0.5 + cos / 2.0, 0.5 + sin / 2.0),
+                )
+            })
+            .collect_vec();
+        for (a, b) in ring.into_iter().tuple_windows() {
+            self.triangle(center, a, b);
+        }
+    }
+
+    /// adds a subdivided icosahedron projected on the unit sphere with spherical texture coordinates
+    #[rustfmt::skip]
+    fn ico_sphere(&mut self, subdivisions: u32) {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut vertices = [
+            (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+            (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+            (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+        ]
+        .map(|(x, y, z)| vec3(x, y, z).normalize())
+        .to_vec();
+        let mut faces: Vec<[usize; 3]> = vec![
+            [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+            [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+            [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+            [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions.min(8) {
+            let mut midpoints: AHashMap<(usize, usize), usize> = AHashMap::new();
+            let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<Vec3>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    vertices.push(((vertices[a] + vertices[b]) / 2.0).normalize());
+                    vertices.len() - 1
+                })
+            };
+            faces = faces
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let ab = midpoint(a, b, &mut vertices);
+                    let bc = midpoint(b, c, &mut vertices);
+                    let ca = midpoint(c, a, &mut vertices);
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let uv = |v: &Vec3| vec2(0.5 + v.z.atan2(v.x) / TAU, 1.0 - v.y.clamp(-1.0, 1.0).acos() / PI);
+        for face in faces {
+            let mut uvs = face.map(|i| uv(&vertices[i]));
+            // fix the texture seam by wrapping triangles that cross it
+            if uvs.iter().map(|uv| uv.x).fold(f32::MIN, f32::max) - uvs.iter().map(|uv| uv.x).fold(f32::MAX, f32::min) > 0.5 {
+                uvs.iter_mut().filter(|uv| uv.x < 0.5).for_each(|uv| uv.x += 1.0);
+            }
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertex(vertices[face[i]], vertices[face[i]], uvs[i]));
+            self.triangle(a, b, c);
+        }
+    }
+}
@@ -4,14 +4,19 @@ use crate::rendering::sprite_renderer::SpriteVertex;
 use std::ffi::CString;
 use std::{mem, ptr};
 
-/// compiles a gl shader
-fn compile_shader(src: &str, ty: GLenum) -> GLuint {
+/// the number of texture units the basic fragment shader uses with pbr materials
+const PBR_TEXTURE_UNITS: GLint = 20;
+
+/// compiles a gl shader, the shader object is deleted if the compilation fails
+fn compile_shader(src: &str, ty: GLenum) -> Result<GLuint, String> {
+    let src = resolve_includes(src)?;
+    let c_str = CString::new(src.as_bytes())
+        .map_err(|_| String::from("Shader source contains a null byte."))?;
     let shader;
     unsafe {
         // Create GLSL shaders
         shader = gl::CreateShader(ty);
         // Attempt to compile the shader
-        let c_str = CString::new(src.as_bytes()).unwrap();
         gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
         gl::CompileShader(shader);
 
@@ -31,17 +36,15 @@ fn compile_shader(src: &str, ty: GLenum) -> GLuint {
                 ptr::null_mut(),
                 buf.as_mut_ptr() as *mut GLchar,
             );
-            panic!(
-                "{}",
-                std::str::from_utf8(&buf).expect("ShaderInfoLog is not valid UTF-8.")
-            );
+            gl::DeleteShader(shader);
+            return Err(String::from_utf8_lossy(&buf).into_owned());
         }
     }
-    shader
+    Ok(shader)
 }
 
-/// links a gl shader program
-fn link_program(vs: GLuint, fs: GLuint, gs: Option<GLuint>) -> GLuint {
+/// links a gl shader program and deletes the shader objects, the program is deleted if the linking fails
+fn link_program(vs: GLuint, fs: GLuint, gs: Option<GLuint>) -> Result<GLuint, String> {
     unsafe {
         let program = gl::CreateProgram();
         gl::AttachShader(program, vs);
@@ -78,12 +81,100 @@ fn link_program(vs: GLuint, fs: GLuint, gs: Option<GLuint>) -> GLuint {
                 ptr::null_mut(),
                 buf.as_mut_ptr() as *mut GLchar,
             );
-            panic!(
-                "{}",
-                std::str::from_utf8(&buf).expect("ProgramInfoLog is not valid UTF-8.")
-            );
+            gl::DeleteProgram(program);
+            return Err(String::from_utf8_lossy(&buf).into_owned());
+        }
+        Ok(program)
+    }
+}
+
+/// the source code of all stages of a shader program
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShaderSources<'a> {
+    pub(crate) vertex: &'a str,
+    pub(crate) fragment: &'a str,
+    pub(crate) geometry: Option<&'a str>,
+}
+
+/// all the shader programs of the shader catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum CatalogShader {
+    Basic,
+    Passthrough,
+    Shadow,
+    CubeShadow,
+    Skybox,
+    Screen,
+    Sprite,
+    Bloom,
+    Outline,
+}
+
+impl CatalogShader {
+    pub(crate) const ALL: [Self; 9] = [
+        Self::Basic,
+        Self::Passthrough,
+        Self::Shadow,
+        Self::CubeShadow,
+        Self::Skybox,
+        Self::Screen,
+        Self::Sprite,
+        Self::Bloom,
+        Self::Outline,
+    ];
+
+    /// the file names of the vertex, fragment and optional geometry stage in the shader directory
+    pub(crate) fn file_names(&self) -> (&'static str, &'static str, Option<&'static str>) {
+        match self {
+            Self::Basic => ("basic.vert", "basic.frag", None),
+            Self::Passthrough => ("passthrough.vert", "passthrough.frag", None),
+            Self::Shadow => ("shadow.vert", "shadow.frag", None),
+            Self::CubeShadow => (
+                "cube_shadow.vert",
+                "cube_shadow.frag",
+                Some("cube_shadow.geom"),
+            ),
+            Self::Skybox => ("skybox.vert", "skybox.frag", None),
+            Self::Screen => ("screen.vert", "screen.frag", None),
+            Self::Sprite => ("sprite.vert", "sprite.frag", None),
+            Self::Bloom => ("blur.vert", "blur.frag", None),
+            Self::Outline => ("outline.vert", "outline.frag", None),
+        }
+    }
+
+    /// the sources that are embedded in the binary
+    fn embedded_sources(&self) -> ShaderSources<'static> {
+        let (vertex, fragment, geometry) = match self {
+            Self::Basic => (BASIC_VERT, BASIC_FRAG, None),
+            Self::Passthrough => (PASSTHROUGH_VERT, PASSTHROUGH_FRAG, None),
+            Self::Shadow => (SHADOW_VERT, SHADOW_FRAG, None),
+            Self::CubeShadow => (CUBE_SHADOW_VERT, CUBE_SHADOW_FRAG, Some(CUBE_SHADOW_GEOM)),
+            Self::Skybox => (SKYBOX_VERT, SKYBOX_FRAG, None),
+            Self::Screen => (SCREEN_VERT, SCREEN_FRAG, None),
+            Self::Sprite => (SPRITE_VERT, SPRITE_FRAG, None),
+            Self::Bloom => (BLUR_VERT, BLUR_FRAG, None),
+            Self::Outline => (OUTLINE_VERT, OUTLINE_FRAG, None),
+        };
+        ShaderSources {
+            vertex,
+            fragment,
+            geometry,
+        }
+    }
+
+    /// the name of the shader program used for logging
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Basic => "Instance Basic",
+            Self::Passthrough => "Instance Passthrough",
+            Self::Shadow => "Instance Shadow",
+            Self::CubeShadow => "Instance Cube Shadow",
+            Self::Skybox => "Skybox",
+            Self::Screen => "Screen Texture",
+            Self::Sprite => "Sprite",
+            Self::Bloom => "Blur",
+            Self::Outline => "Outline",
         }
-        program
     }
 }
 
@@ -94,20 +185,34 @@ pub(crate) struct ShaderProgram {
 }
 
 impl ShaderProgram {
-    /// creates new shader program
-    pub(crate) fn new(
-        vertex_file: &str,
-        fragment_file: &str,
-        geometry_file: Option<&str>,
-        name: &'static str,
-    ) -> Self {
+    /// creates new shader program if the sources compile and link successfully
+    pub(crate) fn new(sources: ShaderSources, name: &'static str) -> Result<Self, String> {
         // compile and link shader program
         let start_time = Instant::now();
 
-        let vs = compile_shader(vertex_file, gl::VERTEX_SHADER);
-        let fs = compile_shader(fragment_file, gl::FRAGMENT_SHADER);
-        let gs = geometry_file.map(|file| compile_shader(file, gl::GEOMETRY_SHADER));
-        let id = link_program(vs, fs, gs);
+        let vs = compile_shader(sources.vertex, gl::VERTEX_SHADER)?;
+        let fs = match compile_shader(sources.fragment, gl::FRAGMENT_SHADER) {
+            Ok(fs) => fs,
+            Err(msg) => {
+                unsafe { gl::DeleteShader(vs) };
+                return Err(msg);
+            }
+        };
+        let gs = match sources
+            .geometry
+            .map(|file| compile_shader(file, gl::GEOMETRY_SHADER))
+            .transpose()
+        {
+            Ok(gs) => gs,
+            Err(msg) => {
+                unsafe {
+                    gl::DeleteShader(vs);
+                    gl::DeleteShader(fs);
+                }
+                return Err(msg);
+            }
+        };
+        let id = link_program(vs, fs, gs)?;
 
         let elapsed_time = start_time.elapsed().as_micros() as f64 / 1000.0;
 
@@ -118,7 +223,7 @@ impl ShaderProgram {
 
         log::debug!("Compiled shader {name:?}: {elapsed_time:.2}ms");
 
-        Self { id, name }
+        Ok(Self { id, name })
     }
 
     /// binds the shader program
@@ -158,6 +263,7 @@ pub(crate) struct ShaderCatalog {
     pub(crate) matrix_buffer: UniformBuffer,
     pub(crate) ortho_buffer: UniformBuffer,
     pub(crate) post_process_buffer: UniformBuffer,
+    pub(crate) supports_pbr: bool,
 }
 
 impl ShaderCatalog {
@@ -172,31 +278,48 @@ impl ShaderCatalog {
         );
         let matrix_buffer = UniformBuffer::new(size_of::<Mat4>() * 2 + size_of::<Vec4>());
         let ortho_buffer = UniformBuffer::new(size_of::<Mat4>() * 2);
-        let post_process_buffer = UniformBuffer::new(size_of::<GLfloat>() * 5 + size_of::<GLint>());
+        let post_process_buffer = UniformBuffer::new(size_of::<GLfloat>() * 6 + size_of::<GLint>());
+
+        let mut texture_units = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut texture_units) };
+        let supports_pbr = texture_units >= PBR_TEXTURE_UNITS;
+        if !supports_pbr {
+            log::warn!("Only {texture_units} texture units available, PBR materials are rendered with Blinn-Phong shading.");
+        }
+
+        let create = |shader: CatalogShader| {
+            Self::create(
+                shader,
+                shader.embedded_sources(),
+                [
+                    &light_buffer,
+                    &matrix_buffer,
+                    &ortho_buffer,
+                    &post_process_buffer,
+                ],
+                supports_pbr,
+            )
+            .unwrap_or_else(|msg| panic!("{msg}"))
+        };
 
         let mut render_shaders = AHashMap::new();
-        render_shaders.insert(
-            ShaderType::Basic,
-            Self::create_basic(&light_buffer, &matrix_buffer, &post_process_buffer),
-        );
-        render_shaders.insert(
-            ShaderType::Passthrough,
-            Self::create_passthrough(&matrix_buffer, &post_process_buffer),
-        );
+        render_shaders.insert(ShaderType::Basic, create(CatalogShader::Basic));
+        render_shaders.insert(ShaderType::Passthrough, create(CatalogShader::Passthrough));
 
         Self {
             render_shaders,
-            shadow: Self::create_shadow(),
-            cube_shadow: Self::create_cube_shadow(),
-            skybox: Self::create_skybox(&matrix_buffer, &post_process_buffer),
-            screen: Self::create_screen(&post_process_buffer),
-            sprite: Self::create_sprite(&ortho_buffer),
-            bloom: Self::create_bloom(),
-            outline: Self::create_outline(&matrix_buffer),
+            shadow: create(CatalogShader::Shadow),
+            cube_shadow: create(CatalogShader::CubeShadow),
+            skybox: create(CatalogShader::Skybox),
+            screen: create(CatalogShader::Screen),
+            sprite: create(CatalogShader::Sprite),
+            bloom: create(CatalogShader::Bloom),
+            outline: create(CatalogShader::Outline),
             light_buffer,
             matrix_buffer,
             ortho_buffer,
             post_process_buffer,
+            supports_pbr,
         }
     }
 
@@ -205,97 +328,116 @@ impl ShaderCatalog {
         self.render_shaders.get(shader_type).unwrap().use_program();
     }
 
-    /// creates a new sprite shader
-    fn create_sprite(ortho_buffer: &UniformBuffer) -> ShaderProgram {
-        let program = ShaderProgram::new(SPRITE_VERT, SPRITE_FRAG, None, "Sprite");
-
-        program.add_unif_buffer("ortho_block", ortho_buffer, 2);
-
-        program
-    }
-
-    /// creates a new screen texture shader
-    fn create_screen(post_process_buffer: &UniformBuffer) -> ShaderProgram {
-        let program = ShaderProgram::new(SCREEN_VERT, SCREEN_FRAG, None, "Screen Texture");
-
-        program.add_unif_buffer("post_process", post_process_buffer, 3);
-
-        program
-    }
-
-    /// creates a new bloom blur shader
-    fn create_bloom() -> ShaderProgram {
-        ShaderProgram::new(BLUR_VERT, BLUR_FRAG, None, "Blur")
-    }
-
-    /// creates a new bloom blur shader
-    fn create_outline(matrix_buffer: &UniformBuffer) -> ShaderProgram {
-        let program = ShaderProgram::new(OUTLINE_VERT, OUTLINE_FRAG, None, "Outline");
-
-        program.add_unif_buffer("matrix_block", matrix_buffer, 1);
-
-        program
-    }
-
-    /// creates a new skybox shader
-    fn create_skybox(
-        matrix_buffer: &UniformBuffer,
-        post_process_buffer: &UniformBuffer,
-    ) -> ShaderProgram {
-        let program = ShaderProgram::new(SKYBOX_VERT, SKYBOX_FRAG, None, "Skybox");
-
-        program.add_unif_buffer("matrix_block", matrix_buffer, 1);
-        program.add_unif_buffer("post_process", post_process_buffer, 3);
-
-        program
-    }
-
-    /// creates a new basic instance renderer shader
-    fn create_basic(
-        light_buffer: &UniformBuffer,
-        matrix_buffer: &UniformBuffer,
-        post_process_buffer: &UniformBuffer,
-    ) -> ShaderProgram {
-        let program = ShaderProgram::new(BASIC_VERT, BASIC_FRAG, None, "Instance Basic");
-
-        program.add_unif_buffer("light_data", light_buffer, 0);
-        program.add_unif_buffer("matrix_block", matrix_buffer, 1);
-        program.add_unif_buffer("post_process", post_process_buffer, 3);
-
-        program
+    /// recompiles a shader program from new sources, the previous program is kept if the new one is invalid
+    pub(crate) fn reload_shader(&mut self, shader: CatalogShader, sources: ShaderSources) -> bool {
+        let program = match Self::create(
+            shader,
+            sources,
+            [
+                &self.light_buffer,
+                &self.matrix_buffer,
+                &self.ortho_buffer,
+                &self.post_process_buffer,
+            ],
+            self.supports_pbr,
+        ) {
+            Ok(program) => program,
+            Err(msg) => {
+                log::warn!(
+                    "Error reloading shader {:?}, keeping the previous version: {msg}",
+                    shader.name()
+                );
+                return false;
+            }
+        };
+        let slot = match shader {
+            CatalogShader::Basic => self.render_shaders.get_mut(&ShaderType::Basic).unwrap(),
+            CatalogShader::Passthrough => self
+                .render_shaders
+                .get_mut(&ShaderType::Passthrough)
+                .unwrap(),
+            CatalogShader::Shadow => &mut self.shadow,
+            CatalogShader::CubeShadow => &mut self.cube_shadow,
+            CatalogShader::Skybox => &mut self.skybox,
+            CatalogShader::Screen => &mut self.screen,
+            CatalogShader::Sprite => &mut self.sprite,
+            CatalogShader::Bloom => &mut self.bloom,
+            CatalogShader::Outline => &mut self.outline,
+        };
+        *slot = program;
+        true
     }
 
-    /// creates a new passthrough instance renderer shader
-    fn create_passthrough(
-        matrix_buffer: &UniformBuffer,
-        post_process_buffer: &UniformBuffer,
-    ) -> ShaderProgram {
+    /// creates a shader program and binds the uniform buffers it uses (light, matrix, ortho, post process), the pbr shading is left out of the basic shader if it is not supported
+    fn create(
+        shader: CatalogShader,
+        sources: ShaderSources,
+        buffers: [&UniformBuffer; 4],
+        supports_pbr: bool,
+    ) -> Result<ShaderProgram, String> {
+        let [light_buffer, matrix_buffer, ortho_buffer, post_process_buffer] = buffers;
+        let fragment = if shader == CatalogShader::Basic && !supports_pbr {
+            without_pbr(sources.fragment)
+        } else {
+            sources.fragment.to_string()
+        };
         let program = ShaderProgram::new(
-            PASSTHROUGH_VERT,
-            PASSTHROUGH_FRAG,
-            None,
-            "Instance Passthrough",
-        );
-
-        program.add_unif_buffer("matrix_block", matrix_buffer, 1);
-        program.add_unif_buffer("post_process", post_process_buffer, 3);
+            ShaderSources {
+                fragment: &fragment,
+                ..sources
+            },
+            shader.name(),
+        )?;
+
+        match shader {
+            CatalogShader::Basic => {
+                program.add_unif_buffer("light_data", light_buffer, 0);
+                program.add_unif_buffer("matrix_block", matrix_buffer, 1);
+                program.add_unif_buffer("post_process", post_process_buffer, 3);
+            }
+            CatalogShader::Passthrough | CatalogShader::Skybox => {
+                program.add_unif_buffer("matrix_block", matrix_buffer, 1);
+                program.add_unif_buffer("post_process", post_process_buffer, 3);
+            }
+            CatalogShader::Outline => {
+                program.add_unif_buffer("matrix_block", matrix_buffer, 1);
+            }
+            CatalogShader::Sprite => {
+                program.add_unif_buffer("ortho_block", ortho_buffer, 2);
+            }
+            CatalogShader::Screen => {
+                program.add_unif_buffer("post_process", post_process_buffer, 3);
+            }
+            CatalogShader::Shadow | CatalogShader::CubeShadow | CatalogShader::Bloom => {}
+        }
 
-        program
+        Ok(program)
     }
+}
 
-    /// creates a new shadow rendering shader for the instance renderer
-    fn create_shadow() -> ShaderProgram {
-        ShaderProgram::new(SHADOW_VERT, SHADOW_FRAG, None, "Instance Shadow")
+/// replaces the ``#include`` directives of a shader source with the shared shader code
+fn resolve_includes(source: &str) -> Result<String, String> {
+    let mut resolved = String::with_capacity(source.len());
+    for line in source.lines() {
+        match line.trim().strip_prefix("#include") {
+            Some(file) => match file.trim() {
+                "\"skinning.glsl\"" => resolved.push_str(SKINNING_GLSL),
+                file => return Err(format!("Unknown shader include {file}.")),
+            },
+            None => {
+                resolved.push_str(line);
+                resolved.push('\n');
+            }
+        }
     }
+    Ok(resolved)
+}
 
-    /// creates a new cube map shadow rendering shader for the instance renderer
-    fn create_cube_shadow() -> ShaderProgram {
-        ShaderProgram::new(
-            CUBE_SHADOW_VERT,
-            CUBE_SHADOW_FRAG,
-            Some(CUBE_SHADOW_GEOM),
-            "Instance Cube Shadow",
-        )
+/// defines ``NO_PBR`` in a fragment shader source (after the version directive)
+fn without_pbr(source: &str) -> String {
+    match source.split_once('\n') {
+        Some((version, rest)) => format!("{version}\n#define NO_PBR\n{rest}"),
+        None => source.to_string(),
     }
 }
 
@@ -472,3 +614,15 @@ pub(crate) unsafe fn bind_instance_otbo() {
     gl::VertexAttribPointer(pos, 1, gl::FLOAT, gl::FALSE as GLboolean, 0, ptr::null());
     gl::VertexAttribDivisor(pos, 1);
 }
+
+/// binds the jbo attrib pointer for the instance renderer (joint indices stay integers)
+pub(crate) unsafe fn bind_instance_jbo() {
+    gl::EnableVertexAttribArray(14);
+    gl::VertexAttribIPointer(14, 4, gl::UNSIGNED_SHORT, 0, ptr::null());
+}
+
+/// binds the wbo attrib pointer for the instance renderer
+pub(crate) unsafe fn bind_instance_wbo() {
+    gl::EnableVertexAttribArray(15);
+    gl::VertexAttribPointer(15, 4, gl::FLOAT, gl::FALSE as GLboolean, 0, ptr::null());
+}
@@ -0,0 +1,556 @@
+use crate::internal_prelude::*;
+use std::ops::{Add, Mul};
+
+/// the maximum number of joints that the shaders can deform a mesh with
+pub(crate) const MAX_JOINTS: usize = 128;
+/// the uniform buffer binding point of the joint matrices in the shaders
+pub(crate) const JOINT_BLOCK_BINDING: GLuint = 4;
+
+/// Translation, rotation and scale of a joint relative to its parent joint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl JointTransform {
+    /// Creates a new ``JointTransform``.
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// the transform as a matrix that is applied in scale, rotation, translation order
+    pub(crate) fn matrix(&self) -> Mat4 {
+        glm::translation(&self.translation)
+            * glm::quat_to_mat4(&self.rotation)
+            * glm::scaling(&self.scale)
+    }
+
+    /// interpolates between two transforms
+    pub(crate) fn blend(&self, other: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(&other.translation, t),
+            rotation: slerp(&self.rotation, &other.rotation, t),
+            scale: self.scale.lerp(&other.scale, t),
+        }
+    }
+}
+
+impl Default for JointTransform {
+    fn default() -> Self {
+        Self::new(Vec3::zeros(), Quat::identity(), Vec3::from_element(1.0))
+    }
+}
+
+/// spherical interpolation between two rotations along the shortest arc
+pub(crate) fn slerp(a: &Quat, b: &Quat, t: f32) -> Quat {
+    let cos = a.dot(b);
+    let (b, cos) = if cos < 0.0 { (-b, -cos) } else { (*b, cos) };
+    if cos > 0.9995 {
+        // nearly identical rotations are numerically safer to interpolate linearly
+        return (a * (1.0 - t) + b * t).normalize();
+    }
+    let angle = cos.acos();
+    let sin = angle.sin();
+    (a * (((1.0 - t) * angle).sin() / sin) + b * ((t * angle).sin() / sin)).normalize()
+}
+
+/// One joint (bone) of a ``Skeleton``.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    name: String,
+    parent: Option<usize>,
+    rest: JointTransform,
+    inverse_bind: Option<Mat4>,
+}
+
+impl Joint {
+    /// Creates a joint with its rest transform relative to the parent joint. Root joints have no parent.
+    pub fn new(name: impl Into<String>, parent: Option<usize>, rest: JointTransform) -> Self {
+        Self {
+            name: name.into(),
+            parent,
+            rest,
+            inverse_bind: None,
+        }
+    }
+
+    /// Sets the inverse bind matrix that transforms mesh vertices into the space of the joint. By default, the inverse of the global rest transform is used.
+    pub fn with_inverse_bind_matrix(mut self, matrix: Mat4) -> Self {
+        self.inverse_bind = Some(matrix);
+        self
+    }
+}
+
+/// A hierarchy of joints that deforms skinned meshes. Skeletons are loaded from the skins of glTF files with ``EntityManager::load_gltf`` or created from a list of joints. The joint indices of the mesh vertices refer to the position of the joints in the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skeleton {
+    name: String,
+    joints: Vec<Joint>,
+    inverse_binds: Vec<Mat4>,
+    order: Vec<usize>,
+    pub(crate) root_transform: Mat4,
+}
+
+impl Skeleton {
+    /// Creates a new skeleton. Returns ``None`` if a parent index is out of bounds, the parents form a cycle or there are more than 128 joints.
+    pub fn new(name: impl Into<String>, joints: Vec<Joint>) -> Option<Self> {
+        let name = name.into();
+        if joints.len() > MAX_JOINTS {
+            log::warn!(
+                "Skeleton {name:?} has {} joints, only {MAX_JOINTS} are supported.",
+                joints.len()
+            );
+            return None;
+        }
+        // the depth of every joint, parents are always evaluated before their children by sorting by depth
+        let mut depths = Vec::with_capacity(joints.len());
+        for joint in joints.iter() {
+            let mut depth = 0;
+            let mut parent = joint.parent;
+            while let Some(index) = parent {
+                if index >= joints.len() || depth >= joints.len() {
+                    log::warn!("Skeleton {name:?} has an invalid joint hierarchy.");
+                    return None;
+                }
+                depth += 1;
+                parent = joints[index].parent;
+            }
+            depths.push(depth);
+        }
+        let order = (0..joints.len())
+            .sorted_by_key(|i| depths[*i])
+            .collect_vec();
+
+        let mut skeleton = Self {
+            name,
+            inverse_binds: Vec::new(),
+            order,
+            root_transform: Mat4::identity(),
+            joints,
+        };
+        let rest_globals = skeleton.global_transforms(&skeleton.rest_pose());
+        skeleton.inverse_binds = skeleton
+            .joints
+            .iter()
+            .zip(rest_globals)
+            .map(|(joint, global)| {
+                joint
+                    .inverse_bind
+                    .unwrap_or_else(|| global.try_inverse().unwrap_or_default())
+            })
+            .collect();
+        Some(skeleton)
+    }
+
+    /// The name of the skeleton.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of joints in the skeleton.
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    /// The index of the joint with the given name.
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    /// the local transforms of all joints in the rest pose
+    pub(crate) fn rest_pose(&self) -> Vec<JointTransform> {
+        self.joints.iter().map(|joint| joint.rest).collect()
+    }
+
+    /// the transforms of all joints relative to the skeleton origin for a pose
+    fn global_transforms(&self, pose: &[JointTransform]) -> Vec<Mat4> {
+        let mut globals = vec![Mat4::identity(); self.joints.len()];
+        for &i in self.order.iter() {
+            let parent = self.joints[i]
+                .parent
+                .map_or(self.root_transform, |parent| globals[parent]);
+            globals[i] = parent * pose[i].matrix();
+        }
+        globals
+    }
+
+    /// the matrices that move the mesh vertices from the bind pose to the given pose
+    pub(crate) fn skinning_matrices(&self, pose: &[JointTransform]) -> Vec<Mat4> {
+        self.global_transforms(pose)
+            .into_iter()
+            .zip(self.inverse_binds.iter())
+            .map(|(global, inverse_bind)| global * inverse_bind)
+            .collect()
+    }
+}
+
+/// Determines how the values between two keyframes are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Interpolation {
+    /// Keeps the value of the previous keyframe.
+    Step,
+    /// Interpolates linearly (rotations are interpolated spherically).
+    #[default]
+    Linear,
+    /// Cubic hermite spline, every keyframe consists of an in-tangent, the value and an out-tangent.
+    CubicSpline,
+}
+
+/// a value that keyframes can be interpolated with
+pub(crate) trait Keyframe: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    /// interpolates to another value
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+
+    /// corrects a value that was computed on a spline
+    fn normalized(self) -> Self {
+        self
+    }
+}
+
+impl Keyframe for Vec3 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Keyframe for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Keyframe for Vec4 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Keyframe for Quat {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        slerp(self, other, t)
+    }
+
+    fn normalized(self) -> Self {
+        self.normalize()
+    }
+}
+
+/// checks that there is at least one keyframe, the times are increasing and there are enough values for the interpolation
+pub(crate) fn valid_keyframes(
+    times: &[f32],
+    value_count: usize,
+    interpolation: Interpolation,
+) -> bool {
+    let values_per_key = if interpolation == Interpolation::CubicSpline {
+        3
+    } else {
+        1
+    };
+    !times.is_empty()
+        && value_count == times.len() * values_per_key
+        && times.iter().tuple_windows().all(|(t1, t2)| t1 < t2)
+}
+
+/// samples keyframes with sorted times at a point in time, times outside of the keyframes are clamped
+pub(crate) fn sample_keyframes<T: Keyframe>(
+    times: &[f32],
+    values: &[T],
+    interpolation: Interpolation,
+    time: f32,
+) -> T {
+    let cubic = interpolation == Interpolation::CubicSpline;
+    let value = |i: usize| if cubic { values[i * 3 + 1] } else { values[i] };
+    let next = times.partition_point(|t| *t <= time);
+    if next == 0 {
+        return value(0);
+    }
+    if next == times.len() {
+        return value(times.len() - 1);
+    }
+    let previous = next - 1;
+    let delta = times[next] - times[previous];
+    let t = (time - times[previous]) / delta;
+    match interpolation {
+        Interpolation::Step => value(previous),
+        Interpolation::Linear => value(previous).interpolate(&value(next), t),
+        Interpolation::CubicSpline => {
+            let out_tangent = values[previous * 3 + 2] * delta;
+            let in_tangent = values[next * 3] * delta;
+            let (t2, t3) = (t * t, t * t * t);
+            (value(previous) * (2.0 * t3 - 3.0 * t2 + 1.0)
+                + out_tangent * (t3 - 2.0 * t2 + t)
+                + value(next) * (-2.0 * t3 + 3.0 * t2)
+                + in_tangent * (t3 - t2))
+                .normalized()
+        }
+    }
+}
+
+/// the animated property of a joint track
+#[derive(Debug, Clone, PartialEq)]
+enum TrackValues {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+}
+
+impl TrackValues {
+    /// the number of stored values
+    fn len(&self) -> usize {
+        match self {
+            Self::Translation(values) | Self::Scale(values) => values.len(),
+            Self::Rotation(values) => values.len(),
+        }
+    }
+}
+
+/// the keyframes of one property of one joint
+#[derive(Debug, Clone, PartialEq)]
+struct JointTrack {
+    joint: String,
+    times: Vec<f32>,
+    values: TrackValues,
+    interpolation: Interpolation,
+}
+
+/// A skeletal animation made of keyframe tracks for the translation, rotation and scale of joints. Tracks refer to joints by name, so a clip can be played on every skeleton with matching joint names. Clips are loaded from the animations of glTF files with ``EntityManager::load_gltf`` or built with the ``with_*`` functions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkeletalClip {
+    name: String,
+    duration: f32,
+    tracks: Vec<JointTrack>,
+}
+
+impl SkeletalClip {
+    /// Creates a new clip without any tracks.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            duration: 0.0,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Adds a translation track for a joint. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_translations(
+        self,
+        joint: impl Into<String>,
+        times: Vec<f32>,
+        values: Vec<Vec3>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(
+            joint.into(),
+            times,
+            TrackValues::Translation(values),
+            interpolation,
+        )
+    }
+
+    /// Adds a rotation track for a joint. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_rotations(
+        self,
+        joint: impl Into<String>,
+        times: Vec<f32>,
+        values: Vec<Quat>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(
+            joint.into(),
+            times,
+            TrackValues::Rotation(values),
+            interpolation,
+        )
+    }
+
+    /// Adds a scale track for a joint. The keyframe times are in seconds and have to be increasing. ``Interpolation::CubicSpline`` requires three values per keyframe.
+    pub fn with_scales(
+        self,
+        joint: impl Into<String>,
+        times: Vec<f32>,
+        values: Vec<Vec3>,
+        interpolation: Interpolation,
+    ) -> Self {
+        self.with_track(
+            joint.into(),
+            times,
+            TrackValues::Scale(values),
+            interpolation,
+        )
+    }
+
+    /// adds a track if the keyframe data is consistent
+    fn with_track(
+        mut self,
+        joint: String,
+        times: Vec<f32>,
+        values: TrackValues,
+        interpolation: Interpolation,
+    ) -> Self {
+        if !valid_keyframes(&times, values.len(), interpolation) {
+            log::warn!(
+                "Skipped invalid track for joint {joint:?} in clip {:?}.",
+                self.name
+            );
+            return self;
+        }
+        self.duration = self.duration.max(*times.last().unwrap());
+        self.tracks.push(JointTrack {
+            joint,
+            times,
+            values,
+            interpolation,
+        });
+        self
+    }
+
+    /// The name of the clip.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The time of the last keyframe in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// the index of the joint that each track animates in a skeleton
+    pub(crate) fn bind(&self, skeleton: &Skeleton) -> Vec<Option<usize>> {
+        self.tracks
+            .iter()
+            .map(|track| skeleton.joint_index(&track.joint))
+            .collect()
+    }
+
+    /// overwrites the properties of the pose that the clip animates with the values at a point in time
+    pub(crate) fn sample(
+        &self,
+        time: f32,
+        bindings: &[Option<usize>],
+        pose: &mut [JointTransform],
+    ) {
+        for (track, joint) in self.tracks.iter().zip(bindings) {
+            let opt_transform = joint.and_then(|joint| pose.get_mut(joint));
+            if opt_transform.is_none() {
+                continue;
+            }
+            let transform = opt_transform.unwrap();
+            let (times, interpolation) = (&track.times, track.interpolation);
+            match &track.values {
+                TrackValues::Translation(values) => {
+                    transform.translation = sample_keyframes(times, values, interpolation, time)
+                }
+                TrackValues::Rotation(values) => {
+                    transform.rotation = sample_keyframes(times, values, interpolation, time)
+                }
+                TrackValues::Scale(values) => {
+                    transform.scale = sample_keyframes(times, values, interpolation, time)
+                }
+            }
+        }
+    }
+}
+
+/// the playback state of one clip on a skeleton
+#[derive(Debug, Clone)]
+pub(crate) struct ClipPlayback {
+    pub(crate) clip: Rc<SkeletalClip>,
+    pub(crate) bindings: Vec<Option<usize>>,
+    pub(crate) time: f32,
+}
+
+impl ClipPlayback {
+    /// starts a clip at the beginning
+    pub(crate) fn new(clip: Rc<SkeletalClip>, skeleton: &Skeleton) -> Self {
+        Self {
+            bindings: clip.bind(skeleton),
+            clip,
+            time: 0.0,
+        }
+    }
+
+    /// moves the playback time forward, looping clips wrap around and others stop at the end
+    pub(crate) fn advance(&mut self, delta: f32, looping: bool) {
+        let duration = self.clip.duration;
+        self.time += delta;
+        if looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0.0, duration);
+        }
+    }
+
+    /// the pose of the clip at the current time, joints without tracks keep their rest transform
+    pub(crate) fn pose(&self, skeleton: &Skeleton) -> Vec<JointTransform> {
+        let mut pose = skeleton.rest_pose();
+        self.clip.sample(self.time, &self.bindings, &mut pose);
+        pose
+    }
+}
+
+/// keyframes of morph target weights with their playback time
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MorphAnimation {
+    times: Vec<f32>,
+    tracks: Vec<Vec<f32>>, // the values of one morph target for all keyframes
+    interpolation: Interpolation,
+    pub(crate) time: f32,
+}
+
+impl MorphAnimation {
+    /// creates the animation from one set of weights per keyframe value, returns ``None`` if the keyframe data is inconsistent
+    pub(crate) fn new(
+        times: Vec<f32>,
+        weights: Vec<Vec<f32>>,
+        interpolation: Interpolation,
+    ) -> Option<Self> {
+        if !valid_keyframes(&times, weights.len(), interpolation) {
+            return None;
+        }
+        let target_count = weights.iter().map(Vec::len).max().unwrap_or(0);
+        let tracks = (0..target_count)
+            .map(|target| {
+                weights
+                    .iter()
+                    .map(|weights| weights.get(target).copied().unwrap_or(0.0))
+                    .collect()
+            })
+            .collect();
+        Some(Self {
+            times,
+            tracks,
+            interpolation,
+            time: 0.0,
+        })
+    }
+
+    /// the time of the last keyframe
+    pub(crate) fn duration(&self) -> f32 {
+        *self.times.last().unwrap()
+    }
+
+    /// moves the playback time forward, looping animations wrap around and others stop at the end
+    pub(crate) fn advance(&mut self, delta: f32, looping: bool) {
+        let duration = self.duration();
+        self.time += delta;
+        if looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0.0, duration);
+        }
+    }
+
+    /// the weights of all morph targets at the current time
+    pub(crate) fn sample(&self) -> Vec<f32> {
+        self.tracks
+            .iter()
+            .map(|values| sample_keyframes(&self.times, values, self.interpolation, self.time))
+            .collect()
+    }
+}
@@ -0,0 +1,244 @@
+use crate::internal_prelude::*;
+use stb_image::image::Image;
+
+/// Settings for packing many sprite images into one atlas texture. The padding is the transparent gap between two sprites and the extrusion repeats the border pixels of each sprite outwards to avoid bleeding when sampling close to the edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpriteAtlasSettings {
+    pub padding: usize,
+    pub extrusion: usize,
+    /// Maximum width and height of the atlas texture in pixels.
+    pub max_size: usize,
+}
+
+impl Default for SpriteAtlasSettings {
+    fn default() -> Self {
+        Self {
+            padding: 2,
+            extrusion: 1,
+            max_size: 4096,
+        }
+    }
+}
+
+impl SpriteAtlasSettings {
+    /// Sets the padding between sprites.
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the number of extruded border pixels around each sprite.
+    pub fn with_extrusion(mut self, extrusion: usize) -> Self {
+        self.extrusion = extrusion;
+        self
+    }
+
+    /// Sets the maximum atlas size.
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+}
+
+/// the pixel region of a single sprite inside of an atlas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AtlasRegion {
+    pub(crate) pixel_index: (usize, usize),
+    pub(crate) pixel_size: (usize, usize),
+}
+
+/// a loaded sprite atlas
+pub(crate) struct SpriteAtlas {
+    pub(crate) texture_id: GLuint,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) regions: AHashMap<Rc<Path>, AtlasRegion>,
+    pub(crate) settings: SpriteAtlasSettings,
+}
+
+impl SpriteAtlas {
+    /// all of the source files of the packed sprites
+    pub(crate) fn sources(&self) -> impl Iterator<Item = &Rc<Path>> {
+        self.regions.keys()
+    }
+}
+
+/// the image data of a packed atlas together with the sprite regions
+pub(crate) struct PackedAtlas {
+    pub(crate) image: Image<u8>,
+    pub(crate) regions: AHashMap<Rc<Path>, AtlasRegion>,
+}
+
+/// skyline bin packer, each node is a horizontal segment (x, y, width) of the current top contour
+struct Skyline {
+    width: usize,
+    height: usize,
+    nodes: Vec<(usize, usize, usize)>,
+}
+
+impl Skyline {
+    /// creates an empty skyline for the given bin size
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            nodes: vec![(0, 0, width)],
+        }
+    }
+
+    /// the lowest y coordinate a rectangle can be placed at when starting at the given node
+    fn fit(&self, index: usize, width: usize, height: usize) -> Option<usize> {
+        let x = self.nodes[index].0;
+        if x + width > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut remaining = width as isize;
+        for &(_, node_y, node_width) in &self.nodes[index..] {
+            if remaining <= 0 {
+                break;
+            }
+            y = y.max(node_y);
+            remaining -= node_width as isize;
+        }
+        (y + height <= self.height).then_some(y)
+    }
+
+    /// inserts a rectangle at the lowest and then leftmost free position
+    fn insert(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (index, y) = (0..self.nodes.len())
+            .filter_map(|index| self.fit(index, width, height).map(|y| (index, y)))
+            .min_by_key(|&(index, y)| (y + height, self.nodes[index].0))?;
+        let x = self.nodes[index].0;
+        self.nodes.insert(index, (x, y + height, width));
+
+        // shrink or remove the nodes that are covered by the new one
+        let right = x + width;
+        while index + 1 < self.nodes.len() {
+            let (node_x, node_y, node_width) = self.nodes[index + 1];
+            if node_x >= right {
+                break;
+            }
+            if node_x + node_width <= right {
+                self.nodes.remove(index + 1);
+            } else {
+                self.nodes[index + 1] = (right, node_y, node_x + node_width - right);
+                break;
+            }
+        }
+        // merge neighboring nodes on the same height
+        self.nodes.dedup_by(|next, prev| {
+            let same_height = next.1 == prev.1;
+            if same_height {
+                prev.2 += next.2;
+            }
+            same_height
+        });
+        Some((x, y))
+    }
+}
+
+/// the result of packing rectangles into a bin
+pub(crate) struct PackedRects {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    /// the positions of the rectangles in input order
+    pub(crate) positions: Vec<(usize, usize)>,
+}
+
+/// packs rectangles into the smallest power of two bin that fits
+pub(crate) fn pack_rects(sizes: &[(usize, usize)], max_size: usize) -> Option<PackedRects> {
+    let area: usize = sizes.iter().map(|(w, h)| w * h).sum();
+    let max_width = sizes.iter().map(|size| size.0).max().unwrap_or(1);
+    let max_height = sizes.iter().map(|size| size.1).max().unwrap_or(1);
+    let side = (area as f32).sqrt().ceil() as usize;
+    let mut width = side.max(max_width).max(1).next_power_of_two();
+    let mut height = side.max(max_height).max(1).next_power_of_two();
+
+    let mut order = (0..sizes.len()).collect_vec();
+    order.sort_by_key(|&i| std::cmp::Reverse((sizes[i].1, sizes[i].0)));
+    while width <= max_size && height <= max_size {
+        let mut skyline = Skyline::new(width, height);
+        let mut positions = vec![(0, 0); sizes.len()];
+        let packed = order.iter().all(|&i| {
+            skyline
+                .insert(sizes[i].0, sizes[i].1)
+                .map(|position| positions[i] = position)
+                .is_some()
+        });
+        if packed {
+            return Some(PackedRects {
+                width,
+                height,
+                positions,
+            });
+        }
+        if width <= height {
+            width *= 2;
+        } else {
+            height *= 2;
+        }
+    }
+    None
+}
+
+/// packs the sprite images into one RGBA atlas image using the padding and extrusion of the settings
+pub(crate) fn build_atlas(
+    images: &[(Rc<Path>, Image<u8>)],
+    settings: &SpriteAtlasSettings,
+) -> Result<PackedAtlas, String> {
+    let border = settings.extrusion * 2 + settings.padding;
+    let cells = images
+        .iter()
+        .map(|(_, image)| (image.width + border, image.height + border))
+        .collect_vec();
+    let packed = pack_rects(&cells, settings.max_size).ok_or_else(|| {
+        format!(
+            "The sprites do not fit into an atlas of size {0}x{0}.",
+            settings.max_size
+        )
+    })?;
+    let (width, height) = (packed.width, packed.height);
+
+    let mut data = vec![0; width * height * 4];
+    let mut regions = AHashMap::new();
+    for ((path, image), (cell_x, cell_y)) in images.iter().zip(packed.positions) {
+        let extrusion = settings.extrusion;
+        for y in 0..image.height + extrusion * 2 {
+            let src_y = y.saturating_sub(extrusion).min(image.height - 1);
+            for x in 0..image.width + extrusion * 2 {
+                let src_x = x.saturating_sub(extrusion).min(image.width - 1);
+                let src = (src_y * image.width + src_x) * 4;
+                let dst = ((cell_y + y) * width + cell_x + x) * 4;
+                data[dst..dst + 4].copy_from_slice(&image.data[src..src + 4]);
+            }
+        }
+        let region = AtlasRegion {
+            pixel_index: (cell_x + extrusion, cell_y + extrusion),
+            pixel_size: (image.width, image.height),
+        };
+        regions.insert(path.clone(), region);
+    }
+    Ok(PackedAtlas {
+        image: Image::new(width, height, 4, data),
+        regions,
+    })
+}
+
+/// loads all sprite images from their files and packs them into an atlas
+pub(crate) fn load_atlas(
+    paths: &[Rc<Path>],
+    settings: &SpriteAtlasSettings,
+) -> Result<PackedAtlas, String> {
+    let images = paths
+        .iter()
+        .unique()
+        .map(|path| {
+            stbi_load_u8_rgba(path)
+                .filter(|image| image.width > 0 && image.height > 0)
+                .map(|image| (path.clone(), image))
+                .ok_or_else(|| format!("Error loading sprite file data from {path:?}."))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    build_atlas(&images, settings)
+}
@@ -62,14 +62,17 @@ impl SpriteRenderer {
 
     /// adds the sprite data to the renderer
     pub(crate) fn add_data(&mut self, entity_manager: &EntityManager) {
-        for (sprite, scale, flags) in unsafe {
-            entity_manager.query3::<&Sprite, Option<&Scale>, Option<&EntityFlags>>((None, None))
-        }
-        .filter(|(_, _, f)| f.is_none_or(|flags| !flags.get_bit(INVISIBLE)))
-        .filter(|(s, _, _)| match s.source {
-            SpriteSource::Colored(color) => color != Color32::TRANSPARENT,
-            _ => true,
-        }) {
+        for (sprite, animation, scale, flags) in unsafe {
+            entity_manager
+                .query4::<&Sprite, Option<&SpriteAnimation>, Option<&Scale>, Option<&EntityFlags>>(
+                    (None, None),
+                )
+        } {
+            if flags.is_some_and(|flags| flags.get_bit(INVISIBLE))
+                || sprite.source == SpriteSource::Colored(Color32::TRANSPARENT)
+            {
+                continue;
+            }
             let invisible_cached = flags.is_some_and(|f| f.get_bit(INVISIBLE_CACHED));
             let scale = scale.copied().unwrap_or_default().scale_matrix();
             let trafo = match sprite.position {
@@ -113,20 +116,47 @@ impl SpriteRenderer {
                         continue;
                     }
                     let sheet = opt_sheet.unwrap();
+                    let (pixel_index, pixel_size) = animation
+                        .and_then(|a| a.current_frame())
+                        .map(|frame| (frame.pixel_index, frame.pixel_size))
+                        .unwrap_or((src.pixel_index, src.pixel_size));
 
-                    let mut tex_coords = SPRITE_PLANE_UVS;
-                    for coord in tex_coords.iter_mut() {
-                        *coord = coord.component_mul(&vec2(
-                            src.pixel_size.0 as f32 / sheet.width as f32,
-                            src.pixel_size.1 as f32 / sheet.height as f32,
-                        )) + vec2(
-                            src.pixel_index.0 as f32 / sheet.width as f32,
-                            src.pixel_index.1 as f32 / sheet.height as f32,
+                    let config = SpriteConfig {
+                        tex_id: sheet.texture_id,
+                        tex_coords: region_tex_coords(
+                            pixel_index,
+                            pixel_size,
+                            (sheet.width, sheet.height),
+                        ),
+                        layer: sprite.layer,
+                        trafo,
+                    };
+                    self.add_tex_sprite(config, invisible_cached);
+                }
+                SpriteSource::Atlas(src) => {
+                    let opt_atlas = entity_manager.texture_map.get_atlas(&src.atlas);
+                    if opt_atlas.is_none() {
+                        continue;
+                    }
+                    let atlas = opt_atlas.unwrap();
+                    let opt_region = atlas.regions.get(&src.sprite);
+                    if opt_region.is_none() {
+                        log::warn!(
+                            "Sprite {:?} is not part of atlas {:?}!",
+                            src.sprite,
+                            src.atlas
                         );
+                        continue;
                     }
+                    let region = opt_region.unwrap();
+
                     let config = SpriteConfig {
-                        tex_id: sheet.texture_id,
-                        tex_coords,
+                        tex_id: atlas.texture_id,
+                        tex_coords: region_tex_coords(
+                            region.pixel_index,
+                            region.pixel_size,
+                            (atlas.width, atlas.height),
+                        ),
                         layer: sprite.layer,
                         trafo,
                     };
@@ -192,6 +222,21 @@ impl SpriteRenderer {
     }
 }
 
+/// computes the texture coordinates of the sprite plane for a pixel region of a texture
+fn region_tex_coords(
+    pixel_index: (usize, usize),
+    pixel_size: (usize, usize),
+    texture_size: (usize, usize),
+) -> [Vec2; 4] {
+    let (width, height) = (texture_size.0 as f32, texture_size.1 as f32);
+    SPRITE_PLANE_UVS.map(|coord| {
+        coord.component_mul(&vec2(
+            pixel_size.0 as f32 / width,
+            pixel_size.1 as f32 / height,
+        )) + vec2(pixel_index.0 as f32 / width, pixel_index.1 as f32 / height)
+    })
+}
+
 /// a sprite render batch
 struct SpriteBatch {
     vao: GLuint,
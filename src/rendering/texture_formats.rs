@@ -0,0 +1,425 @@
+use crate::internal_prelude::*;
+use crate::utils::vfs::read_file;
+use stb_image::image::{load_from_memory_with_depth, LoadResult};
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+// S3TC formats are only available as extension and not part of the generated gl bindings
+const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1: GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3: GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1: GLenum = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1: GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3: GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5: GLenum = 0x8C4F;
+
+/// the pixel format of decoded texture data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PixelFormat {
+    RGBA8,
+    RGBA16F,
+    RGBA32F,
+    BC1,
+    BC1Alpha,
+    BC2,
+    BC3,
+    BC4,
+    BC5,
+    BC6HUnsigned,
+    BC6HSigned,
+    BC7,
+    ETC2RGB8,
+    ETC2RGBA8,
+}
+
+impl PixelFormat {
+    /// checks if the format is block-compressed
+    pub(crate) fn is_compressed(&self) -> bool {
+        !matches!(self, Self::RGBA8 | Self::RGBA16F | Self::RGBA32F)
+    }
+
+    /// the expected byte size of one mip level with the given dimensions (``None`` if the size overflows)
+    pub(crate) fn level_size(&self, width: usize, height: usize) -> Option<usize> {
+        let pixels = width.checked_mul(height)?;
+        let blocks = width
+            .div_ceil(4)
+            .max(1)
+            .checked_mul(height.div_ceil(4).max(1))?;
+        match self {
+            Self::RGBA8 => pixels.checked_mul(4),
+            Self::RGBA16F => pixels.checked_mul(8),
+            Self::RGBA32F => pixels.checked_mul(16),
+            Self::BC1 | Self::BC1Alpha | Self::BC4 | Self::ETC2RGB8 => blocks.checked_mul(8),
+            _ => blocks.checked_mul(16),
+        }
+    }
+
+    /// yields the gl internal format, pixel format and pixel type, the pixel format and type are only relevant for uncompressed data
+    #[rustfmt::skip]
+    pub(crate) fn gl_formats(&self, color_space: ColorSpace, srgb: bool) -> (GLenum, GLenum, GLenum) {
+        let srgb = srgb || color_space == ColorSpace::SRGBA;
+        let internal_format = match (self, srgb) {
+            (Self::RGBA8 | Self::RGBA16F | Self::RGBA32F, _) => match color_space {
+                ColorSpace::SRGBA => gl::SRGB_ALPHA,
+                ColorSpace::RGBA8 if srgb => gl::SRGB_ALPHA,
+                ColorSpace::RGBA8 => gl::RGBA8,
+                ColorSpace::RGBA16F => gl::RGBA16F,
+                ColorSpace::RGBA32F => gl::RGBA32F,
+            },
+            (Self::BC1, false) => COMPRESSED_RGB_S3TC_DXT1,
+            (Self::BC1, true) => COMPRESSED_SRGB_S3TC_DXT1,
+            (Self::BC1Alpha, false) => COMPRESSED_RGBA_S3TC_DXT1,
+            (Self::BC1Alpha, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1,
+            (Self::BC2, false) => COMPRESSED_RGBA_S3TC_DXT3,
+            (Self::BC2, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT3,
+            (Self::BC3, false) => COMPRESSED_RGBA_S3TC_DXT5,
+            (Self::BC3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5,
+            (Self::BC4, _) => gl::COMPRESSED_RED_RGTC1,
+            (Self::BC5, _) => gl::COMPRESSED_RG_RGTC2,
+            (Self::BC6HUnsigned, _) => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            (Self::BC6HSigned, _) => gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+            (Self::BC7, false) => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            (Self::BC7, true) => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            (Self::ETC2RGB8, false) => gl::COMPRESSED_RGB8_ETC2,
+            (Self::ETC2RGB8, true) => gl::COMPRESSED_SRGB8_ETC2,
+            (Self::ETC2RGBA8, false) => gl::COMPRESSED_RGBA8_ETC2_EAC,
+            (Self::ETC2RGBA8, true) => gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+        };
+        let pixel_type = match self {
+            Self::RGBA16F => gl::HALF_FLOAT,
+            Self::RGBA32F => gl::FLOAT,
+            _ => gl::UNSIGNED_BYTE,
+        };
+        (internal_format, gl::RGBA, pixel_type)
+    }
+
+    /// maps a vulkan format id (used in KTX2 files) to a pixel format and wether it is stored in srgb
+    fn from_vk_format(vk_format: u32) -> Option<(Self, bool)> {
+        match vk_format {
+            37 => Some((Self::RGBA8, false)),
+            43 => Some((Self::RGBA8, true)),
+            97 => Some((Self::RGBA16F, false)),
+            109 => Some((Self::RGBA32F, false)),
+            131 => Some((Self::BC1, false)),
+            132 => Some((Self::BC1, true)),
+            133 => Some((Self::BC1Alpha, false)),
+            134 => Some((Self::BC1Alpha, true)),
+            135 => Some((Self::BC2, false)),
+            136 => Some((Self::BC2, true)),
+            137 => Some((Self::BC3, false)),
+            138 => Some((Self::BC3, true)),
+            139 => Some((Self::BC4, false)),
+            141 => Some((Self::BC5, false)),
+            143 => Some((Self::BC6HUnsigned, false)),
+            144 => Some((Self::BC6HSigned, false)),
+            145 => Some((Self::BC7, false)),
+            146 => Some((Self::BC7, true)),
+            147 => Some((Self::ETC2RGB8, false)),
+            148 => Some((Self::ETC2RGB8, true)),
+            151 => Some((Self::ETC2RGBA8, false)),
+            152 => Some((Self::ETC2RGBA8, true)),
+            _ => None,
+        }
+    }
+
+    /// maps a dxgi format id (used in DDS files with a DX10 header) to a pixel format and wether it is stored in srgb
+    fn from_dxgi_format(dxgi_format: u32) -> Option<(Self, bool)> {
+        match dxgi_format {
+            2 => Some((Self::RGBA32F, false)),
+            10 => Some((Self::RGBA16F, false)),
+            28 => Some((Self::RGBA8, false)),
+            29 => Some((Self::RGBA8, true)),
+            71 => Some((Self::BC1Alpha, false)),
+            72 => Some((Self::BC1Alpha, true)),
+            74 => Some((Self::BC2, false)),
+            75 => Some((Self::BC2, true)),
+            77 => Some((Self::BC3, false)),
+            78 => Some((Self::BC3, true)),
+            80 => Some((Self::BC4, false)),
+            83 => Some((Self::BC5, false)),
+            95 => Some((Self::BC6HUnsigned, false)),
+            96 => Some((Self::BC6HSigned, false)),
+            98 => Some((Self::BC7, false)),
+            99 => Some((Self::BC7, true)),
+            _ => None,
+        }
+    }
+}
+
+/// decoded texture data with all of the mip levels that are stored in the source, level 0 is the full size image
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextureData {
+    pub(crate) format: PixelFormat,
+    pub(crate) srgb: bool,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) levels: Vec<Vec<u8>>,
+}
+
+impl TextureData {
+    /// the dimensions of a mip level
+    pub(crate) fn level_dimensions(&self, level: usize) -> (usize, usize) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// the number of levels of a full mip chain for the texture size
+    fn full_chain_length(&self) -> usize {
+        (usize::BITS - self.width.max(self.height).max(1).leading_zeros()) as usize
+    }
+
+    /// decides how many of the stored levels are uploaded and wether the remaining levels are generated on the GPU
+    pub(crate) fn mip_chain(&self, settings: &MipmapSettings) -> (usize, bool) {
+        let stored = self.levels.len();
+        let can_generate = !self.format.is_compressed();
+        let (uploaded, generate) = match settings.mode {
+            MipmapMode::Auto if stored > 1 => (stored, false),
+            MipmapMode::Auto | MipmapMode::Generate => {
+                (if can_generate { 1 } else { stored }, can_generate)
+            }
+            MipmapMode::Stored | MipmapMode::Custom(_) => (stored, false),
+            MipmapMode::Disabled => (1, false),
+        };
+        let max_levels = settings
+            .max_levels
+            .map_or(usize::MAX, |max| max.max(1) as usize);
+        (uploaded.min(max_levels), generate && max_levels > 1)
+    }
+
+    /// creates texture data from decoded stb_image pixels
+    fn from_stbi(result: LoadResult) -> Result<Self, String> {
+        match result {
+            LoadResult::ImageU8(image) => Ok(Self {
+                format: PixelFormat::RGBA8,
+                srgb: false,
+                width: image.width,
+                height: image.height,
+                levels: vec![image.data],
+            }),
+            LoadResult::ImageF32(image) => Ok(Self {
+                format: PixelFormat::RGBA32F,
+                srgb: false,
+                width: image.width,
+                height: image.height,
+                levels: vec![image
+                    .data
+                    .iter()
+                    .flat_map(|value| value.to_ne_bytes())
+                    .collect()],
+            }),
+            LoadResult::Error(error) => Err(error),
+        }
+    }
+
+    /// splits the level data that follows each other in a file, stops at the first level that is not fully present
+    fn split_levels(&mut self, bytes: &[u8], level_count: usize) -> Result<(), String> {
+        let mut offset: usize = 0;
+        for level in 0..level_count.min(self.full_chain_length()) {
+            let (width, height) = self.level_dimensions(level);
+            let end = self
+                .format
+                .level_size(width, height)
+                .and_then(|size| offset.checked_add(size))
+                .ok_or_else(|| format!("Texture level {level} is out of bounds."))?;
+            if let Some(data) = bytes.get(offset..end) {
+                self.levels.push(data.to_vec());
+                offset = end;
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// reads a little endian u32 at the byte offset
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+}
+
+/// reads a little endian u64 at the byte offset
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+}
+
+/// decodes a KTX2 file without supercompression, only 2D textures without layers or faces are supported
+pub(crate) fn decode_ktx2(bytes: &[u8]) -> Result<TextureData, String> {
+    if !bytes.starts_with(&KTX2_IDENTIFIER) {
+        return Err("Missing KTX2 identifier.".into());
+    }
+    let header = |index: usize| u32_at(bytes, 12 + index * 4).ok_or("Truncated KTX2 header.");
+    let vk_format = header(0)?;
+    let (width, height) = (header(2)? as usize, header(3)? as usize);
+    let (depth, layers, faces) = (header(4)?, header(5)?, header(6)?);
+    let level_count = (header(7)? as usize).max(1);
+    let supercompression = header(8)?;
+    if depth > 1 || layers > 1 || faces != 1 {
+        return Err(
+            "Only 2D KTX2 textures without array layers or cube faces are supported.".into(),
+        );
+    }
+    if supercompression != 0 {
+        return Err(format!(
+            "Unsupported KTX2 supercompression scheme {supercompression}."
+        ));
+    }
+    if width == 0 || height == 0 {
+        return Err("KTX2 texture has no size.".into());
+    }
+    let (format, srgb) = PixelFormat::from_vk_format(vk_format)
+        .ok_or_else(|| format!("Unsupported KTX2 vulkan format {vk_format}."))?;
+
+    let mut data = TextureData {
+        format,
+        srgb,
+        width,
+        height,
+        levels: Vec::new(),
+    };
+    for level in 0..level_count.min(data.full_chain_length()) {
+        let entry = 80 + level * 24;
+        let offset = u64_at(bytes, entry).ok_or("Truncated KTX2 level index.")?;
+        let length = u64_at(bytes, entry + 8).ok_or("Truncated KTX2 level index.")?;
+        let out_of_bounds = || format!("KTX2 level {level} is out of bounds.");
+        let (level_width, level_height) = data.level_dimensions(level);
+        let expected_length = format
+            .level_size(level_width, level_height)
+            .ok_or_else(out_of_bounds)?;
+        if usize::try_from(length) != Ok(expected_length) {
+            return Err(format!(
+                "KTX2 level {level} has an unexpected size of {length} bytes."
+            ));
+        }
+        let start = usize::try_from(offset).map_err(|_| out_of_bounds())?;
+        let end = start
+            .checked_add(expected_length)
+            .ok_or_else(out_of_bounds)?;
+        let level_data = bytes.get(start..end).ok_or_else(out_of_bounds)?;
+        data.levels.push(level_data.to_vec());
+    }
+    Ok(data)
+}
+
+/// decodes a DDS file, supports the common DXT / BC formats, uncompressed RGBA8 / BGRA8 and float RGBA as well as DX10 headers
+pub(crate) fn decode_dds(bytes: &[u8]) -> Result<TextureData, String> {
+    if !bytes.starts_with(DDS_MAGIC) {
+        return Err("Missing DDS magic number.".into());
+    }
+    let header = |offset: usize| u32_at(bytes, offset).ok_or("Truncated DDS header.");
+    if header(4)? != 124 {
+        return Err("Invalid DDS header size.".into());
+    }
+    let flags = header(8)?;
+    let (height, width) = (header(12)? as usize, header(16)? as usize);
+    let mip_count = if flags & 0x20000 != 0 {
+        (header(28)? as usize).max(1)
+    } else {
+        1
+    };
+    let pixel_flags = header(80)?;
+    let four_cc = header(84)?.to_le_bytes();
+    let bit_count = header(88)?;
+    let red_mask = header(92)?;
+    if header(112)? & 0x200 != 0 {
+        return Err("DDS cube maps are not supported.".into());
+    }
+    if width == 0 || height == 0 {
+        return Err("DDS texture has no size.".into());
+    }
+
+    let mut data_offset = 128;
+    let mut swap_red_blue = false;
+    let (format, srgb) = if pixel_flags & 0x4 != 0 {
+        match &four_cc {
+            b"DX10" => {
+                data_offset += 20;
+                if header(132)? != 3 || header(140)? > 1 {
+                    return Err("Only 2D DDS textures without array layers are supported.".into());
+                }
+                let dxgi_format = header(128)?;
+                PixelFormat::from_dxgi_format(dxgi_format)
+                    .ok_or_else(|| format!("Unsupported DXGI format {dxgi_format}."))?
+            }
+            b"DXT1" => (PixelFormat::BC1Alpha, false),
+            b"DXT2" | b"DXT3" => (PixelFormat::BC2, false),
+            b"DXT4" | b"DXT5" => (PixelFormat::BC3, false),
+            b"ATI1" | b"BC4U" => (PixelFormat::BC4, false),
+            b"ATI2" | b"BC5U" => (PixelFormat::BC5, false),
+            [113, 0, 0, 0] => (PixelFormat::RGBA16F, false),
+            [116, 0, 0, 0] => (PixelFormat::RGBA32F, false),
+            _ => return Err(format!("Unsupported DDS four character code {four_cc:?}.")),
+        }
+    } else if pixel_flags & 0x40 != 0 && bit_count == 32 {
+        match red_mask {
+            0x0000_00FF => (PixelFormat::RGBA8, false),
+            0x00FF_0000 => {
+                swap_red_blue = true;
+                (PixelFormat::RGBA8, false)
+            }
+            _ => return Err("Unsupported DDS channel layout.".into()),
+        }
+    } else {
+        return Err("Unsupported DDS pixel format.".into());
+    };
+
+    let mut data = TextureData {
+        format,
+        srgb,
+        width,
+        height,
+        levels: Vec::new(),
+    };
+    data.split_levels(&bytes[data_offset.min(bytes.len())..], mip_count)?;
+    if data.levels.is_empty() {
+        return Err("DDS file does not contain the full base level.".into());
+    }
+    if swap_red_blue {
+        for level in data.levels.iter_mut() {
+            level.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+        }
+    }
+    Ok(data)
+}
+
+/// decodes texture file data based on the file content, falls back to stb_image for regular image formats (including ``.hdr``)
+pub(crate) fn decode_texture(bytes: &[u8]) -> Result<TextureData, String> {
+    if bytes.starts_with(&KTX2_IDENTIFIER) {
+        decode_ktx2(bytes)
+    } else if bytes.starts_with(DDS_MAGIC) {
+        decode_dds(bytes)
+    } else {
+        TextureData::from_stbi(load_from_memory_with_depth(bytes, 4, false))
+    }
+}
+
+/// loads the texture data for a texture component, including the custom mip levels
+pub(crate) fn load_texture_data(texture: &Texture) -> Result<TextureData, String> {
+    let load = |path: &Path| {
+        read_file(path)
+            .map_err(|error| format!("Error reading {path:?}: {error}"))
+            .and_then(|bytes| decode_texture(&bytes))
+    };
+    let mut data = load(&texture.path)?;
+    if let MipmapMode::Custom(paths) = &texture.mipmaps.mode {
+        data.levels.truncate(1);
+        for (index, path) in paths.iter().enumerate() {
+            let level = load(path)?;
+            let expected = data.level_dimensions(index + 1);
+            if level.format != data.format || (level.width, level.height) != expected {
+                return Err(format!(
+                    "Custom mip level {path:?} does not match the base format {:?} with size {expected:?}.",
+                    data.format
+                ));
+            }
+            data.levels.push(level.levels.into_iter().next().unwrap());
+        }
+    }
+    Ok(data)
+}
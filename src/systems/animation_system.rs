@@ -1,4 +1,6 @@
 use crate::ecs::entity_manager::EntityManager;
+use crate::ecs::keyframe_animation::AnimatedComponents;
+use crate::ecs::tween::TweenRegister;
 use crate::internal_prelude::*;
 use crate::rendering::data::calc_model_matrix;
 use crate::rendering::mesh::Hitbox;
@@ -7,7 +9,7 @@ use crate::utils::constants::bits::user_level::*;
 use fyrox_sound::math::get_barycentric_coords;
 use winit::keyboard::KeyCode;
 
-/// The system responsible for all animations of entities in the engine. This includes physics and user-determined animations.
+/// The system responsible for all animations of entities in the engine. This includes physics, keyframe, skeletal, morph target and sprite animations and user-determined animations.
 pub struct AnimationSystem {
     /// Changes the gravity value used for physics computations (default is ``constants::G``).
     pub gravity: Acceleration,
@@ -20,6 +22,11 @@ pub struct AnimationSystem {
     pub(crate) curr_cam_pos: Vec3,
     pub(crate) prev_cam_pos: Vec3,
     pub(crate) last_collisions: Vec<(EntityID, CollisionInfo)>,
+    pub(crate) passed_markers: Vec<AnimationMarker>,
+    pub(crate) tweens: TweenRegister,
+    pub(crate) finished_tweens: Vec<TweenFinished>,
+    pub(crate) finished_sprite_animations: Vec<SpriteAnimationFinished>,
+    world: WorldID,
 }
 
 impl AnimationSystem {
@@ -28,21 +35,103 @@ impl AnimationSystem {
         Self {
             gravity: G,
             flying_cam_keys: MovementKeys::default(),
+            world: MAIN_WORLD,
             frame_arena: BumpArena::with_capacity(10_000_000),
             animation_speed: 1.0,
             flying_cam_dir: None,
             curr_cam_pos: ORIGIN,
             prev_cam_pos: ORIGIN,
             last_collisions: Vec::with_capacity(1000),
+            passed_markers: Vec::new(),
+            tweens: TweenRegister::default(),
+            finished_tweens: Vec::new(),
+            finished_sprite_animations: Vec::new(),
         }
     }
 
     /// applys all animaion updates to all entities (called once per time step)
     pub(crate) fn update<T: LeafyApp>(&mut self, engine: &Engine<T>) {
         self.frame_arena.reset();
+        let opt_prev_world = engine.entity_manager().focus_world(self.world);
+        if opt_prev_world.is_none() {
+            return;
+        }
         self.apply_physics(engine.entity_manager_mut().deref_mut());
         self.handle_collisions(engine.entity_manager_mut().deref_mut());
         self.damp_velocities(engine.entity_manager_mut().deref_mut());
+        self.advance_keyframe_animations(engine.entity_manager_mut().deref_mut());
+        self.advance_skeletal_animations(engine.entity_manager_mut().deref_mut());
+        self.advance_morph_animations(engine.entity_manager_mut().deref_mut());
+        self.advance_tweens(engine.entity_manager_mut().deref_mut());
+        self.advance_sprite_animations(engine.entity_manager_mut().deref_mut());
+        engine.entity_manager().focus_world(opt_prev_world.unwrap());
+    }
+
+    /// applies all animation clips one time step later and stores the passed markers to be triggered as events
+    fn advance_keyframe_animations(&mut self, entity_manager: &mut EntityManager) {
+        for (entity, animator, position, orientation, scale, point_light, directional_light) in unsafe {
+            entity_manager
+                .query7::<&EntityID, &mut Animator, Option<&mut Position>, Option<&mut Orientation>, Option<&mut Scale>, Option<&mut PointLight>, Option<&mut DirectionalLight>>((None, None))
+        } {
+            let markers = animator.advance(TIME_STEP.0);
+            if let Some(clip) = animator.clip.as_ref() {
+                self.passed_markers
+                    .extend(markers.into_iter().map(|marker| AnimationMarker {
+                        entity: *entity,
+                        clip: clip.shared_name().clone(),
+                        marker,
+                    }));
+                clip.apply(
+                    animator.time,
+                    AnimatedComponents {
+                        position,
+                        orientation,
+                        scale,
+                        point_light,
+                        directional_light,
+                    },
+                );
+            }
+        }
+    }
+
+    /// moves the playback time of all skeletal animations forward by one time step
+    fn advance_skeletal_animations(&self, entity_manager: &mut EntityManager) {
+        for animator in unsafe { entity_manager.query1::<&mut SkeletalAnimator>((None, None)) } {
+            animator.advance(TIME_STEP.0);
+        }
+    }
+
+    /// samples the animated morph target weights one time step later
+    fn advance_morph_animations(&self, entity_manager: &mut EntityManager) {
+        for morph_weights in unsafe { entity_manager.query1::<&mut MorphWeights>((None, None)) } {
+            morph_weights.advance(TIME_STEP.0);
+        }
+    }
+
+    /// moves all tweens forward by one time step and stores the finished ones to be triggered as events
+    fn advance_tweens(&mut self, entity_manager: &mut EntityManager) {
+        let finished = self.tweens.update(entity_manager, TIME_STEP.0);
+        self.finished_tweens.extend(
+            finished
+                .into_iter()
+                .map(|(tween, entity)| TweenFinished { tween, entity }),
+        );
+    }
+
+    /// moves all sprite animations forward by one time step and stores the finished ones to be triggered as events
+    fn advance_sprite_animations(&mut self, entity_manager: &mut EntityManager) {
+        for (entity, animation) in
+            unsafe { entity_manager.query2::<&EntityID, &mut SpriteAnimation>((None, None)) }
+        {
+            if let Some(sequence) = animation.advance(TIME_STEP.0) {
+                self.finished_sprite_animations
+                    .push(SpriteAnimationFinished {
+                        entity: *entity,
+                        sequence,
+                    });
+            }
+        }
     }
 
     /// stops velocities near zero to make behavior more realistic
@@ -431,19 +520,18 @@ impl AnimationSystem {
             entity_manager
                 .query7::<&mut Position, &mut Velocity, Option<&mut Acceleration>, Option<&RigidBody>, Option<&mut Orientation>, Option<&AngularMomentum>, Option<&EntityFlags>>((None, None))
         } {
-            let total_a = rb_opt
-                .is_some()
-                .then(|| {
-                    flags.map_or(Acceleration::zero(), |f| {
-                        if f.get_bit(FLOATING) {
-                            Acceleration::zero()
-                        } else {
-                            self.gravity
-                        }
-                    })
+            let gravity = if rb_opt.is_some() {
+                flags.map_or(Acceleration::zero(), |f| {
+                    if f.get_bit(FLOATING) {
+                        Acceleration::zero()
+                    } else {
+                        self.gravity
+                    }
                 })
-                .unwrap_or_default()
-                + copied_or_default(&a_opt);
+            } else {
+                Acceleration::default()
+            };
+            let total_a = gravity + copied_or_default(&a_opt);
 
             *v += total_a * TIME_STEP;
             *p += *v * TIME_STEP;
@@ -464,6 +552,42 @@ impl AnimationSystem {
         self.last_collisions.iter().map(|(id, info)| (*id, info))
     }
 
+    /// Starts a tween of a value of a component of an entity in the simulated world and returns its ID. The value is selected by ``field``, e.g. ``|light: &mut PointLight| Some(&mut light.intensity)``. Tweens advance in the fixed time step, so they follow the animation speed and pause outside of ``EngineMode::Running``. A ``TweenFinished`` event is triggered when the tween ends. Tweens whose entity, component or value no longer exists are stopped.
+    pub fn start_tween<C: Component, V: Tweenable>(
+        &mut self,
+        entity: EntityID,
+        field: fn(&mut C) -> Option<&mut V>,
+        tween: Tween<V>,
+    ) -> TweenID {
+        self.tweens.add(entity, field, tween, None)
+    }
+
+    /// Chains a tween after another one, it starts in the time step after the ``previous`` tween finished. If ``previous`` is not running anymore, the tween starts right away. Stopping a tween also stops all of the tweens that are chained after it.
+    pub fn start_tween_after<C: Component, V: Tweenable>(
+        &mut self,
+        previous: TweenID,
+        entity: EntityID,
+        field: fn(&mut C) -> Option<&mut V>,
+        tween: Tween<V>,
+    ) -> TweenID {
+        self.tweens.add(entity, field, tween, Some(previous))
+    }
+
+    /// Stops a tween and keeps the current value, returns wether or not the tween was running.
+    pub fn stop_tween(&mut self, tween: TweenID) -> bool {
+        self.tweens.remove(|id, _| id == tween) > 0
+    }
+
+    /// Stops all tweens of an entity and returns the number of stopped tweens.
+    pub fn stop_entity_tweens(&mut self, entity: EntityID) -> usize {
+        self.tweens.remove(|_, tween_entity| tween_entity == entity)
+    }
+
+    /// Checks wether or not a tween is running or waiting to be started.
+    pub fn is_tween_active(&self, tween: TweenID) -> bool {
+        self.tweens.contains(tween)
+    }
+
     /// Enables/disables the built-in flying cam movement with a movement speed constant.
     pub fn set_flying_cam_movement(&mut self, speed: Option<f32>) {
         log::trace!("Set flying cam movement: {speed:?}.");
@@ -477,6 +601,17 @@ impl AnimationSystem {
         }
     }
 
+    /// Sets the world that the physics, collisions and animations are computed for (default is ``MAIN_WORLD``). If the world does not exist, nothing is simulated.
+    pub fn set_world(&mut self, world: WorldID) {
+        log::debug!("Set animation world: {world:?}.");
+        self.world = world;
+    }
+
+    /// The world that is currently simulated.
+    pub fn world(&self) -> WorldID {
+        self.world
+    }
+
     /// General event handling function for the animation speed change.
     pub(crate) fn on_animation_speed_change(&mut self, event: &AnimationSpeedChange) {
         self.animation_speed = event.new_animation_speed;
@@ -549,7 +684,7 @@ impl ColliderData<'_> {
     /// builds the sphere collider specification data if the hitbox type matches
     fn sphere_spec(&self) -> Option<SphereColliderSpec> {
         match self.hitbox {
-            Hitbox::ConvexMesh(_) => None,
+            Hitbox::ConvexMesh(_) | Hitbox::Compound(_) => None,
             Hitbox::Sphere(radius) => {
                 let mass_offset = glm::translate(&Mat4::identity(), &self.center_of_mass);
                 let inv_mass_offset = mass_offset.try_inverse().unwrap();
@@ -567,25 +702,24 @@ impl ColliderData<'_> {
         }
     }
 
-    /// builds the mesh collider specification data if the hitbox type matches
-    fn mesh_spec(&self) -> Option<MeshColliderSpec<'_>> {
-        match self.hitbox {
-            Hitbox::ConvexMesh(mesh) => {
-                let model = calc_model_matrix(
-                    &self.position,
-                    &self.scale,
-                    &self.orientation,
-                    &self.center_of_mass,
-                );
-                Some(MeshColliderSpec {
-                    transform: model
-                        * self.collider.scale.scale_matrix()
-                        * glm::translate(&Mat4::identity(), &self.collider.offset),
-                    points: &mesh.vertices,
-                })
-            }
-            Hitbox::Sphere(_) => None,
-        }
+    /// builds the mesh collider specification data for every convex part of the hitbox
+    fn mesh_specs(&self) -> impl Iterator<Item = MeshColliderSpec<'_>> + Clone {
+        let model = calc_model_matrix(
+            &self.position,
+            &self.scale,
+            &self.orientation,
+            &self.center_of_mass,
+        );
+        let transform = model
+            * self.collider.scale.scale_matrix()
+            * glm::translate(&Mat4::identity(), &self.collider.offset);
+        self.hitbox
+            .convex_parts()
+            .iter()
+            .map(move |mesh| MeshColliderSpec {
+                transform,
+                points: &mesh.vertices,
+            })
     }
 
     /// checks if two hitboxes collide with each other
@@ -598,23 +732,33 @@ impl ColliderData<'_> {
         } else {
             0.0
         };
+        // hitboxes made of multiple convex parts resolve the deepest collision between any two parts
+        let deepest = |collisions: &mut dyn Iterator<Item = Option<CollisionData>>| {
+            collisions.flatten().max_by(|data1, data2| {
+                let depth1 = data1.translation_vec.norm_squared();
+                depth1.total_cmp(&data2.translation_vec.norm_squared())
+            })
+        };
         // assume the normal vector is from the point of view of 1
         // the normal vector in the collision data should be normalized
-        if let Some(spec1) = self.mesh_spec() {
-            if let Some(spec2) = other.mesh_spec() {
-                gjk(spec1, spec2, translate_factor, arena_allocator)
-            } else {
-                let spec2 = other.sphere_spec().unwrap();
-                gjk(spec1, spec2, translate_factor, arena_allocator)
-            }
-        } else {
-            let spec1 = self.sphere_spec().unwrap();
-            if let Some(spec2) = other.mesh_spec() {
-                gjk(spec1, spec2, translate_factor, arena_allocator)
-            } else {
-                let spec2 = other.sphere_spec().unwrap();
-                gjk(spec1, spec2, translate_factor, arena_allocator)
-            }
+        match (self.sphere_spec(), other.sphere_spec()) {
+            (None, None) => deepest(
+                &mut self
+                    .mesh_specs()
+                    .cartesian_product(other.mesh_specs())
+                    .map(|(spec1, spec2)| gjk(spec1, spec2, translate_factor, arena_allocator)),
+            ),
+            (None, Some(spec2)) => deepest(
+                &mut self
+                    .mesh_specs()
+                    .map(|spec1| gjk(spec1, spec2, translate_factor, arena_allocator)),
+            ),
+            (Some(spec1), None) => deepest(
+                &mut other
+                    .mesh_specs()
+                    .map(|spec2| gjk(spec1, spec2, translate_factor, arena_allocator)),
+            ),
+            (Some(spec1), Some(spec2)) => gjk(spec1, spec2, translate_factor, arena_allocator),
         }
     }
 }
@@ -625,6 +769,7 @@ trait ColliderSpec {
 }
 
 /// specification for a sphere collider used in collision detection
+#[derive(Clone, Copy)]
 struct SphereColliderSpec {
     scale_dimensions: Vec3,
     collider_pos: Vec3,
@@ -648,6 +793,7 @@ impl ColliderSpec for SphereColliderSpec {
 }
 
 /// specification for a mesh collider used in collision detection
+#[derive(Clone, Copy)]
 struct MeshColliderSpec<'a> {
     transform: Mat4,
     points: &'a [Vec3],
@@ -1,12 +1,11 @@
 use crate::ecs::entity_manager::EntityManager;
 use crate::internal_prelude::*;
-use fyrox_resource::io::FsResourceIo;
+use crate::utils::vfs::read_file;
 use fyrox_resource::untyped::ResourceKind;
 use fyrox_sound::buffer::{DataSource, SoundBufferResource, SoundBufferResourceExtension};
 use fyrox_sound::context::{SoundContext, SAMPLE_RATE};
 use fyrox_sound::effects::{reverb::Reverb, Effect};
 use fyrox_sound::engine::SoundEngine;
-use fyrox_sound::futures::executor::block_on;
 use fyrox_sound::hrtf::HrirSphere;
 use fyrox_sound::pool::Handle;
 use fyrox_sound::renderer::hrtf::{HrirSphereResource, HrirSphereResourceExt, HrtfRenderer};
@@ -27,12 +26,22 @@ pub struct AudioSystem {
     using_reverb: bool,
     using_hrtf: bool,
     removed_handles: AHashSet<Handle<SoundSource>>,
+    world: WorldID,
 }
 
 impl AudioSystem {
     /// creates a new audio system
     pub(crate) fn new() -> Self {
-        let sound_engine = SoundEngine::new().expect("Error creating sound engine.");
+        Self::with_sound_engine(SoundEngine::new().expect("Error creating sound engine."))
+    }
+
+    /// creates a new audio system that mixes sounds without sending them to an output device
+    pub(crate) fn without_device() -> Self {
+        Self::with_sound_engine(SoundEngine::without_device())
+    }
+
+    /// sets up the audio system around a sound engine
+    fn with_sound_engine(sound_engine: SoundEngine) -> Self {
         let sound_context = SoundContext::new();
         sound_engine.state().add_context(sound_context.clone());
 
@@ -49,11 +58,16 @@ impl AudioSystem {
             using_reverb: false,
             using_hrtf: false,
             removed_handles: AHashSet::new(),
+            world: MAIN_WORLD,
         }
     }
 
     /// update entity sound positions etc (runs every frame)
     pub(crate) fn update(&mut self, entity_manager: &mut EntityManager) {
+        let opt_prev_world = entity_manager.focus_world(self.world);
+        if opt_prev_world.is_none() {
+            return;
+        }
         let mut state = self.sound_context.state();
 
         for (sound, pos) in
@@ -72,6 +86,7 @@ impl AudioSystem {
                 source.set_position(vec3_to_vector3(pos.data()));
             }
         }
+        entity_manager.focus_world(opt_prev_world.unwrap());
     }
 
     /// updates the doppler effect pitch for all handles of a sound controller
@@ -89,7 +104,7 @@ impl AudioSystem {
         }
     }
 
-    /// Loads a sound from file and caches it (default state of the source is stopped and not looping). Sounds loaded this way should only be attached to at most one entity!
+    /// Loads a sound from file through the virtual file system and caches it (default state of the source is stopped and not looping). Sounds loaded this way should only be attached to at most one entity!
     pub fn load_sound(
         &mut self,
         file_path: impl AsRef<Path>,
@@ -99,9 +114,9 @@ impl AudioSystem {
         let file_path = file_path.as_ref();
 
         let volume = self.absolute_volume(sound_type);
-        let buffer = SoundBufferResource::new_generic(
-            block_on(DataSource::from_file(file_path, &FsResourceIo)).unwrap(),
-        )
+        let buffer = SoundBufferResource::new_generic(DataSource::from_memory(
+            read_file(file_path).unwrap(),
+        ))
         .unwrap();
 
         let mut sb = SoundSourceBuilder::new()
@@ -203,7 +218,6 @@ impl AudioSystem {
                 .set_gain(gain * self.absolute_volume(SoundType::Music));
         } else {
             log::warn!("Handle not in storage.");
-            return;
         }
     }
 
@@ -337,6 +351,17 @@ impl AudioSystem {
         }
     }
 
+    /// Sets the world whose sound controllers are processed (default is ``MAIN_WORLD``). If the world does not exist, no sound positions are updated.
+    pub fn set_world(&mut self, world: WorldID) {
+        log::debug!("Set audio world: {world:?}.");
+        self.world = world;
+    }
+
+    /// The world whose sound controllers are currently processed.
+    pub fn world(&self) -> WorldID {
+        self.world
+    }
+
     /// Calculate the total resulting volume for either the SFX or music.
     fn absolute_volume(&self, sound_type: SoundType) -> f32 {
         match sound_type {
@@ -340,6 +340,41 @@ pub mod events {
         pub horizontal_delta: f32,
     }
 
+    /// Triggered when an asynchronous asset loading operation is finished and the assets are ready to use.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct AssetReady {
+        pub load: LoadID,
+        pub assets: Vec<AssetID>,
+    }
+
+    /// Triggered when an asynchronous asset loading operation failed.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct AssetLoadFailed {
+        pub load: LoadID,
+    }
+
+    /// Triggered when the clip of an ``Animator`` passes one of its markers during playback.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct AnimationMarker {
+        pub entity: EntityID,
+        pub clip: Rc<str>,
+        pub marker: Rc<str>,
+    }
+
+    /// Triggered when a tween that was started in the ``AnimationSystem`` reaches its end.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct TweenFinished {
+        pub tween: TweenID,
+        pub entity: EntityID,
+    }
+
+    /// Triggered when a ``SpriteAnimation`` finishes a sequence with ``SpritePlayMode::Once``.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SpriteAnimationFinished {
+        pub entity: EntityID,
+        pub sequence: Rc<str>,
+    }
+
     /// Contains all events that are also meant to be triggered by the user. They may be triggered by the engine.
     pub mod user_space {
         use crate::internal_prelude::*;
@@ -128,6 +128,11 @@ pub(crate) fn update_cam<T: LeafyApp>(engine: &Engine<T>, dt: TimeDuration) {
 
 /// updates the doppler effect data for the audio system
 pub(crate) fn update_doppler_data<T: LeafyApp>(engine: &Engine<T>, dt: TimeDuration) {
+    let audio_world = engine.audio_system().world();
+    let opt_prev_world = engine.entity_manager().focus_world(audio_world);
+    if opt_prev_world.is_none() {
+        return;
+    }
     let mut animation_system = engine.animation_system_mut();
     for (pos, sound, flags_opt) in unsafe {
         engine
@@ -169,6 +174,7 @@ pub(crate) fn update_doppler_data<T: LeafyApp>(engine: &Engine<T>, dt: TimeDurat
             sound.doppler_pitch = pitch;
         }
     }
+    engine.entity_manager().focus_world(opt_prev_world.unwrap());
 }
 
 /// general event handling function for the window resize
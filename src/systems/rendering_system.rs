@@ -3,9 +3,10 @@ use crate::internal_prelude::*;
 use crate::rendering::data::*;
 use crate::rendering::instance_renderer::InstanceRenderer;
 use crate::rendering::mesh::Mesh;
-use crate::rendering::shader::ShaderCatalog;
+use crate::rendering::shader::{CatalogShader, ShaderCatalog, ShaderSources};
 use crate::rendering::sprite_renderer::{SpriteGrid, SpriteRenderer};
 use crate::systems::event_system::events::user_space::CamPositionChange;
+use crate::utils::file_watcher::FileWatcher;
 use bits::user_level::{INVISIBLE, INVISIBLE_CACHED, STENCIL_OUTLINE};
 
 /// The system responsible for automated rendering of all entities.
@@ -34,6 +35,10 @@ pub struct RenderingSystem {
     samples: GLsizei, // for msaa
     tmp_storage: TempRenderStorage,
     white_texture: GLuint,
+    shader_watcher: Option<(PathBuf, FileWatcher)>,
+    auto_lod_levels: AHashMap<EntityID, usize>,
+    world: WorldID,
+    offscreen_target: Option<OffscreenTarget>,
 }
 
 impl RenderingSystem {
@@ -61,6 +66,7 @@ impl RenderingSystem {
             ambient_light: (Color32::WHITE, 0.2),
             skybox: None,
             post_processing_params: PostProcessingParams::default(),
+            world: MAIN_WORLD,
             point_lights: AHashMap::new(),
             directional_lights: Vec::with_capacity(MAX_DIR_LIGHT_MAPS),
             renderers: Vec::new(),
@@ -74,15 +80,24 @@ impl RenderingSystem {
             samples,
             tmp_storage: TempRenderStorage::default(),
             white_texture: generate_white_texture(),
+            shader_watcher: None,
+            auto_lod_levels: AHashMap::new(),
+            offscreen_target: None,
         }
     }
 
     /// render all entities
     pub(crate) fn render(&mut self, entity_manager: &EntityManager) {
+        self.hot_reload_shaders();
         self.clear_gl_screen();
+        let opt_prev_world = entity_manager.focus_world(self.world);
+        if opt_prev_world.is_none() {
+            return;
+        }
         self.update_lights(entity_manager);
         self.update_uniform_buffers();
         self.reset_renderers();
+        self.refresh_outdated_renderers(entity_manager);
 
         self.add_entity_data(entity_manager);
         self.confirm_data();
@@ -98,6 +113,7 @@ impl RenderingSystem {
         }
         self.cleanup_renderers(entity_manager);
         self.render_sprites(entity_manager);
+        entity_manager.focus_world(opt_prev_world.unwrap());
     }
 
     /// adds and removes light sources according to entity data
@@ -239,21 +255,29 @@ impl RenderingSystem {
     /// add entity data to the renderers
     fn add_entity_data(&mut self, entity_manager: &EntityManager) {
         let (render_dist, cam_pos) = (self.render_distance, self.current_cam_config.0);
-        for (position, renderable, flags, scale, orientation, rb, lod, is_light_source) in unsafe {
+        let mut auto_lod_levels = AHashMap::with_capacity(self.auto_lod_levels.len());
+        for (entity, position, renderable, flags, scale, orientation, rb, lod, is_light_source) in unsafe {
             entity_manager
-                .query9::<&Position, &Renderable, Option<&EntityFlags>, Option<&Scale>, Option<&Orientation>, Option<&RigidBody>, Option<&LOD>, Option<&PointLight>, Option<&DirectionalLight>>((None, None))
+                .query10::<&EntityID, &Position, &Renderable, Option<&EntityFlags>, Option<&Scale>, Option<&Orientation>, Option<&RigidBody>, Option<&LOD>, Option<&PointLight>, Option<&DirectionalLight>>((None, None))
         }
-            .filter(|(_, _, f_opt, ..)| f_opt.is_none_or(|flags| !flags.get_bit(INVISIBLE)))
-            .filter(|(pos, ..)| render_dist.is_none_or(|dist| (pos.data() - cam_pos).norm() <= dist))
-            .map(|(p, rndrbl, f_opt, s, o, rb, lod, pl, dl)| (p, rndrbl, f_opt, s, o, rb, lod.copied().unwrap_or_default(), pl.is_some() || dl.is_some()))
+            .filter(|(_, _, _, f_opt, ..)| f_opt.is_none_or(|flags| !flags.get_bit(INVISIBLE)))
+            .filter(|(_, pos, ..)| render_dist.is_none_or(|dist| (pos.data() - cam_pos).norm() <= dist))
+            .map(|(e, p, rndrbl, f_opt, s, o, rb, lod, pl, dl)| (e, p, rndrbl, f_opt, s, o, rb, lod.copied().unwrap_or_default(), pl.is_some() || dl.is_some()))
         {
             let mesh_handle = renderable.mesh_type.mesh_handle();
+            let lod = if lod == LOD::Auto {
+                let level = self.auto_lod_level(entity_manager, *entity, mesh_handle, position, scale);
+                auto_lod_levels.insert(*entity, level);
+                LOD::from_level(level)
+            } else {
+                lod
+            };
             let opt_mesh = entity_manager.mesh_from_handle(mesh_handle, lod);
             if opt_mesh.is_none() {
                 continue;
             }
             let mesh = opt_mesh.unwrap();
-            let is_base_lod = lod == LOD::None;
+            let is_base_lod = lod.level() == 0;
 
             let trafo = calc_model_matrix(
                 position,
@@ -262,6 +286,7 @@ impl RenderingSystem {
                 &rb.copied().unwrap_or_default().center_of_mass,
             );
 
+            let material_tex_id = |name: Option<&str>| name.and_then(|name| entity_manager.texture_map.get_material_tex_id(name)).unwrap_or(self.white_texture);
             let default_material = Material::default();
             let material = match &renderable.material_source {
                 MaterialSource::Custom(mtl) => mtl,
@@ -269,11 +294,25 @@ impl RenderingSystem {
                 MaterialSource::Inherit => mesh.material_name.as_ref().and_then(|name| entity_manager.material_from_name(name)).unwrap_or(&default_material),
             };
 
+            // skinned meshes are deformed per entity, so every animated entity needs its own renderer
+            let joint_matrices = mesh
+                .skin
+                .as_ref()
+                .and(entity_manager.get_component::<SkeletalAnimator>(*entity))
+                .map(|animator| animator.skinning_matrices());
+            // morphed meshes share a renderer with all entities that use the same weights
+            let morph_weights = (!mesh.morph_targets.is_empty()).then(|| {
+                entity_manager
+                    .get_component::<MorphWeights>(*entity)
+                    .map_or(&[][..], |morph_weights| morph_weights.weights.as_slice())
+            });
+
             let render_data = RenderData {
                 spec: RenderSpec {
                     mesh_type: renderable.mesh_type,
                     shader_type: renderable.shader_type,
                     lod,
+                    skinned_entity: joint_matrices.is_some().then_some(*entity),
                     render_attributes: RenderAttributes {
                         tex_id: renderable.mesh_attribute.texture().and_then(|texture| entity_manager.texture_map.get_tex_id(texture)).unwrap_or(self.white_texture),
                         material_data: MaterialData {
@@ -281,17 +320,20 @@ impl RenderingSystem {
                             diffuse_color: material.diffuse_color_val().unwrap_or(vec3(1.0, 1.0, 1.0)),
                             specular_color: material.specular_color_val().unwrap_or(vec3(1.0, 1.0, 1.0)),
                             shininess: material.shininess,
+                            pbr: material.pbr.as_ref().filter(|_| self.shader_catalog.supports_pbr).map(PbrMaterialData::from),
                         },
-                        ambient_tex_id: material.ambient_texture(is_base_lod).and_then(|name| entity_manager.texture_map.get_material_tex_id(name)).unwrap_or(self.white_texture),
-                        diffuse_tex_id: material.diffuse_texture(is_base_lod).and_then(|name| entity_manager.texture_map.get_material_tex_id(name)).unwrap_or(self.white_texture),
-                        specular_tex_id: material.specular_texture(is_base_lod).and_then(|name| entity_manager.texture_map.get_material_tex_id(name)).unwrap_or(self.white_texture),
-                        normal_tex_id: material.normal_texture(is_base_lod).and_then(|name| entity_manager.texture_map.get_material_tex_id(name)).unwrap_or(self.white_texture),
+                        ambient_tex_id: material_tex_id(material.ambient_texture(is_base_lod)),
+                        diffuse_tex_id: material_tex_id(material.diffuse_texture(is_base_lod)),
+                        specular_tex_id: material_tex_id(material.specular_texture(is_base_lod)),
+                        normal_tex_id: material_tex_id(material.normal_texture(is_base_lod)),
+                        pbr_tex_ids: material.pbr_textures(is_base_lod).map(material_tex_id),
                     },
                     is_light_source,
                 },
                 trafo: &trafo,
                 mesh,
                 mesh_handle,
+                mesh_revision: entity_manager.mesh_revision(mesh_handle),
                 transparent: match &renderable.mesh_attribute {
                     MeshAttribute::Colored(color) => color.a < 255,
                     MeshAttribute::Textured { texture, .. } => texture.is_transparent,
@@ -299,6 +341,8 @@ impl RenderingSystem {
                 draw_stencil_outline: flags.is_some_and(|f| f.get_bit(STENCIL_OUTLINE)),
                 color: renderable.mesh_attribute.color(is_base_lod).unwrap_or(Color32::WHITE).to_vec4() + Vec4::from_element(renderable.added_brightness),
                 outline_data: renderable.outline,
+                joint_matrices: joint_matrices.as_deref(),
+                morph_weights,
             };
 
             let invisible_cached = flags.is_some_and(|f| f.get_bit(INVISIBLE_CACHED));
@@ -309,6 +353,39 @@ impl RenderingSystem {
                 self.add_new_renderer(&render_data);
             }
         }
+        self.auto_lod_levels = auto_lod_levels;
+    }
+
+    /// selects the LOD level of an entity with ``LOD::Auto`` based on the level of the previous frame
+    fn auto_lod_level(
+        &self,
+        entity_manager: &EntityManager,
+        entity: EntityID,
+        mesh_handle: MeshHandle,
+        position: &Position,
+        scale: Option<&Scale>,
+    ) -> usize {
+        let default_thresholds = LODThresholds::default();
+        let thresholds = entity_manager
+            .lod_thresholds(mesh_handle)
+            .unwrap_or(&default_thresholds);
+        let distance = (position.data() - self.current_cam_config.0).norm();
+        let value = match thresholds.metric {
+            LODMetric::Distance => distance,
+            LODMetric::ScreenSize => {
+                let radius = entity_manager
+                    .mesh_from_handle(mesh_handle, LOD::None)
+                    .map_or(0.0, |mesh| mesh.max_reach.norm())
+                    * scale.map_or(1.0, |scale| scale.data().abs().max());
+                radius / (distance * (self.fov().to_radians() / 2.0).tan()).max(f32::EPSILON)
+            }
+        };
+        let current = self
+            .auto_lod_levels
+            .get(&entity)
+            .copied()
+            .unwrap_or_default();
+        thresholds.select_level(value, current, entity_manager.lod_count(mesh_handle))
     }
 
     /// confirms all of the added data in the renderers
@@ -329,18 +406,21 @@ impl RenderingSystem {
 
     /// render all sprite entities
     fn render_sprites(&mut self, entity_manager: &EntityManager) {
+        // creating shadow maps in this frame may have reset the frame buffer binding
+        self.bind_output_fbo();
         self.sprite_renderer.add_data(entity_manager);
         self.shader_catalog.sprite.use_program();
         self.sprite_renderer.render(self.white_texture);
         self.sprite_renderer.reset();
     }
 
-    /// renders the screen texture to the default OpenGL frame buffer
+    /// renders the screen texture to the output frame buffer
     fn render_screen_texture(&self) {
         self.screen_texture.unbind(
             &self.shader_catalog.bloom,
             self.post_processing_params.use_bloom,
             self.post_processing_params.bloom_iterations,
+            self.output_fbo(),
         );
         self.shader_catalog.screen.use_program();
         self.screen_texture.render();
@@ -484,8 +564,14 @@ impl RenderingSystem {
 
             let renderer_fits_spec = renderer_type.spec == rd.spec;
 
+            let morph_compatible = rd.morph_weights.is_none_or(|weights| {
+                renderer_type.renderer.morph_weights() == Some(weights)
+                    || !renderer_type.renderer.contains_data()
+            });
+
             if renderer_fits_spec
                 && stencil_compatible
+                && morph_compatible
                 && (rd.color == renderer_type.renderer.color
                     || !renderer_type.renderer.contains_data())
             {
@@ -494,6 +580,12 @@ impl RenderingSystem {
                         .renderer
                         .add_position(rd.trafo, rd.mesh, rd.outline_data);
                 }
+                if let Some(matrices) = rd.joint_matrices {
+                    renderer_type.renderer.set_joint_matrices(matrices);
+                }
+                if let Some(weights) = rd.morph_weights {
+                    renderer_type.renderer.set_morph_weights(rd.mesh, weights);
+                }
                 //renderer_type.contains_transparency = rd.transparent;
                 renderer_type.used = true;
                 renderer_type.draw_stencil_outline = rd.draw_stencil_outline;
@@ -509,6 +601,12 @@ impl RenderingSystem {
         let mut renderer = InstanceRenderer::new(rd.mesh, rd.color);
 
         renderer.add_position(rd.trafo, rd.mesh, rd.outline_data);
+        if let Some(matrices) = rd.joint_matrices {
+            renderer.set_joint_matrices(matrices);
+        }
+        if let Some(weights) = rd.morph_weights {
+            renderer.set_morph_weights(rd.mesh, weights);
+        }
 
         self.renderers.push(RendererType {
             spec: rd.spec,
@@ -517,6 +615,7 @@ impl RenderingSystem {
             contains_transparency: rd.transparent,
             draw_stencil_outline: rd.draw_stencil_outline,
             mesh_handle: rd.mesh_handle,
+            mesh_revision: rd.mesh_revision,
         });
 
         log::debug!(
@@ -706,8 +805,96 @@ impl RenderingSystem {
         });
     }
 
+    /// refreshes the buffers of renderers that contain outdated data of a replaced mesh, renderers for meshes or LODs that no longer exist are dropped
+    fn refresh_outdated_renderers(&mut self, entity_manager: &EntityManager) {
+        self.renderers.retain_mut(|r| {
+            let revision = entity_manager.mesh_revision(r.mesh_handle);
+            if r.mesh_revision == revision {
+                return true;
+            }
+            match entity_manager.mesh_from_handle(r.mesh_handle, r.spec.lod) {
+                Some(mesh) => {
+                    r.renderer.update_mesh(mesh);
+                    r.mesh_revision = revision;
+                    log::debug!(
+                        "Instance Renderer refreshed for replaced mesh {:?}.",
+                        mesh.name
+                    );
+                    true
+                }
+                None => {
+                    log::debug!("Instance Renderer removed for deleted mesh data.");
+                    false
+                }
+            }
+        });
+    }
+
+    /// recompiles all shaders with modified source files if shader hot reloading is enabled
+    fn hot_reload_shaders(&mut self) {
+        if self
+            .shader_watcher
+            .as_ref()
+            .is_none_or(|(_, watcher)| !watcher.is_due())
+        {
+            return;
+        }
+        let (shader_dir, watcher) = self.shader_watcher.as_mut().unwrap();
+        let changed = watcher.poll();
+        if changed.is_empty() {
+            return;
+        }
+        for shader in CatalogShader::ALL {
+            let (vertex, fragment, geometry) = shader.file_names();
+            let files: Vec<PathBuf> = [Some(vertex), Some(fragment), geometry]
+                .into_iter()
+                .flatten()
+                .map(|name| shader_dir.join(name))
+                .collect();
+            if !files.iter().any(|file| changed.contains(file)) {
+                continue;
+            }
+            let sources = files
+                .iter()
+                .map(std::fs::read_to_string)
+                .collect::<Result<Vec<_>, _>>();
+            match sources {
+                Ok(sources) => {
+                    self.shader_catalog.reload_shader(
+                        shader,
+                        ShaderSources {
+                            vertex: &sources[0],
+                            fragment: &sources[1],
+                            geometry: sources.get(2).map(String::as_str),
+                        },
+                    );
+                }
+                Err(msg) => log::warn!("Error reading shader files for {shader:?}, keeping the previous version: {msg}."),
+            }
+        }
+    }
+
+    /// Enables hot reloading of the built-in shaders from the given directory or disables it with ``None`` (default is ``None``). The directory should contain the shader files with the same names as in ``assets/shaders/`` of the engine. Modified shaders are recompiled during rendering and the previous version is kept if the compilation fails.
+    pub fn set_shader_hot_reloading(&mut self, shader_dir: Option<&Path>) {
+        self.shader_watcher = shader_dir.map(|dir| {
+            let mut watcher = FileWatcher::new(HOT_RELOAD_INTERVAL);
+            for shader in CatalogShader::ALL {
+                let (vertex, fragment, geometry) = shader.file_names();
+                for name in [Some(vertex), Some(fragment), geometry]
+                    .into_iter()
+                    .flatten()
+                {
+                    watcher.watch(dir.join(name));
+                }
+            }
+            (dir.to_path_buf(), watcher)
+        });
+        log::debug!("Set shader hot reloading: {shader_dir:?}.");
+    }
+
     /// clears the OpenGL viewport
     fn clear_gl_screen(&self) {
+        self.bind_output_fbo();
         let float_color = self.clear_color.to_vec4();
         unsafe {
             gl::ClearColor(float_color.x, float_color.y, float_color.z, float_color.w);
@@ -715,6 +902,33 @@ impl RenderingSystem {
         }
     }
 
+    /// the frame buffer that receives the final image (the default one if a window is used)
+    fn output_fbo(&self) -> GLuint {
+        self.offscreen_target
+            .as_ref()
+            .map_or(0, |target| target.fbo())
+    }
+
+    /// binds the frame buffer that receives the final image
+    fn bind_output_fbo(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.output_fbo());
+        }
+    }
+
+    /// renders into an offscreen frame buffer of the given size instead of the default one
+    pub(crate) fn use_offscreen_target(&mut self, width: u32, height: u32) -> Result<(), String> {
+        self.offscreen_target = Some(OffscreenTarget::new(width as GLsizei, height as GLsizei)?);
+        Ok(())
+    }
+
+    /// Reads back the last rendered frame as 8 bit RGBA pixels, starting with the top row. The image has the resolution that the headless engine was created with. This is only available in headless mode and returns ``None`` otherwise.
+    pub fn read_frame_pixels(&self) -> Option<Vec<u8>> {
+        self.offscreen_target
+            .as_ref()
+            .map(|target| target.read_pixels())
+    }
+
     /// event listening function for window resizes
     pub(crate) fn update_viewport_ratio(&mut self, viewport_ratio: f32) {
         self.perspective_camera.update_win_size(viewport_ratio);
@@ -796,6 +1010,17 @@ impl RenderingSystem {
         self.shadow_resolution
     }
 
+    /// Sets the world that is rendered (default is ``MAIN_WORLD``). If the world does not exist, only the background is rendered.
+    pub fn set_world(&mut self, world: WorldID) {
+        log::debug!("Set rendering world: {world:?}.");
+        self.world = world;
+    }
+
+    /// The world that is currently rendered.
+    pub fn world(&self) -> WorldID {
+        self.world
+    }
+
     pub(crate) fn on_cam_position_change(&mut self, event: &CamPositionChange) {
         let new_focus = event.new_pos + event.new_look;
 
@@ -823,6 +1048,7 @@ pub(crate) struct RenderAttributes {
     pub(crate) diffuse_tex_id: GLuint,
     pub(crate) specular_tex_id: GLuint,
     pub(crate) normal_tex_id: GLuint,
+    pub(crate) pbr_tex_ids: [GLuint; 5], // base color, metallic, roughness, occlusion, emissive
 }
 
 /// specifies what renderer to use for rendering an entity
@@ -831,6 +1057,7 @@ struct RenderSpec {
     mesh_type: MeshType,
     shader_type: ShaderType,
     lod: LOD,
+    skinned_entity: Option<EntityID>,
     render_attributes: RenderAttributes,
     is_light_source: bool,
 }
@@ -843,6 +1070,7 @@ struct RendererType {
     contains_transparency: bool,
     draw_stencil_outline: bool,
     mesh_handle: MeshHandle,
+    mesh_revision: u64,
 }
 
 /// data bundle for rendering
@@ -851,10 +1079,13 @@ struct RenderData<'a> {
     trafo: &'a Mat4,
     mesh: &'a Mesh,
     mesh_handle: MeshHandle,
+    mesh_revision: u64,
     transparent: bool,
     draw_stencil_outline: bool,
     color: Vec4,
     outline_data: OutlineData,
+    joint_matrices: Option<&'a [Mat4]>,
+    morph_weights: Option<&'a [f32]>,
 }
 
 /// generates a 1x1 white texture, the user of the returned texture id is responsible for deleting it
@@ -1,4 +1,8 @@
 use crate::internal_prelude::*;
+#[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+use glutin::api::egl;
+#[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+use glutin::config::{Api, ConfigSurfaceTypes};
 use glutin::config::{Config, ConfigTemplateBuilder};
 use glutin::context::{
     ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentContext, PossiblyCurrentContext,
@@ -6,6 +10,8 @@ use glutin::context::{
 };
 use glutin::display::GetGlDisplay;
 use glutin::prelude::*;
+#[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+use glutin::surface::{PbufferSurface, SurfaceAttributesBuilder};
 use glutin::surface::{Surface, SwapInterval, WindowSurface};
 use glutin_winit::{DisplayBuilder, GlWindow};
 use raw_window_handle::HasWindowHandle;
@@ -23,6 +29,9 @@ pub struct VideoSystem {
     pub(crate) gl_context: Option<PossiblyCurrentContext>,
     pub(crate) gl_surface: Option<Surface<WindowSurface>>,
     pub(crate) window: Option<Window>,
+    #[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+    headless_context: Option<HeadlessContext>,
+    headless_size: Option<PhysicalSize<u32>>,
     current_fps: f64,
     last_draw_time: Instant,
     bg_fps_cap: Option<f64>,
@@ -54,6 +63,9 @@ impl VideoSystem {
             gl_context: None,
             gl_surface: None,
             window: None,
+            #[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+            headless_context: None,
+            headless_size: None,
             current_fps: 0f64,
             last_draw_time: Instant::now(),
             bg_fps_cap: config.bg_fps_cap,
@@ -173,6 +185,47 @@ impl VideoSystem {
         Ok(())
     }
 
+    /// creates an offscreen gl context without a window, surfaceless egl is preferred over a pbuffer surface
+    #[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+    pub(crate) fn create_headless_context(&mut self) -> Result<(), Box<dyn Error>> {
+        let (width, height) = self.stored_config.size;
+        if width == 0 || height == 0 {
+            return Err("The headless resolution can not be zero.".into());
+        }
+
+        let mut last_error: Box<dyn Error> =
+            "No EGL device available for headless rendering.".into();
+        for device in egl::device::Device::query_devices()? {
+            match create_headless_egl_context(&device, width, height) {
+                Ok(context) => {
+                    log::info!(
+                        "Created headless rendering context on device {:?}.",
+                        device.name().unwrap_or("unknown")
+                    );
+                    log_gl_config();
+                    unsafe {
+                        gl::Viewport(0, 0, width as GLsizei, height as GLsizei);
+                        gl::Scissor(0, 0, width as GLsizei, height as GLsizei);
+                    }
+                    self.headless_context = Some(context);
+                    self.headless_size = Some(PhysicalSize::new(width, height));
+                    return Ok(());
+                }
+                Err(err) => {
+                    log::debug!("Skipping EGL device for headless rendering: {err}");
+                    last_error = err;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    /// headless rendering needs egl
+    #[cfg(any(target_vendor = "apple", target_family = "wasm"))]
+    pub(crate) fn create_headless_context(&mut self) -> Result<(), Box<dyn Error>> {
+        Err("Headless rendering is not supported on this platform.".into())
+    }
+
     /// called when the engine application is suspended
     pub(crate) fn on_suspended(&mut self) {
         // this event is only raised on Android, where the backing NativeWindow for a GL Surface can appear and disappear at any moment
@@ -336,16 +389,27 @@ impl VideoSystem {
         self.current_fps
     }
 
-    /// Gets the current inner window resolution in physical size pixels (width, height).
+    /// Gets the current inner window resolution in physical size pixels (width, height). In headless mode, this is the offscreen resolution.
     pub fn window_resolution(&self) -> PhysicalSize<u32> {
+        if let Some(size) = self.headless_size {
+            return size;
+        }
         self.window.as_ref().unwrap().inner_size()
     }
 
-    /// Gets the current DPI scale factor of the window.
+    /// Gets the current DPI scale factor of the window (always 1 in headless mode).
     pub fn dpi_scale(&self) -> f64 {
+        if self.headless_size.is_some() {
+            return 1.0;
+        }
         self.window.as_ref().unwrap().scale_factor()
     }
 
+    /// Checks wether or not the engine renders offscreen without a window.
+    pub fn is_headless(&self) -> bool {
+        self.headless_size.is_some()
+    }
+
     /// Sets the optional FPS cap value for the rendering process. This is the same setting that you could also specify in the ``EngineAttributes`` at start-up.
     pub fn set_fps_cap(&mut self, new_cap: Option<f64>) {
         log::trace!("Set FPS cap: {new_cap:?}.");
@@ -438,6 +502,72 @@ impl VideoSystem {
     }
 }
 
+/// keeps the offscreen egl context (and pbuffer surface if surfaceless contexts are unsupported) alive
+#[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+struct HeadlessContext {
+    _context: egl::context::PossiblyCurrentContext,
+    _surface: Option<egl::surface::Surface<PbufferSurface>>,
+}
+
+/// creates a current opengl core context on an egl device and loads the gl functions
+#[cfg(not(any(target_vendor = "apple", target_family = "wasm")))]
+fn create_headless_egl_context(
+    device: &egl::device::Device,
+    width: u32,
+    height: u32,
+) -> Result<HeadlessContext, Box<dyn Error>> {
+    let display = unsafe { egl::display::Display::with_device(device, None)? };
+
+    let config_template = ConfigTemplateBuilder::new()
+        .with_alpha_size(8)
+        .with_stencil_size(8)
+        .with_api(Api::OPENGL)
+        .with_surface_type(ConfigSurfaceTypes::empty())
+        .build();
+    let configs: Vec<_> = unsafe { display.find_configs(config_template)? }.collect();
+    // a pbuffer capable config keeps the fallback open
+    let config = configs
+        .iter()
+        .find(|config| {
+            config
+                .config_surface_types()
+                .contains(ConfigSurfaceTypes::PBUFFER)
+        })
+        .or(configs.first())
+        .ok_or("No suitable EGL config found.")?;
+
+    let context_attributes = ContextAttributesBuilder::new()
+        .with_profile(GlProfile::Core)
+        .with_context_api(ContextApi::OpenGl(None))
+        .build(None);
+
+    let not_current_context = unsafe { display.create_context(config, &context_attributes)? };
+    let (context, surface) = match not_current_context.make_current_surfaceless() {
+        Ok(context) => (context, None),
+        Err(err) => {
+            log::debug!("Surfaceless EGL context unavailable ({err}), using a pbuffer surface.");
+            let not_current_context =
+                unsafe { display.create_context(config, &context_attributes)? };
+            let attrs = SurfaceAttributesBuilder::<PbufferSurface>::new().build(
+                NonZeroU32::new(width).unwrap(),
+                NonZeroU32::new(height).unwrap(),
+            );
+            let surface = unsafe { display.create_pbuffer_surface(config, &attrs)? };
+            (not_current_context.make_current(&surface)?, Some(surface))
+        }
+    };
+
+    gl::load_with(|symbol| {
+        let symbol = CString::new(symbol).unwrap();
+        display.get_proc_address(symbol.as_c_str()).cast()
+    });
+
+    Ok(HeadlessContext {
+        _context: context,
+        _surface: surface,
+    })
+}
+
 /// prints info about the used gl renderer
 fn log_gl_config() {
     if let Some(renderer) = get_gl_string(gl::RENDERER) {
@@ -11,6 +11,7 @@ pub(crate) const MAX_TEXTURE_COUNT: usize = 32;
 pub(crate) const MAX_DIR_LIGHT_MAPS: usize = 5;
 pub(crate) const MAX_POINT_LIGHT_MAPS: usize = 5;
 pub(crate) const MAX_POINT_LIGHT_COUNT: usize = 20; // includes the point lights with shadow maps
+pub(crate) const PBR_MTL_TEXTURE_KEYS: [&str; 3] = ["map_Pm", "map_Pr", "map_Ke"];
 pub(crate) const NEAR_PLANE: f32 = 0.1;
 pub(crate) const FAR_PLANE: f32 = 100.0;
 pub(crate) const FAR_PLANE_SPRITE: f32 = 2.0;
@@ -20,6 +21,11 @@ pub(crate) const COMPONENT_COLUMN_INIT_SIZE: usize = 100;
 pub(crate) const ENTITY_TYPE_STACK_ALLOCATION: usize = 16;
 pub(crate) const COMPONENT_STACK_ALLOCATION_BYTES: usize = 64;
 
+/// the maximum number of worker threads used for asynchronous asset loading
+pub(crate) const MAX_LOADING_THREADS: usize = 4;
+/// the minimum time between two checks for modified asset files when hot reloading is enabled
+pub(crate) const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
 /// a single time step in the animation system
 pub(crate) const TIME_STEP: TimeDuration = TimeDuration(0.002);
 
@@ -34,8 +40,14 @@ pub const Z_AXIS: Vec3 = Vec3::new(0.0, 0.0, 1.0);
 /// Placeholder for an empty entity slot (will never point to an entity).
 pub const NO_ENTITY: EntityID = 0;
 
-/// The number of mesh LOD's available (includes the base mesh aside from simplified versions).
-pub const NUM_LODS: i32 = 5;
+/// The world that exists from the start and is processed by all systems by default (can not be deleted).
+pub const MAIN_WORLD: WorldID = 0;
+
+/// The highest LOD level that can be generated and rendered.
+pub const MAX_LOD_LEVEL: usize = u8::MAX as usize;
+
+/// The highest voxel resolution of a ``DecompositionSettings`` (higher resolutions are clamped to this).
+pub const MAX_DECOMPOSITION_RESOLUTION: usize = 256;
 
 /// Contains all built-in bit flag constants to be used with the ``EntityFlags`` component.
 pub mod bits {
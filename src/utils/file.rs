@@ -1,7 +1,8 @@
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
 use crate::internal_prelude::*;
-use stb_image::image::{load_with_depth, Image, LoadResult};
+use crate::utils::vfs::read_file;
+use stb_image::image::{load_from_memory_with_depth, Image, LoadResult};
 
 // directory paths
 macro_rules! shader_path {
@@ -48,6 +49,7 @@ pub(crate) const BLUR_VERT: &str = include_str!(shader_path!("blur.vert"));
 pub(crate) const BLUR_FRAG: &str = include_str!(shader_path!("blur.frag"));
 pub(crate) const OUTLINE_VERT: &str = include_str!(shader_path!("outline.vert"));
 pub(crate) const OUTLINE_FRAG: &str = include_str!(shader_path!("outline.frag"));
+pub(crate) const SKINNING_GLSL: &str = include_str!(shader_path!("skinning.glsl"));
 
 // included meshes
 pub(crate) const TRIANGLE_MESH: &[u8] = include_bytes!(model_path!("triangle.obj"));
@@ -121,9 +123,14 @@ pub(crate) const SPRITE_PLANE_INDICES: [GLuint; 6] = [0, 1, 2, 0, 3, 1];
 // audio data
 pub(crate) const HRTF_SPHERE: &[u8] = include_bytes!(audio_path!("IRC_1002_C.bin"));
 
-/// Loads an ``Image<u8>`` (probably from a ``.png`` file) with an alpha channel using stb_image from a given path.
+/// Loads an ``Image<u8>`` (probably from a ``.png`` file) with an alpha channel using stb_image from a given path. The path is resolved through the virtual file system.
 pub fn stbi_load_u8_rgba(file_path: impl AsRef<Path>) -> Option<Image<u8>> {
-    match load_with_depth(file_path, 4, false) {
+    read_file(file_path).ok().and_then(|bytes| stbi_load_u8_rgba_from_memory(&bytes))
+}
+
+/// Loads an ``Image<u8>`` with an alpha channel using stb_image from encoded image file data in memory.
+pub fn stbi_load_u8_rgba_from_memory(bytes: &[u8]) -> Option<Image<u8>> {
+    match load_from_memory_with_depth(bytes, 4, false) {
         LoadResult::ImageU8(im) => Some(im),
         _ => None,
     }
@@ -0,0 +1,61 @@
+use crate::internal_prelude::*;
+use std::time::SystemTime;
+
+/// polls the modification times of a set of files to detect changes on every platform
+pub(crate) struct FileWatcher {
+    files: AHashMap<PathBuf, Option<SystemTime>>,
+    interval: Duration,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    /// creates a new watcher that checks the files at most once per interval
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            files: AHashMap::new(),
+            interval,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// starts watching a file if not already watched
+    pub(crate) fn watch(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if !self.files.contains_key(path) {
+            self.files
+                .insert(path.to_path_buf(), modification_time(path));
+            log::trace!("Watching file {path:?} for changes.");
+        }
+    }
+
+    /// stops watching all files that are not in the given set
+    pub(crate) fn retain(&mut self, keep: impl Fn(&Path) -> bool) {
+        self.files.retain(|path, _| keep(path));
+    }
+
+    /// checks wether or not the poll interval has passed since the last poll
+    pub(crate) fn is_due(&self) -> bool {
+        self.last_poll.elapsed() >= self.interval
+    }
+
+    /// returns all files that were modified since the last poll (a file that can not be accessed is not considered modified)
+    pub(crate) fn poll(&mut self) -> Vec<PathBuf> {
+        self.last_poll = Instant::now();
+        let mut changed = Vec::new();
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = modification_time(path);
+            if modified.is_some() && modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// the last modification time of a file if accessible
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
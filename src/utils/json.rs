@@ -0,0 +1,237 @@
+use crate::internal_prelude::*;
+use std::str::Chars;
+
+/// maximum nesting depth of arrays and objects, deeper documents are rejected to not overflow the stack
+const MAX_DEPTH: usize = 128;
+
+/// minimal json value representation that is used for parsing asset description files
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(AHashMap<String, JsonValue>),
+}
+
+impl JsonValue {
+    /// parses a complete json document
+    pub(crate) fn parse(source: &str) -> Option<Self> {
+        let mut parser = JsonParser {
+            chars: source.chars(),
+            peeked: None,
+        };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        parser.peek().is_none().then_some(value)
+    }
+
+    /// gets a member of an object value
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            Self::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    /// gets the array of an object member or an empty slice if not present
+    pub(crate) fn array(&self, key: &str) -> &[JsonValue] {
+        self.get(key).and_then(|v| v.as_array()).unwrap_or(&[])
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|n| n as f32)
+    }
+
+    pub(crate) fn as_usize(&self) -> Option<usize> {
+        self.as_f64()
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+            .map(|n| n as usize)
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            Self::Array(values) => Some(values.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// converts a number array to floats if all the values are numbers
+    pub(crate) fn as_f32_vec(&self) -> Option<Vec<f32>> {
+        self.as_array()?.iter().map(|v| v.as_f32()).collect()
+    }
+}
+
+/// recursive descent parser state
+struct JsonParser<'a> {
+    chars: Chars<'a>,
+    peeked: Option<char>,
+}
+
+impl JsonParser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        if self.peeked.is_none() {
+            self.peeked = self.chars.next();
+        }
+        self.peeked
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.peek();
+        self.peeked.take()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect_literal(&mut self, literal: &str, value: JsonValue) -> Option<JsonValue> {
+        literal
+            .chars()
+            .all(|c| self.next() == Some(c))
+            .then_some(value)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Option<JsonValue> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' | '[' if depth >= MAX_DEPTH => {
+                log::warn!("Json document exceeds the maximum nesting depth of {MAX_DEPTH}.");
+                None
+            }
+            '{' => self.parse_object(depth + 1),
+            '[' => self.parse_array(depth + 1),
+            '"' => self.parse_string().map(JsonValue::String),
+            't' => self.expect_literal("true", JsonValue::Bool(true)),
+            'f' => self.expect_literal("false", JsonValue::Bool(false)),
+            'n' => self.expect_literal("null", JsonValue::Null),
+            _ => self.parse_number(),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Option<JsonValue> {
+        self.next();
+        let mut members = AHashMap::new();
+        self.skip_whitespace();
+        if self.peek()? == '}' {
+            self.next();
+            return Some(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if self.next()? != ':' {
+                return None;
+            }
+            let value = self.parse_value(depth)?;
+            members.insert(key, value);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Some(JsonValue::Object(members)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Option<JsonValue> {
+        self.next();
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == ']' {
+            self.next();
+            return Some(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value(depth)?);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Some(JsonValue::Array(values)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        if self.next()? != '"' {
+            return None;
+        }
+        let mut string = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(string),
+                '\\' => match self.next()? {
+                    '"' => string.push('"'),
+                    '\\' => string.push('\\'),
+                    '/' => string.push('/'),
+                    'b' => string.push('\u{8}'),
+                    'f' => string.push('\u{c}'),
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    'u' => {
+                        let mut code = self.parse_hex_code()?;
+                        if (0xD800..0xDC00).contains(&code) {
+                            // surrogate pair
+                            if self.next()? != '\\' || self.next()? != 'u' {
+                                return None;
+                            }
+                            let low = self.parse_hex_code()?;
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low.checked_sub(0xDC00)?);
+                        }
+                        string.push(char::from_u32(code)?);
+                    }
+                    _ => return None,
+                },
+                c => string.push(c),
+            }
+        }
+    }
+
+    fn parse_hex_code(&mut self) -> Option<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            code = code * 16 + self.next()?.to_digit(16)?;
+        }
+        Some(code)
+    }
+
+    fn parse_number(&mut self) -> Option<JsonValue> {
+        let mut literal = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                literal.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        literal.parse::<f64>().ok().map(JsonValue::Number)
+    }
+}
@@ -0,0 +1,389 @@
+use crate::ecs::asset_loader::LoadedAssetFile;
+use crate::internal_prelude::*;
+use crate::rendering::mesh::{Hitbox, Mesh};
+use crate::rendering::mesh_cache::MeshCache;
+use std::fmt::Write as _;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Exit code of the mesh tool if all files were processed successfully.
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit code of the mesh tool if at least one file could not be loaded or processed or an output could not be written.
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code of the mesh tool if the command line arguments are invalid.
+pub const EXIT_USAGE: u8 = 2;
+/// Exit code of the mesh tool in strict mode if at least one mesh failed the validation (and no other error occured).
+pub const EXIT_INVALID_MESH: u8 = 3;
+
+const USAGE: &str = "\
+Usage: leafy-meshtool [OPTIONS] <INPUT>...
+
+Processes .obj files offline. Every input is either a model file or a directory
+that is searched for .obj files (batch mode). A stats report with triangle counts,
+bounds, mass properties and validation results is printed for every mesh.
+Exporting fails if two input files with the same name would write to the same
+output directory.
+
+Options:
+  -o, --output <DIR>         Directory for the exported files (default: current directory)
+  -r, --recursive            Also search the subdirectories of input directories
+      --lods                 Export the generated LODs as .obj files
+      --hulls                Export the convex hull and simplified convex hull as .obj files
+      --cache <DIR>          Write the binary mesh cache (meshes, LODs and hulls) to the directory
+      --report <FILE>        Also write the stats report to a file
+      --levels <R,R,...>     LOD levels as triangle ratios (e.g. 0.5,0.25)
+      --triangles <N,N,...>  LOD levels as absolute triangle counts
+      --max-error <ERROR>    Maximum simplification error per LOD
+      --preserve-boundaries  Keep open borders in place during simplification
+      --preserve-uv-seams    Keep texture coordinate seams in place during simplification
+      --preserve-color-seams Keep vertex color seams in place during simplification
+      --density <DENSITY>    Density used for the mass properties (default: 1)
+      --strict               Exit with code 3 if a mesh fails the validation
+  -q, --quiet                Do not print the report
+  -h, --help                 Print this help
+
+Exit codes: 0 success, 1 processing error, 2 invalid arguments, 3 invalid mesh (strict mode)";
+
+/// the parsed command line options of the mesh tool
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    recursive: bool,
+    lods: bool,
+    hulls: bool,
+    cache: Option<PathBuf>,
+    report: Option<PathBuf>,
+    settings: SimplificationSettings,
+    density: f32,
+    strict: bool,
+    quiet: bool,
+}
+
+impl Options {
+    /// parses the command line arguments, yields ``None`` if only the help should be printed
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self {
+            inputs: Vec::new(),
+            output: PathBuf::from("."),
+            recursive: false,
+            lods: false,
+            hulls: false,
+            cache: None,
+            report: None,
+            settings: SimplificationSettings::default(),
+            density: 1.0,
+            strict: false,
+            quiet: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {arg}."))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-o" | "--output" => options.output = value()?.into(),
+                "-r" | "--recursive" => options.recursive = true,
+                "--lods" => options.lods = true,
+                "--hulls" => options.hulls = true,
+                "--cache" => options.cache = Some(value()?.into()),
+                "--report" => options.report = Some(value()?.into()),
+                "--levels" => {
+                    let levels = parse_list::<f32>(&value()?)?;
+                    if levels.iter().any(|ratio| !(0.0..=1.0).contains(ratio)) {
+                        return Err("LOD ratios must be between 0 and 1.".into());
+                    }
+                    options.settings.levels = levels
+                        .into_iter()
+                        .map(SimplificationTarget::Ratio)
+                        .collect();
+                }
+                "--triangles" => {
+                    options.settings.levels = parse_list::<usize>(&value()?)?
+                        .into_iter()
+                        .map(SimplificationTarget::TriangleCount)
+                        .collect();
+                }
+                "--max-error" => options.settings.max_error = Some(parse_value(&value()?)?),
+                "--preserve-boundaries" => options.settings.preserve_boundaries = true,
+                "--preserve-uv-seams" => options.settings.preserve_uv_seams = true,
+                "--preserve-color-seams" => options.settings.preserve_color_seams = true,
+                "--density" => options.density = parse_value(&value()?)?,
+                "--strict" => options.strict = true,
+                "-q" | "--quiet" => options.quiet = true,
+                _ if arg.starts_with('-') => return Err(format!("Unknown option {arg}.")),
+                _ => options.inputs.push(arg.into()),
+            }
+        }
+        if options.inputs.is_empty() {
+            return Err("No input files given.".into());
+        }
+        Ok(Some(options))
+    }
+}
+
+/// parses a single command line value
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value {value:?}."))
+}
+
+/// parses a comma separated list of values
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(parse_value).collect()
+}
+
+/// collects the model files of an input together with the output directory of each file, directories are searched for ``.obj`` files
+fn collect_files(
+    input: &Path,
+    output: &Path,
+    recursive: bool,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    if input.is_file() {
+        return Ok(vec![(input.to_path_buf(), output.to_path_buf())]);
+    }
+    if !input.is_dir() {
+        return Err(format!("Input {input:?} does not exist."));
+    }
+    let mut files = Vec::new();
+    let mut entries = std::fs::read_dir(input)
+        .map_err(|error| format!("Error reading directory {input:?}: {error}"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect_vec();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() && recursive {
+            files.extend(collect_files(
+                &path,
+                &output.join(path.file_name().unwrap()),
+                true,
+            )?);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("obj"))
+        {
+            files.push((path, output.to_path_buf()));
+        }
+    }
+    Ok(files)
+}
+
+/// checks if multiple model files would be exported to the same files (e.g. files with the same name in different input directories) and reports them
+fn has_output_collisions(files: &[(PathBuf, PathBuf)]) -> bool {
+    let mut exported_files = AHashMap::new();
+    let mut collision = false;
+    for (file, output) in files.iter() {
+        let stem = output.join(file.file_stem().unwrap_or_default());
+        if let Some(other) = exported_files.insert(stem, file) {
+            eprintln!("error: {other:?} and {file:?} would be exported to the same files");
+            collision = true;
+        }
+    }
+    collision
+}
+
+/// makes a mesh name usable as part of a file name
+fn file_name_part(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// writes an exported file and creates the parent directories
+fn write_output(path: &Path, content: String) -> Result<(), String> {
+    std::fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| std::fs::write(path, content))
+        .map_err(|error| format!("Error writing {path:?}: {error}"))
+}
+
+/// appends the stats of a mesh to the report and yields wether or not the mesh passed the validation
+fn mesh_stats(mesh: &Mesh, lods: &[Mesh], density: f32, report: &mut String) -> bool {
+    let validation = mesh.validate();
+    let (min, max) = mesh.positions.iter().fold(
+        (Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+        |(min, max), position| (min.inf(position), max.sup(position)),
+    );
+    let _ = writeln!(
+        report,
+        "  mesh {:?}: {} vertices, {} triangles",
+        mesh.name, validation.vertex_count, validation.triangle_count
+    );
+    let _ = writeln!(
+        report,
+        "    bounds: min [{}, {}, {}], max [{}, {}, {}]",
+        min.x, min.y, min.z, max.x, max.y, max.z
+    );
+    let (tensor, center_of_mass, mass) = mesh.inertia_tensor(density, &Scale::default());
+    if validation.is_closed() && mass.abs() > f32::EPSILON && mass.is_finite() {
+        let _ = writeln!(
+            report,
+            "    volume: {}, mass: {mass}, center of mass: [{}, {}, {}]",
+            mass / density,
+            center_of_mass.x,
+            center_of_mass.y,
+            center_of_mass.z
+        );
+        let rows = tensor
+            .row_iter()
+            .map(|row| format!("[{}, {}, {}]", row[0], row[1], row[2]))
+            .join(", ");
+        let _ = writeln!(report, "    inertia tensor: [{rows}]");
+    } else {
+        let _ = writeln!(report, "    mass properties: unavailable for open meshes");
+    }
+    if !lods.is_empty() {
+        let counts = lods.iter().map(|lod| lod.num_indices() / 3).join(", ");
+        let _ = writeln!(report, "    lod triangles: {counts}");
+    }
+    let issues = [
+        (validation.duplicate_vertices, "duplicate vertices"),
+        (validation.degenerate_faces, "degenerate faces"),
+        (validation.boundary_edges, "boundary edges"),
+        (validation.non_manifold_edges, "non-manifold edges"),
+        (validation.inconsistent_edges, "inconsistent edges"),
+        (validation.flipped_faces, "flipped faces"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, issue)| format!("{count} {issue}"))
+    .join(", ");
+    let state = if validation.is_valid() {
+        "valid"
+    } else {
+        "invalid"
+    };
+    if issues.is_empty() {
+        let _ = writeln!(report, "    validation: {state}");
+    } else {
+        let _ = writeln!(report, "    validation: {state} ({issues})");
+    }
+    validation.is_valid()
+}
+
+/// processes all meshes of a model file, appends their stats to the report and yields wether or not all meshes are valid
+fn process_file(
+    file: &Path,
+    output: &Path,
+    options: &Options,
+    cache: Option<&MeshCache>,
+    report: &mut String,
+) -> Result<bool, String> {
+    let meshes = LoadedAssetFile::load(file, cache)?.meshes;
+    if meshes.is_empty() {
+        return Err(format!("No meshes found in {file:?}."));
+    }
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let _ = writeln!(report, "{}", file.display());
+    let mut all_valid = true;
+    for mesh in meshes.iter() {
+        let prefix = format!("{stem}_{}", file_name_part(&mesh.name));
+        let lods = if options.lods || options.cache.is_some() {
+            MeshCache::lods_or_generate(cache, mesh, &options.settings)
+        } else {
+            Vec::new()
+        };
+        if options.lods {
+            for (level, lod) in lods.iter().enumerate() {
+                let path = output.join(format!("{prefix}_lod{}.obj", level + 1));
+                write_output(&path, lod.to_obj(None))?;
+            }
+        }
+        if options.hulls || options.cache.is_some() {
+            for (hitbox_type, suffix) in [
+                (HitboxType::ConvexHull, "hull"),
+                (HitboxType::SimplifiedConvexHull, "simplified_hull"),
+            ] {
+                let hitbox = MeshCache::hitbox_or_generate(cache, mesh, hitbox_type);
+                if let (true, Hitbox::ConvexMesh(hull)) = (options.hulls, hitbox) {
+                    let path = output.join(format!("{prefix}_{suffix}.obj"));
+                    write_output(&path, hull.to_obj(&format!("{}_{suffix}", mesh.name), 0))?;
+                }
+            }
+        }
+        all_valid &= mesh_stats(mesh, &lods, options.density, report);
+    }
+    Ok(all_valid)
+}
+
+/// Runs the mesh tool with the given command line arguments (without the program name) and returns the exit code. This is the entry point of the ``leafy-meshtool`` binary.
+pub fn run(args: impl IntoIterator<Item = String>) -> u8 {
+    let options = match Options::parse(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return EXIT_SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            return EXIT_USAGE;
+        }
+    };
+    let cache = options.cache.as_deref().map(MeshCache::new);
+
+    let mut failed = false;
+    let mut files = Vec::new();
+    for input in options.inputs.iter() {
+        match collect_files(input, &options.output, options.recursive) {
+            Ok(input_files) if input_files.is_empty() => {
+                eprintln!("warning: no .obj files found in {input:?}");
+            }
+            Ok(input_files) => files.extend(input_files),
+            Err(msg) => {
+                eprintln!("error: {msg}");
+                failed = true;
+            }
+        }
+    }
+    if files.is_empty() && !failed {
+        eprintln!("error: no model files to process");
+        failed = true;
+    }
+    if (options.lods || options.hulls) && has_output_collisions(&files) {
+        return EXIT_FAILURE;
+    }
+
+    let mut report = String::new();
+    let mut all_valid = true;
+    for (file, output) in files.iter() {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            process_file(file, output, &options, cache.as_ref(), &mut report)
+        }));
+        match result {
+            Ok(Ok(valid)) => all_valid &= valid,
+            Ok(Err(msg)) => {
+                eprintln!("error: {msg}");
+                failed = true;
+            }
+            Err(_) => {
+                eprintln!("error: processing {file:?} failed unexpectedly");
+                failed = true;
+            }
+        }
+    }
+
+    if !options.quiet {
+        print!("{report}");
+    }
+    if let Some(path) = options.report.as_ref() {
+        if let Err(msg) = write_output(path, report) {
+            eprintln!("error: {msg}");
+            failed = true;
+        }
+    }
+    if failed {
+        EXIT_FAILURE
+    } else if options.strict && !all_valid {
+        EXIT_INVALID_MESH
+    } else {
+        EXIT_SUCCESS
+    }
+}
@@ -1,3 +1,9 @@
 pub mod constants;
 pub mod file;
+pub(crate) mod file_watcher;
+pub(crate) mod json;
+// only public for the leafy-meshtool binary
+#[doc(hidden)]
+pub mod meshtool;
 pub mod tools;
+pub mod vfs;
@@ -75,6 +75,33 @@ pub fn clamp_norm(v: Vec3, lb: f32, ub: f32) -> Vec3 {
     }
 }
 
+/// Decomposes an affine transform matrix into its translation, rotation and scale. Shearing is not preserved.
+pub fn decompose_transform(m: &Mat4) -> (Position, Orientation, Scale) {
+    let translation = m.column(3).xyz();
+    let mut axes = [
+        m.column(0).xyz().into_owned(),
+        m.column(1).xyz().into_owned(),
+        m.column(2).xyz().into_owned(),
+    ];
+    let mut scale = vec3(axes[0].norm(), axes[1].norm(), axes[2].norm());
+    // mirroring is represented by a negative scale in the first dimension
+    if axes[0].cross(&axes[1]).dot(&axes[2]) < 0.0 {
+        scale.x = -scale.x;
+    }
+    for (axis, factor) in axes.iter_mut().zip(scale.iter()) {
+        if factor.abs() > f32::EPSILON {
+            *axis /= *factor;
+        }
+    }
+    let rotation = Mat3::from_columns(&axes);
+
+    (
+        Position::from(translation),
+        Orientation(glm::mat3_to_quat(&rotation)),
+        Scale::from(scale),
+    )
+}
+
 /// Checks two types for equality.
 pub fn types_eq<A: ?Sized + 'static, B: ?Sized + 'static>() -> bool {
     TypeId::of::<A>() == TypeId::of::<B>()
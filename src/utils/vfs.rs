@@ -0,0 +1,354 @@
+use crate::internal_prelude::*;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::sync::{Mutex, RwLock};
+
+/// the magic bytes at the start of every archive file
+const ARCHIVE_MAGIC: &[u8; 8] = b"LEAFYPAK";
+/// the current version of the archive format
+const ARCHIVE_VERSION: u32 = 1;
+/// the compression level used for deflate (0-10)
+const COMPRESSION_LEVEL: u8 = 6;
+/// the maximum size ratio of deflate data, larger declared sizes are rejected
+const MAX_DEFLATE_RATIO: u64 = 1032;
+/// the size of an index entry with an empty name
+const MIN_ENTRY_SIZE: u64 = 2 + 8 * 3 + 1;
+
+/// all the mounted sources in order of priority (last mounted is checked first)
+static MOUNTS: RwLock<Vec<Mount>> = RwLock::new(Vec::new());
+
+/// a source of files in the virtual file system
+enum Mount {
+    Directory(PathBuf),
+    Archive(Arc<AssetArchive>),
+}
+
+/// Mounts a directory in the virtual file system. Relative paths are resolved against it before falling back to the regular file system.
+pub fn mount_directory(dir: impl AsRef<Path>) {
+    let dir = dir.as_ref();
+    MOUNTS
+        .write()
+        .unwrap()
+        .push(Mount::Directory(dir.to_path_buf()));
+    log::debug!("Mounted directory {dir:?} in the virtual file system.");
+}
+
+/// Mounts a packed asset archive in the virtual file system. Paths are looked up in the archive index before falling back to the regular file system. Returns wether or not the archive could be opened.
+pub fn mount_archive(archive_path: impl AsRef<Path>) -> bool {
+    let archive_path = archive_path.as_ref();
+    match AssetArchive::open(archive_path) {
+        Ok(archive) => {
+            log::debug!(
+                "Mounted archive {archive_path:?} with {} files in the virtual file system.",
+                archive.entries.len()
+            );
+            MOUNTS
+                .write()
+                .unwrap()
+                .push(Mount::Archive(Arc::new(archive)));
+            true
+        }
+        Err(msg) => {
+            log::error!("Error mounting archive {archive_path:?}: {msg}");
+            false
+        }
+    }
+}
+
+/// Removes all mounted directories and archives from the virtual file system.
+pub fn unmount_all() {
+    MOUNTS.write().unwrap().clear();
+    log::debug!("Unmounted all sources of the virtual file system.");
+}
+
+/// Reads the full contents of a file through the virtual file system. The mounted sources are checked from the most recently mounted one and the path is used directly if no source contains the file.
+pub fn read_file(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    for mount in MOUNTS.read().unwrap().iter().rev() {
+        match mount {
+            Mount::Directory(dir) => {
+                if path.is_relative() && dir.join(path).is_file() {
+                    return std::fs::read(dir.join(path));
+                }
+            }
+            Mount::Archive(archive) => {
+                if let Some(entry) = archive.entries.get(&archive_key(path)) {
+                    return archive.read_entry(entry);
+                }
+            }
+        }
+    }
+    std::fs::read(path)
+}
+
+/// Reads a file through the virtual file system and converts it to a ``String`` (see ``read_file``).
+pub fn read_file_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    String::from_utf8(read_file(path)?).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Checks wether or not a file is present in any of the mounted sources or in the regular file system.
+pub fn file_exists(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    MOUNTS.read().unwrap().iter().any(|mount| match mount {
+        Mount::Directory(dir) => path.is_relative() && dir.join(path).is_file(),
+        Mount::Archive(archive) => archive.entries.contains_key(&archive_key(path)),
+    }) || path.is_file()
+}
+
+/// normalizes a path to the form stored in the archive index (forward slashes, no ``.`` components)
+fn archive_key(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy()),
+            std::path::Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .join("/")
+}
+
+/// location of a file in an archive
+struct ArchiveEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compressed: bool,
+}
+
+/// an opened archive file with its index
+struct AssetArchive {
+    file: Mutex<File>,
+    entries: AHashMap<String, ArchiveEntry>,
+}
+
+impl AssetArchive {
+    /// opens an archive and reads the index
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "not an asset archive",
+            ));
+        }
+        let version = read_u32(&mut file)?;
+        if version != ARCHIVE_VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported archive version {version}"),
+            ));
+        }
+        let file_len = file.metadata()?.len();
+        let invalid = |msg: String| io::Error::new(ErrorKind::InvalidData, msg);
+        let entry_count = read_u32(&mut file)?;
+        if entry_count as u64 > file_len / MIN_ENTRY_SIZE {
+            return Err(invalid(format!("invalid entry count {entry_count}")));
+        }
+        let mut entries = AHashMap::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let mut name_len = [0u8; 2];
+            file.read_exact(&mut name_len)?;
+            let mut name = vec![0u8; u16::from_le_bytes(name_len) as usize];
+            file.read_exact(&mut name)?;
+            let name =
+                String::from_utf8(name).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+            let entry = ArchiveEntry {
+                offset: read_u64(&mut file)?,
+                stored_size: read_u64(&mut file)?,
+                size: read_u64(&mut file)?,
+                compressed: {
+                    let mut flag = [0u8; 1];
+                    file.read_exact(&mut flag)?;
+                    flag[0] != 0
+                },
+            };
+            let max_size = if entry.compressed {
+                entry.stored_size.checked_mul(MAX_DEFLATE_RATIO)
+            } else {
+                Some(entry.stored_size)
+            };
+            let in_file = entry
+                .offset
+                .checked_add(entry.stored_size)
+                .is_some_and(|end| end <= file_len);
+            if !in_file || max_size.is_none_or(|max| entry.size > max) {
+                return Err(invalid(format!("invalid index entry for {name}")));
+            }
+            if usize::try_from(entry.size).is_err() {
+                return Err(invalid(format!("{name} is too large")));
+            }
+            entries.insert(name, entry);
+        }
+        Ok(Self {
+            file: Mutex::new(file),
+            entries,
+        })
+    }
+
+    /// reads and decompresses the data of one file
+    fn read_entry(&self, entry: &ArchiveEntry) -> io::Result<Vec<u8>> {
+        let mut data = vec![0u8; entry.stored_size as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut data)?;
+        }
+        if entry.compressed {
+            let data = decompress_to_vec_with_limit(&data, entry.size as usize)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, format!("{e:?}")))?;
+            if data.len() as u64 != entry.size {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "decompressed size does not match the index",
+                ));
+            }
+            Ok(data)
+        } else {
+            Ok(data)
+        }
+    }
+}
+
+/// Builder for packed asset archives that can be mounted in the virtual file system with ``mount_archive``. The files are stored under the paths they are added with, so the same path strings can be used to load them from a directory or the archive.
+pub struct ArchiveBuilder {
+    files: Vec<(String, Vec<u8>)>,
+    compression: bool,
+}
+
+impl ArchiveBuilder {
+    /// Creates a new empty archive builder with compression enabled.
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            compression: true,
+        }
+    }
+
+    /// Enables or disables the deflate compression of the stored files (default is ``true``). Files that do not get smaller are always stored uncompressed.
+    pub fn with_compression(mut self, flag: bool) -> Self {
+        self.compression = flag;
+        self
+    }
+
+    /// Adds a file from the regular file system that is stored under its path.
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        self.add_bytes(archive_key(path), std::fs::read(path)?);
+        Ok(self)
+    }
+
+    /// Adds all the files in a directory and its sub-directories that are stored under their paths.
+    pub fn with_directory(mut self, dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut dirs = vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    self.add_bytes(archive_key(&path), std::fs::read(&path)?);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Adds file data from memory that is stored under the given path.
+    pub fn with_bytes(mut self, path: impl AsRef<Path>, bytes: Vec<u8>) -> Self {
+        self.add_bytes(archive_key(path.as_ref()), bytes);
+        self
+    }
+
+    /// replaces a previously added file with the same path
+    fn add_bytes(&mut self, key: String, bytes: Vec<u8>) {
+        self.files.retain(|(other, _)| *other != key);
+        self.files.push((key, bytes));
+    }
+
+    /// Writes the archive to a file.
+    pub fn write(&self, archive_path: impl AsRef<Path>) -> io::Result<()> {
+        let stored: Vec<(&String, Vec<u8>, u64, bool)> = self
+            .files
+            .iter()
+            .map(|(key, bytes)| {
+                let compressed = self
+                    .compression
+                    .then(|| compress_to_vec(bytes, COMPRESSION_LEVEL));
+                match compressed {
+                    Some(data) if data.len() < bytes.len() => (key, data, bytes.len() as u64, true),
+                    _ => (key, bytes.clone(), bytes.len() as u64, false),
+                }
+            })
+            .collect();
+
+        let index_size: usize = stored
+            .iter()
+            .map(|(key, ..)| 2 + key.len() + 8 * 3 + 1)
+            .sum();
+        let mut offset = (ARCHIVE_MAGIC.len() + 4 + 4 + index_size) as u64;
+
+        let mut out = io::BufWriter::new(File::create(archive_path.as_ref())?);
+        out.write_all(ARCHIVE_MAGIC)?;
+        out.write_all(&ARCHIVE_VERSION.to_le_bytes())?;
+        out.write_all(&(stored.len() as u32).to_le_bytes())?;
+        for (key, data, size, compressed) in stored.iter() {
+            let name_len = u16::try_from(key.len()).map_err(|_| {
+                io::Error::new(ErrorKind::InvalidInput, format!("path too long: {key}"))
+            })?;
+            out.write_all(&name_len.to_le_bytes())?;
+            out.write_all(key.as_bytes())?;
+            out.write_all(&offset.to_le_bytes())?;
+            out.write_all(&(data.len() as u64).to_le_bytes())?;
+            out.write_all(&size.to_le_bytes())?;
+            out.write_all(&[*compressed as u8])?;
+            offset += data.len() as u64;
+        }
+        for (_, data, ..) in stored.iter() {
+            out.write_all(data)?;
+        }
+        out.flush()?;
+        log::debug!(
+            "Wrote archive {:?} with {} files.",
+            archive_path.as_ref(),
+            stored.len()
+        );
+        Ok(())
+    }
+}
+
+impl Default for ArchiveBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// reads a little endian ``u32``
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// reads a little endian ``u64``
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// loads an ``.obj`` file and the referenced material libraries through the virtual file system
+pub(crate) fn load_obj(path: &Path, options: &tobj::LoadOptions) -> tobj::LoadResult {
+    let bytes = read_file(path).map_err(|_| tobj::LoadError::OpenFileFailed)?;
+    tobj::load_obj_buf(&mut bytes.as_slice(), options, |mtl_path| {
+        load_mtl(&path.with_file_name(mtl_path))
+    })
+}
+
+/// loads a ``.mtl`` file through the virtual file system
+pub(crate) fn load_mtl(path: &Path) -> tobj::MTLLoadResult {
+    let bytes = read_file(path).map_err(|_| tobj::LoadError::OpenFileFailed)?;
+    tobj::load_mtl_buf(&mut bytes.as_slice())
+}